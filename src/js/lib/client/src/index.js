@@ -152,6 +152,13 @@ optParser.options('devtron', {
     describe    : 'Install the Devtron Developer Tools extension',
 })
 
+optParser.options('record-session', {
+    group       : debugOptionsGroup,
+    describe    : 'Log all messages exchanged with the Language Server to the console, so they '
+                + 'can be replayed in tests.',
+    type        : `boolean`
+})
+
 
 // === Style Options ===
 
@@ -182,6 +189,13 @@ optParser.options('theme', {
     type        : `string`
 })
 
+optParser.options('keymap', {
+    group       : styleOptionsGroup,
+    describe    : 'Load the user keymap from the provided URL. Files ending with `.toml` are read as '
+                + 'TOML, other ones as JSON.',
+    type        : `string`
+})
+
 optParser.options('node-labels', {
     group       : styleOptionsGroup,
     describe    : 'Show node labels. Defaults to `true`.',
@@ -547,6 +561,8 @@ function createWindow() {
 
     if (args.project)    { urlCfg.project = args.project }
     if (args.entryPoint) { urlCfg.entry   = args.entryPoint }
+    if (args.keymap)     { urlCfg.keymap  = args.keymap }
+    if (args.recordSession) { urlCfg.record_session = true }
 
     let params  = urlParamsFromObject(urlCfg)
     let address = `${origin}?${params}`
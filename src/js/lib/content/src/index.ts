@@ -803,6 +803,7 @@ class Config {
     public platform: string
     public frame: boolean
     public theme: string
+    public keymap: string
     public dark_theme: boolean
     public high_contrast: boolean
     public use_loader: boolean
@@ -813,6 +814,7 @@ class Config {
     public no_data_gathering: boolean
     public is_in_cloud: boolean
     public verbose: boolean
+    public record_session: boolean
     public authentication_enabled: boolean
     public email: string
     public application_config_url: string
@@ -850,6 +852,7 @@ class Config {
         this.platform = ok(other.platform) ? tryAsString(other.platform) : this.platform
         this.frame = ok(other.frame) ? tryAsBoolean(other.frame) : this.frame
         this.theme = ok(other.theme) ? tryAsString(other.theme) : this.theme
+        this.keymap = ok(other.keymap) ? tryAsString(other.keymap) : this.keymap
         this.dark_theme = ok(other.dark_theme) ? tryAsBoolean(other.dark_theme) : this.dark_theme
         this.high_contrast = ok(other.high_contrast)
             ? tryAsBoolean(other.high_contrast)
@@ -872,6 +875,9 @@ class Config {
             ? tryAsBoolean(other.is_in_cloud)
             : this.is_in_cloud
         this.verbose = ok(other.verbose) ? tryAsBoolean(other.verbose) : this.verbose
+        this.record_session = ok(other.record_session)
+            ? tryAsBoolean(other.record_session)
+            : this.record_session
     }
 }
 
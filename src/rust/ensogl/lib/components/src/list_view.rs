@@ -456,3 +456,22 @@ impl application::View for ListView {
           ]).iter().map(|(a,b,c,d)|Self::self_shortcut_when(*a,*b,*c,*d)).collect()
     }
 }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_shaders_are_valid() {
+        check_shape_system_shaders(&
+            [ ("selection" , selection::ShapeSystem::shader_for)
+            , ("io_rect"   , io_rect::ShapeSystem::shader_for)
+            ]);
+    }
+}
@@ -247,6 +247,17 @@ mod tests {
     use enso_frp::stream::EventEmitter;
     use enso_frp::stream::ValueProvider;
 
+    #[test]
+    fn generated_shaders_are_valid() {
+        check_shape_system_shaders(&
+            [ ("background"     , background::ShapeSystem::shader_for)
+            , ("io_rect"        , io_rect::ShapeSystem::shader_for)
+            , ("track"          , track::ShapeSystem::shader_for)
+            , ("left_overflow"  , left_overflow::ShapeSystem::shader_for)
+            , ("right_overflow" , right_overflow::ShapeSystem::shader_for)
+            ]);
+    }
+
     #[test]
     fn test_shape_is_dragged() {
         let network = enso_frp::Network::new("TestNetwork");
@@ -2,6 +2,7 @@
 
 pub mod args;
 pub mod command;
+pub mod keymap;
 pub mod shortcut;
 pub mod view;
 pub mod frp;
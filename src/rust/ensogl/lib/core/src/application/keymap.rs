@@ -0,0 +1,433 @@
+//! User-defined keymaps, modifying the default shortcuts of views.
+//!
+//! A keymap is a list of entries, each adding, removing or rebinding shortcuts of a command of a
+//! view. Commands are identified by their names, and views by their labels (see `View::label`). A
+//! keymap can be written in JSON or TOML, for example:
+//!
+//! ```toml
+//! [[bindings]]
+//! op       = "rebind"
+//! target   = "ProjectView"
+//! command  = "save_module"
+//! shortcut = "cmd shift s"
+//! when     = "!is_searcher_opened"
+//!
+//! [[bindings]]
+//! op      = "remove"
+//! target  = "GraphEditor"
+//! command = "add_node_at_cursor"
+//! ```
+//!
+//! The `add` and `rebind` entries accept the `target`, `command`, `shortcut`, and optionally the
+//! `when` condition (see `Condition`) and the `type` of the action (`press`, `press_and_repeat`,
+//! `release`, `double_press`, or `double_click`, the default is `press`). The `rebind` entry
+//! removes all shortcuts of the command with the same action type before adding the new one. The
+//! `remove` entry removes all shortcuts of the command, or only the ones with the given `shortcut`.
+//!
+//! The IDE loads the keymap from the URL given by the `keymap` startup argument. Files with the
+//! `.toml` extension are read as TOML, and other ones as JSON. The keymap can be loaded again with
+//! the `reload_keymap` command of the project view.
+
+use crate::prelude::*;
+
+use super::command;
+use super::shortcut::ActionType;
+use super::shortcut::Shortcut;
+use super::shortcut::Rule;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// Errors which can occur when loading or applying a keymap. Entries are identified by their
+/// index in the `bindings` list, starting from zero.
+#[derive(Debug,Fail)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[fail(display="Malformed JSON keymap: {}.",_0)]
+    Json(serde_json::Error),
+    #[fail(display="Malformed TOML keymap: {}.",_0)]
+    Toml(toml::de::Error),
+    #[fail(display="Keymap entry {}: unknown view '{}'.",entry,target)]
+    UnknownTarget { entry:usize, target:String },
+    #[fail(display="Keymap entry {}: the view '{}' has no command '{}'.",entry,target,command)]
+    UnknownCommand { entry:usize, target:String, command:String },
+    #[fail(display="Keymap entry {}: empty shortcut of the command '{}'.",entry,command)]
+    EmptyShortcut { entry:usize, command:String },
+    #[fail(display="Keymap entry {}: no shortcuts of the command '{}' of the view '{}' to remove."
+        ,entry,command,target)]
+    NothingToRemove { entry:usize, target:String, command:String },
+}
+
+
+
+// =================
+// === EventType ===
+// =================
+
+/// Serializable counterpart of `ActionType`.
+#[derive(Clone,Copy,Debug,Deserialize,Eq,PartialEq,Serialize)]
+#[serde(rename_all="snake_case")]
+#[allow(missing_docs)]
+pub enum EventType {
+    Press, PressAndRepeat, Release, DoublePress, DoubleClick
+}
+
+impl Default for EventType {
+    fn default() -> Self {
+        Self::Press
+    }
+}
+
+impl From<EventType> for ActionType {
+    fn from(event_type:EventType) -> Self {
+        match event_type {
+            EventType::Press          => Self::Press,
+            EventType::PressAndRepeat => Self::PressAndRepeat,
+            EventType::Release        => Self::Release,
+            EventType::DoublePress    => Self::DoublePress,
+            EventType::DoubleClick    => Self::DoubleClick,
+        }
+    }
+}
+
+
+
+// ===============
+// === Binding ===
+// ===============
+
+/// A shortcut of a command of a view.
+#[derive(Clone,Debug,Deserialize,Eq,PartialEq,Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Binding {
+    /// Label of the target view.
+    pub target      : String,
+    /// Name of the command.
+    pub command     : String,
+    /// The shortcut pattern, like "cmd shift s".
+    pub shortcut    : String,
+    /// The condition which needs to be true in order for the command to be executed.
+    #[serde(default)]
+    pub when        : String,
+    /// The type of the action triggering the command.
+    #[serde(default,rename="type")]
+    pub action_type : EventType,
+}
+
+impl Binding {
+    /// The shortcut described by this binding.
+    pub fn to_shortcut(&self) -> Shortcut {
+        let rule = Rule::new(self.action_type,&self.shortcut);
+        Shortcut::new_when(rule,&self.target,self.command.as_str(),self.when.as_str())
+    }
+
+    /// Check whether the shortcut was defined for the same command and action type.
+    fn overrides(&self, shortcut:&Shortcut) -> bool {
+        let action_type : ActionType = self.action_type.into();
+        shortcut.target() == self.target && **shortcut.command() == self.command
+            && shortcut.rule().tp == action_type
+    }
+}
+
+
+
+// ================
+// === Selector ===
+// ================
+
+/// Selection of shortcuts of a command of a view.
+#[derive(Clone,Debug,Deserialize,Eq,PartialEq,Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Selector {
+    /// Label of the target view.
+    pub target   : String,
+    /// Name of the command.
+    pub command  : String,
+    /// If set, only shortcuts with this pattern are selected.
+    #[serde(default)]
+    pub shortcut : Option<String>,
+}
+
+impl Selector {
+    fn matches(&self, shortcut:&Shortcut) -> bool {
+        let same_command = shortcut.target() == self.target && **shortcut.command() == self.command;
+        let same_pattern = self.shortcut.as_ref().map(|pattern| {
+            let resolve = enso_shortcuts::resolve_aliases;
+            resolve(pattern) == resolve(&shortcut.rule().pattern)
+        });
+        same_command && same_pattern != Some(false)
+    }
+}
+
+
+
+// =============
+// === Entry ===
+// =============
+
+/// A single modification of the default shortcuts.
+#[derive(Clone,Debug,Deserialize,Eq,PartialEq,Serialize)]
+#[serde(tag="op",rename_all="snake_case")]
+#[allow(missing_docs)]
+pub enum Entry {
+    Add    (Binding),
+    Rebind (Binding),
+    Remove (Selector),
+}
+
+impl Entry {
+    fn target(&self) -> &str {
+        match self {
+            Self::Add(binding) | Self::Rebind(binding) => &binding.target,
+            Self::Remove(selector)                     => &selector.target,
+        }
+    }
+
+    fn command(&self) -> &str {
+        match self {
+            Self::Add(binding) | Self::Rebind(binding) => &binding.command,
+            Self::Remove(selector)                     => &selector.command,
+        }
+    }
+
+    /// Check whether the target view and its command exist. Commands can be checked only if an
+    /// instance of the view exists.
+    fn validate(&self, entry:usize, commands:&command::Registry) -> Result<(),Error> {
+        let target    = self.target().to_string();
+        let command   = self.command().to_string();
+        let name_map  = commands.name_map.borrow();
+        let instances = name_map.get(&target).ok_or_else(|| {
+            Error::UnknownTarget {entry,target:target.clone()}
+        })?;
+        let has_command = |instance:&command::ProviderInstance| {
+            instance.command_map.borrow().contains_key(&command)
+        };
+        if !instances.is_empty() && !instances.iter().any(has_command) {
+            return Err(Error::UnknownCommand {entry,target,command})
+        }
+        match self {
+            Self::Add(binding) | Self::Rebind(binding) if binding.shortcut.trim().is_empty() =>
+                Err(Error::EmptyShortcut {entry,command}),
+            _ => Ok(())
+        }
+    }
+
+    fn apply(&self, entry:usize, shortcuts:&mut Vec<Shortcut>) -> Result<(),Error> {
+        match self {
+            Self::Add(binding) => shortcuts.push(binding.to_shortcut()),
+            Self::Rebind(binding) => {
+                shortcuts.retain(|shortcut| !binding.overrides(shortcut));
+                shortcuts.push(binding.to_shortcut());
+            }
+            Self::Remove(selector) => {
+                let count = shortcuts.len();
+                shortcuts.retain(|shortcut| !selector.matches(shortcut));
+                if shortcuts.len() == count {
+                    let target  = selector.target.clone();
+                    let command = selector.command.clone();
+                    return Err(Error::NothingToRemove {entry,target,command})
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+
+
+// ==============
+// === Keymap ===
+// ==============
+
+/// A list of modifications of the default shortcuts. See the module docs to learn more.
+#[derive(Clone,Debug,Default,Deserialize,Eq,PartialEq,Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Keymap {
+    /// The entries, applied in order.
+    #[serde(default)]
+    pub bindings : Vec<Entry>,
+}
+
+impl Keymap {
+    /// Parse a keymap written in JSON.
+    pub fn from_json(json:&str) -> Result<Self,Error> {
+        serde_json::from_str(json).map_err(Error::Json)
+    }
+
+    /// Parse a keymap written in TOML.
+    pub fn from_toml(toml:&str) -> Result<Self,Error> {
+        toml::from_str(toml).map_err(Error::Toml)
+    }
+
+    /// Check whether the keymap has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    /// Parse a keymap read from the file with the given name or URL. Files with the `.toml`
+    /// extension are parsed as TOML, and all other ones as JSON.
+    pub fn from_file(name:&str, content:&str) -> Result<Self,Error> {
+        let path    = name.split(|c| c == '?' || c == '#').next().unwrap_or(name);
+        let is_toml = path.ends_with(".toml");
+        if is_toml { Self::from_toml(content) } else { Self::from_json(content) }
+    }
+
+    /// Apply all entries to the shortcuts, in order. The entries which cannot be applied are
+    /// skipped, and the returned errors describe them.
+    pub fn apply(&self, shortcuts:&mut Vec<Shortcut>, commands:&command::Registry) -> Vec<Error> {
+        self.apply_filtered(shortcuts,commands,|_| true)
+    }
+
+    /// Apply the entries modifying the shortcuts of the given command of the given view. The
+    /// `shortcuts` should contain all the default shortcuts of this command. This allows updating
+    /// the shortcuts of a single command without applying the whole keymap again.
+    pub fn apply_to_command
+    (&self, target:&str, command:&str, shortcuts:&mut Vec<Shortcut>, commands:&command::Registry)
+    -> Vec<Error> {
+        self.apply_filtered(shortcuts,commands,|entry| {
+            entry.target() == target && entry.command() == command
+        })
+    }
+
+    fn apply_filtered
+    ( &self
+    , shortcuts : &mut Vec<Shortcut>
+    , commands  : &command::Registry
+    , filter    : impl Fn(&Entry) -> bool
+    ) -> Vec<Error> {
+        let entries = self.bindings.iter().enumerate().filter(|(_,entry)| filter(entry));
+        entries.filter_map(|(index,entry)| {
+            entry.validate(index,commands).and_then(|_| entry.apply(index,shortcuts)).err()
+        }).collect()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON : &str = r#"{ "bindings": [
+        { "op":"rebind", "target":"View", "command":"second", "shortcut":"ctrl x", "when":"!edit" },
+        { "op":"remove", "target":"View", "command":"first" },
+        { "op":"add", "target":"View", "command":"third", "shortcut":"ctrl t", "type":"release" },
+        { "op":"add", "target":"Other", "command":"first", "shortcut":"ctrl o" },
+        { "op":"remove", "target":"View", "command":"fourth" },
+        { "op":"add", "target":"View", "command":"fifth", "shortcut":" " }
+    ]}"#;
+
+    const TOML : &str = r#"
+        [[bindings]]
+        op       = "rebind"
+        target   = "View"
+        command  = "second"
+        shortcut = "ctrl x"
+        when     = "!edit"
+
+        [[bindings]]
+        op      = "remove"
+        target  = "View"
+        command = "first"
+
+        [[bindings]]
+        op       = "add"
+        target   = "View"
+        command  = "third"
+        shortcut = "ctrl t"
+        type     = "release"
+
+        [[bindings]]
+        op       = "add"
+        target   = "Other"
+        command  = "first"
+        shortcut = "ctrl o"
+
+        [[bindings]]
+        op      = "remove"
+        target  = "View"
+        command = "fourth"
+
+        [[bindings]]
+        op       = "add"
+        target   = "View"
+        command  = "fifth"
+        shortcut = " "
+    "#;
+
+    fn shortcut(action_type:ActionType, pattern:&str, command:&str, when:&str) -> Shortcut {
+        Shortcut::new_when(Rule::new(action_type,pattern),"View",command,when)
+    }
+
+    #[test]
+    fn parsing() {
+        let keymap = Keymap::from_json(JSON).unwrap();
+        assert_eq!(keymap.bindings.len(),6);
+        assert_eq!(Keymap::from_toml(TOML).unwrap(),keymap);
+        assert!(matches!(Keymap::from_json("{ \"bindings\": [{ \"op\":\"bind\" }] }"),
+            Err(Error::Json(_))));
+        assert!(matches!(Keymap::from_toml("bindings = 1"),Err(Error::Toml(_))));
+    }
+
+    #[test]
+    fn applying() {
+        let commands = command::Registry::create(Logger::new("test"));
+        commands.name_map.borrow_mut().insert("View".into(),default());
+        let mut shortcuts = vec!
+            [ shortcut(ActionType::Press   , "ctrl a" , "first"  , "")
+            , shortcut(ActionType::Press   , "ctrl b" , "second" , "edit")
+            , shortcut(ActionType::Release , "ctrl b" , "second" , "")
+            ];
+        let errors = Keymap::from_json(JSON).unwrap().apply(&mut shortcuts,&commands);
+        let expected = vec!
+            [ shortcut(ActionType::Release , "ctrl b" , "second" , "")
+            , shortcut(ActionType::Press   , "ctrl x" , "second" , "!edit")
+            , shortcut(ActionType::Release , "ctrl t" , "third"  , "")
+            ];
+        assert_eq!(shortcuts,expected);
+        assert_eq!(errors.len(),3);
+        assert!(matches!(errors[0],Error::UnknownTarget   {entry:3,..}));
+        assert!(matches!(errors[1],Error::NothingToRemove {entry:4,..}));
+        assert!(matches!(errors[2],Error::EmptyShortcut   {entry:5,..}));
+    }
+
+    #[test]
+    fn applying_to_command() {
+        let commands = command::Registry::create(Logger::new("test"));
+        commands.name_map.borrow_mut().insert("View".into(),default());
+        let keymap = Keymap::from_json(JSON).unwrap();
+        let mut shortcuts = vec!
+            [ shortcut(ActionType::Press   , "ctrl b" , "second" , "edit")
+            , shortcut(ActionType::Release , "ctrl b" , "second" , "")
+            ];
+        let errors   = keymap.apply_to_command("View","second",&mut shortcuts,&commands);
+        let expected = vec!
+            [ shortcut(ActionType::Release , "ctrl b" , "second" , "")
+            , shortcut(ActionType::Press   , "ctrl x" , "second" , "!edit")
+            ];
+        assert_eq!(shortcuts,expected);
+        assert!(errors.is_empty());
+        let mut shortcuts = vec![shortcut(ActionType::Press,"ctrl a","first","")];
+        let errors        = keymap.apply_to_command("View","first",&mut shortcuts,&commands);
+        assert!(shortcuts.is_empty());
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn format_detection() {
+        let toml = Keymap::from_file("http://localhost/keymap.toml?version=1",TOML).unwrap();
+        let json = Keymap::from_file("keymap.json",JSON).unwrap();
+        assert_eq!(toml,json);
+        assert!(Keymap::from_file("keymap",TOML).is_err());
+    }
+}
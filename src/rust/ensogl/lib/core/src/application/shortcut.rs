@@ -3,6 +3,8 @@
 use crate::prelude::*;
 
 use super::command;
+use super::keymap;
+use super::keymap::Keymap;
 
 use crate::frp::io::keyboard;
 use crate::frp::io::mouse::Mouse;
@@ -10,6 +12,7 @@ use crate::frp;
 
 use enso_shortcuts as shortcuts;
 use enso_shortcuts::traits::*;
+use std::collections::BTreeSet;
 
 pub use shortcuts::ActionType;
 
@@ -20,7 +23,9 @@ pub use shortcuts::ActionType;
 // ============
 
 /// Shortcut action rule, a combination of `ActionType`, like `Press` and a pattern, like
-/// "ctrl shift s".
+/// "ctrl shift s". The pattern can also describe a sequence of strokes, like "ctrl k ctrl s",
+/// which is matched when the strokes are pressed one after another. A new stroke starts whenever a
+/// modifier key follows a regular key. Only `Press` and `Release` rules can describe sequences.
 #[derive(Clone,Debug,Eq,PartialEq,Hash)]
 #[allow(missing_docs)]
 pub struct Rule {
@@ -122,6 +127,52 @@ impl Condition {
     }
 }
 
+impl Condition {
+    /// Check whether this condition being true implies that the `other` condition is true. The
+    /// check is conservative, it may return `false` for some conditions which imply each other.
+    pub fn implies(&self, other:&Self) -> bool {
+        use Condition::*;
+        match (self,other) {
+            _ if self == other       => true,
+            (Never,_) | (_,Always)   => true,
+            (Or(a,b),_)              => a.implies(other) && b.implies(other),
+            (_,And(a,b))             => self.implies(a) && self.implies(b),
+            (And(a,b),_) if a.implies(other) || b.implies(other) => true,
+            (_,Or(a,b))              => self.implies(a) || self.implies(b),
+            _                        => false,
+        }
+    }
+
+    /// Check whether this condition and the `other` condition can never be true at the same time.
+    /// The check is conservative, it may return `false` for some exclusive conditions.
+    pub fn excludes(&self, other:&Self) -> bool {
+        use Condition::*;
+        match (self,other) {
+            (Never,_) | (_,Never)                   => true,
+            (Not(a),b) | (b,Not(a)) if **a == *b    => true,
+            (Or(a,b),_)                             => a.excludes(other) && b.excludes(other),
+            (_,Or(a,b))                             => self.excludes(a) && self.excludes(b),
+            (And(a,b),_)                            => a.excludes(other) || b.excludes(other),
+            (_,And(a,b))                            => self.excludes(a) || self.excludes(b),
+            _                                       => false,
+        }
+    }
+
+    /// Prefix all variables with the target name. Variables are resolved in the status map of the
+    /// target instance, so variables of the same name used by different targets are not related.
+    fn qualified(&self, target:&str) -> Self {
+        use Condition::*;
+        match self {
+            Always     => Always,
+            Never      => Never,
+            When(name) => When(format!("{}::{}",target,name)),
+            Not(a)     => Self::not(a.qualified(target)),
+            Or(a,b)    => Self::or(a.qualified(target),b.qualified(target)),
+            And(a,b)   => Self::and(a.qualified(target),b.qualified(target)),
+        }
+    }
+}
+
 impl From<&str> for Condition {
     fn from(s:&str) -> Self {
         Self::parse(s)
@@ -158,6 +209,21 @@ impl Action {
         let command   = command.into();
         Self {target,command,condition}
     }
+
+    /// Label of the target view.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// The command evaluated on the target.
+    pub fn command(&self) -> &Command {
+        &self.command
+    }
+
+    /// The condition which needs to be true in order for the command to be executed.
+    pub fn condition(&self) -> &Condition {
+        &self.condition
+    }
 }
 
 
@@ -197,6 +263,99 @@ impl Shortcut {
         let rule   = rule.into();
         Self {action,rule}
     }
+
+    /// The rule triggering this shortcut.
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+}
+
+
+
+// ================
+// === Conflict ===
+// ================
+
+/// Kind of a `Conflict`.
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
+pub enum ConflictKind {
+    /// Both shortcuts fire if their conditions are true at the same time, which may happen.
+    Ambiguous,
+    /// The condition of the first shortcut implies the condition of the second one, so the first
+    /// shortcut never fires alone. This includes shortcuts with equivalent conditions.
+    Shadowed,
+}
+
+/// Two shortcuts which can be triggered by the same input, like "cmd s" and "ctrl s" on Linux,
+/// and whose conditions do not exclude each other, so both their commands can be evaluated at once.
+#[derive(Clone,Debug,Eq,Hash,PartialEq)]
+#[allow(missing_docs)]
+pub struct Conflict {
+    pub kind   : ConflictKind,
+    pub first  : Shortcut,
+    pub second : Shortcut,
+}
+
+impl Conflict {
+    /// Check the conditions of two shortcuts which are triggered by the same input.
+    fn check(first:&Shortcut, second:&Shortcut) -> Option<Self> {
+        let first_condition  = first.condition.qualified(&first.target);
+        let second_condition = second.condition.qualified(&second.target);
+        let (kind,first,second) = if first_condition.excludes(&second_condition) {
+            return None
+        } else if first_condition.implies(&second_condition) {
+            (ConflictKind::Shadowed,first,second)
+        } else if second_condition.implies(&first_condition) {
+            (ConflictKind::Shadowed,second,first)
+        } else {
+            (ConflictKind::Ambiguous,first,second)
+        };
+        let first  = first.clone();
+        let second = second.clone();
+        Some(Self {kind,first,second})
+    }
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |t:&Shortcut| format!("{:?} \"{}\" ({}::{} when {:?})",
+            t.rule.tp,t.rule.pattern,t.target,t.command.name,t.condition);
+        let first  = describe(&self.first);
+        let second = describe(&self.second);
+        match self.kind {
+            ConflictKind::Ambiguous => write!(f,"{} and {} can fire together.",first,second),
+            ConflictKind::Shadowed  => write!(f,"{} never fires without {}.",first,second),
+        }
+    }
+}
+
+/// Find all conflicts between the shortcuts. Shortcuts triggered by the same input are found by
+/// using the DFA built by `shortcuts::AutomataRegistry`, so for example "ctrl a" and "ctrl-left a"
+/// are detected as well. Key aliases, like "cmd", are resolved for the current platform.
+pub fn conflicts(registered:&[Shortcut]) -> Vec<Conflict> {
+    let mut patterns = HashMap::<(ActionType,String),Vec<usize>>::new();
+    for (index,shortcut) in registered.iter().enumerate() {
+        let pattern = shortcuts::resolve_aliases(&shortcut.rule.pattern);
+        patterns.entry((shortcut.rule.tp,pattern)).or_default().push(index);
+    }
+    let patterns = patterns.into_iter().collect_vec();
+    let automata = shortcuts::AutomataRegistry::<usize>::new();
+    for (pattern_index,((tp,pattern),_)) in patterns.iter().enumerate() {
+        automata.add(*tp,pattern,pattern_index);
+    }
+    let same_pattern = patterns.iter().map(|(_,indices)| indices.clone());
+    let same_input   = automata.overlapping_actions().into_iter().map(|(_,pattern_indices)| {
+        pattern_indices.into_iter().flat_map(|t| patterns[t].1.iter().copied()).collect_vec()
+    });
+    let mut pairs = BTreeSet::new();
+    for group in same_pattern.chain(same_input) {
+        for (first,second) in group.into_iter().sorted().tuple_combinations() {
+            pairs.insert((first,second));
+        }
+    }
+    pairs.into_iter().filter_map(|(first,second)| {
+        Conflict::check(&registered[first],&registered[second])
+    }).collect()
 }
 
 
@@ -211,9 +370,8 @@ impl Shortcut {
 /// dropped, the shortcut will be lazily removed. This is useful when defining shortcuts by GUI
 /// components. When a component is unloaded, all its default shortcuts should be removed as well.
 ///
-/// ## Implementation Notes
-/// There should be a layer for user shortcuts which will remember handles permanently until a
-/// shortcut is unregistered.
+/// User shortcuts are defined by a `Keymap`, which is applied on top of the default shortcuts of
+/// all views. See the `keymap` module to learn more.
 #[derive(Clone,CloneRef,Debug)]
 pub struct Registry {
     model   : RegistryModel,
@@ -228,6 +386,11 @@ pub struct RegistryModel {
     mouse              : Mouse,
     command_registry   : command::Registry,
     shortcuts_registry : shortcuts::HashSetRegistry<Shortcut>,
+    /// Shortcuts added to the registry, usually the default shortcuts of views.
+    defaults           : Rc<RefCell<Vec<Shortcut>>>,
+    keymap             : Rc<RefCell<Keymap>>,
+    /// Shortcuts which are currently active, that is, the defaults modified by the keymap.
+    shortcuts          : Rc<RefCell<Vec<Shortcut>>>,
 }
 
 impl Deref for Registry {
@@ -270,7 +433,108 @@ impl RegistryModel {
         let mouse              = mouse.clone_ref();
         let command_registry   = command_registry.clone_ref();
         let shortcuts_registry = default();
-        Self {logger,keyboard,mouse,command_registry,shortcuts_registry}
+        let defaults           = default();
+        let keymap             = default();
+        let shortcuts          = default();
+        Self {logger,keyboard,mouse,command_registry,shortcuts_registry,defaults,keymap,shortcuts}
+    }
+
+    /// Shortcuts which are currently active, that is, the added shortcuts modified by the keymap.
+    pub fn shortcuts(&self) -> Vec<Shortcut> {
+        self.shortcuts.borrow().clone()
+    }
+
+    /// Set the user keymap, replacing the previous one, and rebuild the registry. It can be called
+    /// at any time to reload the keymap. Entries which cannot be applied are skipped, and the
+    /// returned errors describe them. Entries targeting views which are not registered yet are
+    /// applied again when their shortcuts are added, so the keymap can be set before registering
+    /// views, but then the errors describing such entries are not meaningful.
+    pub fn set_keymap(&self, keymap:Keymap) -> Vec<keymap::Error> {
+        *self.keymap.borrow_mut() = keymap;
+        self.rebuild()
+    }
+
+    /// Remove the user keymap, restoring the default shortcuts.
+    pub fn clear_keymap(&self) {
+        self.set_keymap(default());
+    }
+
+    fn rebuild(&self) -> Vec<keymap::Error> {
+        let mut shortcuts = self.defaults.borrow().clone();
+        let errors        = self.keymap.borrow().apply(&mut shortcuts,&self.command_registry);
+        self.shortcuts_registry.clear();
+        for shortcut in &shortcuts {
+            self.register(shortcut);
+        }
+        *self.shortcuts.borrow_mut() = shortcuts;
+        errors
+    }
+
+    /// Apply the keymap to the shortcuts of a single command. It is used when a default shortcut
+    /// is added, as applying the whole keymap for every added shortcut would make registering views
+    /// quadratic. The shortcuts which are no longer active cannot be removed from the registry, so
+    /// in such a case the registry is rebuilt.
+    fn update_command(&self, target:&str, command:&str) {
+        let is_command  = |t:&&Shortcut| t.target == target && t.command.name == command;
+        let defaults    = self.defaults.borrow().iter().filter(is_command).cloned().collect_vec();
+        let mut updated = defaults;
+        let keymap      = self.keymap.borrow();
+        let commands    = &self.command_registry;
+        let errors      = keymap.apply_to_command(target,command,&mut updated,commands);
+        drop(keymap);
+        for error in errors {
+            // Shortcuts removed by the keymap may be not added yet.
+            if !matches!(error,keymap::Error::NothingToRemove{..}) {
+                warning!(&self.logger,"Cannot apply the keymap: {error}");
+            }
+        }
+        let mut added   = updated.clone();
+        let mut removed = false;
+        for shortcut in self.shortcuts.borrow().iter().filter(is_command) {
+            match added.iter().position(|t| t == shortcut) {
+                Some(index) => { added.remove(index); }
+                None        => { removed = true; }
+            }
+        }
+        if removed {
+            // The errors were already reported when the other commands were updated.
+            self.rebuild();
+        } else {
+            for shortcut in &added {
+                self.register(shortcut);
+            }
+            let mut shortcuts = self.shortcuts.borrow_mut();
+            shortcuts.retain(|t| !is_command(&t));
+            shortcuts.extend(updated);
+        }
+    }
+
+    fn register(&self, shortcut:&Shortcut) {
+        self.shortcuts_registry.add(shortcut.rule.tp,&shortcut.rule.pattern,shortcut.clone());
+    }
+
+    /// Find all conflicts between the registered shortcuts. See `Conflict` to learn more.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        conflicts(&self.shortcuts.borrow())
+    }
+
+    /// Log all conflicts between the registered shortcuts as warnings. Intended to be bound to a
+    /// debug command.
+    pub fn report_conflicts(&self) {
+        for conflict in self.conflicts() {
+            warning!(&self.logger,"Shortcut conflict: {conflict}");
+        }
+    }
+
+    /// Set the maximum time in milliseconds between subsequent strokes of shortcut sequences.
+    pub fn set_sequence_timeout(&self, timeout:f32) {
+        self.shortcuts_registry.set_sequence_timeout(timeout)
+    }
+
+    /// Shortcut sequences which were partially matched and are waiting for the next stroke. It can
+    /// be used to display a hint about the shortcut being typed.
+    pub fn pending_sequences(&self) -> Vec<shortcuts::PendingSequence<Shortcut>> {
+        self.shortcuts_registry.pending_sequences()
     }
 
     fn process_rules(&self, rules:&[Shortcut]) {
@@ -315,6 +579,59 @@ impl RegistryModel {
 impl Add<Shortcut> for &Registry {
     type Output = ();
     fn add(self, shortcut:Shortcut) {
-        self.model.shortcuts_registry.add(shortcut.rule.tp,&shortcut.rule.pattern,shortcut.clone());
+        self.model.defaults.borrow_mut().push(shortcut.clone());
+        if self.model.keymap.borrow().is_empty() {
+            self.model.register(&shortcut);
+            self.model.shortcuts.borrow_mut().push(shortcut);
+        } else {
+            self.model.update_command(&shortcut.target,&shortcut.command.name);
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortcut(pattern:&str, command:&str, condition:&str) -> Shortcut {
+        Shortcut::new_when(Rule::new(ActionType::Press,pattern),"View",command,condition)
+    }
+
+    #[test]
+    fn condition_implication_and_exclusion() {
+        let parse = |expr:&str| Condition::parse(expr);
+        assert!(parse("a & b").implies(&parse("a")));
+        assert!(parse("a").implies(&parse("a | b")));
+        assert!(parse("a").implies(&parse("")));
+        assert!(!parse("a").implies(&parse("b")));
+        assert!(parse("a").excludes(&parse("!a")));
+        assert!(parse("a & b").excludes(&parse("!b")));
+        assert!(!parse("a").excludes(&parse("!b")));
+    }
+
+    #[test]
+    fn detecting_conflicts() {
+        let registered = vec!
+            [ shortcut("ctrl a"      , "first"  , "")
+            , shortcut("ctrl-left a" , "second" , "editing")
+            , shortcut("ctrl b"      , "third"  , "editing")
+            , shortcut("ctrl b"      , "fourth" , "!editing")
+            , shortcut("ctrl c"      , "fifth"  , "editing")
+            , shortcut("ctrl c"      , "sixth"  , "selecting")
+            ];
+        let found = conflicts(&registered).into_iter().map(|conflict| {
+            (conflict.kind,conflict.first.command.name.clone(),conflict.second.command.name.clone())
+        }).collect_vec();
+        let expected = vec!
+            [ (ConflictKind::Shadowed  , "second".to_string() , "first".to_string())
+            , (ConflictKind::Ambiguous , "fifth".to_string()  , "sixth".to_string())
+            ];
+        assert_eq!(found,expected);
     }
 }
@@ -46,11 +46,13 @@ impl Registry {
     /// View registration.
     pub fn register<V:View>(&self) {
         let label = V::label().into();
+        // Commands are registered first, so the keymap entries targeting this view can be
+        // validated when its shortcuts are added.
+        self.command_registry.register::<V>();
         for shortcut in V::default_shortcuts() {
             self.shortcut_registry.add(shortcut)
         }
         self.definitions.borrow_mut().insert(label);
-        self.command_registry.register::<V>();
     }
 
     /// New view constructor.
@@ -473,6 +473,51 @@ impl Camera2d {
 }
 
 
+// === Coordinate Spaces ===
+
+impl Camera2d {
+    /// Convert the screen position (in pixels, relative to the screen center) to the position on
+    /// the scene's `z = 0` plane.
+    pub fn screen_to_scene(&self, position:ScreenPosition) -> ScenePosition {
+        let view_projection          = self.view_projection_matrix();
+        let inversed_view_projection = self.inversed_view_projection_matrix();
+        screen_to_scene(self.screen(),&view_projection,&inversed_view_projection,position)
+    }
+
+    /// Convert the position on the scene's `z = 0` plane to the screen position (in pixels,
+    /// relative to the screen center).
+    pub fn scene_to_screen(&self, position:ScenePosition) -> ScreenPosition {
+        scene_to_screen(self.screen(),&self.view_projection_matrix(),position)
+    }
+}
+
+/// Convert the screen position to the position on the scene's `z = 0` plane, as seen by the camera
+/// with the given matrices. The camera is assumed to look along the z axis.
+fn screen_to_scene
+( screen                   : Screen
+, view_projection          : &Matrix4<f32>
+, inversed_view_projection : &Matrix4<f32>
+, position                 : ScreenPosition
+) -> ScenePosition {
+    let origin       = view_projection * Vector4(0.0,0.0,0.0,1.0);
+    let clip_space_x = origin.w * 2.0 * position.x / screen.width;
+    let clip_space_y = origin.w * 2.0 * position.y / screen.height;
+    let clip_space   = Vector4(clip_space_x,clip_space_y,origin.z,origin.w);
+    let scene_space  = inversed_view_projection * clip_space;
+    ScenePosition(scene_space.x,scene_space.y)
+}
+
+/// Convert the position on the scene's `z = 0` plane to the screen position, as seen by the camera
+/// with the given view-projection matrix.
+fn scene_to_screen
+(screen:Screen, view_projection:&Matrix4<f32>, position:ScenePosition) -> ScreenPosition {
+    let clip_space = view_projection * Vector4(position.x,position.y,0.0,1.0);
+    let x          = clip_space.x / clip_space.w * screen.width  / 2.0;
+    let y          = clip_space.y / clip_space.w * screen.height / 2.0;
+    ScreenPosition(x,y)
+}
+
+
 // === Conversions ===
 
 impl display::Object for Camera2d {
@@ -480,3 +525,55 @@ impl display::Object for Camera2d {
         &self.display_object
     }
 }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The matrices of the camera placed like `Camera2d` does for the given zoom and panning.
+    fn camera_matrices
+    (screen:Screen, zoom:f32, pan:Vector2<f32>) -> (Matrix4<f32>,Matrix4<f32>) {
+        let fov           = 45.0f32.to_radians();
+        let clipping      = Clipping::default();
+        let z_zoom_1      = screen.height / (2.0 * (fov / 2.0).tan());
+        let position      = Vector3(pan.x,pan.y,z_zoom_1 / zoom);
+        let view_inversed = Matrix4::new_translation(&position);
+        let view          = view_inversed.try_inverse().unwrap();
+        let perspective   = Perspective3::new(screen.aspect(),fov,clipping.near,clipping.far);
+        let projection    = *perspective.as_matrix();
+        (projection * view, view_inversed * perspective.inverse())
+    }
+
+    fn assert_approx_eq<T:Into<Vector2<f32>>>(actual:T, expected:T) {
+        let (actual,expected) = (actual.into(),expected.into());
+        assert!((actual - expected).norm() < 0.01, "{:?} != {:?}",actual,expected);
+    }
+
+    #[test]
+    fn screen_scene_round_trip() {
+        let screen = Screen {width:800.0,height:600.0};
+        let (view_projection,inversed) = camera_matrices(screen,1.0,Vector2(0.0,0.0));
+        let scene = screen_to_scene(screen,&view_projection,&inversed,ScreenPosition(10.0,20.0));
+        assert_approx_eq(scene,ScenePosition(10.0,20.0));
+
+        // Zooming in twice makes the screen pixels half as big in the scene.
+        let (view_projection,inversed) = camera_matrices(screen,2.0,Vector2(100.0,50.0));
+        let screen_position            = ScreenPosition(10.0,20.0);
+        let scene = screen_to_scene(screen,&view_projection,&inversed,screen_position);
+        assert_approx_eq(scene,ScenePosition(105.0,60.0));
+        assert_approx_eq(scene_to_screen(screen,&view_projection,scene),screen_position);
+
+        let (view_projection,inversed) = camera_matrices(screen,0.5,Vector2(-30.0,70.0));
+        let scene_position             = ScenePosition(-130.0,20.0);
+        let screen_position = scene_to_screen(screen,&view_projection,scene_position);
+        assert_approx_eq(screen_position,ScreenPosition(-50.0,-25.0));
+        let scene = screen_to_scene(screen,&view_projection,&inversed,screen_position);
+        assert_approx_eq(scene,scene_position);
+    }
+}
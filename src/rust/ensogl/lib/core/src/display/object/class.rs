@@ -14,6 +14,7 @@ use crate::display::scene::layer::LayerId;
 use data::opt_vec::OptVec;
 use nalgebra::Matrix4;
 use nalgebra::Vector3;
+use nalgebra::Vector4;
 use transform::CachedTransform;
 
 
@@ -852,6 +853,21 @@ pub trait ObjectOps<Host=Scene> : Object<Host> {
         self.display_object().rc.global_position()
     }
 
+    /// Convert the scene position to the local coordinate system of this display object. Returns
+    /// `None` if the object's transformation is not invertible, e.g. when it is scaled to zero.
+    fn scene_to_local(&self, position:ScenePosition) -> Option<LocalPosition> {
+        let matrix = self.transform_matrix().try_inverse()?;
+        let local  = matrix * Vector4::new(position.x,position.y,0.0,1.0);
+        Some(LocalPosition(local.x,local.y))
+    }
+
+    /// Convert the position in the local coordinate system of this display object to the scene
+    /// position.
+    fn local_to_scene(&self, position:LocalPosition) -> ScenePosition {
+        let scene = self.transform_matrix() * Vector4::new(position.x,position.y,0.0,1.0);
+        ScenePosition(scene.x,scene.y)
+    }
+
 
     // === Position ===
 
@@ -1189,6 +1205,32 @@ mod tests {
         assert_eq!(node3.global_position() , Vector3::new(7.0,6.0,0.0));
     }
 
+    #[test]
+    fn coordinate_spaces_test() {
+        let node1 = Instance::<()>::new(Logger::new("node1"));
+        let node2 = Instance::<()>::new(Logger::new("node2"));
+        node1.add_child(&node2);
+        node1.mod_position(|t| t.x += 7.0);
+        node2.mod_position(|t| t.y += 5.0);
+        node2.mod_rotation(|t| t.z += PI/2.0);
+        node2.mod_scale(|t| *t *= 2.0);
+        node1.update(&());
+
+        let assert_near = |actual:Vector2<f32>, expected:Vector2<f32>| {
+            assert!((actual - expected).norm() < 0.001, "{:?} != {:?}",actual,expected);
+        };
+        let scene = node2.local_to_scene(LocalPosition(1.0,0.0));
+        assert_near(scene.into(),Vector2(7.0,7.0));
+        let local = node2.scene_to_local(ScenePosition(7.0,7.0)).unwrap();
+        assert_near(local.into(),Vector2(1.0,0.0));
+        let local = node2.scene_to_local(ScenePosition(-3.0,12.0)).unwrap();
+        assert_near(node2.local_to_scene(local).into(),Vector2(-3.0,12.0));
+
+        node2.set_scale_x(0.0);
+        node1.update(&());
+        assert_eq!(node2.scene_to_local(ScenePosition(7.0,7.0)),None);
+    }
+
     #[test]
     fn parent_test() {
         let node1 = Instance::<()>::new(Logger::new("node1"));
@@ -0,0 +1,29 @@
+// ==============
+// === Encode ===
+// ==============
+
+// This encoding must correspond to the decoding in the `Target` struct in
+// src\rust\ensogl\src\display\scene.rs See there for more explanation.
+uvec3 encode(int value1, int value2) {
+    uint chunk1 = (uint(value1) >> 4u) & 0x00FFu;
+    uint chunk2 = (uint(value1) & 0x000Fu) << 4u;
+    chunk2 = chunk2 + ((uint(value2) & 0x0F00u) >> 8u);
+    uint chunk3 = uint(value2) & 0x00FFu;
+    return uvec3(chunk1,chunk2,chunk3);
+}
+
+// Encodes a uint values so it can be stored in a u8 encoded float. Will clamp values that are
+// out of range.
+float as_float_u8(uint value) {
+    return clamp(float(value) / 255.0);
+}
+
+vec3 as_float_u8(uvec3 v) {
+    return vec3(as_float_u8(v.x),as_float_u8(v.y),as_float_u8(v.z));
+}
+
+// Encodes the IDs as the color of the ID buffer. The IDs are passed as floats, as the integer
+// shader inputs are not available in GLSL ES 1.00, see `encode_es100.glsl`.
+vec3 encode_id(float symbol_id, float instance_id) {
+    return as_float_u8(encode(int(symbol_id),int(instance_id)));
+}
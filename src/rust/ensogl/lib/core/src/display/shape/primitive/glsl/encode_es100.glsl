@@ -0,0 +1,27 @@
+// ==============
+// === Encode ===
+// ==============
+
+// The GLSL ES 1.00 version of `encode.glsl`, which has no unsigned integers and no bitwise
+// operators. The chunks are computed with the float arithmetic instead. It is exact, as the
+// divisors are powers of two and the encoded values are much smaller than 2^24. The values are
+// rounded first, as they may be interpolated varyings.
+vec3 encode(float value1, float value2) {
+    float v1     = floor(value1 + 0.5);
+    float v2     = floor(value2 + 0.5);
+    float chunk1 = mod(floor(v1 / 16.0), 256.0);
+    float chunk2 = mod(v1, 16.0) * 16.0 + mod(floor(v2 / 256.0), 16.0);
+    float chunk3 = mod(v2, 256.0);
+    return vec3(chunk1,chunk2,chunk3);
+}
+
+// Encodes the chunks so they can be stored in u8 encoded floats. Will clamp values that are out
+// of range.
+vec3 as_float_u8(vec3 v) {
+    return clamp(v / 255.0);
+}
+
+// Encodes the IDs as the color of the ID buffer.
+vec3 encode_id(float symbol_id, float instance_id) {
+    return as_float_u8(encode(symbol_id,instance_id));
+}
@@ -33,11 +33,11 @@ float alpha    = shape.color.color.raw.a;
 // === Object ID Rendering ===
 // ===========================
 
-uvec3 chunks      = encode(input_symbol_id,input_instance_id);
+vec3  id_color    = encode_id(float(input_symbol_id),float(input_instance_id));
 float alpha_no_aa = alpha > ID_ALPHA_THRESHOLD ? 1.0 : 0.0;
 
 if (pointer_events_enabled) {
-    output_id = vec4(as_float_u8(chunks),alpha_no_aa);
+    output_id = vec4(id_color,alpha_no_aa);
     output_id.rgb *= alpha_no_aa;
 }
 
@@ -60,7 +60,7 @@ if (input_display_mode == DISPLAY_MODE_NORMAL) {
     output_color.rgb *= alpha_no_aa;
 
 } else if (input_display_mode == DISPLAY_MODE_DEBUG_ID) {
-    float object_hue  = float((input_instance_id * 7) % 100) / 100.0;
+    float object_hue  = mod(float(input_instance_id) * 7.0, 100.0) / 100.0;
     Srgb object_color = srgb(hsv(object_hue, 1.0, 0.5));
     output_color.rgb  = object_color.raw.rgb;
     output_color.a    = alpha_no_aa;
@@ -6,6 +6,7 @@ use crate::prelude::*;
 use crate::display::shape::primitive::def::primitive;
 use crate::display::shape::primitive::shader::overload;
 use crate::display::symbol::shader::builder::CodeTemplate;
+use crate::system::gpu::shader::glsl;
 use super::canvas;
 use super::canvas::Canvas;
 
@@ -18,6 +19,8 @@ use super::canvas::Canvas;
 // === GLSL Sources ===
 
 const MATH            :&str = include_str!("../glsl/math.glsl");
+const ENCODE          :&str = include_str!("../glsl/encode.glsl");
+const ENCODE_ES100    :&str = include_str!("../glsl/encode_es100.glsl");
 const COLOR           :&str = include_str!("../glsl/color.glsl");
 const DEBUG           :&str = include_str!("../glsl/debug.glsl");
 const SHAPE           :&str = include_str!("../glsl/shape.glsl");
@@ -32,9 +35,10 @@ const FRAGMENT_RUNNER :&str = include_str!("../glsl/fragment_runner.glsl");
 pub struct Builder {}
 
 impl Builder {
-    /// Returns the final GLSL code. If `pointer_events_enabled` is set to false, the generated
-    /// shape will be transparent for pointer events and will pass them trough.
-    pub fn run<S:canvas::Draw>(shape:&S, pointer_events_enabled:bool) -> CodeTemplate {
+    /// Returns the final GLSL code in the given GLSL version. If `pointer_events_enabled` is set to
+    /// false, the generated shape will be transparent for pointer events and will pass them trough.
+    pub fn run<S:canvas::Draw>
+    (shape:&S, pointer_events_enabled:bool, target:glsl::Target) -> CodeTemplate {
         let sdf_defs     = primitive::all_shapes_glsl_definitions();
         let mut canvas   = Canvas::default();
         let shape_ref    = shape.draw(&mut canvas);
@@ -44,14 +48,20 @@ impl Builder {
         canvas.submit_shape_constructor("run");
         let defs = iformat!("{defs_header}\n\n{sdf_defs}\n\n\n\n{shape_header}\n\n{canvas.to_glsl()}");
 
-        let redirections = overload::builtin_redirections();
+        let encode       = match target {
+            glsl::Target::Es300 => ENCODE,
+            glsl::Target::Es100 => ENCODE_ES100,
+        };
+        let redirections = overload::builtin_redirections_for(target);
         let math         = overload::allow_overloading(MATH);
+        let encode       = overload::allow_overloading(encode);
         let color        = overload::allow_overloading(COLOR);
         let debug        = overload::allow_overloading(DEBUG);
         let shape        = overload::allow_overloading(SHAPE);
 
         let defs = overload::allow_overloading(&defs);
-        let code = format!("{}\n\n{}\n\n{}\n\n{}\n\n{}\n\n{}",redirections,math,color,debug,shape,defs);
+        let code = format!("{}\n\n{}\n\n{}\n\n{}\n\n{}\n\n{}\n\n{}",redirections,math,encode,color,debug
+                          ,shape,defs);
         let main = format!("bool pointer_events_enabled = {};\n{}",pointer_events_enabled,FRAGMENT_RUNNER.to_string());
 
         CodeTemplate::new(code,main,"")
@@ -1,17 +1,143 @@
 //! GLSL 300 does not allow for overloading primitive functions. This module defines an utility
 //! which scans the GLSL code and mangles all names of primitive functions. This way we can define
 //! overloaded functions the same way as we did in GLSL 100.
+//!
+//! The utility is implemented in JavaScript. The native targets, which run the shader builders in
+//! tests only, use an equivalent Rust implementation. Both of them read the built-in functions list
+//! from the `overload_builtins.txt` file.
 
-use wasm_bindgen::prelude::*;
+use crate::prelude::*;
+
+use crate::system::gpu::shader::check;
+use crate::system::gpu::shader::glsl;
+
+#[cfg(target_arch="wasm32")]
+pub use wasm::*;
+#[cfg(not(target_arch="wasm32"))]
+pub use native::*;
+
+/// The built-in function signatures, like `vec2 mod(vec2 x, float y)`, one per line.
+const BUILTINS : &str = include_str!("../glsl/overload_builtins.txt");
+
+
+
+// ====================
+// === Redirections ===
+// ====================
+
+/// Returns GLSL code which redirects mangled function names to their original primitive
+/// definitions, skipping the primitives missing in the GLSL version, like `inverse` in GLSL
+/// ES 1.00.
+pub fn builtin_redirections_for(target:glsl::Target) -> String {
+    let redirections = builtin_redirections();
+    if target == glsl::Target::Es300 {
+        return redirections
+    }
+    let available = check::builtins::functions(check::Stage::Fragment,target);
+    redirections.lines().filter(|redirection| {
+        let call = redirection.split("{return ").nth(1).unwrap_or_default();
+        let name = call.split('(').next().unwrap_or_default();
+        available.contains_key(name)
+    }).join("\n")
+}
+
+
+
+// ============
+// === Wasm ===
+// ============
+
+#[cfg(target_arch="wasm32")]
+mod wasm {
+    use super::BUILTINS;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(module = "/src/display/shape/primitive/glsl/overload.js")]
+    extern "C" {
+        #[allow(unsafe_code)]
+        #[wasm_bindgen(js_name = builtin_redirections)]
+        fn builtin_redirections_js(builtins:&str) -> String;
+
+        #[allow(unsafe_code)]
+        #[wasm_bindgen(js_name = allow_overloading)]
+        fn allow_overloading_js(s:&str, builtins:&str) -> String;
+    }
+
+    /// Returns GLSL code which redirects mangled function names to their original primitive
+    /// definitions.
+    pub fn builtin_redirections() -> String {
+        builtin_redirections_js(BUILTINS)
+    }
+
+    /// Mangles the provided GLSL code to allow primitive definitions overloading.
+    pub fn allow_overloading(s:&str) -> String {
+        allow_overloading_js(s,BUILTINS)
+    }
+}
+
+
+
+// ==============
+// === Native ===
+// ==============
+
+#[cfg(not(target_arch="wasm32"))]
+mod native {
+    use crate::prelude::*;
+
+    use super::BUILTINS;
+
+    const MANGLING_PREFIX   : &str   = "overloaded_";
+    const RESERVED_BUILTINS : &[&str] = &["union","sample"];
+
+    /// The non-empty lines of the built-in function signatures list.
+    fn builtins() -> impl Iterator<Item=&'static str> {
+        BUILTINS.lines().map(|line| line.trim()).filter(|line| !line.is_empty())
+    }
+
+    /// Split the signature to the return type, the function name, and the parameters.
+    fn parse_signature(signature:&str) -> (&str,&str,&str) {
+        let malformed     = || panic!("Malformed built-in signature `{}`.",signature);
+        let (head,params) = signature.split_once('(').unwrap_or_else(malformed);
+        let (out,name)    = head.split_once(' ').unwrap_or_else(malformed);
+        (out,name.trim(),params.trim_end_matches(')'))
+    }
+
+    fn builtin_names() -> HashSet<&'static str> {
+        let names = builtins().map(|signature| parse_signature(signature).1);
+        names.chain(RESERVED_BUILTINS.iter().copied()).collect()
+    }
 
-#[wasm_bindgen(module = "/src/display/shape/primitive/glsl/overload.js")]
-extern "C" {
     /// Returns GLSL code which redirects mangled function names to their original primitive
     /// definitions.
-    #[allow(unsafe_code)]
-    pub fn builtin_redirections() -> String;
+    pub fn builtin_redirections() -> String {
+        builtins().map(|signature| {
+            let (out,name,params) = parse_signature(signature);
+            let args = params.split(", ").filter_map(|param| param.split(' ').nth(1)).join(",");
+            format!("{} {}{} ({}) {{return {}({});}}",out,MANGLING_PREFIX,name,params,name,args)
+        }).join("\n")
+    }
 
     /// Mangles the provided GLSL code to allow primitive definitions overloading.
-    #[allow(unsafe_code)]
-    pub fn allow_overloading(s:&str) -> String;
+    pub fn allow_overloading(s:&str) -> String {
+        let names      = builtin_names();
+        let is_start   = |c:char| c.is_ascii_alphabetic() || c == '_';
+        let is_ident   = |c:char| c.is_ascii_alphanumeric() || c == '_';
+        let mut output = String::with_capacity(s.len());
+        let mut rest   = s;
+        while let Some(start) = rest.find(is_start) {
+            output.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let end        = rest.find(|c| !is_ident(c)).unwrap_or(rest.len());
+            let name       = &rest[..end];
+            let is_call    = rest[end..].trim_start_matches(' ').starts_with('(');
+            if is_call && names.contains(name) {
+                output.push_str(MANGLING_PREFIX);
+            }
+            output.push_str(name);
+            rest = &rest[end..];
+        }
+        output.push_str(rest);
+        output
+    }
 }
@@ -4,6 +4,7 @@
 use crate::prelude::*;
 
 use super::def;
+use super::style_watch::StyleWatch;
 
 use crate::display::scene::Scene;
 use crate::display::shape::primitive::shader;
@@ -12,9 +13,13 @@ use crate::display::symbol::geometry::Sprite;
 use crate::display::symbol::geometry::SpriteSystem;
 use crate::display::symbol::material::Material;
 use crate::display::symbol::material;
+use crate::display::symbol::ScopeType;
+use crate::display::style;
+use crate::display::symbol;
 use crate::display;
 use crate::system::gpu::data::attribute;
 use crate::system::gpu::data::buffer::item::Storable;
+use crate::system::gpu::shader::glsl;
 use crate::system::gpu::types::*;
 
 
@@ -69,7 +74,7 @@ impl ShapeSystem {
     // We should handle these attributes in a nicer way. Currently, they are hardcoded here and we
     // use magic to access them in shader builders.
     /// Defines a default material of this system.
-    fn surface_material() -> Material {
+    pub fn surface_material() -> Material {
         let mut material = Material::new();
         material.add_input  ("pixel_ratio"  , 1.0);
         material.add_input  ("z_zoom_1"     , 1.0);
@@ -80,6 +85,24 @@ impl ShapeSystem {
         material
     }
 
+    /// Generates the shader of the shape without binding it to any scene. The `material` should be
+    /// the surface material of this system, extended with the shape parameters. As there is no
+    /// scene, all the shader inputs are declared as uniforms. Thus, the code is not meant to be
+    /// rendered, but to be validated statically, see `system::gpu::shader::check`. The code is
+    /// generated in the provided GLSL version.
+    pub fn shader
+    (shape:&def::AnyShape, material:&Material, pointer_events:bool, target:glsl::Target)
+    -> symbol::shader::builder::Shader {
+        let mut material      = material.clone();
+        let geometry_material = SpriteSystem::geometry_material();
+        material.set_code(shader::builder::Builder::run(shape,pointer_events,target));
+        let inputs   = geometry_material.inputs().iter().chain(material.inputs());
+        let bindings = inputs.map(|(name,decl)| {
+            symbol::shader::VarBinding::new(name.as_str(),decl.clone(),Some(ScopeType::Symbol))
+        }).collect_vec();
+        symbol::shader::generate(&bindings,&geometry_material,&material,target)
+    }
+
     /// Enables or disables pointer events on this shape system. All shapes of a shape system which
     /// has pointer events disabled would be completely transparent for the mouse (they would pass
     /// through all mouse events).
@@ -88,6 +111,13 @@ impl ShapeSystem {
         self.reload_shape();
     }
 
+    /// Sets the GLSL version of the shader. It has to match the version of the WebGL context. See
+    /// the `glsl::Target` docs to learn more.
+    pub fn set_target(&self, target:glsl::Target) {
+        self.sprite_system.symbol().shader().set_target(target);
+        self.reload_shape();
+    }
+
     /// Replaces the shape definition.
     pub fn set_shape<S:Into<def::AnyShape>>(&self, shape:S) {
         let shape = shape.into();
@@ -98,7 +128,9 @@ impl ShapeSystem {
     /// Generates the shape again. It is used after some parameters are changed, like setting new
     /// `pointer_events` value.
     fn reload_shape(&self) {
-        let code = shader::builder::Builder::run(&*self.shape.borrow(),self.pointer_events.get());
+        let target = self.sprite_system.symbol().shader().target();
+        let shape  = &*self.shape.borrow();
+        let code   = shader::builder::Builder::run(shape,self.pointer_events.get(),target);
         self.material.borrow_mut().set_code(code);
         self.reload_material();
     }
@@ -126,6 +158,31 @@ impl display::Object for ShapeSystem {
 
 
 
+// =====================
+// === Shader Checks ===
+// =====================
+
+/// Generator of a shape system shader in the given GLSL version, like the `shader_for` function
+/// defined by the `define_shape_system` macro.
+pub type ShaderGenerator = fn(&StyleWatch,glsl::Target) -> symbol::shader::builder::Shader;
+
+/// Checks the shaders of the named shape systems statically, generating them in all GLSL versions
+/// with an empty style sheet. Panics with the found errors, so it is meant to be used in tests. See
+/// `ShapeSystem::shader` to learn more.
+pub fn check_shape_system_shaders(shaders:&[(&str,ShaderGenerator)]) {
+    let sheet       = style::Sheet::new();
+    let style_watch = StyleWatch::new(&sheet);
+    for &(name,generator) in shaders {
+        for &target in &[glsl::Target::Es300,glsl::Target::Es100] {
+            if let Err(errors) = generator(&style_watch,target).check() {
+                panic!("Invalid {} shape shader ({:?}):\n{}",name,target,errors);
+            }
+        }
+    }
+}
+
+
+
 // ===========================
 // === ShapeSystemInstance ===
 // ===========================
@@ -593,6 +650,29 @@ macro_rules! _define_shape_system {
                     self
                 }
 
+                /// The shader of the shape system, generated without binding it to any scene. See
+                /// the `display::shape::ShapeSystem::shader` docs to learn more.
+                pub fn shader
+                (style_watch:&display::shape::StyleWatch)
+                -> display::symbol::shader::builder::Shader {
+                    Self::shader_for(style_watch,default())
+                }
+
+                /// The shader of the shape system, generated in the provided GLSL version.
+                pub fn shader_for
+                ( style_watch : &display::shape::StyleWatch
+                , target      : gpu::shader::glsl::Target
+                ) -> display::symbol::shader::builder::Shader {
+                    let shape_def    = Self::shape_def(style_watch);
+                    let mut material = display::shape::ShapeSystem::surface_material();
+                    $(
+                        let name = stringify!($gpu_param);
+                        let val  = gpu::data::default::gpu_default::<$gpu_param_type>();
+                        material.add_input(name,val);
+                    )*
+                    display::shape::ShapeSystem::shader(&shape_def,&material,true,target)
+                }
+
                 /// The canvas shape definition.
                 pub fn shape_def
                 (__style_watch__:&display::shape::StyleWatch)
@@ -616,3 +696,75 @@ macro_rules! _define_shape_system {
         }
     };
 }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::data::color;
+    use crate::display::shape::*;
+    use crate::system::gpu::shader::check;
+    use crate::system::gpu::shader::optimizer;
+
+    mod example_shape {
+        use super::*;
+        crate::define_shape_system! {
+            (style:Style,color_rgba:Vector4<f32>) {
+                let circle = Circle(50.px()).translate_x(-(50.0.px()));
+                let rect   = Rect((100.0.px(),100.0.px())).corners_radius(10.px());
+                let shape  = circle + rect;
+                shape.fill(color_rgba).into()
+            }
+        }
+    }
+
+    #[test]
+    fn generated_shaders_are_valid() {
+        check_shape_system_shaders(&
+            [ ("example" , example_shape::ShapeSystem::shader_for)
+            , ("cursor"  , crate::gui::cursor::shape::ShapeSystem::shader_for)
+            ]);
+    }
+
+    #[test]
+    fn generated_es100_shader() {
+        let sheet       = style::Sheet::new();
+        let style_watch = StyleWatch::new(&sheet);
+        let shader      = example_shape::ShapeSystem::shader_for(&style_watch,glsl::Target::Es100);
+        assert!(shader.vertex.starts_with("#version 100"));
+        assert!(shader.fragment.starts_with("#version 100"));
+    }
+
+    #[test]
+    fn optimized_shader_is_valid() {
+        let sheet       = style::Sheet::new();
+        let style_watch = StyleWatch::new(&sheet);
+        let shader      = example_shape::ShapeSystem::shader(&style_watch);
+        for &shorten_identifiers in &[false,true] {
+            let eliminate_dead_code = true;
+            let options             = optimizer::Options {eliminate_dead_code,shorten_identifiers};
+            let (optimized,stats)   = shader.optimize(options).unwrap();
+            assert!(stats.optimized_size * 2 < stats.original_size,"Poor optimization: {}.",stats);
+            if let Err(errors) = optimized.check() {
+                panic!("Invalid optimized shape shader:\n{}",errors);
+            }
+        }
+    }
+
+    #[test]
+    fn undeclared_shader_input() {
+        let radius : Var<Pixels> = "input_radius".into();
+        let shape  : AnyShape    = Circle(radius).fill(color::Rgba::new(1.0,0.0,0.0,1.0)).into();
+        let material = ShapeSystem::surface_material();
+        let shader   = ShapeSystem::shader(&shape,&material,true,default());
+        let errors   = shader.check().unwrap_err();
+        let kind     = check::ErrorKind::UndeclaredInput {name:"input_radius".into()};
+        assert!(errors.0.iter().any(|error| error.kind == kind));
+    }
+}
@@ -326,7 +326,8 @@ impl SpriteSystem {
         shader.set_material          (&surface_material);
     }
 
-    fn geometry_material() -> Material {
+    /// The geometry material of all sprites.
+    pub fn geometry_material() -> Material {
         let mut material = Material::new();
         material.add_input_def  :: <Vector2<f32>> ("size");
         material.add_input_def  :: <Vector2<f32>> ("uv");
@@ -345,7 +346,8 @@ impl SpriteSystem {
         material
     }
 
-    fn surface_material() -> Material {
+    /// The default surface material of sprites.
+    pub fn surface_material() -> Material {
         let mut material = Material::new();
         // FIXME We need to use this output, as we need to declare the same amount of shader
         // FIXME outputs as the number of attachments to framebuffer. We should manage this more
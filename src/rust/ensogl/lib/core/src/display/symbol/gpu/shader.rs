@@ -55,6 +55,8 @@ pub struct ShaderData {
     context           : Option<Context>,
     geometry_material : Material,
     surface_material  : Material,
+    optimizer_options : optimizer::Options,
+    target            : glsl::Target,
     program           : Option<WebGlProgram>,
     dirty             : Dirty,
     logger            : Logger,
@@ -84,17 +86,38 @@ impl {
         self.dirty.set();
     }
 
+    /// Set the optimizations of the generated code. See the `optimizer` module to learn more.
+    pub fn set_optimizer_options(&mut self, options:optimizer::Options) {
+        self.optimizer_options = options;
+        self.dirty.set();
+    }
+
+    /// Set the GLSL version of the generated code. It has to match the version of the WebGL
+    /// context. See the `glsl::Target` docs to learn more.
+    pub fn set_target(&mut self, target:glsl::Target) {
+        self.target = target;
+        self.dirty.set();
+    }
+
+    /// The GLSL version of the generated code.
+    pub fn target(&self) -> glsl::Target {
+        self.target
+    }
+
     /// Creates new shader with attached callback.
     pub fn new<OnMut:CallbackFn>(logger:Logger, stats:&Stats, on_mut:OnMut) -> Self {
         stats.inc_shader_count();
         let context           = default();
         let geometry_material = default();
         let surface_material  = default();
+        let optimizer_options = default();
+        let target            = default();
         let program           = default();
         let dirty_logger      = Logger::sub(&logger,"dirty");
         let dirty             = Dirty::new(dirty_logger,Box::new(on_mut));
         let stats             = stats.clone_ref();
-        Self {context,geometry_material,surface_material,program,dirty,logger,stats}
+        Self {context,geometry_material,surface_material,optimizer_options,target,program,dirty
+             ,logger,stats}
     }
 
     /// Check dirty flags and update the state accordingly.
@@ -105,39 +128,20 @@ impl {
 
                     self.stats.inc_shader_compile_count();
 
-                    let mut shader_cfg     = shader::builder::ShaderConfig::new();
-                    let mut shader_builder = shader::builder::ShaderBuilder::new();
-
-                    for binding in bindings {
-                        let name = &binding.name;
-                        let tp   = &binding.decl.tp;
-                        match binding.scope {
-                            None => {
-                                warning!(self.logger,"[TODO] Default shader values are not \
-                                    implemented. This will cause visual glitches.");
-                                shader_cfg.add_uniform(name,tp);
-                            },
-                            Some(scope_type) => match scope_type {
-                                ScopeType::Symbol => shader_cfg.add_uniform   (name,tp),
-                                ScopeType::Global => shader_cfg.add_uniform   (name,tp),
-                                _                 => shader_cfg.add_attribute (name,tp),
-                            }
-                        }
+                    if bindings.iter().any(|binding| binding.scope.is_none()) {
+                        warning!(self.logger,"[TODO] Default shader values are not \
+                            implemented. This will cause visual glitches.");
                     }
-
-                    self.geometry_material.outputs().iter().for_each(|(name,decl)|{
-                        shader_cfg.add_shared_attribute(name,&decl.tp);
-                    });
-
-                    shader_cfg.add_output("color", glsl::PrimType::Vec4);
-                    self.surface_material.outputs().iter().for_each(|(name,decl)|{
-                        shader_cfg.add_output(name,&decl.tp);
-                    });
-
-                    let vertex_code   = self.geometry_material.code().clone();
-                    let fragment_code = self.surface_material.code().clone();
-                    shader_builder.compute(&shader_cfg,vertex_code,fragment_code);
-                    let shader      = shader_builder.build();
+                    let geometry    = &self.geometry_material;
+                    let surface     = &self.surface_material;
+                    let shader      = generate(bindings,geometry,surface,self.target);
+                    let shader      = match self.target {
+                        glsl::Target::Es300 =>
+                            optimize(&self.logger,shader,self.optimizer_options),
+                        // The optimizer would evaluate the preprocessor conditionals, including
+                        // the ones checking the platform capabilities.
+                        glsl::Target::Es100 => shader,
+                    };
                     let vert_shader = compile_vertex_shader  (context,&shader.vertex);
                     let frag_shader = compile_fragment_shader(context,&shader.fragment);
                     if let Err(ref err) = frag_shader {
@@ -169,3 +173,80 @@ impl Drop for ShaderData {
         self.stats.dec_shader_count();
     }
 }
+
+
+
+// ==================
+// === Generation ===
+// ==================
+
+/// Generates the shader code of the materials in the given GLSL version. Variables bound to the
+/// symbol and global scopes are declared as uniforms, and the other ones as attributes. Unbound
+/// variables are declared as uniforms as well.
+pub fn generate
+( bindings          : &[VarBinding]
+, geometry_material : &Material
+, surface_material  : &Material
+, target            : glsl::Target
+) -> shader::builder::Shader {
+    let mut shader_cfg     = shader::builder::ShaderConfig::new();
+    let mut shader_builder = shader::builder::ShaderBuilder::new();
+    shader_builder.set_target(target);
+
+    for binding in bindings {
+        let name = &binding.name;
+        let tp   = &binding.decl.tp;
+        match binding.scope {
+            None                     => shader_cfg.add_uniform   (name,tp),
+            Some(ScopeType::Symbol)  => shader_cfg.add_uniform   (name,tp),
+            Some(ScopeType::Global)  => shader_cfg.add_uniform   (name,tp),
+            Some(ScopeType::Mesh(_)) => shader_cfg.add_attribute (name,tp),
+        }
+    }
+
+    geometry_material.outputs().iter().for_each(|(name,decl)|{
+        shader_cfg.add_shared_attribute(name,&decl.tp);
+    });
+
+    shader_cfg.add_output("color", glsl::PrimType::Vec4);
+    surface_material.outputs().iter().for_each(|(name,decl)|{
+        shader_cfg.add_output(name,&decl.tp);
+    });
+
+    let vertex_code   = geometry_material.code().clone();
+    let fragment_code = surface_material.code().clone();
+    shader_builder.compute(&shader_cfg,vertex_code,fragment_code);
+    shader_builder.build()
+}
+
+/// The optimized shaders by their source code and the optimizations performed.
+type OptimizedShaders =
+    HashMap<(shader::builder::Shader,optimizer::Options),shader::builder::Shader>;
+
+thread_local! {
+    /// Many symbols share the same shader, and the optimization is too slow to repeat it for each
+    /// of them.
+    static OPTIMIZED : RefCell<OptimizedShaders> = default();
+}
+
+/// Optimizes the generated shader, logging the code size reduction. If the code cannot be
+/// optimized, it is returned unchanged. The results are cached per shader source.
+fn optimize
+(logger:&Logger, shader:shader::builder::Shader, options:optimizer::Options)
+-> shader::builder::Shader {
+    let key    = (shader,options);
+    let cached = OPTIMIZED.with(|cache| cache.borrow().get(&key).cloned());
+    if let Some(optimized) = cached { return optimized }
+    let optimized = match key.0.optimize(options) {
+        Ok((optimized,stats)) => {
+            debug!(logger,"Optimized the shader code: {stats}.");
+            optimized
+        }
+        Err(error) => {
+            warning!(logger,"Unable to optimize the shader code: {error}.");
+            key.0.clone()
+        }
+    };
+    OPTIMIZED.with(|cache| cache.borrow_mut().insert(key,optimized.clone()));
+    optimized
+}
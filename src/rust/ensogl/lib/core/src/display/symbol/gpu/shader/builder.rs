@@ -2,7 +2,9 @@
 
 use crate::prelude::*;
 
+use crate::system::gpu::shader::check;
 use crate::system::gpu::shader::glsl;
+use crate::system::gpu::shader::optimizer;
 
 use code_builder::HasCodeRepr;
 use std::collections::BTreeMap;
@@ -13,12 +15,29 @@ use std::collections::BTreeMap;
 // === Shader ===
 // ==============
 
-#[derive(Clone,Debug)]
+#[derive(Clone,Debug,Eq,Hash,PartialEq)]
 pub struct Shader {
     pub vertex   : String,
     pub fragment : String,
 }
 
+impl Shader {
+    /// Checks the code statically, without compiling it. See the `check` module to learn more.
+    pub fn check(&self) -> check::Result {
+        check::check_program(&self.vertex,&self.fragment)
+    }
+
+    /// Optimizes the code size of both shader stages. See the `optimizer` module to learn more.
+    pub fn optimize
+    (&self, options:optimizer::Options) -> check::parser::Result<(Self,optimizer::Stats)> {
+        let vertex   = optimizer::optimize(&self.vertex,options)?;
+        let fragment = optimizer::optimize(&self.fragment,options)?;
+        let stats    = vertex.stats + fragment.stats;
+        let shader   = Self {vertex:vertex.code,fragment:fragment.code};
+        Ok((shader,stats))
+    }
+}
+
 
 
 // ====================
@@ -273,15 +292,29 @@ impl CodeTemplate {
 // === ShaderBuilder ===
 // =====================
 
-#[derive(Clone,Debug,Default)]
+#[derive(Clone,Debug)]
 pub struct ShaderBuilder {
     pub vertex   : glsl::Module,
     pub fragment : glsl::Module,
 }
 
+impl Default for ShaderBuilder {
+    fn default() -> Self {
+        let vertex   = glsl::Module::new(glsl::Stage::Vertex);
+        let fragment = glsl::Module::new(glsl::Stage::Fragment);
+        Self {vertex,fragment}
+    }
+}
+
 impl ShaderBuilder {
     pub fn new() -> Self { default() }
 
+    /// Sets the GLSL version of the generated code. See the `glsl::Target` docs to learn more.
+    pub fn set_target(&mut self, target:glsl::Target) {
+        self.vertex.target   = target;
+        self.fragment.target = target;
+    }
+
     pub fn compute
     (&mut self, cfg:&ShaderConfig, vertex_code:CodeTemplate, fragment_code:CodeTemplate) {
         self.gen_precision_code(cfg);
@@ -365,3 +398,153 @@ pub fn mk_out_name      <S:Str> (s:S) -> String { format!("output_{}" , s.as_ref
 pub fn mk_vertex_name   <S:Str> (s:S) -> String { format!("vertex_{}" , s.as_ref()) }
 pub fn mk_fragment_name <S:Str> (s:S) -> String { format!("input_{}"  , s.as_ref()) }
 pub fn mk_uniform_name  <S:Str> (s:S) -> String { format!("input_{}"  , s.as_ref()) }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a shader with all kinds of inputs and outputs, including the integer ones.
+    fn build_shader(target:glsl::Target) -> Shader {
+        let mut cfg = ShaderConfig::new();
+        cfg.add_attribute("position",glsl::PrimType::Vec2);
+        cfg.add_attribute("instance_id",glsl::PrimType::Int);
+        cfg.add_shared_attribute("uv",glsl::PrimType::Vec2);
+        cfg.add_uniform("image",glsl::PrimType::Sampler2d);
+        cfg.add_output("color",glsl::PrimType::Vec4);
+        cfg.add_output("id",glsl::PrimType::Vec4);
+        let vertex_main   = "input_uv = vertex_position;\n\
+            gl_Position = vec4(vertex_position,0.0,1.0);";
+        let fragment_defs = "vec4 fetch(vec2 uv) { return texture(input_image,uv); }";
+        let fragment_main = "output_color = fetch(input_uv);\n\
+            output_id = vec4(float(input_instance_id));";
+        let vertex_code   = CodeTemplate::from_main(vertex_main);
+        let fragment_code = CodeTemplate::new(fragment_defs,fragment_main,"");
+        let mut builder   = ShaderBuilder::new();
+        builder.set_target(target);
+        builder.compute(&cfg,vertex_code,fragment_code);
+        builder.build()
+    }
+
+    #[test]
+    fn es300_code() {
+        let shader = build_shader(glsl::Target::Es300);
+        assert_eq!(shader.vertex,ES300_VERTEX);
+        assert_eq!(shader.fragment,ES300_FRAGMENT);
+        assert!(shader.check().is_ok());
+    }
+
+    #[test]
+    fn es100_code() {
+        let shader = build_shader(glsl::Target::Es100);
+        assert_eq!(shader.vertex,ES100_VERTEX);
+        assert_eq!(shader.fragment,ES100_FRAGMENT);
+        assert!(shader.check().is_ok());
+    }
+
+    #[test]
+    fn es100_code_errors() {
+        // The GLSL ES 3.00 functions are not available in GLSL ES 1.00.
+        let mut shader = build_shader(glsl::Target::Es100);
+        shader.fragment = shader.fragment.replace("texture2D(","texture(");
+        assert!(shader.check().is_err());
+    }
+
+    const ES300_VERTEX : &str = "#version 300 es
+
+precision highp float;
+precision highp int;
+precision highp int;
+
+flat in int vertex_instance_id;
+flat out int input_instance_id;
+in vec2 vertex_position;
+out vec2 input_position;
+out vec2 input_uv;
+uniform sampler2D input_image;
+
+
+void main () {
+    input_instance_id = vertex_instance_id;
+    input_position = vertex_position;
+    input_uv = vertex_position;
+gl_Position = vec4(vertex_position,0.0,1.0);
+}";
+
+    const ES300_FRAGMENT : &str = "#version 300 es
+
+precision highp float;
+precision highp int;
+precision highp int;
+
+flat in int input_instance_id;
+in vec2 input_position;
+in vec2 input_uv;
+uniform sampler2D input_image;
+layout(location=0 )out vec4 output_color;
+layout(location=1 )out vec4 output_id;
+
+vec4 fetch(vec2 uv) { return texture(input_image,uv); }
+void main () {
+    output_color = fetch(input_uv);
+output_id = vec4(float(input_instance_id));
+}";
+
+    const ES100_VERTEX : &str = "#version 100
+
+precision highp float;
+precision highp int;
+precision highp int;
+
+attribute float vertex_instance_id;
+varying float input_instance_id;
+attribute vec2 vertex_position;
+varying vec2 input_position;
+varying vec2 input_uv;
+uniform sampler2D input_image;
+
+
+void main () {
+    input_instance_id = vertex_instance_id;
+    input_position = vertex_position;
+    input_uv = vertex_position;
+gl_Position = vec4(vertex_position,0.0,1.0);
+}";
+
+    const ES100_FRAGMENT : &str = "#version 100
+#extension GL_EXT_draw_buffers : require
+
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+precision highp float;
+#else
+precision mediump float;
+#endif
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+precision highp int;
+#else
+precision mediump int;
+#endif
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+precision highp int;
+#else
+precision mediump int;
+#endif
+
+varying float input_instance_id;
+varying vec2 input_position;
+varying vec2 input_uv;
+uniform sampler2D input_image;
+#define output_color gl_FragData[0]
+#define output_id gl_FragData[1]
+
+vec4 fetch(vec2 uv) { return texture2D(input_image,uv); }
+void main () {
+    output_color = fetch(input_uv);
+output_id = vec4(float(input_instance_id));
+}";
+}
@@ -1,7 +1,11 @@
 #![allow(missing_docs)]
 
+#[warn(missing_docs)]
+pub mod check;
 #[warn(missing_docs)]
 pub mod glsl;
+#[warn(missing_docs)]
+pub mod optimizer;
 
 use enso_prelude::*;
 
@@ -0,0 +1,1132 @@
+//! Static analysis of the GLSL code. It allows validating the generated shaders natively, for
+//! example in tests, instead of discovering the errors at runtime, when WebGL fails to compile
+//! them.
+//!
+//! The checker parses the code (see the `parser` module), resolves all identifiers, and checks the
+//! types of expressions according to the GLSL ES 3.00 rules. The code starting with the
+//! `#version 100` directive is checked against the GLSL ES 1.00 types, operators, built-in
+//! variables, and functions instead, see the `builtins` module. Please note that it is not a
+//! complete GLSL compiler. It focuses on the errors which can be introduced by the code generators, like
+//! undeclared shader inputs, misspelled functions, or mismatched types. It does not check, for
+//! example, the precision qualifiers, the constant expressions, or the limits of the platform.
+
+pub mod builtins;
+pub mod parser;
+
+use crate::prelude::*;
+
+use crate::system::gpu::shader::glsl;
+use crate::system::gpu::shader::glsl::PrimType;
+use crate::system::gpu::shader::glsl::Type;
+
+use code_builder::HasCodeRepr;
+use parser::Expr;
+use parser::ExprKind;
+use parser::Item;
+use parser::ParamQualifier;
+use parser::Statement;
+use parser::Storage;
+use parser::type_name;
+
+pub use glsl::Stage;
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// The prefixes of the names of variables generated for the shader inputs and outputs. See the
+/// `display::symbol::shader::builder` module to learn more.
+const INPUT_PREFIXES : &[&str] = &["input_","vertex_","output_"];
+
+/// The kind of an error found in the code.
+#[derive(Clone,Debug,Fail,PartialEq)]
+#[allow(missing_docs)]
+pub enum ErrorKind {
+    #[fail(display="Syntax error: {}",message)]
+    Syntax { message:String },
+    #[fail(display="Undeclared variable `{}`.",name)]
+    UndeclaredVariable { name:String },
+    #[fail(display="Undeclared shader input `{}`. It is neither a uniform nor an attribute.",name)]
+    UndeclaredInput { name:String },
+    #[fail(display="Undeclared type `{}`.",name)]
+    UndeclaredType { name:String },
+    #[fail(display="Undeclared function `{}`.",name)]
+    UndeclaredFunction { name:String },
+    #[fail(display="No overload of `{}` accepts arguments ({}).",name,args)]
+    NoMatchingOverload { name:String, args:String },
+    #[fail(display="Redefinition of `{}`.",name)]
+    Redefinition { name:String },
+    #[fail(display="Built-in function `{}` cannot be redefined or overloaded.",name)]
+    BuiltinRedefinition { name:String },
+    #[fail(display="Expected type `{}`, found `{}`.",expected,found)]
+    TypeMismatch { expected:String, found:String },
+    #[fail(display="Operator `{}` cannot be applied to {}.",op,operands)]
+    InvalidOperands { op:String, operands:String },
+    #[fail(display="Type `{}` has no field `{}`.",typ,field)]
+    InvalidField { typ:String, field:String },
+    #[fail(display="Type `{}` cannot be indexed.",typ)]
+    InvalidIndex { typ:String },
+    #[fail(display="Type `{}` cannot be constructed from ({}).",typ,args)]
+    InvalidConstructor { typ:String, args:String },
+    #[fail(display="Variable `{}` is read-only.",name)]
+    ReadOnly { name:String },
+    #[fail(display="Expression cannot be assigned to.")]
+    NotAssignable,
+    #[fail(display="`{}` is not allowed here.",keyword)]
+    MisplacedJump { keyword:String },
+    #[fail(display="Missing the `main` function.")]
+    MissingMain,
+    #[fail(display="Fragment shader input `{}` is not an output of the vertex shader.",name)]
+    UnlinkedInput { name:String },
+    #[fail(display="{} is not supported in GLSL ES 1.00.",feature)]
+    UnsupportedInEs100 { feature:String },
+}
+
+/// An error found in the code.
+#[derive(Clone,Debug,Fail,PartialEq)]
+#[fail(display="{} shader, line {}: {}",stage,line,kind)]
+#[allow(missing_docs)]
+pub struct Error {
+    pub stage : Stage,
+    pub line  : usize,
+    pub kind  : ErrorKind,
+}
+
+/// All errors found in the code.
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct Errors(pub Vec<Error>);
+
+impl Errors {
+    fn into_result(self) -> Result {
+        if self.0.is_empty() { Ok(()) } else { Err(self) }
+    }
+}
+
+impl Display for Errors {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,"{}",self.0.iter().join("\n"))
+    }
+}
+
+impl Fail for Errors {}
+
+/// The result of the check.
+pub type Result = std::result::Result<(),Errors>;
+
+
+
+// =================
+// === Signature ===
+// =================
+
+/// Signature of a function.
+#[derive(Clone,Debug,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub struct Signature {
+    pub params : Vec<Type>,
+    pub ret    : Type,
+}
+
+
+
+// ===========
+// === API ===
+// ===========
+
+/// Check the code of a single shader stage.
+pub fn check_code(code:&str, stage:Stage) -> Result {
+    Errors(analyze(code,stage).1).into_result()
+}
+
+/// Check the module of a single shader stage.
+pub fn check_module(module:&glsl::Module) -> Result {
+    check_code(&module.to_code(),module.stage)
+}
+
+/// Check the code of both shader stages, including the linkage between them. Every input of the
+/// fragment shader has to be an output of the vertex shader of the same type.
+pub fn check_program(vertex:&str, fragment:&str) -> Result {
+    let (vertex_items,mut errors)      = analyze(vertex,Stage::Vertex);
+    let (fragment_items,fragment_errs) = analyze(fragment,Stage::Fragment);
+    errors.extend(fragment_errs);
+    if let (Some(vertex_items),Some(fragment_items)) = (vertex_items,fragment_items) {
+        let outputs = interface(&vertex_items,&[Storage::Out,Storage::Varying]);
+        let inputs  = interface(&fragment_items,&[Storage::In,Storage::Varying]);
+        for (line,name,typ) in inputs {
+            let output = outputs.iter().find(|(_,output,_)| output == &name);
+            let kind   = match output {
+                None                             => ErrorKind::UnlinkedInput {name},
+                Some((_,_,output)) if output == &typ => continue,
+                Some((_,_,output))               => {
+                    let expected = type_name(output);
+                    let found    = type_name(&typ);
+                    ErrorKind::TypeMismatch {expected,found}
+                }
+            };
+            errors.push(Error {stage:Stage::Fragment,line,kind});
+        }
+    }
+    Errors(errors).into_result()
+}
+
+/// The GLSL version of the code, as declared by the `#version` directive.
+fn target(code:&str) -> glsl::Target {
+    let version = code.lines().map(|line| line.trim()).find(|line| line.starts_with("#version"));
+    let words   = version.map(|line| line.split_whitespace().collect_vec()).unwrap_or_default();
+    match words.as_slice() {
+        ["#version","100"] => glsl::Target::Es100,
+        _                  => glsl::Target::Es300,
+    }
+}
+
+/// Parse and check the code. Returns the parsed items, unless a syntax error was found.
+fn analyze(code:&str, stage:Stage) -> (Option<Vec<Item>>,Vec<Error>) {
+    match parser::parse(code) {
+        Ok(items) => {
+            let mut checker = Checker::new(stage,target(code));
+            checker.run(&items,code.lines().count());
+            (Some(items),checker.errors)
+        }
+        Err(error) => {
+            let kind = ErrorKind::Syntax {message:error.message};
+            (None,vec![Error {stage,line:error.line,kind}])
+        }
+    }
+}
+
+/// The global variables of the given storage qualifiers: their lines, names, and types.
+fn interface(items:&[Item], storages:&[Storage]) -> Vec<(usize,String,Type)> {
+    let mut variables = vec![];
+    for item in items {
+        if let Item::Global(global) = item {
+            if global.storage.map_or(false,|storage| storages.contains(&storage)) {
+                let declaration = &global.declaration;
+                for declarator in &declaration.declarators {
+                    let array = declarator.array.or(declaration.typ.array);
+                    let typ   = Type {prim:declaration.typ.prim.clone(),array};
+                    variables.push((declaration.line,declarator.name.clone(),typ));
+                }
+            }
+        }
+    }
+    variables
+}
+
+
+
+// ==============
+// === Layout ===
+// ==============
+
+/// The type of the components of scalars, vectors and matrices.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+enum Base { Float, Int, UInt, Bool }
+
+/// The shape of scalars, vectors and matrices. Matrices are described by the number of columns and
+/// rows, respectively.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+enum Shape { Scalar, Vector(usize), Matrix(usize,usize) }
+
+impl Shape {
+    fn components(self) -> usize {
+        match self {
+            Self::Scalar              => 1,
+            Self::Vector(size)        => size,
+            Self::Matrix(cols,rows)   => cols * rows,
+        }
+    }
+}
+
+/// The layout of scalar, vector, and matrix types. Other types have no layout.
+fn layout(typ:&Type) -> Option<(Base,Shape)> {
+    use PrimType::*;
+    if typ.array.is_some() { return None }
+    let layout = match typ.prim {
+        Float           => (Base::Float , Shape::Scalar),
+        Int             => (Base::Int   , Shape::Scalar),
+        UInt            => (Base::UInt  , Shape::Scalar),
+        Bool            => (Base::Bool  , Shape::Scalar),
+        Vec2            => (Base::Float , Shape::Vector(2)),
+        Vec3            => (Base::Float , Shape::Vector(3)),
+        Vec4            => (Base::Float , Shape::Vector(4)),
+        IVec2           => (Base::Int   , Shape::Vector(2)),
+        IVec3           => (Base::Int   , Shape::Vector(3)),
+        IVec4           => (Base::Int   , Shape::Vector(4)),
+        UVec2           => (Base::UInt  , Shape::Vector(2)),
+        UVec3           => (Base::UInt  , Shape::Vector(3)),
+        UVec4           => (Base::UInt  , Shape::Vector(4)),
+        BVec2           => (Base::Bool  , Shape::Vector(2)),
+        BVec3           => (Base::Bool  , Shape::Vector(3)),
+        BVec4           => (Base::Bool  , Shape::Vector(4)),
+        Mat2 | Mat2x2   => (Base::Float , Shape::Matrix(2,2)),
+        Mat3 | Mat3x3   => (Base::Float , Shape::Matrix(3,3)),
+        Mat4 | Mat4x4   => (Base::Float , Shape::Matrix(4,4)),
+        Mat2x3          => (Base::Float , Shape::Matrix(2,3)),
+        Mat2x4          => (Base::Float , Shape::Matrix(2,4)),
+        Mat3x2          => (Base::Float , Shape::Matrix(3,2)),
+        Mat3x4          => (Base::Float , Shape::Matrix(3,4)),
+        Mat4x2          => (Base::Float , Shape::Matrix(4,2)),
+        Mat4x3          => (Base::Float , Shape::Matrix(4,3)),
+        _               => return None,
+    };
+    Some(layout)
+}
+
+/// The type of the given layout. Matrices have always the `Float` base.
+fn numeric_type(base:Base, shape:Shape) -> Type {
+    let prefix = match base {
+        Base::Float => "",
+        Base::Int   => "i",
+        Base::UInt  => "u",
+        Base::Bool  => "b",
+    };
+    let name = match shape {
+        Shape::Scalar => match base {
+            Base::Float => "float".to_string(),
+            Base::Int   => "int".to_string(),
+            Base::UInt  => "uint".to_string(),
+            Base::Bool  => "bool".to_string(),
+        }
+        Shape::Vector(size)      => format!("{}vec{}",prefix,size),
+        Shape::Matrix(cols,rows) => format!("mat{}x{}",cols,rows),
+    };
+    let prim = parser::builtin_type(&name);
+    prim.unwrap_or_else(|| panic!("Invalid numeric type `{}`.",name)).into()
+}
+
+fn type_names(types:&[Type]) -> String {
+    types.iter().map(type_name).join(", ")
+}
+
+/// The number of components selected by the swizzle, like `xy` or `rgba`.
+fn swizzle_size(swizzle:&str, size:usize) -> Option<usize> {
+    const COMPONENTS : &[&str] = &["xyzw","rgba","stpq"];
+    let is_valid = (1..=4).contains(&swizzle.len()) && COMPONENTS.iter().any(|components| {
+        swizzle.chars().all(|component| components[..size].contains(component))
+    });
+    if is_valid { Some(swizzle.len()) } else { None }
+}
+
+/// Check whether the built-in type can be constructed from arguments of the given types.
+fn is_valid_constructor(typ:&Type, args:&[Type]) -> bool {
+    let shape = match layout(typ) {
+        Some((_,shape)) => shape,
+        None            => return false,
+    };
+    let args = match args.iter().map(layout).collect::<Option<Vec<_>>>() {
+        Some(args) => args,
+        None       => return false,
+    };
+    let is_matrix = |shape| matches!(shape,Shape::Matrix(..));
+    match (shape,args.as_slice()) {
+        (_,[])                                    => false,
+        (_,[(_,Shape::Scalar)])                   => true,
+        (Shape::Scalar,[_])                       => true,
+        (Shape::Matrix(..),[(_,Shape::Matrix(..))]) => true,
+        (Shape::Matrix(..),_) if args.iter().any(|(_,shape)| is_matrix(*shape)) => false,
+        _ => {
+            // Every argument has to be used, but the last one may be used partially.
+            let needed = shape.components();
+            let sizes  = args.iter().map(|(_,shape)| shape.components()).collect_vec();
+            let total  = sizes.iter().sum::<usize>();
+            let last   = sizes.last().copied().unwrap_or_default();
+            total >= needed && total - last < needed
+        }
+    }
+}
+
+
+
+// ===============
+// === Checker ===
+// ===============
+
+/// A variable visible in a scope. The type is `None` if it is not known because of a previously
+/// reported error.
+#[derive(Clone,Debug)]
+struct Binding {
+    typ      : Option<Type>,
+    writable : bool,
+}
+
+/// The checker state.
+#[derive(Debug)]
+struct Checker {
+    stage       : Stage,
+    target      : glsl::Target,
+    errors      : Vec<Error>,
+    structs     : HashMap<String,Vec<(Type,String)>>,
+    builtins    : HashMap<String,Vec<Signature>>,
+    functions   : HashMap<String,Vec<Signature>>,
+    /// Functions with bodies, by their names and parameter types.
+    defined     : Vec<(String,Vec<Type>)>,
+    /// The stack of scopes. The first one contains the built-in variables, the second one contains
+    /// the global variables.
+    scopes      : Vec<HashMap<String,Binding>>,
+    return_type : Option<Type>,
+    loop_depth  : usize,
+}
+
+impl Checker {
+    fn new(stage:Stage, target:glsl::Target) -> Self {
+        let errors      = default();
+        let structs     = default();
+        let builtins    = builtins::functions(stage,target);
+        let functions   = default();
+        let defined     = default();
+        let return_type = default();
+        let loop_depth  = default();
+        let variables   = builtins::variables(stage,target).iter().map(|variable| {
+            let prim     = parser::builtin_type(variable.typ);
+            let array    = variable.array;
+            let typ      = prim.map(|prim| Type {prim,array});
+            let writable = variable.writable;
+            (variable.name.to_string(),Binding {typ,writable})
+        }).collect();
+        let scopes = vec![variables,default()];
+        Self {stage,target,errors,structs,builtins,functions,defined,scopes,return_type
+             ,loop_depth}
+    }
+
+    fn run(&mut self, items:&[Item], last_line:usize) {
+        for item in items {
+            self.item(item);
+        }
+        if !self.functions.contains_key("main") {
+            self.error(last_line,ErrorKind::MissingMain);
+        }
+    }
+
+    fn error(&mut self, line:usize, kind:ErrorKind) {
+        let error = Error {stage:self.stage,line,kind};
+        if !self.errors.contains(&error) {
+            self.errors.push(error);
+        }
+    }
+
+    fn type_mismatch(&mut self, line:usize, expected:&Type, found:&Type) {
+        let expected = type_name(expected);
+        let found    = type_name(found);
+        self.error(line,ErrorKind::TypeMismatch {expected,found});
+    }
+
+    /// Report the type mismatch, unless the found type is unknown.
+    fn expect_type(&mut self, line:usize, expected:&Type, found:Option<&Type>) {
+        if let Some(found) = found {
+            if found != expected {
+                self.type_mismatch(line,expected,found);
+            }
+        }
+    }
+
+    /// Check whether the type is declared. Reports an error otherwise. The types missing in the
+    /// GLSL version of the code are reported, but treated as known.
+    fn check_type(&mut self, line:usize, typ:&Type) -> bool {
+        self.check_type_available(line,&typ.prim);
+        match &typ.prim {
+            PrimType::Struct(name) if !self.structs.contains_key(&name.0) => {
+                let name = name.0.clone();
+                self.error(line,ErrorKind::UndeclaredType {name});
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Report an error if the type is missing in the GLSL version of the code.
+    fn check_type_available(&mut self, line:usize, prim:&PrimType) {
+        if !builtins::is_type_available(prim,self.target) {
+            let feature = format!("Type `{}`",type_name(&prim.clone().into()));
+            self.error(line,ErrorKind::UnsupportedInEs100 {feature});
+        }
+    }
+}
+
+
+// === Scopes ===
+
+impl Checker {
+    fn scoped(&mut self, f:impl FnOnce(&mut Self)) {
+        self.scopes.push(default());
+        f(self);
+        self.scopes.pop();
+    }
+
+    fn lookup(&self, name:&str) -> Option<&Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn declare(&mut self, line:usize, name:&str, binding:Binding) {
+        let scope = self.scopes.last_mut().expect("The global scope is never removed.");
+        if scope.contains_key(name) {
+            let name = name.to_string();
+            self.error(line,ErrorKind::Redefinition {name});
+        } else {
+            scope.insert(name.to_string(),binding);
+        }
+    }
+}
+
+
+// === Items ===
+
+impl Checker {
+    fn item(&mut self, item:&Item) {
+        match item {
+            Item::Precision(_)       => {}
+            Item::Struct(structure)  => self.structure(structure),
+            Item::Global(global)     => self.global(global),
+            Item::Function(function) => self.function(function),
+        }
+    }
+
+    fn structure(&mut self, structure:&parser::Struct) {
+        for (typ,_) in &structure.fields {
+            self.check_type(structure.line,typ);
+        }
+        let name         = structure.name.clone();
+        let is_builtin   = parser::builtin_type(&name).is_some();
+        let is_redefined = is_builtin || self.structs.contains_key(&name);
+        if is_redefined {
+            self.error(structure.line,ErrorKind::Redefinition {name});
+        } else {
+            self.structs.insert(name,structure.fields.clone());
+        }
+    }
+
+    fn global(&mut self, global:&parser::Global) {
+        let writable = match global.storage {
+            None | Some(Storage::Out) => true,
+            Some(Storage::Varying)    => self.stage == Stage::Vertex,
+            Some(_)                   => false,
+        };
+        self.declaration(&global.declaration,writable);
+    }
+
+    fn function(&mut self, function:&parser::Function) {
+        let line      = function.line;
+        let name      = function.name.clone();
+        let mut known = self.check_type(line,&function.typ);
+        for param in &function.params {
+            known &= self.check_type(line,&param.typ);
+        }
+        let params    = function.params.iter().map(|param| param.typ.clone()).collect_vec();
+        let ret       = function.typ.clone();
+        let signature = Signature {params:params.clone(),ret};
+        if self.builtins.contains_key(&name) {
+            self.error(line,ErrorKind::BuiltinRedefinition {name:name.clone()});
+        }
+        let overloads = self.functions.entry(name.clone()).or_default();
+        let declared  = overloads.iter().find(|overload| overload.params == signature.params);
+        match declared.map(|overload| overload.ret == signature.ret) {
+            Some(true)  => {}
+            Some(false) => self.error(line,ErrorKind::Redefinition {name:name.clone()}),
+            None        => overloads.push(signature),
+        }
+        if let Some(body) = &function.body {
+            let definition = (name,params);
+            if self.defined.contains(&definition) {
+                self.error(line,ErrorKind::Redefinition {name:definition.0.clone()});
+            } else {
+                self.defined.push(definition);
+            }
+            self.scoped(|this| {
+                for param in &function.params {
+                    if let Some(name) = &param.name {
+                        let typ      = if known { Some(param.typ.clone()) } else { None };
+                        let writable = param.qualifier != ParamQualifier::Const;
+                        this.declare(line,name,Binding {typ,writable});
+                    }
+                }
+                this.return_type = if known { Some(function.typ.clone()) } else { None };
+                for statement in body {
+                    this.statement(statement);
+                }
+                this.return_type = None;
+            });
+        }
+    }
+
+    fn declaration(&mut self, declaration:&parser::Declaration, writable:bool) {
+        let line     = declaration.line;
+        let known    = self.check_type(line,&declaration.typ);
+        let writable = writable && !declaration.constant;
+        for declarator in &declaration.declarators {
+            let array = declarator.array.or(declaration.typ.array);
+            let typ   = Type {prim:declaration.typ.prim.clone(),array};
+            if let Some(init) = &declarator.init {
+                let found = self.expr(init);
+                if known {
+                    self.expect_type(init.line,&typ,found.as_ref());
+                }
+            }
+            let typ = if known { Some(typ) } else { None };
+            self.declare(line,&declarator.name,Binding {typ,writable});
+        }
+    }
+}
+
+
+// === Statements ===
+
+impl Checker {
+    fn statement(&mut self, statement:&Statement) {
+        match statement {
+            Statement::Declaration(declaration) => self.declaration(declaration,true),
+            Statement::Expr(expr) => {
+                self.expr(expr);
+            }
+            Statement::Block(statements) => self.scoped(|this| {
+                for statement in statements {
+                    this.statement(statement);
+                }
+            }),
+            Statement::If {cond,then,otherwise} => {
+                self.condition(cond);
+                self.scoped(|this| this.statement(then));
+                if let Some(otherwise) = otherwise {
+                    self.scoped(|this| this.statement(otherwise));
+                }
+            }
+            Statement::For {init,cond,step,body} => self.scoped(|this| {
+                if let Some(init) = init {
+                    this.statement(init);
+                }
+                if let Some(cond) = cond {
+                    this.condition(cond);
+                }
+                if let Some(step) = step {
+                    this.expr(step);
+                }
+                this.loop_body(body);
+            }),
+            Statement::While {cond,body} => {
+                self.condition(cond);
+                self.loop_body(body);
+            }
+            Statement::DoWhile {body,cond} => {
+                self.loop_body(body);
+                self.condition(cond);
+            }
+            Statement::Return {line,value} => {
+                let found = value.as_ref().map(|value| self.expr(value));
+                let void  = Type::from(PrimType::Void);
+                if let Some(expected) = self.return_type.clone() {
+                    match found {
+                        Some(found) => self.expect_type(*line,&expected,found.as_ref()),
+                        None        => self.expect_type(*line,&expected,Some(&void)),
+                    }
+                }
+            }
+            Statement::Jump {line,keyword} => {
+                let is_allowed = match keyword.as_str() {
+                    "discard" => self.stage == Stage::Fragment,
+                    _         => self.loop_depth > 0,
+                };
+                if !is_allowed {
+                    let keyword = keyword.clone();
+                    self.error(*line,ErrorKind::MisplacedJump {keyword});
+                }
+            }
+            Statement::Empty => {}
+        }
+    }
+
+    fn loop_body(&mut self, body:&Statement) {
+        self.loop_depth += 1;
+        self.scoped(|this| this.statement(body));
+        self.loop_depth -= 1;
+    }
+
+    fn condition(&mut self, cond:&Expr) {
+        let found = self.expr(cond);
+        self.expect_type(cond.line,&PrimType::Bool.into(),found.as_ref());
+    }
+}
+
+
+// === Expressions ===
+
+impl Checker {
+    /// Check the expression, returning its type. The `None` is returned if the type is not known
+    /// because of an error.
+    fn expr(&mut self, expr:&Expr) -> Option<Type> {
+        let line = expr.line;
+        match &expr.kind {
+            ExprKind::Literal(prim)   => {
+                self.check_type_available(line,prim);
+                Some(prim.clone().into())
+            }
+            ExprKind::Variable(name)  => self.variable(line,name),
+            ExprKind::Call {name,args} => {
+                let args = args.iter().map(|arg| self.expr(arg)).collect_vec();
+                self.call(line,name,&args)
+            }
+            ExprKind::Method {base,name,args} => {
+                let base = self.expr(base);
+                for arg in args {
+                    self.expr(arg);
+                }
+                let base = base?;
+                if name == "length" && args.is_empty() && base.array.is_some() {
+                    Some(PrimType::Int.into())
+                } else {
+                    let typ   = type_name(&base);
+                    let field = format!("{}()",name);
+                    self.error(line,ErrorKind::InvalidField {typ,field});
+                    None
+                }
+            }
+            ExprKind::Field {base,field} => {
+                let base = self.expr(base)?;
+                self.field(line,&base,field)
+            }
+            ExprKind::Index {base,index} => {
+                let base  = self.expr(base);
+                let index = self.expr(index);
+                if let Some(index) = index {
+                    let is_integer = matches!(layout(&index),
+                        Some((Base::Int,Shape::Scalar)) | Some((Base::UInt,Shape::Scalar)));
+                    if !is_integer {
+                        self.type_mismatch(line,&PrimType::Int.into(),&index);
+                    }
+                }
+                self.index(line,&base?)
+            }
+            ExprKind::Prefix {op,expr} | ExprKind::Postfix {op,expr} => {
+                let typ = self.expr(expr);
+                if op == "++" || op == "--" {
+                    self.check_assignable(expr);
+                }
+                self.unary(line,op,&typ?)
+            }
+            ExprKind::Binary {op,lhs,rhs} => {
+                let lhs = self.expr(lhs);
+                let rhs = self.expr(rhs);
+                self.binary(line,op,&lhs?,&rhs?)
+            }
+            ExprKind::Assign {op,lhs:target,rhs:value} => {
+                let lhs = self.expr(target);
+                let rhs = self.expr(value);
+                self.check_assignable(target);
+                let (lhs,rhs) = (lhs?,rhs?);
+                if op == "=" {
+                    self.expect_type(line,&lhs,Some(&rhs));
+                } else {
+                    let op = &op[..op.len()-1];
+                    if let Some(result) = self.binary(line,op,&lhs,&rhs) {
+                        self.expect_type(line,&lhs,Some(&result));
+                    }
+                }
+                Some(lhs)
+            }
+            ExprKind::Ternary {cond,then,otherwise} => {
+                self.condition(cond);
+                let then      = self.expr(then);
+                let otherwise = self.expr(otherwise);
+                let (then,otherwise) = (then?,otherwise?);
+                self.expect_type(line,&then,Some(&otherwise));
+                Some(then)
+            }
+            ExprKind::Sequence(exprs) => {
+                let mut types = exprs.iter().map(|expr| self.expr(expr)).collect_vec();
+                types.pop().flatten()
+            }
+        }
+    }
+
+    fn variable(&mut self, line:usize, name:&str) -> Option<Type> {
+        if let Some(binding) = self.lookup(name) {
+            return binding.typ.clone()
+        }
+        let is_input = INPUT_PREFIXES.iter().any(|prefix| name.starts_with(prefix));
+        let kind     = if is_input { ErrorKind::UndeclaredInput {name:name.into()} }
+                       else        { ErrorKind::UndeclaredVariable {name:name.into()} };
+        self.error(line,kind);
+        // The variable is declared with an unknown type to report the error only once.
+        let binding = Binding {typ:None,writable:true};
+        self.scopes[1].insert(name.to_string(),binding);
+        None
+    }
+
+    fn call(&mut self, line:usize, name:&str, args:&[Option<Type>]) -> Option<Type> {
+        let args = args.iter().cloned().collect::<Option<Vec<_>>>();
+        if let Some(prim) = parser::builtin_type(name) {
+            self.check_type_available(line,&prim);
+            let typ = Type::from(prim);
+            if let Some(args) = args {
+                if !is_valid_constructor(&typ,&args) {
+                    let args = type_names(&args);
+                    self.error(line,ErrorKind::InvalidConstructor {typ:name.into(),args});
+                }
+            }
+            return Some(typ)
+        }
+        if let Some(fields) = self.structs.get(name) {
+            if let Some(args) = args {
+                let types    = fields.iter().map(|(typ,_)| typ);
+                let is_valid = args.len() == fields.len() && args.iter().eq(types);
+                if !is_valid {
+                    let args = type_names(&args);
+                    self.error(line,ErrorKind::InvalidConstructor {typ:name.into(),args});
+                }
+            }
+            return Some(PrimType::Struct(name.into()).into())
+        }
+        let functions = self.functions.get(name).into_iter().flatten();
+        let builtins  = self.builtins.get(name).into_iter().flatten();
+        let overloads = functions.chain(builtins).cloned().collect_vec();
+        if overloads.is_empty() {
+            self.error(line,ErrorKind::UndeclaredFunction {name:name.into()});
+            return None
+        }
+        match args {
+            Some(args) => match overloads.iter().find(|overload| overload.params == args) {
+                Some(overload) => Some(overload.ret.clone()),
+                None => {
+                    let args = type_names(&args);
+                    self.error(line,ErrorKind::NoMatchingOverload {name:name.into(),args});
+                    None
+                }
+            }
+            None => {
+                let ret       = &overloads[0].ret;
+                let is_unique = overloads.iter().all(|overload| &overload.ret == ret);
+                if is_unique { Some(ret.clone()) } else { None }
+            }
+        }
+    }
+
+    fn field(&mut self, line:usize, base:&Type, field:&str) -> Option<Type> {
+        if let (PrimType::Struct(name),None) = (&base.prim,base.array) {
+            let mut fields = self.structs.get(&name.0).into_iter().flatten();
+            let found      = fields.find(|(_,name)| name == field).map(|(typ,_)| typ);
+            if let Some(typ) = found {
+                return Some(typ.clone())
+            }
+        }
+        if let Some((base,Shape::Vector(size))) = layout(base) {
+            if let Some(size) = swizzle_size(field,size) {
+                let shape = if size == 1 { Shape::Scalar } else { Shape::Vector(size) };
+                return Some(numeric_type(base,shape))
+            }
+        }
+        let typ   = type_name(base);
+        let field = field.to_string();
+        self.error(line,ErrorKind::InvalidField {typ,field});
+        None
+    }
+
+    fn index(&mut self, line:usize, base:&Type) -> Option<Type> {
+        if base.array.is_some() {
+            return Some(base.prim.clone().into())
+        }
+        match layout(base) {
+            Some((base,Shape::Vector(_)))    => Some(numeric_type(base,Shape::Scalar)),
+            Some((base,Shape::Matrix(_,rows))) => Some(numeric_type(base,Shape::Vector(rows))),
+            _ => {
+                let typ = type_name(base);
+                self.error(line,ErrorKind::InvalidIndex {typ});
+                None
+            }
+        }
+    }
+
+    /// Report an error if the expression cannot be assigned to.
+    fn check_assignable(&mut self, expr:&Expr) {
+        match &expr.kind {
+            ExprKind::Variable(name) => {
+                let is_read_only = self.lookup(name).map_or(false,|binding| !binding.writable);
+                if is_read_only {
+                    self.error(expr.line,ErrorKind::ReadOnly {name:name.clone()});
+                }
+            }
+            ExprKind::Field {base,..} | ExprKind::Index {base,..} => self.check_assignable(base),
+            _ => self.error(expr.line,ErrorKind::NotAssignable),
+        }
+    }
+}
+
+
+// === Operators ===
+
+impl Checker {
+    fn unary(&mut self, line:usize, op:&str, typ:&Type) -> Option<Type> {
+        self.check_operator_available(line,op);
+        let layout   = layout(typ);
+        let is_valid = match op {
+            "!"                   => layout == Some((Base::Bool,Shape::Scalar)),
+            "~"                   => matches!(layout,Some((Base::Int,_)) | Some((Base::UInt,_))),
+            "+" | "-" | "++" | "--" => matches!(layout,Some((base,_)) if base != Base::Bool),
+            _                     => false,
+        };
+        if is_valid { Some(typ.clone()) } else {
+            let op       = op.to_string();
+            let operands = format!("`{}`",type_name(typ));
+            self.error(line,ErrorKind::InvalidOperands {op,operands});
+            None
+        }
+    }
+
+    fn binary(&mut self, line:usize, op:&str, lhs:&Type, rhs:&Type) -> Option<Type> {
+        self.check_operator_available(line,op);
+        let result = binary_result(op,lhs,rhs);
+        if result.is_none() {
+            let op       = op.to_string();
+            let operands = format!("`{}` and `{}`",type_name(lhs),type_name(rhs));
+            self.error(line,ErrorKind::InvalidOperands {op,operands});
+        }
+        result
+    }
+
+    /// Report an error if the operator is missing in the GLSL version of the code.
+    fn check_operator_available(&mut self, line:usize, op:&str) {
+        if !builtins::is_operator_available(op,self.target) {
+            let feature = format!("Operator `{}`",op);
+            self.error(line,ErrorKind::UnsupportedInEs100 {feature});
+        }
+    }
+}
+
+/// The result type of the binary operator, if it can be applied to the operands.
+fn binary_result(op:&str, lhs:&Type, rhs:&Type) -> Option<Type> {
+    let bool_type = Type::from(PrimType::Bool);
+    match op {
+        "==" | "!=" => {
+            let is_comparable = lhs == rhs && lhs.prim != PrimType::Void;
+            if is_comparable { Some(bool_type) } else { None }
+        }
+        "&&" | "||" | "^^" => {
+            let is_bool = lhs == &bool_type && rhs == &bool_type;
+            if is_bool { Some(bool_type) } else { None }
+        }
+        "<" | ">" | "<=" | ">=" => {
+            let (base,shape) = layout(lhs)?;
+            let is_valid     = lhs == rhs && shape == Shape::Scalar && base != Base::Bool;
+            if is_valid { Some(bool_type) } else { None }
+        }
+        "+" | "-" | "*" | "/" => arithmetic_result(op,lhs,rhs),
+        "%" | "&" | "|" | "^" => {
+            let (lhs_base,lhs_shape) = layout(lhs)?;
+            let (rhs_base,rhs_shape) = layout(rhs)?;
+            let is_integer = matches!(lhs_base,Base::Int | Base::UInt) && lhs_base == rhs_base;
+            let shape      = broadcast(lhs_shape,rhs_shape)?;
+            if is_integer { Some(numeric_type(lhs_base,shape)) } else { None }
+        }
+        "<<" | ">>" => {
+            let (lhs_base,lhs_shape) = layout(lhs)?;
+            let (rhs_base,rhs_shape) = layout(rhs)?;
+            let is_integer  = |base| matches!(base,Base::Int | Base::UInt);
+            let is_integers = is_integer(lhs_base) && is_integer(rhs_base);
+            let is_valid    = rhs_shape == Shape::Scalar || rhs_shape == lhs_shape;
+            if is_integers && is_valid { Some(lhs.clone()) } else { None }
+        }
+        _ => None,
+    }
+}
+
+/// The result type of the arithmetic operator, if it can be applied to the operands.
+fn arithmetic_result(op:&str, lhs:&Type, rhs:&Type) -> Option<Type> {
+    let (lhs_base,lhs_shape) = layout(lhs)?;
+    let (rhs_base,rhs_shape) = layout(rhs)?;
+    if lhs_base != rhs_base || lhs_base == Base::Bool { return None }
+    let shape = match (lhs_shape,rhs_shape) {
+        (Shape::Matrix(lhs_cols,lhs_rows),Shape::Matrix(rhs_cols,rhs_rows)) if op == "*" =>
+            if lhs_cols == rhs_rows { Shape::Matrix(rhs_cols,lhs_rows) } else { return None },
+        (Shape::Matrix(cols,rows),Shape::Vector(size)) if op == "*" =>
+            if cols == size { Shape::Vector(rows) } else { return None },
+        (Shape::Vector(size),Shape::Matrix(cols,rows)) if op == "*" =>
+            if rows == size { Shape::Vector(cols) } else { return None },
+        _ => broadcast(lhs_shape,rhs_shape)?,
+    };
+    Some(numeric_type(lhs_base,shape))
+}
+
+/// The shape of the component-wise operation result. Scalars are broadcast to all components of
+/// the other operand.
+fn broadcast(lhs:Shape, rhs:Shape) -> Option<Shape> {
+    match (lhs,rhs) {
+        _ if lhs == rhs       => Some(lhs),
+        (Shape::Scalar,shape) => Some(shape),
+        (shape,Shape::Scalar) => Some(shape),
+        _                     => None,
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER       : &str = "#version 300 es\nprecision highp float;\n";
+    const ES100_HEADER : &str = "#version 100\nprecision highp float;\n";
+
+    fn errors_with_header(header:&str, code:&str, stage:Stage) -> Vec<ErrorKind> {
+        let code = format!("{}{}",header,code);
+        match check_code(&code,stage) {
+            Ok(())      => vec![],
+            Err(errors) => errors.0.into_iter().map(|error| error.kind).collect(),
+        }
+    }
+
+    fn errors(code:&str, stage:Stage) -> Vec<ErrorKind> {
+        errors_with_header(HEADER,code,stage)
+    }
+
+    fn fragment_errors(code:&str) -> Vec<ErrorKind> {
+        errors(code,Stage::Fragment)
+    }
+
+    fn es100_fragment_errors(code:&str) -> Vec<ErrorKind> {
+        errors_with_header(ES100_HEADER,code,Stage::Fragment)
+    }
+
+    fn mismatch(expected:&str, found:&str) -> ErrorKind {
+        ErrorKind::TypeMismatch {expected:expected.into(),found:found.into()}
+    }
+
+    #[test]
+    fn valid_code() {
+        let code = "struct Sdf { float distance; vec2 normal; };\n\
+                    uniform vec2 input_size;\n\
+                    in vec3 input_local;\n\
+                    out vec4 output_color;\n\
+                    const float PI = 3.14;\n\
+                    Sdf circle(vec2 position, float radius) {\n\
+                        return Sdf(length(position) - radius, normalize(position));\n\
+                    }\n\
+                    float grow(float a);\n\
+                    float grow(float a) { return a * 2.0; }\n\
+                    vec2 grow(vec2 a) { return a * 2.0; }\n\
+                    void main() {\n\
+                        mat2 rotation = mat2(cos(PI), -sin(PI), sin(PI), cos(PI));\n\
+                        vec2 position = rotation * input_local.xy / input_size;\n\
+                        Sdf sdf = circle(grow(position), grow(1.0));\n\
+                        int ids[3];\n\
+                        for (int i = 0; i < ids.length(); i++) { ids[i] = i << 1; }\n\
+                        if (sdf.distance > 0.0) discard;\n\
+                        output_color = vec4(vec3(sdf.normal, 1.0).bgr, 1.0);\n\
+                        output_color.rgb *= float(ids[0]) > 0.0 ? 0.5 : 1.0;\n\
+                    }";
+        assert_eq!(fragment_errors(code),vec![]);
+    }
+
+    #[test]
+    fn undeclared_identifiers() {
+        let code = "void main() {\n\
+                        float a = input_time + missing_var + missing_fn(1.0);\n\
+                        MissingType b;\n\
+                        a += input_time;\n\
+                    }";
+        assert_eq!(fragment_errors(code),vec![
+            ErrorKind::UndeclaredInput    {name:"input_time".into()},
+            ErrorKind::UndeclaredVariable {name:"missing_var".into()},
+            ErrorKind::UndeclaredFunction {name:"missing_fn".into()},
+            ErrorKind::UndeclaredType     {name:"MissingType".into()},
+        ]);
+        assert_eq!(fragment_errors("void helper() {}"),vec![ErrorKind::MissingMain]);
+    }
+
+    #[test]
+    fn type_errors() {
+        let code = "float f(float a) { return a; }\n\
+                    void main() {\n\
+                        float a = 1;\n\
+                        vec3 b = vec2(1.0);\n\
+                        vec2 c = vec2(1.0) + vec3(1.0);\n\
+                        float d = f(1);\n\
+                        vec4 e = vec4(vec2(1.0), 1.0);\n\
+                        float g = c.z;\n\
+                        if (a) {}\n\
+                    }";
+        assert_eq!(fragment_errors(code),vec![
+            mismatch("float","int"),
+            mismatch("vec3","vec2"),
+            ErrorKind::InvalidOperands {op:"+".into(), operands:"`vec2` and `vec3`".into()},
+            ErrorKind::NoMatchingOverload {name:"f".into(), args:"int".into()},
+            ErrorKind::InvalidConstructor {typ:"vec4".into(), args:"vec2, float".into()},
+            ErrorKind::InvalidField {typ:"vec2".into(), field:"z".into()},
+            mismatch("bool","float"),
+        ]);
+    }
+
+    #[test]
+    fn statement_errors() {
+        let code = "uniform float input_time;\n\
+                    const float PI = 3.14;\n\
+                    float sin(float a) { return a; }\n\
+                    float f() { return; }\n\
+                    void main() {\n\
+                        float a = 1.0;\n\
+                        float a = 2.0;\n\
+                        input_time = PI;\n\
+                        PI = 1.0;\n\
+                        gl_Position = vec4(1.0);\n\
+                        break;\n\
+                        discard;\n\
+                    }";
+        assert_eq!(errors(code,Stage::Vertex),vec![
+            ErrorKind::BuiltinRedefinition {name:"sin".into()},
+            mismatch("float","void"),
+            ErrorKind::Redefinition {name:"a".into()},
+            ErrorKind::ReadOnly {name:"input_time".into()},
+            ErrorKind::ReadOnly {name:"PI".into()},
+            ErrorKind::MisplacedJump {keyword:"break".into()},
+            ErrorKind::MisplacedJump {keyword:"discard".into()},
+        ]);
+    }
+
+    #[test]
+    fn es100_errors() {
+        let code = "void main() {\n\
+                        uint a = 1u;\n\
+                        uvec3 b = uvec3(a);\n\
+                        int c = 7 % 2;\n\
+                        c <<= 1;\n\
+                        float d = trunc(1.5);\n\
+                        int e = abs(-1);\n\
+                        float f = abs(-1.0) + mod(7.0, 2.0);\n\
+                        gl_FragColor = vec4(f);\n\
+                    }";
+        let unsupported = |feature:&str| ErrorKind::UnsupportedInEs100 {feature:feature.into()};
+        assert_eq!(es100_fragment_errors(code),vec![
+            unsupported("Type `uint`"),
+            unsupported("Type `uvec3`"),
+            unsupported("Operator `%`"),
+            unsupported("Operator `<<`"),
+            ErrorKind::UndeclaredFunction {name:"trunc".into()},
+            ErrorKind::NoMatchingOverload {name:"abs".into(), args:"int".into()},
+        ]);
+        assert_eq!(fragment_errors(&code.replace("gl_FragColor","vec4 g")),vec![]);
+    }
+
+    #[test]
+    fn error_lines() {
+        let code   = format!("{}void main() {{\n    float a = 1.0;\n    a = b;\n}}",HEADER);
+        let errors = check_code(&code,Stage::Fragment).unwrap_err();
+        assert_eq!(errors.to_string(),"fragment shader, line 5: Undeclared variable `b`.");
+        let code   = format!("{}void main() {{\n    float a = ;\n}}",HEADER);
+        let errors = check_code(&code,Stage::Fragment).unwrap_err();
+        assert_eq!(errors.0[0].line,4);
+        assert!(matches!(errors.0[0].kind,ErrorKind::Syntax{..}));
+    }
+
+    #[test]
+    fn linking() {
+        let main     = "void main() { input_uv = vec2(0.0); }";
+        let vertex   = format!("{}out vec2 input_uv;\n{}",HEADER,main);
+        let fragment = |inputs:&str| format!("{}{}\nvoid main() {{}}",HEADER,inputs);
+        assert!(check_program(&vertex,&fragment("in vec2 input_uv;")).is_ok());
+        let errors = check_program(&vertex,&fragment("in vec3 input_uv;\nin float input_id;"));
+        let kinds  = errors.unwrap_err().0.into_iter().map(|error| error.kind).collect_vec();
+        let unlinked = ErrorKind::UnlinkedInput {name:"input_id".into()};
+        assert_eq!(kinds,vec![mismatch("vec2","vec3"),unlinked]);
+    }
+}
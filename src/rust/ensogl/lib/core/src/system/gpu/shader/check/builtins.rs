@@ -0,0 +1,372 @@
+//! Built-in types, operators, variables, and functions of GLSL ES 3.00 and GLSL ES 1.00.
+
+use crate::prelude::*;
+
+use super::Signature;
+use super::Stage;
+use super::parser;
+
+use crate::system::gpu::shader::glsl::PrimType;
+use crate::system::gpu::shader::glsl::Target;
+use crate::system::gpu::shader::glsl::Type;
+
+
+
+// =============
+// === Types ===
+// =============
+
+/// Checks whether the type is available in the GLSL version. GLSL ES 1.00 has no unsigned
+/// integers, no non-square matrices, and no samplers other than `sampler2D` and `samplerCube`.
+pub fn is_type_available(prim:&PrimType, target:Target) -> bool {
+    use PrimType::*;
+    target == Target::Es300 || matches!(prim,
+        Float | Int | Void | Bool | Mat2 | Mat3 | Mat4 | Mat2x2 | Mat3x3 | Mat4x4 | Vec2 | Vec3
+        | Vec4 | IVec2 | IVec3 | IVec4 | BVec2 | BVec3 | BVec4 | Sampler2d | SamplerCube
+        | Struct(_))
+}
+
+
+
+// =================
+// === Operators ===
+// =================
+
+/// Operators reserved in GLSL ES 1.00. The compound assignments are checked as the operators they
+/// are made of.
+const ES300_OPERATORS : &[&str] = &["%","&","|","^","<<",">>","~"];
+
+/// Checks whether the unary or binary operator is available in the GLSL version.
+pub fn is_operator_available(op:&str, target:Target) -> bool {
+    target == Target::Es300 || !ES300_OPERATORS.contains(&op)
+}
+
+
+
+// =================
+// === Variables ===
+// =================
+
+/// A built-in variable.
+#[derive(Clone,Copy,Debug)]
+pub struct Variable {
+    /// The variable's name.
+    pub name     : &'static str,
+    /// The variable's type.
+    pub typ      : &'static str,
+    /// Whether the shader can assign to the variable.
+    pub writable : bool,
+    /// The array size, if the variable is an array.
+    pub array    : Option<usize>,
+}
+
+const VERTEX_VARIABLES : &[Variable] = &[
+    Variable {name:"gl_Position"   , typ:"vec4" , writable:true  , array:None},
+    Variable {name:"gl_PointSize"  , typ:"float", writable:true  , array:None},
+    Variable {name:"gl_VertexID"   , typ:"int"  , writable:false , array:None},
+    Variable {name:"gl_InstanceID" , typ:"int"  , writable:false , array:None},
+];
+
+const FRAGMENT_VARIABLES : &[Variable] = &[
+    Variable {name:"gl_FragCoord"   , typ:"vec4" , writable:false , array:None},
+    Variable {name:"gl_FrontFacing" , typ:"bool" , writable:false , array:None},
+    Variable {name:"gl_PointCoord"  , typ:"vec2" , writable:false , array:None},
+    Variable {name:"gl_FragDepth"   , typ:"float", writable:true  , array:None},
+];
+
+const ES100_VERTEX_VARIABLES : &[Variable] = &[
+    Variable {name:"gl_Position"  , typ:"vec4" , writable:true , array:None},
+    Variable {name:"gl_PointSize" , typ:"float", writable:true , array:None},
+];
+
+/// The size of `gl_FragData` is the minimal number of draw buffers guaranteed by the
+/// `GL_EXT_draw_buffers` extension.
+const ES100_FRAGMENT_VARIABLES : &[Variable] = &[
+    Variable {name:"gl_FragCoord"   , typ:"vec4" , writable:false , array:None},
+    Variable {name:"gl_FrontFacing" , typ:"bool" , writable:false , array:None},
+    Variable {name:"gl_PointCoord"  , typ:"vec2" , writable:false , array:None},
+    Variable {name:"gl_FragColor"   , typ:"vec4" , writable:true  , array:None},
+    Variable {name:"gl_FragData"    , typ:"vec4" , writable:true  , array:Some(4)},
+];
+
+/// Built-in variables available in the shader stage.
+pub fn variables(stage:Stage, target:Target) -> &'static [Variable] {
+    match (target,stage) {
+        (Target::Es300,Stage::Vertex)   => VERTEX_VARIABLES,
+        (Target::Es300,Stage::Fragment) => FRAGMENT_VARIABLES,
+        (Target::Es100,Stage::Vertex)   => ES100_VERTEX_VARIABLES,
+        (Target::Es100,Stage::Fragment) => ES100_FRAGMENT_VARIABLES,
+    }
+}
+
+
+
+// =================
+// === Functions ===
+// =================
+
+/// Signatures of the built-in functions: the name, the return type, and the parameter types.
+///
+/// The signatures use the generic type names of the GLSL specification. The `genType` stands for
+/// `float` and `vec2` - `vec4`. Similarly, `genIType`, `genUType`, and `genBType` stand for the
+/// `int`, `uint`, and `bool` based types. The `vec`, `ivec`, `uvec`, `bvec`, and `mat` stand for
+/// the vectors and square matrices of 2 - 4 dimensions. All generic types of a single signature
+/// have the same dimension. The `g` prefix in samplers stands for the `float`, `int`, and `uint`
+/// based sampler and vector types.
+const FUNCTIONS : &[(&str,&str,&[&str])] = &[
+    // === Angle and Trigonometry ===
+    ("radians"           , "genType"  , &["genType"]),
+    ("degrees"           , "genType"  , &["genType"]),
+    ("sin"               , "genType"  , &["genType"]),
+    ("cos"               , "genType"  , &["genType"]),
+    ("tan"               , "genType"  , &["genType"]),
+    ("asin"              , "genType"  , &["genType"]),
+    ("acos"              , "genType"  , &["genType"]),
+    ("atan"              , "genType"  , &["genType","genType"]),
+    ("atan"              , "genType"  , &["genType"]),
+    ("sinh"              , "genType"  , &["genType"]),
+    ("cosh"              , "genType"  , &["genType"]),
+    ("tanh"              , "genType"  , &["genType"]),
+    ("asinh"             , "genType"  , &["genType"]),
+    ("acosh"             , "genType"  , &["genType"]),
+    ("atanh"             , "genType"  , &["genType"]),
+
+    // === Exponential ===
+    ("pow"               , "genType"  , &["genType","genType"]),
+    ("exp"               , "genType"  , &["genType"]),
+    ("log"               , "genType"  , &["genType"]),
+    ("exp2"              , "genType"  , &["genType"]),
+    ("log2"              , "genType"  , &["genType"]),
+    ("sqrt"              , "genType"  , &["genType"]),
+    ("inversesqrt"       , "genType"  , &["genType"]),
+
+    // === Common ===
+    ("abs"               , "genType"  , &["genType"]),
+    ("abs"               , "genIType" , &["genIType"]),
+    ("sign"              , "genType"  , &["genType"]),
+    ("sign"              , "genIType" , &["genIType"]),
+    ("floor"             , "genType"  , &["genType"]),
+    ("trunc"             , "genType"  , &["genType"]),
+    ("round"             , "genType"  , &["genType"]),
+    ("roundEven"         , "genType"  , &["genType"]),
+    ("ceil"              , "genType"  , &["genType"]),
+    ("fract"             , "genType"  , &["genType"]),
+    ("mod"               , "genType"  , &["genType","float"]),
+    ("mod"               , "genType"  , &["genType","genType"]),
+    ("min"               , "genType"  , &["genType","genType"]),
+    ("min"               , "genType"  , &["genType","float"]),
+    ("min"               , "genIType" , &["genIType","genIType"]),
+    ("min"               , "genIType" , &["genIType","int"]),
+    ("min"               , "genUType" , &["genUType","genUType"]),
+    ("min"               , "genUType" , &["genUType","uint"]),
+    ("max"               , "genType"  , &["genType","genType"]),
+    ("max"               , "genType"  , &["genType","float"]),
+    ("max"               , "genIType" , &["genIType","genIType"]),
+    ("max"               , "genIType" , &["genIType","int"]),
+    ("max"               , "genUType" , &["genUType","genUType"]),
+    ("max"               , "genUType" , &["genUType","uint"]),
+    ("clamp"             , "genType"  , &["genType","genType","genType"]),
+    ("clamp"             , "genType"  , &["genType","float","float"]),
+    ("clamp"             , "genIType" , &["genIType","genIType","genIType"]),
+    ("clamp"             , "genIType" , &["genIType","int","int"]),
+    ("clamp"             , "genUType" , &["genUType","genUType","genUType"]),
+    ("clamp"             , "genUType" , &["genUType","uint","uint"]),
+    ("mix"               , "genType"  , &["genType","genType","genType"]),
+    ("mix"               , "genType"  , &["genType","genType","float"]),
+    ("mix"               , "genType"  , &["genType","genType","genBType"]),
+    ("step"              , "genType"  , &["genType","genType"]),
+    ("step"              , "genType"  , &["float","genType"]),
+    ("smoothstep"        , "genType"  , &["genType","genType","genType"]),
+    ("smoothstep"        , "genType"  , &["float","float","genType"]),
+    ("isnan"             , "genBType" , &["genType"]),
+    ("isinf"             , "genBType" , &["genType"]),
+    ("floatBitsToInt"    , "genIType" , &["genType"]),
+    ("floatBitsToUint"   , "genUType" , &["genType"]),
+    ("intBitsToFloat"    , "genType"  , &["genIType"]),
+    ("uintBitsToFloat"   , "genType"  , &["genUType"]),
+
+    // === Floating-Point Pack and Unpack ===
+    ("packSnorm2x16"     , "uint"     , &["vec2"]),
+    ("unpackSnorm2x16"   , "vec2"     , &["uint"]),
+    ("packUnorm2x16"     , "uint"     , &["vec2"]),
+    ("unpackUnorm2x16"   , "vec2"     , &["uint"]),
+    ("packHalf2x16"      , "uint"     , &["vec2"]),
+    ("unpackHalf2x16"    , "vec2"     , &["uint"]),
+
+    // === Geometric ===
+    ("length"            , "float"    , &["genType"]),
+    ("distance"          , "float"    , &["genType","genType"]),
+    ("dot"               , "float"    , &["genType","genType"]),
+    ("cross"             , "vec3"     , &["vec3","vec3"]),
+    ("normalize"         , "genType"  , &["genType"]),
+    ("faceforward"       , "genType"  , &["genType","genType","genType"]),
+    ("reflect"           , "genType"  , &["genType","genType"]),
+    ("refract"           , "genType"  , &["genType","genType","float"]),
+
+    // === Matrix ===
+    ("matrixCompMult"    , "mat"      , &["mat","mat"]),
+    ("outerProduct"      , "mat"      , &["vec","vec"]),
+    ("transpose"         , "mat"      , &["mat"]),
+    ("determinant"       , "float"    , &["mat"]),
+    ("inverse"           , "mat"      , &["mat"]),
+
+    // === Vector Relational ===
+    ("lessThan"          , "bvec"     , &["vec","vec"]),
+    ("lessThan"          , "bvec"     , &["ivec","ivec"]),
+    ("lessThan"          , "bvec"     , &["uvec","uvec"]),
+    ("lessThanEqual"     , "bvec"     , &["vec","vec"]),
+    ("lessThanEqual"     , "bvec"     , &["ivec","ivec"]),
+    ("lessThanEqual"     , "bvec"     , &["uvec","uvec"]),
+    ("greaterThan"       , "bvec"     , &["vec","vec"]),
+    ("greaterThan"       , "bvec"     , &["ivec","ivec"]),
+    ("greaterThan"       , "bvec"     , &["uvec","uvec"]),
+    ("greaterThanEqual"  , "bvec"     , &["vec","vec"]),
+    ("greaterThanEqual"  , "bvec"     , &["ivec","ivec"]),
+    ("greaterThanEqual"  , "bvec"     , &["uvec","uvec"]),
+    ("equal"             , "bvec"     , &["vec","vec"]),
+    ("equal"             , "bvec"     , &["ivec","ivec"]),
+    ("equal"             , "bvec"     , &["uvec","uvec"]),
+    ("equal"             , "bvec"     , &["bvec","bvec"]),
+    ("notEqual"          , "bvec"     , &["vec","vec"]),
+    ("notEqual"          , "bvec"     , &["ivec","ivec"]),
+    ("notEqual"          , "bvec"     , &["uvec","uvec"]),
+    ("notEqual"          , "bvec"     , &["bvec","bvec"]),
+    ("any"               , "bool"     , &["bvec"]),
+    ("all"               , "bool"     , &["bvec"]),
+    ("not"               , "bvec"     , &["bvec"]),
+
+    // === Texture Lookup ===
+    ("textureSize"       , "ivec2"    , &["gsampler2D","int"]),
+    ("textureSize"       , "ivec3"    , &["gsampler3D","int"]),
+    ("textureSize"       , "ivec2"    , &["gsamplerCube","int"]),
+    ("textureSize"       , "ivec3"    , &["gsampler2DArray","int"]),
+    ("texture"           , "gvec4"    , &["gsampler2D","vec2"]),
+    ("texture"           , "gvec4"    , &["gsampler2D","vec2","float"]),
+    ("texture"           , "gvec4"    , &["gsampler3D","vec3"]),
+    ("texture"           , "gvec4"    , &["gsampler3D","vec3","float"]),
+    ("texture"           , "gvec4"    , &["gsamplerCube","vec3"]),
+    ("texture"           , "gvec4"    , &["gsamplerCube","vec3","float"]),
+    ("texture"           , "gvec4"    , &["gsampler2DArray","vec3"]),
+    ("texture"           , "gvec4"    , &["gsampler2DArray","vec3","float"]),
+    ("texture"           , "float"    , &["sampler2DShadow","vec3"]),
+    ("textureProj"       , "gvec4"    , &["gsampler2D","vec3"]),
+    ("textureProj"       , "gvec4"    , &["gsampler2D","vec4"]),
+    ("textureLod"        , "gvec4"    , &["gsampler2D","vec2","float"]),
+    ("textureLod"        , "gvec4"    , &["gsampler3D","vec3","float"]),
+    ("textureLod"        , "gvec4"    , &["gsamplerCube","vec3","float"]),
+    ("textureLod"        , "gvec4"    , &["gsampler2DArray","vec3","float"]),
+    ("texelFetch"        , "gvec4"    , &["gsampler2D","ivec2","int"]),
+    ("texelFetch"        , "gvec4"    , &["gsampler3D","ivec3","int"]),
+    ("texelFetch"        , "gvec4"    , &["gsampler2DArray","ivec3","int"]),
+    ("textureGrad"       , "gvec4"    , &["gsampler2D","vec2","vec2","vec2"]),
+];
+
+/// Signatures of the built-in functions available only in the fragment shader. See `FUNCTIONS`
+/// to learn more.
+const FRAGMENT_FUNCTIONS : &[(&str,&str,&[&str])] = &[
+    ("dFdx"              , "genType"  , &["genType"]),
+    ("dFdy"              , "genType"  , &["genType"]),
+    ("fwidth"            , "genType"  , &["genType"]),
+];
+
+/// Signatures of the GLSL ES 1.00 texture lookup functions, which replace the GLSL ES 3.00 ones.
+/// See `FUNCTIONS` to learn more.
+const ES100_TEXTURE_FUNCTIONS : &[(&str,&str,&[&str])] = &[
+    ("texture2D"         , "vec4"     , &["sampler2D","vec2"]),
+    ("texture2D"         , "vec4"     , &["sampler2D","vec2","float"]),
+    ("texture2DProj"     , "vec4"     , &["sampler2D","vec3"]),
+    ("texture2DProj"     , "vec4"     , &["sampler2D","vec4"]),
+    ("texture2DLod"      , "vec4"     , &["sampler2D","vec2","float"]),
+    ("textureCube"       , "vec4"     , &["samplerCube","vec3"]),
+    ("textureCube"       , "vec4"     , &["samplerCube","vec3","float"]),
+    ("textureCubeLod"    , "vec4"     , &["samplerCube","vec3","float"]),
+];
+
+/// Functions of `FUNCTIONS` and `FRAGMENT_FUNCTIONS` missing in GLSL ES 1.00, apart from the
+/// texture lookup ones. The derivatives are available only with the `GL_OES_standard_derivatives`
+/// extension, which the generated code does not enable.
+const ES300_FUNCTIONS : &[&str] = &[
+    "sinh", "cosh", "tanh", "asinh", "acosh", "atanh", "trunc", "round", "roundEven", "isnan",
+    "isinf", "floatBitsToInt", "floatBitsToUint", "intBitsToFloat", "uintBitsToFloat",
+    "packSnorm2x16", "unpackSnorm2x16", "packUnorm2x16", "unpackUnorm2x16", "packHalf2x16",
+    "unpackHalf2x16", "outerProduct", "transpose", "determinant", "inverse", "dFdx", "dFdy",
+    "fwidth",
+];
+
+/// Generic types of the signatures which are missing in GLSL ES 1.00. The integer and boolean
+/// overloads of the common functions were introduced in GLSL ES 3.00.
+const ES300_GENERIC_TYPES : &[&str] = &["genIType","genUType","genBType","uvec","uint"];
+
+/// Checks whether the signature of `FUNCTIONS` or `FRAGMENT_FUNCTIONS` is available in GLSL
+/// ES 1.00.
+fn is_es100_function(name:&str, ret:&str, params:&[&str]) -> bool {
+    let is_texture = name.starts_with("texture") || name.starts_with("texel");
+    let mut types  = std::iter::once(&ret).chain(params);
+    let is_es300   = ES300_FUNCTIONS.contains(&name);
+    let uses_es300 = types.any(|typ| ES300_GENERIC_TYPES.contains(typ));
+    !is_texture && !is_es300 && !uses_es300
+}
+
+/// Built-in functions available in the shader stage, by their names. The GLSL ES 1.00 functions
+/// are the subset of the GLSL ES 3.00 ones, with the texture lookup functions replaced.
+pub fn functions(stage:Stage, target:Target) -> HashMap<String,Vec<Signature>> {
+    let fragment_functions : &[_] = match stage {
+        Stage::Vertex   => &[],
+        Stage::Fragment => FRAGMENT_FUNCTIONS,
+    };
+    let all_functions = FUNCTIONS.iter().chain(fragment_functions);
+    let all_functions = all_functions.filter(|(name,ret,params)| {
+        target == Target::Es300 || is_es100_function(name,ret,params)
+    });
+    let es100_functions : &[_] = match target {
+        Target::Es300 => &[],
+        Target::Es100 => ES100_TEXTURE_FUNCTIONS,
+    };
+    let mut functions : HashMap<String,Vec<Signature>> = default();
+    for (name,ret,params) in all_functions.chain(es100_functions) {
+        let signatures = functions.entry(name.to_string()).or_default();
+        signatures.extend(instantiate(ret,params));
+    }
+    functions
+}
+
+/// Instantiate the generic signature for all the dimensions and sampler kinds.
+fn instantiate(ret:&str, params:&[&str]) -> Vec<Signature> {
+    let names      = std::iter::once(ret).chain(params.iter().copied()).collect_vec();
+    let is_vector  = names.iter().any(|name| ["vec","ivec","uvec","bvec","mat"].contains(name));
+    let is_generic = names.iter().any(|name| name.starts_with("gen"));
+    let is_sampler = names.iter().any(|name| name.starts_with('g') && !name.starts_with("gen"));
+    let dimensions = if is_vector { 2..=4 } else if is_generic { 1..=4 } else { 1..=1 };
+    let prefixes   = if is_sampler { &["","i","u"][..] } else { &[""][..] };
+    let mut signatures = vec![];
+    for dimension in dimensions {
+        for prefix in prefixes {
+            let instantiate = |name:&str| -> Type {
+                let name = instantiate_name(name,dimension,prefix);
+                let prim = parser::builtin_type(&name);
+                prim.unwrap_or_else(|| panic!("Unknown built-in type `{}`.",name)).into()
+            };
+            let ret    = instantiate(ret);
+            let params = params.iter().map(|param| instantiate(param)).collect();
+            signatures.push(Signature {params,ret});
+        }
+    }
+    signatures
+}
+
+/// Instantiate the name of a generic type.
+fn instantiate_name(name:&str, dimension:usize, prefix:&str) -> String {
+    let scalar_or_vector = |scalar:&str, vector:&str| {
+        if dimension == 1 { scalar.to_string() } else { format!("{}{}",vector,dimension) }
+    };
+    match name {
+        "genType"                  => scalar_or_vector("float","vec"),
+        "genIType"                 => scalar_or_vector("int","ivec"),
+        "genUType"                 => scalar_or_vector("uint","uvec"),
+        "genBType"                 => scalar_or_vector("bool","bvec"),
+        "vec" | "ivec" | "uvec"    => format!("{}{}",name,dimension),
+        "bvec" | "mat"             => format!("{}{}",name,dimension),
+        _ if name.starts_with('g') => format!("{}{}",prefix,&name[1..]),
+        _                          => name.to_string(),
+    }
+}
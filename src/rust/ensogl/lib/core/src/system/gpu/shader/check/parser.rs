@@ -0,0 +1,1144 @@
+//! A lightweight GLSL parser, used for the static analysis of the generated shaders.
+//!
+//! The parser handles the subset of GLSL ES 3.00 used by the code generators, including the
+//! preprocessor macros (with parameters) used by the GLSL libraries of the shape system. It does
+//! not validate the code in any way, see the `check` module to learn more.
+
+use crate::prelude::*;
+
+use crate::system::gpu::shader::glsl::PrimType;
+use crate::system::gpu::shader::glsl::Type;
+use std::ops::Range;
+
+
+
+// ===================
+// === SyntaxError ===
+// ===================
+
+/// An error of lexing, preprocessing, or parsing the code.
+#[derive(Clone,Debug,Fail,PartialEq)]
+#[fail(display="{}",message)]
+pub struct SyntaxError {
+    /// The line the error was found at, counted from 1.
+    pub line    : usize,
+    /// The description of the error.
+    pub message : String,
+}
+
+impl SyntaxError {
+    /// Constructor.
+    pub fn new(line:usize, message:impl Into<String>) -> Self {
+        let message = message.into();
+        Self {line,message}
+    }
+}
+
+/// The result of parsing.
+pub type Result<T> = std::result::Result<T,SyntaxError>;
+
+
+
+// =============
+// === Token ===
+// =============
+
+/// The kind of a token.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub enum TokenKind {
+    Identifier, Int, UInt, Float, Punctuation
+}
+
+/// A single token of the code.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Token {
+    /// The kind of the token.
+    pub kind         : TokenKind,
+    /// The token's text.
+    pub text         : String,
+    /// The line of the token, counted from 1.
+    pub line         : usize,
+    /// Whether the token is preceded by a whitespace or a comment.
+    pub space_before : bool,
+}
+
+impl Token {
+    /// Check if this is the given punctuation or identifier.
+    pub fn is(&self, text:&str) -> bool {
+        let is_word = matches!(self.kind,TokenKind::Identifier | TokenKind::Punctuation);
+        is_word && self.text == text
+    }
+
+    /// Check if this is an identifier.
+    pub fn is_identifier(&self) -> bool {
+        self.kind == TokenKind::Identifier
+    }
+}
+
+/// Punctuation tokens, the longer ones first.
+const PUNCTUATION : &[&str] = &[
+    "<<=", ">>=",
+    "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "^^", "+=", "-=", "*=", "/=", "%=",
+    "&=", "|=", "^=",
+    "(", ")", "{", "}", "[", "]", ";", ",", ".", "+", "-", "*", "/", "%", "<", ">", "=", "!", "~",
+    "&", "|", "^", "?", ":", "#",
+];
+
+
+
+// =============
+// === Lexer ===
+// =============
+
+/// Split the code into logical lines of tokens. Lines ending with a backslash are joined with the
+/// following ones. Comments are skipped.
+pub fn lex(code:&str) -> Result<Vec<Vec<Token>>> {
+    let chars      = code.chars().collect_vec();
+    let mut lines  = vec![];
+    let mut tokens = vec![];
+    let mut line   = 1;
+    let mut space  = true;
+    let mut offset = 0;
+    while offset < chars.len() {
+        let current = chars[offset];
+        let next    = chars.get(offset + 1).copied();
+        if current == '\n' {
+            lines.push(std::mem::take(&mut tokens));
+            line   += 1;
+            offset += 1;
+            space   = true;
+        } else if current == '\\' {
+            let rest = chars[offset+1..].iter().take_while(|c| **c != '\n');
+            if rest.clone().all(|c| c.is_whitespace()) {
+                offset += rest.count() + 2;
+                line   += 1;
+                space   = true;
+            } else {
+                return Err(SyntaxError::new(line,"Unexpected character `\\`."))
+            }
+        } else if current.is_whitespace() {
+            offset += 1;
+            space   = true;
+        } else if current == '/' && next == Some('/') {
+            offset += chars[offset..].iter().take_while(|c| **c != '\n').count();
+            space   = true;
+        } else if current == '/' && next == Some('*') {
+            let start = line;
+            offset += 2;
+            loop {
+                match chars.get(offset) {
+                    None                                            =>
+                        return Err(SyntaxError::new(start,"Unterminated comment.")),
+                    Some('*') if chars.get(offset + 1) == Some(&'/') => break,
+                    Some('\n')                                      => line += 1,
+                    _                                               => {}
+                }
+                offset += 1;
+            }
+            offset += 2;
+            space   = true;
+        } else {
+            let (kind,length) = lex_token(&chars[offset..]).ok_or_else(|| {
+                SyntaxError::new(line,format!("Unexpected character `{}`.",current))
+            })?;
+            let text         = chars[offset..offset+length].iter().collect();
+            let space_before = space;
+            tokens.push(Token {kind,text,line,space_before});
+            offset += length;
+            space   = false;
+        }
+    }
+    lines.push(tokens);
+    Ok(lines)
+}
+
+/// Lex a single token at the beginning of `chars`, returning its kind and length.
+fn lex_token(chars:&[char]) -> Option<(TokenKind,usize)> {
+    let is_digit = |index:usize| chars.get(index).map_or(false,|c| c.is_ascii_digit());
+    let first    = chars[0];
+    if first.is_alphabetic() || first == '_' {
+        let length = chars.iter().take_while(|c| c.is_alphanumeric() || **c == '_').count();
+        Some((TokenKind::Identifier,length))
+    } else if is_digit(0) || (first == '.' && is_digit(1)) {
+        Some(lex_number(chars))
+    } else {
+        let is_prefix = |punctuation:&&&str| {
+            punctuation.chars().enumerate().all(|(index,c)| chars.get(index) == Some(&c))
+        };
+        let punctuation = PUNCTUATION.iter().find(is_prefix)?;
+        Some((TokenKind::Punctuation,punctuation.len()))
+    }
+}
+
+/// Lex a number literal at the beginning of `chars`, returning its kind and length.
+fn lex_number(chars:&[char]) -> (TokenKind,usize) {
+    let digits   = |from:usize| chars[from..].iter().take_while(|c| c.is_ascii_digit()).count();
+    let is_hex   = chars[0] == '0' && matches!(chars.get(1),Some('x') | Some('X'));
+    let mut kind = TokenKind::Int;
+    let mut end  = if is_hex {
+        2 + chars[2..].iter().take_while(|c| c.is_ascii_hexdigit()).count()
+    } else {
+        digits(0)
+    };
+    if !is_hex && chars.get(end) == Some(&'.') {
+        kind = TokenKind::Float;
+        end += 1 + digits(end + 1);
+    }
+    if !is_hex && matches!(chars.get(end),Some('e') | Some('E')) {
+        let sign = matches!(chars.get(end + 1),Some('+') | Some('-'));
+        let from = end + 1 + sign as usize;
+        if digits(from) > 0 {
+            kind = TokenKind::Float;
+            end  = from + digits(from);
+        }
+    }
+    match chars.get(end) {
+        Some('u') | Some('U') if kind == TokenKind::Int => (TokenKind::UInt,end + 1),
+        Some('f') | Some('F') if kind == TokenKind::Float => (kind,end + 1),
+        _ => (kind,end),
+    }
+}
+
+
+
+// ====================
+// === Preprocessor ===
+// ====================
+
+/// A macro defined with the `#define` directive.
+#[derive(Clone,Debug)]
+struct Macro {
+    params : Option<Vec<String>>,
+    body   : Vec<Token>,
+}
+
+/// Preprocess the code, returning the tokens of the translation unit. Macros (including the ones
+/// with parameters) and the `#ifdef` family of conditionals are supported. Other directives, like
+/// `#version` or `#extension`, are skipped.
+pub fn preprocess(code:&str) -> Result<Vec<Token>> {
+    let mut macros     : HashMap<String,Macro> = default();
+    let mut conditions : Vec<bool>             = default();
+    let mut output     = vec![];
+    let mut chunk      = vec![];
+    for line in lex(code)? {
+        let is_directive = line.first().map_or(false,|token| token.is("#"));
+        let is_active    = conditions.iter().all(|condition| *condition);
+        if !is_directive {
+            if is_active {
+                chunk.extend(line);
+            }
+            continue;
+        }
+        output.extend(expand(&macros,std::mem::take(&mut chunk),&[])?);
+        let line_number = line[0].line;
+        let directive   = line.get(1).map(|token| token.text.as_str()).unwrap_or_default();
+        let name        = line.get(2).filter(|token| token.is_identifier());
+        let expect_name = || name.ok_or_else(|| {
+            SyntaxError::new(line_number,format!("Expected a name after `#{}`.",directive))
+        });
+        match directive {
+            "define" if is_active => {
+                let name = expect_name()?;
+                let (params,body) = match line.get(3) {
+                    Some(token) if token.is("(") && !token.space_before => {
+                        let end = line.iter().position(|token| token.is(")")).ok_or_else(|| {
+                            SyntaxError::new(line_number,"Unterminated macro parameters.")
+                        })?;
+                        let params = line[4..end].iter().filter(|token| token.is_identifier());
+                        let params = params.map(|token| token.text.clone()).collect();
+                        (Some(params),line[end+1..].to_vec())
+                    }
+                    _ => (None,line[3..].to_vec()),
+                };
+                macros.insert(name.text.clone(),Macro {params,body});
+            }
+            "undef" if is_active => {
+                macros.remove(&expect_name()?.text);
+            }
+            "ifdef"  => conditions.push(macros.contains_key(&expect_name()?.text)),
+            "ifndef" => conditions.push(!macros.contains_key(&expect_name()?.text)),
+            "else"   => match conditions.last_mut() {
+                Some(condition) => *condition = !*condition,
+                None            => return Err(SyntaxError::new(line_number,"Unexpected `#else`.")),
+            },
+            "endif"  => if conditions.pop().is_none() {
+                return Err(SyntaxError::new(line_number,"Unexpected `#endif`."))
+            },
+            "if" | "elif" => {
+                let message = format!("Unsupported directive `#{}`.",directive);
+                return Err(SyntaxError::new(line_number,message))
+            }
+            _ => {}
+        }
+    }
+    output.extend(expand(&macros,chunk,&[])?);
+    Ok(output)
+}
+
+/// Expand macros in the tokens. The `disabled` macros are the ones being currently expanded, they
+/// are not expanded again to prevent infinite recursion.
+fn expand
+(macros:&HashMap<String,Macro>, tokens:Vec<Token>, disabled:&[&str]) -> Result<Vec<Token>> {
+    let mut output = vec![];
+    let mut index  = 0;
+    while index < tokens.len() {
+        let token      = &tokens[index];
+        let is_enabled = token.is_identifier() && !disabled.contains(&token.text.as_str());
+        let definition = macros.get(&token.text).filter(|_| is_enabled);
+        let line       = token.line;
+        let relocate   = |tokens:&[Token]| {
+            tokens.iter().map(|token| Token {line,..token.clone()}).collect_vec()
+        };
+        let nested     = disabled.iter().copied().chain(Some(token.text.as_str())).collect_vec();
+        match definition {
+            None => {
+                output.push(token.clone());
+                index += 1;
+            }
+            Some(Macro {params:None,body}) => {
+                output.extend(expand(macros,relocate(body),&nested)?);
+                index += 1;
+            }
+            Some(Macro {params:Some(params),body}) => {
+                if !tokens.get(index + 1).map_or(false,|token| token.is("(")) {
+                    output.push(token.clone());
+                    index += 1;
+                    continue;
+                }
+                let (args,end) = macro_arguments(&tokens,index + 2)?;
+                let no_args    = params.is_empty() && args.len() == 1 && args[0].is_empty();
+                if args.len() != params.len() && !no_args {
+                    let message = format!("Wrong number of arguments of macro `{}`.",token.text);
+                    return Err(SyntaxError::new(token.line,message))
+                }
+                let args = args.into_iter().map(|arg| expand(macros,arg,disabled));
+                let args = args.collect::<Result<Vec<_>>>()?;
+                let mut substituted = vec![];
+                for body_token in body {
+                    let param = params.iter().position(|param| body_token.is_identifier()
+                        && *param == body_token.text);
+                    match param {
+                        Some(param) => substituted.extend(relocate(&args[param])),
+                        None        => substituted.extend(relocate(&[body_token.clone()])),
+                    }
+                }
+                output.extend(expand(macros,substituted,&nested)?);
+                index = end + 1;
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Split the macro arguments, starting at `start`, by the top-level commas. Returns the arguments
+/// and the index of the closing parenthesis.
+fn macro_arguments(tokens:&[Token], start:usize) -> Result<(Vec<Vec<Token>>,usize)> {
+    let mut args  = vec![vec![]];
+    let mut depth = 0;
+    for (index,token) in tokens.iter().enumerate().skip(start) {
+        if token.is(")") && depth == 0 {
+            return Ok((args,index))
+        } else if token.is(",") && depth == 0 {
+            args.push(vec![]);
+        } else {
+            if token.is("(") { depth += 1 }
+            if token.is(")") { depth -= 1 }
+            if let Some(arg) = args.last_mut() { arg.push(token.clone()) }
+        }
+    }
+    let line = tokens.get(start - 1).map_or(0,|token| token.line);
+    Err(SyntaxError::new(line,"Unterminated macro arguments."))
+}
+
+
+
+// ===========
+// === AST ===
+// ===========
+
+/// Storage qualifier of a global variable.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub enum Storage {
+    Const, In, Out, Uniform, Attribute, Varying
+}
+
+/// Qualifier of a function parameter.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub enum ParamQualifier {
+    In, Out, InOut, Const
+}
+
+/// Top-level item of the translation unit.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub enum Item {
+    Precision (Type),
+    Struct    (Struct),
+    Global    (Global),
+    Function  (Function),
+}
+
+/// Structure definition.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct Struct {
+    pub line   : usize,
+    pub name   : String,
+    pub fields : Vec<(Type,String)>,
+}
+
+/// Global variables declaration.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct Global {
+    pub storage     : Option<Storage>,
+    pub declaration : Declaration,
+}
+
+/// Function definition or declaration.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct Function {
+    pub line   : usize,
+    pub typ    : Type,
+    pub name   : String,
+    pub params : Vec<Param>,
+    /// The function body. It is `None` for function declarations (prototypes).
+    pub body   : Option<Vec<Statement>>,
+}
+
+/// Function parameter.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct Param {
+    pub qualifier : ParamQualifier,
+    pub typ       : Type,
+    pub name      : Option<String>,
+}
+
+/// Variables declaration, like `float a = 1.0, b;`.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct Declaration {
+    pub line        : usize,
+    pub constant    : bool,
+    pub typ         : Type,
+    pub declarators : Vec<Declarator>,
+}
+
+/// A single variable of the declaration.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct Declarator {
+    pub name  : String,
+    pub array : Option<usize>,
+    pub init  : Option<Expr>,
+}
+
+/// Statement of a function body.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub enum Statement {
+    Declaration (Declaration),
+    Expr        (Expr),
+    Block       (Vec<Statement>),
+    If          {cond:Expr, then:Box<Statement>, otherwise:Option<Box<Statement>>},
+    For         {init:Option<Box<Statement>>, cond:Option<Expr>, step:Option<Expr>
+                , body:Box<Statement>},
+    While       {cond:Expr, body:Box<Statement>},
+    DoWhile     {body:Box<Statement>, cond:Expr},
+    Return      {line:usize, value:Option<Expr>},
+    /// The `break`, `continue`, or `discard` statement.
+    Jump        {line:usize, keyword:String},
+    Empty,
+}
+
+/// Expression with the line it starts at.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct Expr {
+    pub line : usize,
+    pub kind : ExprKind,
+}
+
+/// The kind of an expression.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub enum ExprKind {
+    /// A literal of a scalar type.
+    Literal   (PrimType),
+    Variable  (String),
+    /// Function call or a constructor.
+    Call      {name:String, args:Vec<Expr>},
+    /// Method call, like `array.length()`.
+    Method    {base:Box<Expr>, name:String, args:Vec<Expr>},
+    Field     {base:Box<Expr>, field:String},
+    Index     {base:Box<Expr>, index:Box<Expr>},
+    Prefix    {op:String, expr:Box<Expr>},
+    Postfix   {op:String, expr:Box<Expr>},
+    Binary    {op:String, lhs:Box<Expr>, rhs:Box<Expr>},
+    Assign    {op:String, lhs:Box<Expr>, rhs:Box<Expr>},
+    Ternary   {cond:Box<Expr>, then:Box<Expr>, otherwise:Box<Expr>},
+    /// Expressions separated with the comma operator.
+    Sequence  (Vec<Expr>),
+}
+
+
+
+// =============
+// === Types ===
+// =============
+
+/// The built-in type of the given name.
+pub fn builtin_type(name:&str) -> Option<PrimType> {
+    use PrimType::*;
+    let prim = match name {
+        "float"                => Float,
+        "int"                  => Int,
+        "uint"                 => UInt,
+        "void"                 => Void,
+        "bool"                 => Bool,
+        "mat2"   | "mat2x2"    => Mat2,
+        "mat3"   | "mat3x3"    => Mat3,
+        "mat4"   | "mat4x4"    => Mat4,
+        "mat2x3"               => Mat2x3,
+        "mat2x4"               => Mat2x4,
+        "mat3x2"               => Mat3x2,
+        "mat3x4"               => Mat3x4,
+        "mat4x2"               => Mat4x2,
+        "mat4x3"               => Mat4x3,
+        "vec2"                 => Vec2,
+        "vec3"                 => Vec3,
+        "vec4"                 => Vec4,
+        "ivec2"                => IVec2,
+        "ivec3"                => IVec3,
+        "ivec4"                => IVec4,
+        "uvec2"                => UVec2,
+        "uvec3"                => UVec3,
+        "uvec4"                => UVec4,
+        "bvec2"                => BVec2,
+        "bvec3"                => BVec3,
+        "bvec4"                => BVec4,
+        "sampler2D"            => Sampler2d,
+        "sampler3D"            => Sampler3d,
+        "samplerCube"          => SamplerCube,
+        "sampler2DShadow"      => Sampler2dShadow,
+        "samplerCubeShadow"    => SamplerCubeShadow,
+        "sampler2DArray"       => Sampler2dArray,
+        "sampler2DArrayShadow" => Sampler2dArrayShadow,
+        "isampler2D"           => ISampler2d,
+        "isampler3D"           => ISampler3d,
+        "isamplerCube"         => ISamplerCube,
+        "isampler2DArray"      => ISampler2dArray,
+        "usampler2D"           => USampler2d,
+        "usampler3D"           => USampler3d,
+        "usamplerCube"         => USamplerCube,
+        "usampler2DArray"      => USampler2dArray,
+        _                      => return None,
+    };
+    Some(prim)
+}
+
+/// The name of the type, as used in the code.
+pub fn type_name(typ:&Type) -> String {
+    let prim = match &typ.prim {
+        PrimType::UInt         => "uint".to_string(),
+        PrimType::Struct(name) => name.0.clone(),
+        prim                   => prim.to_string(),
+    };
+    match typ.array {
+        Some(size) => format!("{}[{}]",prim,size),
+        None       => prim,
+    }
+}
+
+const PRECISIONS     : &[&str] = &["lowp","mediump","highp"];
+const INTERPOLATIONS : &[&str] = &["smooth","flat","centroid","invariant"];
+
+
+
+// ==============
+// === Parser ===
+// ==============
+
+/// Parse the code, returning the top-level items of the translation unit.
+pub fn parse(code:&str) -> Result<Vec<Item>> {
+    let items = parse_tokens(preprocess(code)?)?;
+    Ok(items.into_iter().map(|(item,_)| item).collect())
+}
+
+/// Parse the preprocessed tokens, returning the top-level items of the translation unit along with
+/// the ranges of tokens they were parsed from.
+pub fn parse_tokens(tokens:Vec<Token>) -> Result<Vec<(Item,Range<usize>)>> {
+    let mut parser = Parser {tokens,offset:0};
+    let mut items  = vec![];
+    while !parser.is_eof() {
+        if !parser.accept(";") {
+            let start = parser.offset;
+            let item  = parser.item()?;
+            items.push((item,start..parser.offset));
+        }
+    }
+    Ok(items)
+}
+
+/// Recursive descent parser state.
+#[derive(Debug)]
+struct Parser {
+    tokens : Vec<Token>,
+    offset : usize,
+}
+
+
+// === Helpers ===
+
+impl Parser {
+    fn is_eof(&self) -> bool {
+        self.offset >= self.tokens.len()
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.peek_nth(0)
+    }
+
+    fn peek_nth(&self, n:usize) -> Option<&Token> {
+        self.tokens.get(self.offset + n)
+    }
+
+    fn peek_is(&self, text:&str) -> bool {
+        self.peek().map_or(false,|token| token.is(text))
+    }
+
+    fn line(&self) -> usize {
+        self.peek().or_else(|| self.tokens.last()).map_or(1,|token| token.line)
+    }
+
+    fn error<T>(&self, message:impl Into<String>) -> Result<T> {
+        Err(SyntaxError::new(self.line(),message))
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        match self.peek().cloned() {
+            Some(token) => {
+                self.offset += 1;
+                Ok(token)
+            }
+            None => self.error("Unexpected end of code."),
+        }
+    }
+
+    fn accept(&mut self, text:&str) -> bool {
+        let accepted = self.peek_is(text);
+        if accepted {
+            self.offset += 1;
+        }
+        accepted
+    }
+
+    fn expect(&mut self, text:&str) -> Result<()> {
+        if self.accept(text) { Ok(()) } else {
+            let found = self.peek().map_or("end of code".into(),|token| format!("`{}`",token.text));
+            self.error(format!("Expected `{}`, found {}.",text,found))
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(token) if token.is_identifier() => Ok(self.next()?.text),
+            _                                    => self.error("Expected an identifier."),
+        }
+    }
+
+    fn array_size(&mut self) -> Result<Option<usize>> {
+        if !self.accept("[") { return Ok(None) }
+        let token = self.next()?;
+        let size  = token.text.parse().ok().filter(|_| token.kind == TokenKind::Int);
+        match size {
+            Some(size) => {
+                self.expect("]")?;
+                Ok(Some(size))
+            }
+            None => self.error("Array size has to be an integer literal."),
+        }
+    }
+
+    fn typ(&mut self) -> Result<Type> {
+        let name  = self.identifier()?;
+        let prim  = builtin_type(&name).unwrap_or_else(|| PrimType::Struct(name.into()));
+        let array = self.array_size()?;
+        Ok(Type {prim,array})
+    }
+
+    /// Skip the precision and interpolation qualifiers.
+    fn skip_qualifiers(&mut self) {
+        while PRECISIONS.iter().chain(INTERPOLATIONS).any(|qual| self.peek_is(qual)) {
+            self.offset += 1;
+        }
+    }
+}
+
+
+// === Items ===
+
+impl Parser {
+    fn item(&mut self) -> Result<Item> {
+        if self.accept("precision") {
+            self.skip_qualifiers();
+            let typ = self.typ()?;
+            self.expect(";")?;
+            Ok(Item::Precision(typ))
+        } else if self.peek_is("struct") {
+            self.structure().map(Item::Struct)
+        } else {
+            let storage = self.global_qualifiers()?;
+            let line    = self.line();
+            let typ     = self.typ()?;
+            let name    = self.identifier()?;
+            if self.peek_is("(") {
+                self.function(line,typ,name).map(Item::Function)
+            } else {
+                let constant    = storage == Some(Storage::Const);
+                let declaration = self.declaration_rest(line,constant,typ,name)?;
+                Ok(Item::Global(Global {storage,declaration}))
+            }
+        }
+    }
+
+    fn global_qualifiers(&mut self) -> Result<Option<Storage>> {
+        let mut storage = None;
+        loop {
+            self.skip_qualifiers();
+            if self.accept("layout") {
+                self.expect("(")?;
+                while !self.accept(")") {
+                    self.next()?;
+                }
+                continue;
+            }
+            let qualifier = match self.peek().map(|token| token.text.as_str()) {
+                Some("const")     => Storage::Const,
+                Some("in")        => Storage::In,
+                Some("out")       => Storage::Out,
+                Some("uniform")   => Storage::Uniform,
+                Some("attribute") => Storage::Attribute,
+                Some("varying")   => Storage::Varying,
+                _                 => return Ok(storage),
+            };
+            self.offset += 1;
+            storage = Some(qualifier);
+        }
+    }
+
+    fn structure(&mut self) -> Result<Struct> {
+        let line = self.line();
+        self.expect("struct")?;
+        let name       = self.identifier()?;
+        let mut fields = vec![];
+        self.expect("{")?;
+        while !self.accept("}") {
+            self.skip_qualifiers();
+            let typ = self.typ()?;
+            loop {
+                let name  = self.identifier()?;
+                let array = self.array_size()?.or(typ.array);
+                fields.push((Type {prim:typ.prim.clone(),array},name));
+                if !self.accept(",") { break }
+            }
+            self.expect(";")?;
+        }
+        self.expect(";")?;
+        Ok(Struct {line,name,fields})
+    }
+
+    fn function(&mut self, line:usize, typ:Type, name:String) -> Result<Function> {
+        let mut params = vec![];
+        self.expect("(")?;
+        let is_void = self.peek_is("void") && self.peek_nth(1).map_or(false,|token| token.is(")"));
+        if is_void {
+            self.offset += 1;
+        }
+        while !self.accept(")") {
+            if !params.is_empty() {
+                self.expect(",")?;
+            }
+            params.push(self.param()?);
+        }
+        let body = if self.accept(";") { None } else { Some(self.block()?) };
+        Ok(Function {line,typ,name,params,body})
+    }
+
+    fn param(&mut self) -> Result<Param> {
+        let mut qualifier = ParamQualifier::In;
+        loop {
+            self.skip_qualifiers();
+            qualifier = match self.peek().map(|token| token.text.as_str()) {
+                Some("const") => ParamQualifier::Const,
+                Some("in")    => qualifier,
+                Some("out")   => ParamQualifier::Out,
+                Some("inout") => ParamQualifier::InOut,
+                _             => break,
+            };
+            self.offset += 1;
+        }
+        let mut typ = self.typ()?;
+        let name    = match self.peek() {
+            Some(token) if token.is_identifier() => Some(self.identifier()?),
+            _                                    => None,
+        };
+        typ.array = self.array_size()?.or(typ.array);
+        Ok(Param {qualifier,typ,name})
+    }
+}
+
+
+// === Statements ===
+
+impl Parser {
+    fn block(&mut self) -> Result<Vec<Statement>> {
+        let mut statements = vec![];
+        self.expect("{")?;
+        while !self.accept("}") {
+            statements.push(self.statement()?);
+        }
+        Ok(statements)
+    }
+
+    fn statement(&mut self) -> Result<Statement> {
+        let line    = self.line();
+        let keyword = self.peek().filter(|token| token.is_identifier()).map(|t| t.text.clone());
+        match keyword.as_deref() {
+            _ if self.peek_is("{") => self.block().map(Statement::Block),
+            _ if self.accept(";")  => Ok(Statement::Empty),
+            Some("if") => {
+                self.offset += 1;
+                let cond      = self.parenthesized()?;
+                let then      = Box::new(self.statement()?);
+                let otherwise = if self.accept("else") { Some(Box::new(self.statement()?)) }
+                                else                   { None };
+                Ok(Statement::If {cond,then,otherwise})
+            }
+            Some("for") => {
+                self.offset += 1;
+                self.expect("(")?;
+                let init = if self.accept(";") { None }
+                           else                { Some(Box::new(self.simple_statement()?)) };
+                let cond = if self.peek_is(";") { None } else { Some(self.expr()?) };
+                self.expect(";")?;
+                let step = if self.peek_is(")") { None } else { Some(self.expr()?) };
+                self.expect(")")?;
+                let body = Box::new(self.statement()?);
+                Ok(Statement::For {init,cond,step,body})
+            }
+            Some("while") => {
+                self.offset += 1;
+                let cond = self.parenthesized()?;
+                let body = Box::new(self.statement()?);
+                Ok(Statement::While {cond,body})
+            }
+            Some("do") => {
+                self.offset += 1;
+                let body = Box::new(self.statement()?);
+                self.expect("while")?;
+                let cond = self.parenthesized()?;
+                self.expect(";")?;
+                Ok(Statement::DoWhile {body,cond})
+            }
+            Some("return") => {
+                self.offset += 1;
+                let value = if self.peek_is(";") { None } else { Some(self.expr()?) };
+                self.expect(";")?;
+                Ok(Statement::Return {line,value})
+            }
+            Some(keyword @ "break") | Some(keyword @ "continue") | Some(keyword @ "discard") => {
+                let keyword = keyword.to_string();
+                self.offset += 1;
+                self.expect(";")?;
+                Ok(Statement::Jump {line,keyword})
+            }
+            _ => self.simple_statement(),
+        }
+    }
+
+    /// Declaration or expression statement.
+    fn simple_statement(&mut self) -> Result<Statement> {
+        let line          = self.line();
+        let is_identifier = |token:Option<&Token>| token.map_or(false,Token::is_identifier);
+        let is_qualifier  = self.peek_is("const")
+            || PRECISIONS.iter().any(|precision| self.peek_is(precision));
+        let is_typed      = is_identifier(self.peek()) && is_identifier(self.peek_nth(1));
+        if is_qualifier || is_typed {
+            let constant = self.accept("const");
+            self.skip_qualifiers();
+            let typ  = self.typ()?;
+            let name = self.identifier()?;
+            self.declaration_rest(line,constant,typ,name).map(Statement::Declaration)
+        } else {
+            let expr = self.expr()?;
+            self.expect(";")?;
+            Ok(Statement::Expr(expr))
+        }
+    }
+
+    /// Parse the declaration after its first variable name.
+    fn declaration_rest
+    (&mut self, line:usize, constant:bool, typ:Type, name:String) -> Result<Declaration> {
+        let mut declarators = vec![];
+        let mut name        = name;
+        loop {
+            let array = self.array_size()?;
+            let init  = if self.accept("=") { Some(self.assignment()?) } else { None };
+            declarators.push(Declarator {name,array,init});
+            if !self.accept(",") { break }
+            name = self.identifier()?;
+        }
+        self.expect(";")?;
+        Ok(Declaration {line,constant,typ,declarators})
+    }
+
+    fn parenthesized(&mut self) -> Result<Expr> {
+        self.expect("(")?;
+        let expr = self.expr()?;
+        self.expect(")")?;
+        Ok(expr)
+    }
+}
+
+
+// === Expressions ===
+
+/// Binary operators, grouped by precedence, from the lowest one.
+const BINARY_OPERATORS : &[&[&str]] = &[
+    &["||"],
+    &["^^"],
+    &["&&"],
+    &["|"],
+    &["^"],
+    &["&"],
+    &["==","!="],
+    &["<",">","<=",">="],
+    &["<<",">>"],
+    &["+","-"],
+    &["*","/","%"],
+];
+
+const ASSIGNMENT_OPERATORS : &[&str] =
+    &["=","+=","-=","*=","/=","%=","<<=",">>=","&=","^=","|="];
+
+const PREFIX_OPERATORS : &[&str] = &["++","--","+","-","!","~"];
+
+impl Parser {
+    fn expr(&mut self) -> Result<Expr> {
+        let line      = self.line();
+        let mut exprs = vec![self.assignment()?];
+        while self.accept(",") {
+            exprs.push(self.assignment()?);
+        }
+        if exprs.len() == 1 {
+            Ok(exprs.remove(0))
+        } else {
+            Ok(Expr {line,kind:ExprKind::Sequence(exprs)})
+        }
+    }
+
+    fn assignment(&mut self) -> Result<Expr> {
+        let line = self.line();
+        let lhs  = self.ternary()?;
+        let op   = ASSIGNMENT_OPERATORS.iter().find(|op| self.peek_is(op));
+        match op {
+            Some(op) => {
+                self.offset += 1;
+                let op  = op.to_string();
+                let lhs = Box::new(lhs);
+                let rhs = Box::new(self.assignment()?);
+                Ok(Expr {line,kind:ExprKind::Assign {op,lhs,rhs}})
+            }
+            None => Ok(lhs),
+        }
+    }
+
+    fn ternary(&mut self) -> Result<Expr> {
+        let line = self.line();
+        let cond = self.binary(0)?;
+        if self.accept("?") {
+            let then = Box::new(self.expr()?);
+            self.expect(":")?;
+            let otherwise = Box::new(self.assignment()?);
+            let cond      = Box::new(cond);
+            Ok(Expr {line,kind:ExprKind::Ternary {cond,then,otherwise}})
+        } else {
+            Ok(cond)
+        }
+    }
+
+    fn binary(&mut self, level:usize) -> Result<Expr> {
+        let operators = match BINARY_OPERATORS.get(level) {
+            Some(operators) => operators,
+            None            => return self.prefix(),
+        };
+        let line    = self.line();
+        let mut lhs = self.binary(level + 1)?;
+        while let Some(op) = operators.iter().find(|op| self.peek_is(op)) {
+            self.offset += 1;
+            let op  = op.to_string();
+            let rhs = Box::new(self.binary(level + 1)?);
+            lhs = Expr {line,kind:ExprKind::Binary {op,lhs:Box::new(lhs),rhs}};
+        }
+        Ok(lhs)
+    }
+
+    fn prefix(&mut self) -> Result<Expr> {
+        let line = self.line();
+        match PREFIX_OPERATORS.iter().find(|op| self.peek_is(op)) {
+            Some(op) => {
+                self.offset += 1;
+                let op   = op.to_string();
+                let expr = Box::new(self.prefix()?);
+                Ok(Expr {line,kind:ExprKind::Prefix {op,expr}})
+            }
+            None => self.postfix(),
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Expr> {
+        let line     = self.line();
+        let mut expr = self.primary()?;
+        loop {
+            let kind = if self.accept("[") {
+                let index = Box::new(self.expr()?);
+                self.expect("]")?;
+                ExprKind::Index {base:Box::new(expr),index}
+            } else if self.accept(".") {
+                let name = self.identifier()?;
+                if self.peek_is("(") {
+                    let args = self.arguments()?;
+                    ExprKind::Method {base:Box::new(expr),name,args}
+                } else {
+                    ExprKind::Field {base:Box::new(expr),field:name}
+                }
+            } else if self.peek_is("++") || self.peek_is("--") {
+                let op = self.next()?.text;
+                ExprKind::Postfix {op,expr:Box::new(expr)}
+            } else {
+                return Ok(expr)
+            };
+            expr = Expr {line,kind};
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let token = self.next()?;
+        let line  = token.line;
+        let kind  = match token.kind {
+            TokenKind::Int   => ExprKind::Literal(PrimType::Int),
+            TokenKind::UInt  => ExprKind::Literal(PrimType::UInt),
+            TokenKind::Float => ExprKind::Literal(PrimType::Float),
+            TokenKind::Identifier if token.text == "true" || token.text == "false" =>
+                ExprKind::Literal(PrimType::Bool),
+            TokenKind::Identifier if self.peek_is("(") => {
+                let args = self.arguments()?;
+                ExprKind::Call {name:token.text,args}
+            }
+            TokenKind::Identifier => ExprKind::Variable(token.text),
+            TokenKind::Punctuation if token.text == "(" => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                return Ok(expr)
+            }
+            TokenKind::Punctuation => {
+                let message = format!("Unexpected `{}`.",token.text);
+                return Err(SyntaxError::new(line,message))
+            }
+        };
+        Ok(Expr {line,kind})
+    }
+
+    fn arguments(&mut self) -> Result<Vec<Expr>> {
+        let mut args = vec![];
+        self.expect("(")?;
+        if self.peek_is("void") && self.peek_nth(1).map_or(false,|token| token.is(")")) {
+            self.offset += 1;
+        }
+        while !self.accept(")") {
+            if !args.is_empty() {
+                self.expect(",")?;
+            }
+            args.push(self.assignment()?);
+        }
+        Ok(args)
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(tokens:&[Token]) -> Vec<&str> {
+        tokens.iter().map(|token| token.text.as_str()).collect()
+    }
+
+    #[test]
+    fn lexing() {
+        let code  = "float a = 1.5e-3 + 0x0Fu; // comment\n/* multi\nline */ a <<= 2.;";
+        let lines = lex(code).unwrap();
+        let kinds = lines[0].iter().map(|token| token.kind).collect_vec();
+        assert_eq!(lines.len(),2);
+        assert_eq!(texts(&lines[0]),vec!["float","a","=","1.5e-3","+","0x0Fu",";"]);
+        assert_eq!(kinds[3],TokenKind::Float);
+        assert_eq!(kinds[5],TokenKind::UInt);
+        assert_eq!(texts(&lines[1]),vec!["a","<<=","2.",";"]);
+        assert_eq!(lines[1][0].line,3);
+    }
+
+    #[test]
+    fn preprocessing() {
+        let code = "#version 300 es\n\
+                    #define PI 3.14\n\
+                    #define TAU (2.0*PI)\n\
+                    #define DEF(name,value) \\\n float name() { return value; }\n\
+                    DEF(tau,TAU)\n\
+                    #ifdef UNDEFINED\n\
+                    float skipped;\n\
+                    #endif\n";
+        let tokens = preprocess(code).unwrap();
+        let expected = vec!["float","tau","(",")","{","return","(","2.0","*","3.14",")",";","}"];
+        assert_eq!(texts(&tokens),expected);
+        assert!(tokens.iter().all(|token| token.line == 6));
+    }
+
+    #[test]
+    fn parsing() {
+        let code = "precision highp float;\n\
+                    struct Sdf { float distance; };\n\
+                    layout(location = 0) out vec4 output_color;\n\
+                    Sdf grow(Sdf a, float size) { return Sdf(a.distance - size); }\n\
+                    void main() { for (int i = 0; i < 2; i++) { output_color.rgb *= 0.5; } }";
+        let items = parse(code).unwrap();
+        assert_eq!(items.len(),5);
+        assert!(matches!(&items[1],Item::Struct(s) if s.name == "Sdf" && s.fields.len() == 1));
+        assert!(matches!(&items[2],Item::Global(g) if g.storage == Some(Storage::Out)));
+        match &items[3] {
+            Item::Function(function) => {
+                assert_eq!(function.name,"grow");
+                assert_eq!(function.params.len(),2);
+                assert_eq!(function.line,4);
+            }
+            item => panic!("Unexpected item: {:?}",item),
+        }
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_eq!(parse("void main() {\n float a = ;\n}").unwrap_err().line,2);
+        assert!(parse("void main() { a = (1.0; }").is_err());
+        assert!(parse("/* unterminated").is_err());
+    }
+}
@@ -2,6 +2,8 @@
 
 //! A lightweight GLSL implementation. Based on section the GLSL ES Spec docs:
 //! https://www.khronos.org/registry/OpenGL/specs/es/3.0/GLSL_ES_Specification_3.00.pdf
+//!
+//! The code can be also generated in GLSL ES 1.00, used by WebGL 1.0, see the `Target` docs.
 
 use crate::prelude::*;
 
@@ -492,7 +494,7 @@ impl HasCodeRepr for PrecisionDecl {
 // ============
 
 /// Abstraction for any GLSL type, including array types.
-#[derive(Clone,Debug)]
+#[derive(Clone,Debug,Eq,PartialEq)]
 pub struct Type {
     pub prim  : PrimType,
     pub array : Option<usize>
@@ -598,6 +600,20 @@ impl HasCodeRepr for PrimType {
     }
 }
 
+impl PrimType {
+    /// The floating point type of the same size. Shader inputs and outputs of other types are not
+    /// supported by GLSL ES 1.00.
+    pub fn to_float(&self) -> Self {
+        match self {
+            Self::Int   | Self::UInt  | Self::Bool  => Self::Float,
+            Self::IVec2 | Self::UVec2 | Self::BVec2 => Self::Vec2,
+            Self::IVec3 | Self::UVec3 | Self::BVec3 => Self::Vec3,
+            Self::IVec4 | Self::UVec4 | Self::BVec4 => Self::Vec4,
+            other                                   => other.clone(),
+        }
+    }
+}
+
 impl From<&str> for PrimType {
     fn from(s:&str) -> Self {
         Self::Struct(s.into())
@@ -769,17 +785,61 @@ impl From<&Precision> for Precision {
 // === Module ======================================================================================
 // =================================================================================================
 
+// === Stage ===
+
+/// The shader stage the code is run in.
+#[derive(Clone,Copy,Debug,Eq,Fail,PartialEq)]
+#[allow(missing_docs)]
+pub enum Stage {
+    #[fail(display="vertex")]
+    Vertex,
+    #[fail(display="fragment")]
+    Fragment,
+}
+
+
+// === Target ===
+
+/// The GLSL version the code is generated in.
+///
+/// The GLSL ES 1.00 code is generated from the same AST. The shader inputs and outputs are
+/// declared as attributes and varyings of floating point types, the fragment shader outputs are
+/// bound to the `gl_FragData` array, and the `texture` function calls are replaced with the
+/// `texture2D` ones. The high precision declared for the fragment shader falls back to the medium
+/// one where it is not supported. Please note that the other parts of the raw code are not
+/// translated, so they have to be compatible with both versions.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub enum Target {
+    /// GLSL ES 3.00, used by WebGL 2.0.
+    Es300,
+    /// GLSL ES 1.00, used by WebGL 1.0.
+    Es100,
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        Self::Es300
+    }
+}
+
+
+// === Definition ===
+
 /// Translation unit definition. It represents the whole GLSL file.
 #[derive(Clone,Debug)]
 pub struct Module {
+    pub stage       : Stage,
+    pub target      : Target,
     pub prec_decls  : Vec<PrecisionDecl>,
     pub global_vars : Vec<GlobalVar>,
     pub statements  : Vec<Statement>,
     pub main        : Function
 }
 
-impl Default for Module {
-    fn default() -> Self {
+impl Module {
+    /// Constructor.
+    pub fn new(stage:Stage) -> Self {
+        let target      = default();
         let prec_decls  = default();
         let global_vars = default();
         let statements  = default();
@@ -788,7 +848,7 @@ impl Default for Module {
             ident : "main".into(),
             body  : default()
         };
-        Self {prec_decls,global_vars,statements,main}
+        Self {stage,target,prec_decls,global_vars,statements,main}
     }
 }
 
@@ -822,31 +882,128 @@ impl AddMut<Expr> for Module {
 
 impl HasCodeRepr for Module {
     fn build(&self, builder:&mut CodeBuilder) {
-        builder.add("#version 300 es");
+        match self.target {
+            Target::Es300 => builder.add("#version 300 es"),
+            Target::Es100 => builder.add("#version 100"),
+        };
         builder.newline();
+        if self.uses_draw_buffers() {
+            builder.add("#extension GL_EXT_draw_buffers : require");
+            builder.newline();
+        }
         builder.newline();
 
         for t in &self.prec_decls {
-            builder.add(t);
+            match self.target {
+                Target::Es300 => { builder.add(t); }
+                Target::Es100 => self.build_es100_precision(t,builder),
+            }
             builder.newline();
         }
         builder.newline();
 
         for t in &self.global_vars {
-            builder.add(t);
-            builder.terminator();
+            match self.target {
+                Target::Es300 => {
+                    builder.add(t);
+                    builder.terminator();
+                }
+                Target::Es100 => self.build_es100_global_var(t,builder),
+            }
             builder.newline();
         }
         builder.newline();
 
+        let mut code = CodeBuilder::default();
         for t in &self.statements {
-            builder.add(t);
+            code.add(t);
+            code.newline();
+        }
+        code.add(&self.main);
+        match self.target {
+            Target::Es300 => builder.write(code.buffer),
+            Target::Es100 => builder.write(replace_calls(&code.buffer,"texture","texture2D")),
+        }
+    }
+}
+
+
+// === GLSL ES 1.00 ===
+
+impl Module {
+    /// Checks whether the GLSL ES 1.00 fragment shader writes to multiple draw buffers, which
+    /// requires the `GL_EXT_draw_buffers` extension.
+    fn uses_draw_buffers(&self) -> bool {
+        let is_es100_fragment = self.target == Target::Es100 && self.stage == Stage::Fragment;
+        is_es100_fragment && self.global_vars.iter().any(|var| {
+            let is_output = matches!(var.storage,Some(GlobalVarStorage::OutStorage(_)));
+            is_output && var.layout.map_or(false,|layout| layout.location > 0)
+        })
+    }
+
+    /// Declares the precision in GLSL ES 1.00. The high precision is optional in the fragment
+    /// shaders, so the medium one is declared if the high one is not supported.
+    fn build_es100_precision(&self, decl:&PrecisionDecl, builder:&mut CodeBuilder) {
+        let is_high = matches!(decl.prec,Precision::High);
+        if is_high && self.stage == Stage::Fragment {
+            let fallback = PrecisionDecl::new(Precision::Medium,decl.typ.clone());
+            builder.add("#ifdef GL_FRAGMENT_PRECISION_HIGH");
+            builder.newline();
+            builder.add(decl);
             builder.newline();
+            builder.add("#else");
+            builder.newline();
+            builder.add(&fallback);
+            builder.newline();
+            builder.add("#endif");
+        } else {
+            builder.add(decl);
+        }
+    }
+
+    /// Declares the global variable in GLSL ES 1.00. The fragment shader outputs are defined as
+    /// aliases of the `gl_FragData` array elements.
+    fn build_es100_global_var(&self, var:&GlobalVar, builder:&mut CodeBuilder) {
+        let float_type = Type {prim:var.typ.prim.to_float(),array:var.typ.array};
+        let linkage    = |builder:&mut CodeBuilder, storage:&str| {
+            builder.add(storage).add(&float_type).add(&var.ident);
+            builder.terminator();
+        };
+        match (&var.storage,self.stage) {
+            (Some(GlobalVarStorage::OutStorage(_)),Stage::Fragment) => {
+                let location = var.layout.map_or(0,|layout| layout.location);
+                builder.add(format!("#define {} gl_FragData[{}]",var.ident.0,location));
+            }
+            (Some(GlobalVarStorage::InStorage(_)),Stage::Vertex) => linkage(builder,"attribute"),
+            (Some(GlobalVarStorage::InStorage(_)),Stage::Fragment) |
+            (Some(GlobalVarStorage::OutStorage(_)),Stage::Vertex)  => linkage(builder,"varying"),
+            _ => {
+                builder.add(&var.storage).add(&var.typ).add(&var.ident);
+                builder.terminator();
+            }
         }
-        builder.add(&self.main);
     }
 }
 
+/// Replaces the names of the called functions. Identifiers which are not followed by a parenthesis
+/// are left unchanged.
+fn replace_calls(code:&str, name:&str, new_name:&str) -> String {
+    let is_ident   = |c:char| c.is_ascii_alphanumeric() || c == '_';
+    let mut output = String::with_capacity(code.len());
+    let mut rest   = code;
+    while let Some(start) = rest.find(is_ident) {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end     = rest.find(|c| !is_ident(c)).unwrap_or(rest.len());
+        let ident   = &rest[..end];
+        let is_call = rest[end..].trim_start().starts_with('(');
+        output.push_str(if is_call && ident == name { new_name } else { ident });
+        rest = &rest[end..];
+    }
+    output.push_str(rest);
+    output
+}
+
 
 // ============================
 // === PrimType Conversions ===
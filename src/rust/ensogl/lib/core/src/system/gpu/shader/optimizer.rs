@@ -0,0 +1,386 @@
+//! Size optimization of the GLSL code. The generated shaders include whole GLSL libraries, like the
+//! SDF primitives of the shape system, even if they use just a few functions of them. The smaller
+//! code compiles faster, which shortens the application startup.
+//!
+//! The optimizer removes the comments and redundant whitespaces, expands the preprocessor macros,
+//! and (optionally) removes the unreachable code and shortens the identifiers. It uses the parser
+//! of the `check` module, so it handles the same subset of GLSL.
+
+use crate::prelude::*;
+
+use crate::system::gpu::shader::check;
+use crate::system::gpu::shader::check::parser;
+use crate::system::gpu::shader::check::parser::Item;
+use crate::system::gpu::shader::check::parser::Statement;
+use crate::system::gpu::shader::check::parser::Storage;
+use crate::system::gpu::shader::check::parser::Token;
+use crate::system::gpu::shader::glsl;
+
+use std::cmp::Reverse;
+
+
+
+// ===============
+// === Options ===
+// ===============
+
+/// The optimizations to perform. Comments and redundant whitespaces are always removed.
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
+pub struct Options {
+    /// Remove functions, structures, and global variables which are not reachable from the `main`
+    /// function.
+    pub eliminate_dead_code : bool,
+    /// Rename functions, structures, and variables to the shortest available names. The shader
+    /// inputs and outputs are never renamed, as they are bound by names.
+    pub shorten_identifiers : bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        let eliminate_dead_code = true;
+        let shorten_identifiers = false;
+        Self {eliminate_dead_code,shorten_identifiers}
+    }
+}
+
+
+
+// =============
+// === Stats ===
+// =============
+
+/// The code size before and after the optimization, in bytes.
+#[derive(Clone,Copy,Debug,Default,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub struct Stats {
+    pub original_size  : usize,
+    pub optimized_size : usize,
+}
+
+impl std::ops::Add for Stats {
+    type Output = Self;
+    fn add(self, other:Self) -> Self {
+        let original_size  = self.original_size  + other.original_size;
+        let optimized_size = self.optimized_size + other.optimized_size;
+        Self {original_size,optimized_size}
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = (self.optimized_size * 100).checked_div(self.original_size).unwrap_or(100);
+        write!(f,"{} B -> {} B ({}%)",self.original_size,self.optimized_size,percent)
+    }
+}
+
+
+
+// =================
+// === Optimizer ===
+// =================
+
+/// The optimized code.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct Optimized {
+    pub code  : String,
+    pub stats : Stats,
+}
+
+/// Optimize the code of a single shader stage. Fails if the code cannot be parsed.
+pub fn optimize(code:&str, options:Options) -> parser::Result<Optimized> {
+    let tokens      = parser::preprocess(code)?;
+    let items       = parser::parse_tokens(tokens.clone())?;
+    let items       = items.into_iter().map(|(item,range)| (item,&tokens[range])).collect_vec();
+    let items       = if options.eliminate_dead_code { reachable(items) } else { items };
+    let renames     = if options.shorten_identifiers { shortened_names(&items,&tokens) }
+                      else { default() };
+    let mut output  = directives(code);
+    for (_,item_tokens) in &items {
+        print(item_tokens,&renames,&mut output);
+        output.push('\n');
+    }
+    let original_size  = code.len();
+    let optimized_size = output.len();
+    let stats          = Stats {original_size,optimized_size};
+    Ok(Optimized {code:output,stats})
+}
+
+/// The `#version` and `#extension` directives of the code. They are skipped by the preprocessor,
+/// and have to be kept at the beginning of the code.
+fn directives(code:&str) -> String {
+    let is_kept = |line:&&str| line.starts_with("#version") || line.starts_with("#extension");
+    code.lines().map(|line| line.trim()).filter(is_kept).map(|line| format!("{}\n",line)).collect()
+}
+
+/// Check whether the token refers to a function, a type, or a variable, which is the case for all
+/// identifiers except the struct fields and swizzles.
+fn is_reference(token:&Token, previous:Option<&Token>) -> bool {
+    token.is_identifier() && !previous.map_or(false,|previous| previous.is("."))
+}
+
+/// The tokens paired with their predecessors.
+fn with_previous(tokens:&[Token]) -> impl Iterator<Item=(&Token,Option<&Token>)> {
+    tokens.iter().zip(std::iter::once(None).chain(tokens.iter().map(Some)))
+}
+
+
+// === Dead Code Elimination ===
+
+/// Filter the items reachable from the `main` function and the items which cannot be removed, like
+/// the shader inputs and outputs.
+fn reachable<'t>(items:Vec<(Item,&'t [Token])>) -> Vec<(Item,&'t [Token])> {
+    let mut declarations : HashMap<&str,Vec<usize>> = default();
+    for (index,(item,_)) in items.iter().enumerate() {
+        for name in declared_names(item) {
+            declarations.entry(name).or_default().push(index);
+        }
+    }
+    let roots            = (0..items.len()).filter(|index| is_root(&items[*index].0));
+    let mut queue        = roots.collect_vec();
+    let mut is_reachable = vec![false;items.len()];
+    while let Some(index) = queue.pop() {
+        if !is_reachable[index] {
+            is_reachable[index] = true;
+            for (token,previous) in with_previous(items[index].1) {
+                if is_reference(token,previous) {
+                    queue.extend(declarations.get(token.text.as_str()).into_iter().flatten());
+                }
+            }
+        }
+    }
+    let items = items.into_iter().zip(is_reachable);
+    items.filter(|(_,is_reachable)| *is_reachable).map(|(item,_)| item).collect()
+}
+
+/// The names of functions, structures, and global variables defined by the item.
+fn declared_names(item:&Item) -> Vec<&str> {
+    match item {
+        Item::Precision(_)       => vec![],
+        Item::Struct(structure)  => vec![structure.name.as_str()],
+        Item::Function(function) => vec![function.name.as_str()],
+        Item::Global(global)     => global.declaration.declarators.iter()
+            .map(|declarator| declarator.name.as_str()).collect(),
+    }
+}
+
+/// Check whether the item has to be kept even if it is not used.
+fn is_root(item:&Item) -> bool {
+    match item {
+        Item::Precision(_)       => true,
+        Item::Struct(_)          => false,
+        Item::Function(function) => function.name == "main",
+        Item::Global(global)     => is_interface(global),
+    }
+}
+
+/// Check whether the global variable is a shader input or output.
+fn is_interface(global:&parser::Global) -> bool {
+    global.storage.map_or(false,|storage| storage != Storage::Const)
+}
+
+
+// === Identifiers Shortening ===
+
+/// GLSL ES 3.00 keywords and reserved words which could be generated as new names.
+const KEYWORDS : &[&str] = &[
+    "attribute", "const", "uniform", "varying", "layout", "centroid", "flat", "smooth", "break",
+    "continue", "do", "for", "while", "switch", "case", "default", "if", "else", "in", "out",
+    "inout", "true", "false", "invariant", "discard", "return", "lowp", "mediump", "highp",
+    "precision", "struct", "asm", "class", "union", "enum", "typedef", "template", "this",
+    "goto", "inline", "noinline", "volatile", "public", "static", "extern", "external",
+    "interface", "long", "short", "double", "half", "fixed", "unsigned", "input", "output",
+    "sizeof", "cast", "namespace", "using", "packed", "resource", "patch", "sample", "common",
+    "partition", "active", "filter", "superp", "coherent", "restrict", "readonly", "writeonly",
+];
+
+/// New names of the functions, structures, and variables declared in the code. The most frequently
+/// used names get the shortest replacements.
+fn shortened_names(items:&[(Item,&[Token])], tokens:&[Token]) -> HashMap<String,String> {
+    let mut declared : HashSet<String> = default();
+    let mut fixed    : HashSet<String> = default();
+    for (item,_) in items {
+        match item {
+            Item::Precision(_)      => {}
+            Item::Struct(structure) => {
+                declared.insert(structure.name.clone());
+                fixed.extend(structure.fields.iter().map(|(_,name)| name.clone()));
+            }
+            Item::Global(global) => {
+                let names = global.declaration.declarators.iter().map(|decl| decl.name.clone());
+                if is_interface(global) { fixed.extend(names) } else { declared.extend(names) }
+            }
+            Item::Function(function) => {
+                declared.insert(function.name.clone());
+                declared.extend(function.params.iter().filter_map(|param| param.name.clone()));
+                for statement in function.body.iter().flatten() {
+                    local_names(statement,&mut declared);
+                }
+            }
+        }
+    }
+    fixed.insert("main".into());
+    for target in &[glsl::Target::Es300,glsl::Target::Es100] {
+        let functions = check::builtins::functions(check::Stage::Fragment,*target);
+        fixed.extend(functions.into_iter().map(|(name,_)| name));
+        for stage in &[check::Stage::Vertex,check::Stage::Fragment] {
+            let variables = check::builtins::variables(*stage,*target);
+            fixed.extend(variables.iter().map(|var| var.name.to_string()));
+        }
+    }
+
+    let mut counts : HashMap<&str,usize> = default();
+    for (token,previous) in with_previous(tokens) {
+        if is_reference(token,previous) {
+            *counts.entry(&token.text).or_default() += 1;
+        }
+    }
+    let mut names = declared.iter().filter(|name| !fixed.contains(*name)).collect_vec();
+    names.sort_by_key(|name| (Reverse(counts.get(name.as_str()).copied()),name.as_str()));
+    let is_free = |name:&String| {
+        let is_keyword = KEYWORDS.contains(&name.as_str()) || parser::builtin_type(name).is_some();
+        !is_keyword && !fixed.contains(name) && !counts.contains_key(name.as_str())
+    };
+    let mut free_names = (0..).map(short_name).filter(is_free);
+    names.into_iter().filter_map(|name| Some((name.clone(),free_names.next()?))).collect()
+}
+
+/// Collect the names of local variables declared in the statement.
+fn local_names(statement:&Statement, names:&mut HashSet<String>) {
+    match statement {
+        Statement::Declaration(declaration) => {
+            names.extend(declaration.declarators.iter().map(|decl| decl.name.clone()));
+        }
+        Statement::Block(statements) => {
+            for statement in statements {
+                local_names(statement,names);
+            }
+        }
+        Statement::If {then,otherwise,..} => {
+            local_names(then,names);
+            if let Some(otherwise) = otherwise {
+                local_names(otherwise,names);
+            }
+        }
+        Statement::For {init,body,..} => {
+            if let Some(init) = init {
+                local_names(init,names);
+            }
+            local_names(body,names);
+        }
+        Statement::While   {body,..} => local_names(body,names),
+        Statement::DoWhile {body,..} => local_names(body,names),
+        _                            => {}
+    }
+}
+
+/// The name of the given index in the sequence `a`, `b`, ..., `Z`, `aa`, `ab`, ...
+fn short_name(mut index:usize) -> String {
+    const LETTERS : &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let mut name = String::new();
+    loop {
+        name.insert(0,LETTERS[index % LETTERS.len()] as char);
+        index /= LETTERS.len();
+        if index == 0 { break name }
+        index -= 1;
+    }
+}
+
+
+// === Printing ===
+
+/// Characters which may form multi-character operators.
+const OPERATOR_CHARS : &str = "+-*/%<>=!&|^";
+
+/// Print the tokens with the minimal whitespaces, applying the renames.
+fn print(tokens:&[Token], renames:&HashMap<String,String>, output:&mut String) {
+    let mut last_char = None;
+    for (token,previous) in with_previous(tokens) {
+        let rename = renames.get(&token.text).filter(|_| is_reference(token,previous));
+        let text   = rename.unwrap_or(&token.text);
+        if let (Some(last),Some(first)) = (last_char,text.chars().next()) {
+            let is_word     = |c:char| c.is_alphanumeric() || c == '_';
+            let is_operator = |c:char| OPERATOR_CHARS.contains(c);
+            if is_word(last) && is_word(first) || is_operator(last) && is_operator(first) {
+                output.push(' ');
+            }
+        }
+        output.push_str(text);
+        last_char = text.chars().last();
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE : &str = "#version 300 es
+precision highp float;
+in  vec2 input_position;
+out vec4 output_color;
+
+#define SCALE 2.0
+
+/* Not used by `main`. */
+float unused(float value) { return value; }
+struct Circle { float radius; };
+float scaled(float value) { return value * SCALE; }
+
+// Uses only the `Circle` structure and the `scaled` function.
+void main() {
+    Circle circle  = Circle(scaled(1.0));
+    float  dist    = length(input_position) - circle.radius;
+    output_color   = vec4(dist - -1.0);
+}
+";
+
+    fn optimized(shorten_identifiers:bool) -> Optimized {
+        let eliminate_dead_code = true;
+        optimize(CODE,Options {eliminate_dead_code,shorten_identifiers}).unwrap()
+    }
+
+    #[test]
+    fn dead_code_elimination() {
+        let expected = "#version 300 es
+precision highp float;
+in vec2 input_position;
+out vec4 output_color;
+struct Circle{float radius;};
+float scaled(float value){return value*2.0;}
+void main(){Circle circle=Circle(scaled(1.0));float dist=length(input_position)-circle.radius;\
+output_color=vec4(dist- -1.0);}
+";
+        let output = optimized(false);
+        assert_eq!(output.code,expected);
+        assert_eq!(output.stats.original_size,CODE.len());
+        assert_eq!(output.stats.optimized_size,expected.len());
+        assert!(check::check_code(&output.code,check::Stage::Fragment).is_ok());
+    }
+
+    #[test]
+    fn identifiers_shortening() {
+        let output = optimized(true);
+        let code   = &output.code;
+        assert!(check::check_code(code,check::Stage::Fragment).is_ok());
+        for name in &["input_position","output_color","main","length",".radius"] {
+            assert!(code.contains(name),"Missing `{}` in:\n{}",name,code);
+        }
+        for name in &["Circle","scaled","value","circle","dist"] {
+            assert!(!code.contains(name),"Unexpected `{}` in:\n{}",name,code);
+        }
+        assert!(output.stats.optimized_size < optimized(false).stats.optimized_size);
+    }
+
+    #[test]
+    fn short_names() {
+        let indices = [0,1,51,52,53,52 + 52 * 52];
+        let names   = indices.iter().map(|index| short_name(*index)).collect_vec();
+        assert_eq!(names,vec!["a","b","Z","aa","ab","aaa"]);
+    }
+}
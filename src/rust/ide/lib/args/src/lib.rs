@@ -30,6 +30,7 @@ ensogl::read_args! {
         platform             : web::platform::Platform,
         frame                : bool,
         theme                : String,
+        keymap               : String,
         dark_theme           : bool,
         high_contrast        : bool,
         use_loader           : bool,
@@ -40,5 +41,6 @@ ensogl::read_args! {
         no_data_gathering    : bool,
         is_in_cloud          : bool,
         verbose              : bool,
+        record_session       : bool,
     }
 }
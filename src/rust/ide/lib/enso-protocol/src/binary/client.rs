@@ -62,7 +62,8 @@ pub type Event = crate::common::event::Event<Notification>;
 /// The Engine Services Language Server Binary Protocol Client API.
 #[automock]
 pub trait API {
-    /// Initializes the protocol. Must be called exactly once before making any other calls.
+    /// Initializes the protocol. Must be called before making any other calls, and once again
+    /// after the connection has been reestablished.
     fn init(&self, client_id:Uuid) -> StaticBoxFuture<FallibleResult>;
 
     /// Writes binary data to the file.
@@ -350,6 +351,20 @@ mod tests {
 
 
 
+    #[test]
+    fn test_request_failing_on_disconnect() {
+        let mut fixture = ClientFixture::new();
+        let mut fut     = fixture.client.init(Uuid::new_v4());
+        fixture.transport.expect_binary_message();
+        fut.expect_pending();
+
+        fixture.transport.mock_connection_closed();
+        fixture.executor.run_until_stalled();
+        fut.expect_err();
+    }
+
+
+
     // =============================
     // === Testing Notifications ===
     // =============================
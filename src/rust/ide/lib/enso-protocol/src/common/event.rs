@@ -3,6 +3,8 @@
 /// Event emitted by the RPC handler.
 #[derive(Debug)]
 pub enum Event<N> {
+    /// The handler's transport has been opened, e.g. reconnected after being closed.
+    Opened,
     /// The handler's transport has been closed.
     Closed,
     /// An error has occurred.
@@ -130,8 +130,13 @@ where Id           : Copy + Debug + Display + Hash + Eq + Send + Sync + 'static,
                         Disposition::Ignore => {}
                     }
                 }
-                TransportEvent::Opened => {}
-                TransportEvent::Closed => { self.emit_event(Event::Closed) }
+                TransportEvent::Opened => { self.emit_event(Event::Opened) }
+                TransportEvent::Closed => {
+                    // The replies to the ongoing calls will never come, so their futures are
+                    // cancelled rather than left pending.
+                    self.ongoing_calls.clear();
+                    self.emit_event(Event::Closed)
+                }
             }
         });
     }
@@ -52,11 +52,11 @@ trait API {
     fn delete_file(&self, path:Path) -> ();
 
     /// Check if file system object exists.
-    #[MethodInput=FileExistsInput,rpc_name="file/exists"]
+    #[MethodInput=FileExistsInput,rpc_name="file/exists",idempotent=true]
     fn file_exists(&self, path:Path) -> response::FileExists;
 
     /// List all file-system objects in the specified path.
-    #[MethodInput=FileListInput,rpc_name="file/list"]
+    #[MethodInput=FileListInput,rpc_name="file/list",idempotent=true]
     fn file_list(&self, path:Path) -> response::FileList;
 
     /// Move file system object to another location.
@@ -64,15 +64,15 @@ trait API {
     fn move_file(&self, from:Path, to:Path) -> ();
 
     /// Reads file's content as a String.
-    #[MethodInput=ReadFileInput,rpc_name="file/read"]
+    #[MethodInput=ReadFileInput,rpc_name="file/read",idempotent=true]
     fn read_file(&self, path:Path) -> response::Read;
 
     /// Gets file system object's attributes information.
-    #[MethodInput=FileInfoInput,rpc_name="file/info"]
+    #[MethodInput=FileInfoInput,rpc_name="file/info",idempotent=true]
     fn file_info(&self, path:Path) -> response::FileInfo;
 
     /// Requests that the language server provide the checksum of the provided file.
-    #[MethodInput=FileChecksumInput,rpc_name="file/checksum"]
+    #[MethodInput=FileChecksumInput,rpc_name="file/checksum",idempotent=true]
     fn file_checksum(&self, path:Path) -> response::FileChecksum;
 
     /// Creates the specified file system object.
@@ -143,16 +143,17 @@ trait API {
     (&self, visualisation_id:Uuid, visualisation_config:VisualisationConfiguration) -> ();
 
     /// Obtain the full suggestions database.
-    #[MethodInput=GetSuggestionsDatabaseInput,rpc_name="search/getSuggestionsDatabase"]
+    #[MethodInput=GetSuggestionsDatabaseInput,rpc_name="search/getSuggestionsDatabase",
+        idempotent=true]
     fn get_suggestions_database(&self) -> response::GetSuggestionDatabase;
 
     /// Receive the current version of the suggestions database.
     #[MethodInput=GetSuggestionsDatabaseVersionInput,
-        rpc_name="search/getSuggestionsDatabaseVersion"]
+        rpc_name="search/getSuggestionsDatabaseVersion",idempotent=true]
     fn get_suggestions_database_version(&self) -> response::GetSuggestionDatabaseVersion;
 
     /// Receive the autocomplete suggestion.
-    #[MethodInput=CompletionInput,rpc_name="search/completion"]
+    #[MethodInput=CompletionInput,rpc_name="search/completion",idempotent=true]
     fn completion
     ( &self
     , file        : Path
@@ -258,6 +258,40 @@ fn test_file_requests() {
         ());
 }
 
+#[test]
+fn test_batch_requests() {
+    let mut fixture = setup_language_server();
+    let root_id     = default();
+    let main        = Path { root_id, segments: vec!["Main.txt".into()] };
+    let path_main   = json!({"path" : {
+            "rootId"   : "00000000-0000-0000-0000-000000000000",
+            "segments" : ["Main.txt"]
+        }
+    });
+
+    let mut batch      = fixture.client.batch();
+    let mut exists_fut = batch.file_exists(&main);
+    let mut read_fut   = batch.read_file(&main);
+    batch.send();
+
+    let requests = fixture.transport.expect_json_message::<Vec<RequestMessage<Value>>>();
+    assert_eq!(requests.len(),2);
+    assert_eq!(requests[0].method, "file/exists");
+    assert_eq!(requests[1].method, "file/read");
+    assert_eq!(requests[0].params, path_main);
+    assert_eq!(requests[1].params, path_main);
+    exists_fut.expect_pending();
+    read_fut.expect_pending();
+
+    // The server may reply to the batch in any order.
+    let read_response   = Message::new_success(requests[1].id, json!({"contents":"Hello"}));
+    let exists_response = Message::new_success(requests[0].id, json!({"exists":true}));
+    fixture.transport.mock_peer_json_message(vec![read_response,exists_response]);
+    fixture.executor.run_until_stalled();
+    assert_eq!(exists_fut.expect_ok(),response::FileExists {exists:true});
+    assert_eq!(read_fut.expect_ok(),response::Read {contents:"Hello".into()});
+}
+
 #[test]
 fn test_protocol_connection() {
     let init_protocol_connection_response = response::InitProtocolConnection {
@@ -62,5 +62,7 @@ pub mod traits {
     pub use crate::binary::serialization::SerializableUnion;
 
     pub use crate::language_server::API as TRAIT_LanguageServerAPI;
+    pub use crate::language_server::BatchAPI as TRAIT_LanguageServerBatchAPI;
     pub use crate::project_manager::API as TRAIT_ProjectManagerAPI;
+    pub use crate::project_manager::BatchAPI as TRAIT_ProjectManagerBatchAPI;
 }
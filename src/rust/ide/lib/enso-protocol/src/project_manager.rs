@@ -56,7 +56,7 @@ trait API {
 
     /// Request the project manager to lists all user's projects. The list of projects is sorted by
     /// the open time.
-    #[MethodInput=ListRecentProjectsInput,rpc_name="project/list"]
+    #[MethodInput=ListRecentProjectsInput,rpc_name="project/list",idempotent=true]
     fn list_projects(&self, number_of_projects:Option<u32>) -> response::ProjectList;
 
     /// Request the creation of a new project.
@@ -74,7 +74,7 @@ trait API {
     fn delete_project(&self, project_id:Uuid) -> ();
 
     /// Request a list of sample projects that are available to the user.
-    #[MethodInput=ListSamplesInput,rpc_name="project/listSample"]
+    #[MethodInput=ListSamplesInput,rpc_name="project/listSample",idempotent=true]
     fn list_samples(&self, num_projects:u32) -> response::ProjectList;
 }}
 
@@ -289,6 +289,30 @@ mod mock_client_tests {
         let sample_projects = result(mock_client.list_samples(&2)).expect(list_sample_error);
         assert_eq!(sample_projects, expected_sample_projects);
     }
+
+    #[test]
+    fn batch() {
+        let mock_client   = MockClient::default();
+        let expected_uuid = Uuid::default();
+        expect_call!(mock_client.close_project(expected_uuid) => Ok(()));
+        expect_call!(mock_client.delete_project(expected_uuid) => error("Project doesn't exist."));
+        expect_call!(mock_client.delete_project(expected_uuid) => Ok(()));
+
+        let mut batch      = mock_client.batch();
+        let mut close_fut  = batch.close_project(&expected_uuid);
+        let mut delete_fut = batch.delete_project(&expected_uuid);
+        close_fut.expect_pending();
+        delete_fut.expect_pending();
+        batch.send();
+        close_fut.expect_ok();
+        delete_fut.expect_err();
+
+        // The requests of a batch dropped without being sent fail.
+        let mut batch      = mock_client.batch();
+        let mut delete_fut = batch.delete_project(&expected_uuid);
+        drop(batch);
+        delete_fut.expect_err();
+    }
 }
 
 
@@ -41,3 +41,20 @@ pub fn into_request_message<In:RemoteMethodCall>
 (input:In, id:Id) -> RequestMessage<In> {
     Message::new_request(id,In::NAME,input)
 }
+
+
+
+// =======================
+// === LocalMethodCall ===
+// =======================
+
+/// Structure describing a call to a local method, i.e. a request made by the remote peer.
+///
+/// A deserialized value of this trait represents the method's input arguments.
+pub trait LocalMethodCall : DeserializeOwned + Debug {
+    /// Name of the method, as called by the remote peer.
+    const NAME:&'static str;
+
+    /// A type of value returned to the remote peer from a successful call.
+    type Returned:Serialize;
+}
@@ -4,6 +4,7 @@
 use crate::prelude::*;
 
 use crate::messages::Error;
+use crate::messages::INTERNAL_ERROR_CODE;
 use crate::messages::Response;
 
 use futures::channel::oneshot::Canceled;
@@ -48,6 +49,20 @@ impl RpcError {
             data    : None,
         })
     }
+
+    /// Converts into the error to be sent to the remote peer in a reply to its request.
+    ///
+    /// Any error other than `RemoteError` is reported as an internal error.
+    pub fn into_remote_error(self) -> Error {
+        match self {
+            RpcError::RemoteError(error) => error,
+            error                        => Error {
+                code    : INTERNAL_ERROR_CODE,
+                message : error.to_string(),
+                data    : None,
+            }
+        }
+    }
 }
 
 impl From<Canceled> for RpcError {
@@ -82,6 +97,10 @@ pub enum HandlingError {
     #[fail(display = "Server generated a response with no matching request: id={:?}.", _0)]
     UnexpectedResponse(Response<serde_json::Value>),
 
+    /// Server sent a request for a method which has no handler registered.
+    #[fail(display = "Server sent a request with no matching handler: method={}.", _0)]
+    UnexpectedRequest(String),
+
     /// JSON-RPC client does not expect any binary messages, yet it received one.
     #[fail(display = "Server sent unexpected binary message: {:?}.", _0)]
     UnexpectedBinaryMessage(Vec<u8>),
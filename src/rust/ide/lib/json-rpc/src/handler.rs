@@ -14,13 +14,16 @@ use crate::transport::Transport;
 use crate::transport::TransportEvent;
 
 use futures::future;
+use futures::future::LocalBoxFuture;
 use futures::FutureExt;
 use futures::StreamExt;
 use futures::Stream;
 use futures::channel::mpsc::unbounded;
 use futures::channel::mpsc::UnboundedSender;
 use futures::channel::oneshot;
+use serde::Serialize;
 use serde::de::DeserializeOwned;
+use shrinkwraprs::Shrinkwrap;
 use std::future::Future;
 use utils::channel;
 
@@ -46,6 +49,17 @@ pub fn decode_result<Ret:DeserializeOwned>
     }
 }
 
+/// Converts `Result<Ret>` into reply message with JSON-serialized result, to be sent to the peer.
+pub fn encode_result<Ret:Serialize>(result:Result<Ret>) -> ReplyMessage {
+    let result = result.and_then(|ret| Ok(serde_json::to_value(ret)?));
+    match result {
+        Ok(ret) =>
+            messages::Result::new_success(ret),
+        Err(error) =>
+            messages::Result::Error {error:error.into_remote_error()},
+    }
+}
+
 
 
 // ===================
@@ -87,6 +101,239 @@ impl Default for IdGenerator {
 }
 
 
+// ===================
+// === RetryPolicy ===
+// ===================
+
+/// Describes how the requests which failed due to timeout or lost connection are retried. Shall be
+/// used only for idempotent methods, as the peer might have already handled the failed request.
+///
+/// The delay before each subsequent retry grows exponentially, up to the `max_delay`.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct RetryPolicy {
+    /// How many times the request may be retried after the first failed attempt.
+    pub max_retries    : usize,
+    /// Delay before the first retry.
+    pub initial_delay  : Duration,
+    /// Factor by which the delay is multiplied after each retry.
+    pub backoff_factor : f64,
+    /// Upper bound of the delay before a retry.
+    pub max_delay      : Duration,
+}
+
+impl RetryPolicy {
+    /// Delays before the subsequent retries.
+    pub fn delays(&self) -> impl Iterator<Item = Duration> {
+        let RetryPolicy {max_retries,initial_delay,backoff_factor,max_delay} = *self;
+        let next = move |delay:&Duration| Some(delay.mul_f64(backoff_factor).min(max_delay));
+        std::iter::successors(Some(initial_delay.min(max_delay)),next).take(max_retries)
+    }
+
+    /// Checks if the request, which failed with given error, may be retried.
+    pub fn is_retryable(error:&RpcError) -> bool {
+        matches!(error, RpcError::TimeoutError{..} | RpcError::LostConnection)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries    : 3,
+            initial_delay  : Duration::from_millis(250),
+            backoff_factor : 2.0,
+            max_delay      : Duration::from_secs(5),
+        }
+    }
+}
+
+
+
+// ======================
+// === RequestOptions ===
+// ======================
+
+/// Options of a single request made by the `Handler`.
+#[derive(Clone,Copy,Debug,Default,PartialEq)]
+pub struct RequestOptions {
+    /// Timeout of the request, overriding the one set for the `Handler`.
+    pub timeout : Option<Duration>,
+    /// Policy of retrying the failed request. Shall be set only for idempotent methods.
+    pub retry   : Option<RetryPolicy>,
+}
+
+
+
+// =====================
+// === MethodOptions ===
+// =====================
+
+/// Options of the requests made by a client, depending on the called method.
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct MethodOptions {
+    /// Timeouts overriding the handler's one for the requests of specific methods.
+    pub timeouts : HashMap<String,Duration>,
+    /// Policy of retrying the failed requests of idempotent methods.
+    pub retry    : Option<RetryPolicy>,
+}
+
+impl MethodOptions {
+    /// Options for a new request of the method with given name.
+    pub fn request_options(&self, method_name:&str, idempotent:bool) -> RequestOptions {
+        let timeout = self.timeouts.get(method_name).copied();
+        let retry   = if idempotent { self.retry } else { None };
+        RequestOptions {timeout,retry}
+    }
+}
+
+
+
+// =============
+// === Timer ===
+// =============
+
+/// Provides the `Future`s completing after given time, used by the `Handler` for the request
+/// timeouts and the delays before retries. By default it uses the real time, but may be replaced
+/// in tests, see `test_util::timer::MockTimer`.
+#[derive(Clone,CloneRef)]
+pub struct Timer {
+    sleep : Rc<dyn Fn(Duration) -> LocalBoxFuture<'static,()>>,
+}
+
+impl Timer {
+    /// Constructor from the function returning the `Future` completing after given time.
+    pub fn new(sleep:impl Fn(Duration) -> LocalBoxFuture<'static,()> + 'static) -> Self {
+        let sleep = Rc::new(sleep);
+        Self {sleep}
+    }
+
+    /// Returns a `Future` completing after the given time.
+    pub fn sleep(&self, duration:Duration) -> LocalBoxFuture<'static,()> {
+        (self.sleep)(duration)
+    }
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new(|duration| sleep(duration).boxed_local())
+    }
+}
+
+impl Debug for Timer {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        write!(f,"Timer")
+    }
+}
+
+
+
+// ===================
+// === RequestGate ===
+// ===================
+
+/// Allows holding back the requests made through a `Handler`, e.g. while the session with the peer
+/// is being restored after reconnecting.
+///
+/// While the gate is held, the new requests (including the retries of the failed ones) are queued
+/// and sent once the gate is released. Their timeouts start when they are sent. The requests made
+/// by the futures wrapped with `exempt` are sent immediately, so the session can be restored.
+///
+/// The gate also counts the times the `Handler`'s transport has been closed, so the restoration of
+/// the session may check whether the connection was lost again in the meantime.
+#[derive(Clone,CloneRef,Debug,Default)]
+pub struct RequestGate {
+    rc : Rc<RequestGateData>,
+}
+
+#[derive(Default)]
+struct RequestGateData {
+    held           : Cell<bool>,
+    /// Number of the `exempt` futures being polled at the moment.
+    exempt_depth   : Cell<usize>,
+    on_release     : RefCell<Vec<Box<dyn FnOnce()>>>,
+    disconnections : Cell<usize>,
+}
+
+impl Debug for RequestGateData {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        let mut debug = f.debug_struct("RequestGateData");
+        debug.field("held",&self.held).field("disconnections",&self.disconnections).finish()
+    }
+}
+
+impl RequestGate {
+    /// Start holding back the new requests.
+    pub fn hold(&self) {
+        self.rc.held.set(true);
+    }
+
+    /// Send all the requests held back, and stop holding the new ones.
+    pub fn release(&self) {
+        self.rc.held.set(false);
+        let callbacks = std::mem::take(&mut *self.rc.on_release.borrow_mut());
+        for callback in callbacks {
+            callback();
+        }
+    }
+
+    /// Checks if the new requests are held back.
+    pub fn is_held(&self) -> bool {
+        self.rc.held.get()
+    }
+
+    /// Checks if the requests made at the moment are sent immediately.
+    pub fn is_open(&self) -> bool {
+        !self.is_held() || self.rc.exempt_depth.get() > 0
+    }
+
+    /// Returns a `Future` which completes once the gate is not held, or is dropped.
+    pub fn released(&self) -> impl Future<Output = ()> {
+        let receiver = self.is_held().as_some_from(|| {
+            let (sender,receiver) = oneshot::channel();
+            self.on_release(move || { let _ = sender.send(()); });
+            receiver
+        });
+        future::OptionFuture::from(receiver).map(|_| ())
+    }
+
+    /// Returns a `Future` which completes once the request made at the moment may be sent, that is
+    /// immediately if the gate is open, or once it is released.
+    pub fn passage(&self) -> impl Future<Output = ()> {
+        let released = (!self.is_open()).as_some_from(|| self.released());
+        future::OptionFuture::from(released).map(|_| ())
+    }
+
+    /// Wraps the `future`, so all the requests made while it is polled are sent immediately, even
+    /// if the gate is held.
+    pub fn exempt<F:Future>(&self, future:F) -> impl Future<Output = F::Output> {
+        let data       = self.rc.clone();
+        let mut future = Box::pin(future);
+        future::poll_fn(move |cx| {
+            data.exempt_depth.set(data.exempt_depth.get() + 1);
+            let result = future.as_mut().poll(cx);
+            data.exempt_depth.set(data.exempt_depth.get() - 1);
+            result
+        })
+    }
+
+    /// Number of times the connection has been lost. The `Handler` increases it when its transport
+    /// is closed, before the pending requests fail due to the lost connection.
+    pub fn disconnections(&self) -> usize {
+        self.rc.disconnections.get()
+    }
+
+    /// Record that the connection has been lost. See `disconnections`.
+    pub fn record_disconnection(&self) {
+        self.rc.disconnections.set(self.rc.disconnections.get() + 1);
+    }
+
+    /// Calls the `callback` once the gate is released.
+    fn on_release(&self, callback:impl FnOnce() + 'static) {
+        self.rc.on_release.borrow_mut().push(Box::new(callback));
+    }
+}
+
+
+
 // =============
 // === Event ===
 // =============
@@ -94,6 +341,8 @@ impl Default for IdGenerator {
 /// Event emitted by the `Handler<N>`.
 #[derive(Debug)]
 pub enum Event<N> {
+    /// Transport has been opened, e.g. reconnected after being closed.
+    Opened,
     /// Transport has been closed.
     Closed,
     /// Error occurred.
@@ -113,6 +362,21 @@ pub enum Event<N> {
 /// from this container.
 pub type OngoingCalls = HashMap<Id,oneshot::Sender<ReplyMessage>>;
 
+/// Handler of the requests made by the peer for a single method. Gets the JSON-serialized request
+/// parameters and returns a `Future` yielding the reply message.
+pub type RequestHandler = Rc<dyn Fn(serde_json::Value) -> LocalBoxFuture<'static,ReplyMessage>>;
+
+/// Container that stores handlers of the requests made by the peer, keyed by the method name.
+#[derive(Clone,Default,Shrinkwrap)]
+#[shrinkwrap(mutable)]
+pub struct RequestHandlers(pub HashMap<String,RequestHandler>);
+
+impl Debug for RequestHandlers {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.keys()).finish()
+    }
+}
+
 
 
 
@@ -140,15 +404,21 @@ shared! { Handler
 #[derive(Debug)]
 pub struct HandlerData<Notification> {
     /// Timeout for futures.
-    timeout         : Duration,
+    timeout          : Duration,
     /// Ongoing calls.
-    ongoing_calls   : OngoingCalls,
+    ongoing_calls    : OngoingCalls,
+    /// Handlers of requests made by the peer.
+    request_handlers : RequestHandlers,
     /// Handle to send outgoing events.
-    outgoing_events : Option<UnboundedSender<Event<Notification>>>,
+    outgoing_events  : Option<UnboundedSender<Event<Notification>>>,
     /// Provides identifiers for requests.
-    id_generator    : IdGenerator,
+    id_generator     : IdGenerator,
     /// Transports text messages between this handler and the peer.
-    transport       : Box<dyn Transport>,
+    transport        : Box<dyn Transport>,
+    /// Holds back the requests, see `RequestGate`.
+    request_gate     : RequestGate,
+    /// Measures the request timeouts and the delays before retries.
+    timer            : Timer,
 }
 
 
@@ -174,6 +444,17 @@ impl<Notification> {
         self.ongoing_calls.clear()
     }
 
+    /// Registers a handler for the requests made by the peer for given method. Any handler
+    /// previously registered for this method is replaced.
+    pub fn insert_request_handler(&mut self, method:String, handler:RequestHandler) {
+        self.request_handlers.insert(method,handler);
+    }
+
+    /// Returns the handler registered for the requests made by the peer for given method.
+    pub fn request_handler(&self, method:&str) -> Option<RequestHandler> {
+        self.request_handlers.get(method).cloned()
+    }
+
     /// Obtains an id for a new request to be made.
     pub fn generate_new_id(&mut self) -> Id {
         self.id_generator.generate()
@@ -210,6 +491,21 @@ impl<Notification> {
     pub fn set_timeout(&mut self, timeout:Duration) {
         self.timeout = timeout;
     }
+
+    /// The gate holding back the requests made through this handler. See `RequestGate`.
+    pub fn request_gate(&self) -> RequestGate {
+        self.request_gate.clone_ref()
+    }
+
+    /// The timer measuring the request timeouts and the delays before retries.
+    pub fn timer(&self) -> Timer {
+        self.timer.clone_ref()
+    }
+
+    /// Replace the timer, e.g. with a mock one in tests. Pending requests are not affected.
+    pub fn set_timer(&mut self, timer:Timer) {
+        self.timer = timer;
+    }
 }
 } // shared!
 
@@ -237,11 +533,14 @@ impl<Notification> Handler<Notification> {
     /// `Transport` must be functional (e.g. not in the process of opening).
     pub fn new(transport:impl Transport + 'static) -> Handler<Notification> {
         let data = HandlerData {
-            timeout         : crate::constants::TIMEOUT,
-            ongoing_calls   : default(),
-            id_generator    : IdGenerator::new(),
-            transport       : Box::new(transport),
-            outgoing_events : None,
+            timeout          : crate::constants::TIMEOUT,
+            ongoing_calls    : default(),
+            request_handlers : default(),
+            id_generator     : IdGenerator::new(),
+            transport        : Box::new(transport),
+            outgoing_events  : None,
+            request_gate     : default(),
+            timer            : default(),
         };
         Handler {rc: Rc::new(RefCell::new(data))}
     }
@@ -275,12 +574,125 @@ impl<Notification> Handler<Notification> {
         self.open_request_with_message(id,&serialized_message)
     }
 
+    /// Sends a request to the peer and returns a `Future` that shall yield a reply message. The
+    /// request is made according to the given `options`, see `RequestOptions`.
+    ///
+    /// Each attempt of a request retried due to the retry policy is sent with a new id. Once an
+    /// attempt fails, e.g. due to the timeout, it is no longer pending, so a late reply to it is
+    /// reported as `HandlingError::UnexpectedResponse` event, also if it comes while waiting
+    /// before the next attempt.
+    ///
+    /// See `open_request_with_json` docstring for the reasons this method takes JSON input.
+    pub fn open_request_with_options<Returned:DeserializeOwned>
+    (&self, method_name:&str, input:&serde_json::Value, options:RequestOptions)
+    -> impl Future<Output = Result<Returned>> {
+        let timeout       = options.timeout.unwrap_or_else(|| self.timeout());
+        let first_attempt = self.send_new_request(method_name,input,timeout);
+        self.retry_failed(first_attempt,method_name,input,timeout,options.retry,|| true)
+    }
+
+    /// Sends a request with a new id to the peer and returns a `Future` that shall yield a reply
+    /// message.
+    fn send_new_request<Returned:DeserializeOwned>
+    (&self, method_name:&str, input:&serde_json::Value, timeout:Duration)
+    -> impl Future<Output = Result<Returned>> {
+        let id                 = self.generate_new_id();
+        let message            = messages::Message::new_request(id,method_name,input);
+        let serialized_message = serde_json::to_string(&message).unwrap();
+        self.send_request(id,&serialized_message,timeout)
+    }
+
+    /// Returns a `Future` yielding the result of the `first_attempt` of a request, or of its
+    /// retries made according to the `retry` policy if it fails. The request is not retried if
+    /// `may_retry` returns false after the failure.
+    fn retry_failed<Returned:DeserializeOwned>
+    ( &self
+    , first_attempt : impl Future<Output = Result<Returned>>
+    , method_name   : &str
+    , input         : &serde_json::Value
+    , timeout       : Duration
+    , retry         : Option<RetryPolicy>
+    , may_retry     : impl Fn() -> bool
+    ) -> impl Future<Output = Result<Returned>> {
+        let weak_data = Rc::downgrade(&self.rc);
+        let timer     = self.timer();
+        // The request is copied only if it might be sent again.
+        let request   = retry.map(|policy| (policy,method_name.to_owned(),input.clone()));
+        async move {
+            let mut result = first_attempt.await;
+            if let Some((policy,method_name,input)) = request {
+                for delay in policy.delays() {
+                    match &result {
+                        Err(error) if RetryPolicy::is_retryable(error) && may_retry() => {},
+                        _                                                             => break,
+                    }
+                    timer.sleep(delay).await;
+                    let attempt = match weak_data.upgrade() {
+                        Some(rc) => Handler {rc}.send_new_request(&method_name,&input,timeout),
+                        None     => break,
+                    };
+                    result = attempt.await;
+                }
+            }
+            result
+        }
+    }
+
     /// Sends a request to the peer and returns a `Future` that shall yield a reply message.
     ///
     /// Helper common \code for `open_request` and `open_request_with_json`. See
     /// `open_request_with_json` docstring for more information.
     pub fn open_request_with_message<Returned:DeserializeOwned>
     (&self, id:Id, message_json:&str) -> impl Future<Output = Result<Returned>> {
+        self.send_request(id,message_json,self.timeout())
+    }
+
+    /// Sends a request to the peer, or queues it if the `RequestGate` is held.
+    fn send_request<Returned:DeserializeOwned>
+    (&self, id:Id, message_json:&str, timeout:Duration) -> impl Future<Output = Result<Returned>> {
+        let gate = self.request_gate();
+        if gate.is_open() {
+            self.send_request_now(id,message_json,timeout).left_future()
+        } else {
+            let weak_data = Rc::downgrade(&self.rc);
+            let message   = message_json.to_owned();
+            async move {
+                gate.released().await;
+                match weak_data.upgrade() {
+                    Some(rc) => Handler {rc}.send_request_now(id,&message,timeout).await,
+                    None     => Err(RpcError::LostConnection),
+                }
+            }.right_future()
+        }
+    }
+
+    fn send_request_now<Returned:DeserializeOwned>
+    (&self, id:Id, message_json:&str, timeout:Duration) -> impl Future<Output = Result<Returned>> {
+        let ret = self.register_request(id,timeout);
+        if self.send_text_message(message_json).is_err() {
+            // If message cannot be send, future ret must be cancelled.
+            self.remove_ongoing_request(id);
+        }
+        ret
+    }
+
+    /// Registers an ongoing request with given id and returns a `Future` that shall yield its
+    /// reply message, or fail once the timeout passes.
+    ///
+    /// The request message itself is not sent. Once the returned `Future` completes or is dropped
+    /// (i.e. the request is cancelled), the ongoing request is removed.
+    fn register_request<Returned:DeserializeOwned>
+    (&self, id:Id, timeout:Duration) -> impl Future<Output = Result<Returned>> {
+        self.register_request_sent_on(id,timeout,future::ready(true))
+    }
+
+    /// Registers an ongoing request like `register_request`, but its timeout starts only once the
+    /// `sent` future yields `true`. If it yields `false`, the request does not time out, it can
+    /// only complete with the reply or be cancelled.
+    fn register_request_sent_on<Returned,Sent>
+    (&self, id:Id, timeout:Duration, sent:Sent) -> impl Future<Output = Result<Returned>>
+    where Returned : DeserializeOwned,
+          Sent     : Future<Output = bool> + Unpin {
         let (sender, receiver) = oneshot::channel::<ReplyMessage>();
         let ret                = receiver.map(|result_or_cancel| {
             let result = result_or_cancel?;
@@ -288,18 +700,48 @@ impl<Notification> Handler<Notification> {
         });
 
         self.insert_ongoing_request(id,sender);
-        if self.send_text_message(message_json).is_err() {
-            // If message cannot be send, future ret must be cancelled.
-            self.remove_ongoing_request(id);
-        }
 
-        let millis = self.timeout().as_millis();
-        future::select(ret, sleep(self.timeout()).boxed_local()).map(move |either|
+        let guard     = OngoingRequestGuard {data:Rc::downgrade(&self.rc),id};
+        let millis    = timeout.as_millis();
+        let timer     = self.timer();
+        let timed_out = sent.then(move |sent| {
+            if sent { timer.sleep(timeout).left_future() } else { future::pending().right_future() }
+        });
+        future::select(ret, timed_out).map(move |either| {
+            drop(guard);
             match either {
                 future::Either::Left ((x, _)) => x,
                 future::Either::Right((_, _)) => Err(RpcError::TimeoutError{millis}),
             }
-        )
+        })
+    }
+
+    /// Registers a handler for the requests of type `In` made by the peer. Once the `Future`
+    /// returned by the handler completes, its result or error is sent back to the peer.
+    ///
+    /// Any handler previously registered for the same method is replaced.
+    pub fn set_request_handler<In,Handler,Reply>(&self, handler:Handler)
+    where In           : api::LocalMethodCall,
+          In::Returned : 'static,
+          Handler      : Fn(In) -> Reply + 'static,
+          Reply        : Future<Output = Result<In::Returned>> + 'static {
+        let handler = move |params:serde_json::Value| {
+            match serde_json::from_value::<In>(params) {
+                Ok(input) => handler(input).map(encode_result).boxed_local(),
+                Err(err)  => {
+                    let message = err.to_string();
+                    let code    = messages::INVALID_PARAMS_ERROR_CODE;
+                    future::ready(messages::Result::new_error_simple(code,message)).boxed_local()
+                }
+            }
+        };
+        self.insert_request_handler(In::NAME.into(),Rc::new(handler));
+    }
+
+    /// Starts a new batch of requests. The requests collected in the batch are sent to the peer
+    /// together, as a single message.
+    pub fn batch(&self) -> Batch<Notification> {
+        Batch::new(self)
     }
 
     /// Deal with `Response` message from the peer.
@@ -316,6 +758,37 @@ impl<Notification> Handler<Notification> {
         }
     }
 
+    /// Deal with `Request` message from the peer.
+    ///
+    /// The request is passed to the handler registered for its method. If there is no such
+    /// handler, the peer gets an error reply and the relevant error is emitted. Returns a `Future`
+    /// that sends the reply to the peer once it is ready.
+    pub fn process_request
+    (&self, message:messages::Request<messages::MethodCall<serde_json::Value>>)
+    -> impl Future<Output = ()> {
+        let id                                  = message.id;
+        let messages::MethodCall {method,params} = message.call;
+        let reply = match self.request_handler(&method) {
+            Some(handler) => handler(params),
+            None          => {
+                let code   = messages::METHOD_NOT_FOUND_ERROR_CODE;
+                let result = messages::Result::new_error_simple(code,method.clone());
+                self.error_occurred(HandlingError::UnexpectedRequest(method));
+                future::ready(result).boxed_local()
+            }
+        };
+        let weak_data = Rc::downgrade(&self.rc);
+        reply.map(move |result| {
+            if let Some(rc) = weak_data.upgrade() {
+                let message            = messages::Message::new(messages::Response {id,result});
+                let serialized_message = serde_json::to_string(&message).unwrap();
+                // Disregard any error. If the reply cannot be sent, the peer is disconnected and
+                // does not wait for it anymore.
+                Handler {rc}.send_text_message(&serialized_message).ok();
+            }
+        })
+    }
+
     /// Deal with `Notification` message from the peer.
     ///
     /// If possible, emits a message with notification. In case of failure,
@@ -337,18 +810,33 @@ impl<Notification> Handler<Notification> {
 
     /// Deal with incoming text message from the peer.
     ///
-    /// The message must conform either to the `Response` or to the
+    /// The message must conform either to the `Response`, `Request` or to the
     /// `Notification` JSON-serialized format. Otherwise, an error is raised.
-    pub fn process_incoming_message(&self, message:String)
+    ///
+    /// The text may also contain a batch, i.e. an array of such messages. Each of them is processed
+    /// separately.
+    ///
+    /// Returns a `Future` sending the replies to the requests made by the peer.
+    pub fn process_incoming_message(&self, message:String) -> impl Future<Output = ()>
     where Notification: DeserializeOwned {
-        match messages::decode_incoming_message(&message) {
-            Ok(messages::IncomingMessage::Response(response)) =>
-                self.process_response(response),
-            Ok(messages::IncomingMessage::Notification(notification)) =>
-                self.process_notification(notification),
+        let mut replies = Vec::new();
+        match messages::decode_incoming_messages(&message) {
+            Ok(messages) => for message in messages {
+                match message {
+                    Ok(messages::IncomingMessage::Response(response)) =>
+                        self.process_response(response),
+                    Ok(messages::IncomingMessage::Request(request)) =>
+                        replies.push(self.process_request(request)),
+                    Ok(messages::IncomingMessage::Notification(notification)) =>
+                        self.process_notification(notification),
+                    Err(err) =>
+                        self.error_occurred(HandlingError::InvalidMessage(err)),
+                }
+            },
             Err(err) =>
                 self.error_occurred(HandlingError::InvalidMessage(err)),
         }
+        future::join_all(replies).map(|_| ())
     }
 
     /// With with a handling error. Uses `on_error` callback to notify the
@@ -359,21 +847,30 @@ impl<Notification> Handler<Notification> {
 
     /// Processes a single transport event.
     ///
-    /// Each event either completes a requests or is translated into `Event`.
-    pub fn process_event(&self, event:TransportEvent)
+    /// Each event either completes a requests, is passed to the request handler or is translated
+    /// into `Event`. Returns a `Future` sending the replies to the requests made by the peer.
+    pub fn process_event(&self, event:TransportEvent) -> impl Future<Output = ()>
     where Notification: DeserializeOwned {
-        match event {
+        let replies = match event {
             TransportEvent::TextMessage(msg) =>
-                self.process_incoming_message(msg),
-            TransportEvent::BinaryMessage(data) =>
-                self.error_occurred(HandlingError::UnexpectedBinaryMessage(data)),
-            TransportEvent::Opened => {}
+                Some(self.process_incoming_message(msg)),
+            TransportEvent::BinaryMessage(data) => {
+                self.error_occurred(HandlingError::UnexpectedBinaryMessage(data));
+                None
+            }
+            TransportEvent::Opened => {
+                self.emit_event(Event::Opened);
+                None
+            }
             TransportEvent::Closed => {
+                self.request_gate().record_disconnection();
                 // Dropping all ongoing calls will cancel their futures.
                 self.clear_ongoing_requests();
                 self.emit_event(Event::Closed);
+                None
             }
-        }
+        };
+        future::OptionFuture::from(replies).map(|_| ())
     }
 
     /// Returns a `Future` that processes transport events incoming to this `Handler`.
@@ -385,20 +882,188 @@ impl<Notification> Handler<Notification> {
     /// dropping the `Transport` itself.
     ///
     /// It is expected that upon setting up the `Handler`, this future shall be
-    /// passed to the main executor.
+    /// passed to the main executor. It also drives the handlers of requests made by the peer, so
+    /// the replies are sent while it runs.
     pub fn runner(&mut self) -> impl Future<Output = ()>
     where Notification: DeserializeOwned + 'static {
         let event_receiver  = self.transport_event_stream();
         let weak_data       = Rc::downgrade(&self.rc);
-        event_receiver.for_each(move |event:TransportEvent| {
+        event_receiver.for_each_concurrent(None,move |event:TransportEvent| {
             let data_opt    = weak_data.clone().upgrade();
             let handler_opt = data_opt.map(|rc| Handler {rc});
-            if let Some(handler) = handler_opt {
-                handler.process_event(event)
+            // If the data is inaccessible, it is ok to just drop the event here.
+            let replies     = handler_opt.map(|handler| handler.process_event(event));
+            future::OptionFuture::from(replies).map(|_| ())
+        })
+    }
+}
+
+
+
+// ===========================
+// === OngoingRequestGuard ===
+// ===========================
+
+/// Removes the ongoing request from the `Handler` when dropped. It is owned by the request's
+/// `Future`, so the request does not stay pending once it is dropped.
+#[derive(Debug)]
+struct OngoingRequestGuard<Notification> {
+    data : Weak<RefCell<HandlerData<Notification>>>,
+    id   : Id,
+}
+
+impl<Notification> Drop for OngoingRequestGuard<Notification> {
+    fn drop(&mut self) {
+        if let Some(rc) = self.data.upgrade() {
+            Handler {rc}.remove_ongoing_request(self.id);
+        }
+    }
+}
+
+
+
+// =============
+// === Batch ===
+// =============
+
+/// A batch of requests, that are sent to the peer together as a single JSON-RPC batch message.
+///
+/// Each request opened in the batch immediately yields a `Future` with its reply, but nothing is
+/// sent until `send` is called, and the request timeouts start once the batch is actually sent. If
+/// the batch is dropped without being sent, the futures of its requests fail with
+/// `RpcError::LostConnection`.
+///
+/// The peer may reply to the batched requests in any order, possibly also with a batch message.
+#[derive(Debug)]
+pub struct Batch<Notification> {
+    handler       : Handler<Notification>,
+    ids           : Vec<Id>,
+    messages      : Vec<serde_json::Value>,
+    /// Notify the requests that the batch was sent, so their timeouts start.
+    sent_handlers : Vec<oneshot::Sender<()>>,
+    sent          : Rc<Cell<bool>>,
+}
+
+impl<Notification> Batch<Notification> {
+    /// Creates a new, empty batch of requests to be made through the given handler.
+    pub fn new(handler:&Handler<Notification>) -> Self {
+        let handler       = Handler {rc:handler.rc.clone()};
+        let ids           = default();
+        let messages      = default();
+        let sent_handlers = default();
+        let sent          = default();
+        Batch {handler,ids,messages,sent_handlers,sent}
+    }
+
+    /// Adds a request to the batch and returns a `Future` that shall yield its reply message.
+    /// It is automatically decoded into the expected type.
+    pub fn open_request<In:api::RemoteMethodCall>
+    (&mut self, input:In) -> impl Future<Output = Result<In::Returned>> {
+        let id      = self.handler.generate_new_id();
+        let message = api::into_request_message(input,id);
+        let timeout = self.handler.timeout();
+        self.open_request_with_message(id,serde_json::to_value(&message).unwrap(),timeout)
+    }
+
+    /// Adds a request to the batch and returns a `Future` that shall yield its reply message.
+    ///
+    /// See `Handler::open_request_with_json` docstring for the reasons this method exists.
+    pub fn open_request_with_json<Returned:DeserializeOwned>
+    (&mut self, method_name:&str, input:&serde_json::Value)
+    -> impl Future<Output = Result<Returned>> {
+        let id      = self.handler.generate_new_id();
+        let message = messages::Message::new_request(id,method_name,input);
+        let timeout = self.handler.timeout();
+        self.open_request_with_message(id,serde_json::to_value(&message).unwrap(),timeout)
+    }
+
+    /// Adds a request to the batch and returns a `Future` that shall yield its reply message. The
+    /// request is made according to the given `options`, see `RequestOptions`. If it is retried,
+    /// the subsequent attempts are sent separately, as in `Handler::open_request_with_options`.
+    pub fn open_request_with_options<Returned:DeserializeOwned>
+    (&mut self, method_name:&str, input:&serde_json::Value, options:RequestOptions)
+    -> impl Future<Output = Result<Returned>> {
+        let id            = self.handler.generate_new_id();
+        let message       = messages::Message::new_request(id,method_name,input);
+        let message       = serde_json::to_value(&message).unwrap();
+        let timeout       = options.timeout.unwrap_or_else(|| self.handler.timeout());
+        let first_attempt = self.open_request_with_message(id,message,timeout);
+        // The requests of a batch dropped without being sent are not retried.
+        let sent          = self.sent.clone();
+        let may_retry     = move || sent.get();
+        self.handler.retry_failed(first_attempt,method_name,input,timeout,options.retry,may_retry)
+    }
+
+    fn open_request_with_message<Returned:DeserializeOwned>
+    (&mut self, id:Id, message:serde_json::Value, timeout:Duration)
+    -> impl Future<Output = Result<Returned>> {
+        let (sent_handler,sent) = oneshot::channel();
+        self.ids.push(id);
+        self.messages.push(message);
+        self.sent_handlers.push(sent_handler);
+        self.handler.register_request_sent_on(id,timeout,sent.map(|result| result.is_ok()))
+    }
+
+    /// Number of requests in the batch.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Checks if there are no requests in the batch.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Sends all the requests of the batch to the peer. If the handler's `RequestGate` is held,
+    /// the batch is sent once it is released.
+    ///
+    /// Empty batch is not sent at all, as the JSON-RPC specification considers it invalid.
+    pub fn send(mut self) where Notification:'static {
+        self.sent.set(true);
+        let ids           = std::mem::take(&mut self.ids);
+        let messages      = std::mem::take(&mut self.messages);
+        let sent_handlers = std::mem::take(&mut self.sent_handlers);
+        if !messages.is_empty() {
+            let serialized_batch = serde_json::to_string(&messages).unwrap();
+            let gate             = self.handler.request_gate();
+            if gate.is_open() {
+                Self::send_serialized(&self.handler,ids,sent_handlers,&serialized_batch);
             } else {
-                // If the data is inaccessible, it is ok to just drop the event here.
+                let weak_data = Rc::downgrade(&self.handler.rc);
+                gate.on_release(move || {
+                    if let Some(rc) = weak_data.upgrade() {
+                        let handler = Handler {rc};
+                        Self::send_serialized(&handler,ids,sent_handlers,&serialized_batch);
+                    }
+                });
             }
-            futures::future::ready(())
-        })
+        }
+    }
+
+    fn send_serialized
+    ( handler          : &Handler<Notification>
+    , ids              : Vec<Id>
+    , sent_handlers    : Vec<oneshot::Sender<()>>
+    , serialized_batch : &str
+    ) {
+        if handler.send_text_message(serialized_batch).is_ok() {
+            for sent_handler in sent_handlers {
+                // The request may be already cancelled, so there is nobody to notify.
+                let _ = sent_handler.send(());
+            }
+        } else {
+            // If batch cannot be send, all its futures must be cancelled.
+            for id in ids {
+                handler.remove_ongoing_request(id);
+            }
+        }
+    }
+}
+
+impl<Notification> Drop for Batch<Notification> {
+    fn drop(&mut self) {
+        for id in std::mem::take(&mut self.ids) {
+            self.handler.remove_ongoing_request(id);
+        }
     }
 }
@@ -20,16 +20,37 @@
 ///     fn expect_call_me_please
 ///     (&mut self, my_number_is:String,result:json_rpc::api::Result<()>) { /* impl */ }
 /// ```
+///
+/// Methods which are idempotent may be marked with `idempotent=true` attribute, after `rpc_name`.
+/// Such methods are retried after a failure, according to the client's retry policy.
+///
+/// The `trait API` item may be followed by a `requests` block, describing the requests made by
+/// the server. For each of them the `API` gets a method registering its handler, named by the
+/// `set_handler` attribute, e.g.:
+/// ```rust,compile_fail
+/// requests {
+///     #[MethodInput=ConfirmInput,rpc_name="confirm",set_handler=set_confirm_handler]
+///     fn confirm(&self, question:String) -> bool;
+/// }
+/// ```
 #[macro_export]
 macro_rules! make_rpc_methods {
     (
         $(#[doc = $impl_doc:expr])+
         trait API {
             $($(#[doc = $doc:expr])+
-            #[MethodInput=$method_input:ident,rpc_name=$rpc_name:expr]
+            #[MethodInput=$method_input:ident,rpc_name=$rpc_name:expr
+              $(,idempotent=$idempotent:expr)?]
             fn $method:ident(&self $(,$param_name:ident:$param_ty:ty)*) -> $result:ty;
             )*
         }
+        requests {
+            $($(#[doc = $req_doc:expr])+
+            #[MethodInput=$req_input:ident,rpc_name=$req_rpc_name:expr,
+              set_handler=$set_handler:ident]
+            fn $req:ident(&self $(,$req_param_name:ident:$req_param_ty:ty)*) -> $req_result:ty;
+            )*
+        }
     ) => {
         // ===========
         // === API ===
@@ -44,10 +65,54 @@ macro_rules! make_rpc_methods {
                 -> std::pin::Pin<Box<dyn Future<Output=Result<$result>>>>;
             )*
 
+            $(
+                /// Sets the handler of the following request made by the server, replacing the
+                /// previous one:
+                ///
+                $(#[doc = $req_doc])+
+                fn $set_handler(&self, handler:Box<dyn Fn($($req_param_ty),*)
+                -> std::pin::Pin<Box<dyn Future<Output=Result<$req_result>>>>>);
+            )*
+
             /// Asynchronous event stream with notification and errors.
             ///
             /// On a repeated call, previous stream is closed.
             fn events(&self) -> futures::stream::LocalBoxStream<'static,Event>;
+
+            /// The gate which may hold back the requests, e.g. while the session is restored after
+            /// reconnecting. See `json_rpc::handler::RequestGate`.
+            fn request_gate(&self) -> json_rpc::handler::RequestGate;
+
+            /// Set new timeout for future requests. Pending requests are not affected.
+            fn set_timeout(&self, timeout:std::time::Duration);
+
+            /// Set new timeout for future requests of the method with given `rpc_name`, overriding
+            /// the one set by `set_timeout`. Pending requests are not affected.
+            fn set_method_timeout(&self, rpc_name:&str, timeout:std::time::Duration);
+
+            /// Set the policy of retrying the failed requests of idempotent methods. Pending
+            /// requests are not affected.
+            fn set_retry_policy(&self, policy:Option<json_rpc::handler::RetryPolicy>);
+
+            /// Starts a new batch of requests, which will be sent to the server together. The
+            /// requests are made with the options set for their methods at this moment.
+            fn batch(&self) -> Box<dyn BatchAPI + '_>;
+        }
+
+        /// A batch of requests, sent to the server together as a single message by `send`.
+        ///
+        /// Each method returns a future with the reply for its own request. If the batch is
+        /// dropped without being sent, these futures fail with `RpcError::LostConnection`.
+        #[allow(clippy::ptr_arg)]
+        pub trait BatchAPI {
+            $(
+                $(#[doc = $doc])+
+                fn $method(&mut self $(,$param_name:&$param_ty)*)
+                -> std::pin::Pin<Box<dyn Future<Output=Result<$result>>>>;
+            )*
+
+            /// Sends all the requests of the batch to the server.
+            fn send(self:Box<Self>);
         }
 
 
@@ -60,14 +125,17 @@ macro_rules! make_rpc_methods {
         #[derive(Debug)]
         pub struct Client {
             /// JSON-RPC protocol handler.
-            handler : RefCell<Handler<Notification>>,
+            handler        : RefCell<Handler<Notification>>,
+            /// Timeouts and retry policy of the requests of specific methods.
+            method_options : RefCell<json_rpc::handler::MethodOptions>,
         }
 
         impl Client {
             /// Create a new client that will use given transport.
             pub fn new(transport:impl json_rpc::Transport + 'static) -> Self {
-                let handler = RefCell::new(Handler::new(transport));
-                Self { handler }
+                let handler        = RefCell::new(Handler::new(transport));
+                let method_options = default();
+                Self {handler,method_options}
             }
 
             /// Returns a future that performs any background, asynchronous work needed
@@ -77,9 +145,10 @@ macro_rules! make_rpc_methods {
                 self.handler.borrow_mut().runner()
             }
 
-            /// Set new timeout for future requests. Pending requests are not affected.
-            pub fn set_timeout(&mut self, timeout:std::time::Duration) {
-                self.handler.borrow().set_timeout(timeout);
+            /// Replace the timer measuring the request timeouts and the delays before retries,
+            /// e.g. with `json_rpc::test_util::timer::MockTimer` in tests.
+            pub fn set_timer(&self, timer:json_rpc::handler::Timer) {
+                self.handler.borrow().set_timer(timer);
             }
         }
 
@@ -91,16 +160,84 @@ macro_rules! make_rpc_methods {
                 let input      = $method_input { phantom, $($param_name:&$param_name),* };
                 let input_json = serde_json::to_value(input).unwrap();
                 let name       = $method_input::NAME;
-                let result_fut = self.handler.borrow().open_request_with_json(name,&input_json);
+                let idempotent = false $(|| $idempotent)?;
+                let options    = self.method_options.borrow().request_options(name,idempotent);
+                let handler    = self.handler.borrow();
+                let result_fut = handler.open_request_with_options(name,&input_json,options);
                 Box::pin(result_fut)
             })*
 
+            $(fn $set_handler(&self, handler:Box<dyn Fn($($req_param_ty),*)
+            -> std::pin::Pin<Box<dyn Future<Output=Result<$req_result>>>>>) {
+                self.handler.borrow().set_request_handler(move |input:$req_input| {
+                    handler($(input.$req_param_name),*)
+                });
+            })*
+
             fn events(&self) -> futures::stream::LocalBoxStream<'static,Event> {
                 self.handler.borrow_mut().handler_event_stream().boxed_local()
             }
+
+            fn request_gate(&self) -> json_rpc::handler::RequestGate {
+                self.handler.borrow().request_gate()
+            }
+
+            fn set_timeout(&self, timeout:std::time::Duration) {
+                self.handler.borrow().set_timeout(timeout);
+            }
+
+            fn set_method_timeout(&self, rpc_name:&str, timeout:std::time::Duration) {
+                let mut options = self.method_options.borrow_mut();
+                options.timeouts.insert(rpc_name.to_owned(),timeout);
+            }
+
+            fn set_retry_policy(&self, policy:Option<json_rpc::handler::RetryPolicy>) {
+                self.method_options.borrow_mut().retry = policy;
+            }
+
+            fn batch(&self) -> Box<dyn BatchAPI + '_> {
+                let batch   = self.handler.borrow().batch();
+                let options = self.method_options.borrow().clone();
+                Box::new(Batch {batch,options})
+            }
         }
 
-        $(
+
+
+
+        // =============
+        // === Batch ===
+        // =============
+
+        /// A batch of requests made by `Client`, see `BatchAPI`. The failed requests of idempotent
+        /// methods are retried separately, according to the client's retry policy.
+        #[derive(Debug)]
+        pub struct Batch {
+            batch   : json_rpc::handler::Batch<Notification>,
+            options : json_rpc::handler::MethodOptions,
+        }
+
+        impl BatchAPI for Batch {
+            $(
+                fn $method(&mut self $(,$param_name:&$param_ty)*)
+                -> std::pin::Pin<Box<dyn Future<Output=Result<$result>>>> {
+                    use json_rpc::api::RemoteMethodCall;
+                    let phantom    = std::marker::PhantomData;
+                    let input      = $method_input { phantom, $($param_name:&$param_name),* };
+                    let input_json = serde_json::to_value(input).unwrap();
+                    let name       = $method_input::NAME;
+                    let idempotent = false $(|| $idempotent)?;
+                    let options    = self.options.request_options(name,idempotent);
+                    Box::pin(self.batch.open_request_with_options(name,&input_json,options))
+                }
+            )*
+
+            fn send(self:Box<Self>) {
+                self.batch.send()
+            }
+        }
+
+       $(
             /// Structure transporting method arguments.
             #[derive(Serialize,Debug,PartialEq)]
             #[serde(rename_all="camelCase")]
@@ -116,6 +253,20 @@ macro_rules! make_rpc_methods {
             }
         )*
 
+        $(
+            /// Structure transporting arguments of the request made by the server.
+            #[derive(Deserialize,Debug)]
+            #[serde(rename_all="camelCase")]
+            struct $req_input {
+                $($req_param_name : $req_param_ty),*
+            }
+
+            impl json_rpc::api::LocalMethodCall for $req_input {
+                const NAME:&'static str = $req_rpc_name;
+                type Returned = $req_result;
+            }
+        )*
+
 
 
         // ==================
@@ -139,8 +290,12 @@ macro_rules! make_rpc_methods {
             pub struct Client {
                 require_all_calls : Cell<bool>,
                 /// Expected calls handlers.
-                pub expect : ExpectedCalls,
-                events     : RefCell<Option<futures::channel::mpsc::UnboundedReceiver<Event>>>,
+                pub expect   : ExpectedCalls,
+                /// Handlers of the requests made by the server, which may be called to mock them.
+                pub requests : RequestHandlers,
+                events       : RefCell<Option<futures::channel::mpsc::UnboundedReceiver<Event>>>,
+                /// The calls made while the gate is held complete once it is released.
+                request_gate : json_rpc::handler::RequestGate,
             }
 
             impl API for Client {
@@ -150,7 +305,16 @@ macro_rules! make_rpc_methods {
                     assert!(!handlers.is_empty(),"Unexpected call {}",$rpc_name);
                     let handler      = handlers.remove(0);
                     let result       = handler($($param_name),*);
-                    Box::pin(futures::future::ready(result))
+                    let passage      = self.request_gate.passage();
+                    Box::pin(async move {
+                        passage.await;
+                        result
+                    })
+                })*
+
+                $(fn $set_handler(&self, handler:Box<dyn Fn($($req_param_ty),*)
+                -> std::pin::Pin<Box<dyn Future<Output=Result<$req_result>>>>>) {
+                    *self.requests.$req.borrow_mut() = Some(handler);
                 })*
 
                 fn events(&self) -> futures::stream::LocalBoxStream<'static,Event> {
@@ -160,6 +324,26 @@ macro_rules! make_rpc_methods {
                         futures::stream::empty().boxed_local()
                     }
                 }
+
+                fn request_gate(&self) -> json_rpc::handler::RequestGate {
+                    self.request_gate.clone_ref()
+                }
+
+                // The mock replies immediately, so the timeouts and retries do not apply.
+
+                fn set_timeout(&self, _timeout:std::time::Duration) {}
+
+                fn set_method_timeout(&self, _rpc_name:&str, _timeout:std::time::Duration) {}
+
+                fn set_retry_policy(&self, _policy:Option<json_rpc::handler::RetryPolicy>) {}
+
+                fn batch(&self) -> Box<dyn BatchAPI + '_> {
+                    let client = self;
+                    let gate   = json_rpc::handler::RequestGate::default();
+                    let sent   = default();
+                    gate.hold();
+                    Box::new(Batch {client,gate,sent})
+                }
             }
 
             impl Client {
@@ -186,6 +370,43 @@ macro_rules! make_rpc_methods {
                 }
             }
 
+            /// Mock of a batch of requests. The expected calls handlers are called when the requests
+            /// are added, but their results are yielded only once the batch is sent.
+            #[derive(Debug)]
+            pub struct Batch<'a> {
+                client : &'a Client,
+                /// Held until the batch is sent or dropped.
+                gate   : json_rpc::handler::RequestGate,
+                sent   : Rc<Cell<bool>>,
+            }
+
+            impl BatchAPI for Batch<'_> {
+                $(fn $method(&mut self $(,$param_name:&$param_ty)*)
+                -> std::pin::Pin<Box<dyn Future<Output=Result<$result>>>> {
+                    let mut handlers = self.client.expect.$method.borrow_mut();
+                    assert!(!handlers.is_empty(),"Unexpected call {}",$rpc_name);
+                    let handler      = handlers.remove(0);
+                    let result       = handler($($param_name),*);
+                    let released     = self.gate.released();
+                    let sent         = self.sent.clone();
+                    let request_gate = self.client.request_gate.clone_ref();
+                    Box::pin(async move {
+                        released.await;
+                        if sent.get() {
+                            request_gate.passage().await;
+                            result
+                        } else {
+                            Err(json_rpc::error::RpcError::LostConnection)
+                        }
+                    })
+                })*
+
+                fn send(self:Box<Self>) {
+                    self.sent.set(true);
+                    self.gate.release();
+                }
+            }
+
             /// A set of handlers of expected Mock Client calls. Handlers get call's parameters and
             /// returns the value to be returned.
             #[derive(Default)]
@@ -209,9 +430,46 @@ macro_rules! make_rpc_methods {
                     write!(f, "Expected calls for Mock Client")
                 }
             }
+
+            /// A set of handlers of the requests made by the server, as registered in the Mock
+            /// Client. Calling a request method mocks the server making the request.
+            #[derive(Default)]
+            pub struct RequestHandlers {
+                $($req : RefCell<Option<Box<dyn Fn($($req_param_ty),*)
+                -> std::pin::Pin<Box<dyn Future<Output=Result<$req_result>>>>>>>,)*
+            }
+
+            impl RequestHandlers {
+                $(
+                    /// Calls the handler registered for the request and returns its reply.
+                    /// Panics if no handler was registered.
+                    pub fn $req(&self $(,$req_param_name:$req_param_ty)*)
+                    -> std::pin::Pin<Box<dyn Future<Output=Result<$req_result>>>> {
+                        let handler = self.$req.borrow();
+                        let handler = handler.as_ref().expect("No handler for the request.");
+                        handler($($req_param_name),*)
+                    }
+                )*
+            }
+
+            impl Debug for RequestHandlers {
+                fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    write!(f, "Request handlers of Mock Client")
+                }
+            }
         }
         pub use mock::Client as MockClient;
-    }
+    };
+    (
+        $(#[doc = $impl_doc:expr])+
+        trait API { $($api:tt)* }
+    ) => {
+        $crate::make_rpc_methods! {
+            $(#[doc = $impl_doc])+
+            trait API { $($api)* }
+            requests {}
+        }
+    };
 }
 
 /// A shortcut for creating call's handlers for client mocks; when you want to just check if call's
@@ -196,13 +196,24 @@ pub struct Error<Payload=serde_json::Value> {
     pub data    : Option<Payload>
 }
 
-/// A message that can come from Server to Client — either a response or
-/// notification.
+/// Error code of the reply to a request calling a method, which is not available.
+pub const METHOD_NOT_FOUND_ERROR_CODE:i64 = -32601;
+
+/// Error code of the reply to a request with parameters not matching its method.
+pub const INVALID_PARAMS_ERROR_CODE:i64 = -32602;
+
+/// Error code of the reply to a request, which failed for any other reason.
+pub const INTERNAL_ERROR_CODE:i64 = -32603;
+
+/// A message that can come from Server to Client — either a response,
+/// request or notification.
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(untagged)]
 pub enum IncomingMessage {
     /// A response to a call made by client.
     Response    (Response    <serde_json::Value>),
+    /// A request (initiated by the server) awaiting a reply.
+    Request     (Request     <MethodCall<serde_json::Value>>),
     /// A notification call (initiated by the server).
     Notification(Notification<serde_json::Value>),
 }
@@ -210,7 +221,7 @@ pub enum IncomingMessage {
 /// Partially decodes incoming message.
 ///
 /// This checks if has `jsonrpc` version string, and whether it is a
-/// response or a notification.
+/// response, a request or a notification.
 pub fn decode_incoming_message
 (message:&str) -> serde_json::Result<IncomingMessage> {
     use serde_json::Value;
@@ -220,6 +231,26 @@ pub fn decode_incoming_message
     from_value::<IncomingMessage>(message.payload)
 }
 
+/// Partially decodes incoming text message, which may be either a single message or a batch, i.e.
+/// an array of messages.
+///
+/// Each message of the batch is decoded separately, so a single malformed message does not prevent
+/// handling the other ones.
+pub fn decode_incoming_messages
+(message:&str) -> serde_json::Result<Vec<serde_json::Result<IncomingMessage>>> {
+    use serde_json::Value;
+    use serde_json::from_str;
+    use serde_json::from_value;
+    let decode = |value:Value| {
+        let message = from_value::<Message<Value>>(value)?;
+        from_value::<IncomingMessage>(message.payload)
+    };
+    match from_str::<Value>(message)? {
+        Value::Array(messages) => Ok(messages.into_iter().map(decode).collect()),
+        message                => Ok(vec![decode(message)]),
+    }
+}
+
 /// Message from server to client.
 ///
 /// `In` is any serializable (or already serialized) representation of the
@@ -361,6 +392,40 @@ mod tests {
         assert_eq!(got_value, Version::V2);
     }
 
+    #[test]
+    fn decode_incoming_batch_text() {
+        let text = r#"[
+            {"jsonrpc":"2.0","id":1,"error":{"code":1,"message":"Service error"}},
+            {"jsonrpc":"2.0","method":"update","params":{}},
+            {"id":2},
+            {"jsonrpc":"2.0","id":0,"result":null}
+        ]"#;
+        let messages = decode_incoming_messages(text).unwrap();
+        assert_eq!(messages.len(),4);
+        assert!(matches!(messages[0],Ok(IncomingMessage::Response(Response{id:Id(1),..}))));
+        assert!(matches!(messages[1],Ok(IncomingMessage::Notification(_))));
+        assert!(messages[2].is_err());
+        assert!(matches!(messages[3],Ok(IncomingMessage::Response(Response{id:Id(0),..}))));
+
+        let single = r#"{"jsonrpc":"2.0","id":3,"result":true}"#;
+        let messages = decode_incoming_messages(single).unwrap();
+        assert!(matches!(messages[..],[Ok(IncomingMessage::Response(Response{id:Id(3),..}))]));
+        assert!(decode_incoming_messages("[1,2").is_err());
+    }
+
+    #[test]
+    fn decode_incoming_request_text() {
+        let text = r#"{"jsonrpc":"2.0","id":7,"method":"confirm","params":{"text":"Sure?"}}"#;
+        let message = decode_incoming_message(text).unwrap();
+        if let IncomingMessage::Request(request) = message {
+            assert_eq!(request.id,Id(7));
+            assert_eq!(request.method,"confirm");
+            assert_eq!(request.params,serde_json::json!({"text":"Sure?"}));
+        } else {
+            panic!("Expected a request, got {:?}.",message)
+        }
+    }
+
     #[test]
     fn decode_incoming_error_message_text() {
         let text    = r#"{"jsonrpc":"2.0","id":1,"error":{"code":1,"message":"Service error"}}"#;
@@ -2,4 +2,5 @@
 //!
 //! Reusable code for other crates that want to test usage of this crate.
 
+pub mod timer;
 pub mod transport;
@@ -0,0 +1,70 @@
+//! Module provides a `MockTimer`, which allows controlling the time measured by the `Handler`.
+//!
+//! It is meant to be used in tests, so they do not depend on the real time passing.
+
+use crate::prelude::*;
+
+use crate::ensogl::Duration;
+use crate::handler::Timer;
+
+use futures::FutureExt;
+use futures::channel::oneshot;
+
+
+
+// =================
+// === MockTimer ===
+// =================
+
+/// Mock timer shared data.
+#[derive(Debug,Default)]
+pub struct MockTimerData {
+    /// Time passed since the timer was created.
+    pub now     : Duration,
+    /// The deadlines of the pending sleeps, with the channels notifying their `Future`s.
+    pub pending : Vec<(Duration,oneshot::Sender<()>)>,
+}
+
+/// Timer in which the time passes only when `advance` is called.
+#[derive(Clone,CloneRef,Debug,Default)]
+pub struct MockTimer(Rc<RefCell<MockTimerData>>);
+
+impl MockTimer {
+    /// Create a new timer.
+    pub fn new() -> MockTimer {
+        default()
+    }
+
+    /// Returns a `Timer` using this mock, to be set for the `Handler`.
+    pub fn timer(&self) -> Timer {
+        let this = self.clone_ref();
+        Timer::new(move |duration| this.sleep(duration))
+    }
+
+    /// Returns a `Future` completing once the time is advanced by the given `duration`.
+    pub fn sleep(&self, duration:Duration) -> futures::future::LocalBoxFuture<'static,()> {
+        let (sender,receiver) = oneshot::channel();
+        let mut data          = self.0.borrow_mut();
+        let deadline          = data.now + duration;
+        data.pending.push((deadline,sender));
+        receiver.map(|_| ()).boxed_local()
+    }
+
+    /// Advance the time, completing the `Future`s of all sleeps which passed their deadline. They
+    /// must be polled afterwards, e.g. by running the executor.
+    pub fn advance(&self, duration:Duration) {
+        let finished = with(self.0.borrow_mut(), |mut data| {
+            data.now += duration;
+            let now = data.now;
+            let (finished,pending) = std::mem::take(&mut data.pending).into_iter().partition(|t| {
+                t.0 <= now
+            });
+            data.pending = pending;
+            finished
+        });
+        for (_,sender) in finished {
+            // The sleeping `Future` might have been dropped already.
+            let _ = sender.send(());
+        }
+    }
+}
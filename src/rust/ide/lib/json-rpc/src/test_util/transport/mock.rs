@@ -4,6 +4,8 @@
 
 use crate::prelude::*;
 
+use crate::messages::Id;
+use crate::messages::Message;
 use crate::transport::Transport;
 use crate::transport::TransportEvent;
 
@@ -120,6 +122,12 @@ impl MockTransport {
         self.mock_peer_text_message(text)
     }
 
+    /// Generates event that mocks receiving a request from a peer, which expects a reply with the
+    /// same id.
+    pub fn mock_peer_request<T:Serialize>(&mut self, id:Id, method:&str, params:T) {
+        self.mock_peer_json_message(Message::new_request(id,method,params))
+    }
+
     /// Generates event that mocks receiving a text message from a peer.
     pub fn mock_peer_binary_message(&mut self, data:&[u8]) {
         if let Some(ref mut transmitter) = self.0.borrow_mut().event_transmitter {
@@ -139,6 +147,17 @@ impl MockTransport {
         })
     }
 
+    /// Mocks event generated when the socket has been opened again, e.g. by reconnecting after the
+    /// connection was lost.
+    pub fn mock_connection_opened(&mut self) {
+        self.with_mut_data(|data| {
+            if let Some(ref mut transmitter) = data.event_transmitter {
+                data.is_closed = false;
+                channel::emit(transmitter,TransportEvent::Opened);
+            }
+        })
+    }
+
     /// Takes the text message sent by the client and returns its contents.
     ///
     /// If the client has not sent any text messages, panics.
@@ -1,3 +1,4 @@
 //! Test utilities for the Transport.
 
 pub mod mock;
+pub mod replay;
@@ -0,0 +1,370 @@
+//! Module provides a `ReplayTransport` that plays back the peer's side of a `Recording`.
+//!
+//! Each message sent through the transport is matched against the next frame recorded as sent. If
+//! they match, all the frames recorded as received up to the next sent frame are emitted. The
+//! timestamps are ignored, so the replay is deterministic.
+//!
+//! The owner of the transport will likely not send exactly the same messages as in the recorded
+//! session: request ids, client ids and other randomly generated identifiers differ. Therefore the
+//! text messages may differ only in such identifiers, and the differences found between the
+//! recorded and actual messages are substituted in all the frames emitted afterwards:
+//!  * JSON-RPC message ids are substituted in the `id` fields of the received messages.
+//!  * Differing UUIDs and values of the `GENERATED_FIELDS` are substituted where the whole string
+//!    value is equal.
+//!  * Differing byte ranges of the same-length binary messages are substituted wherever they occur.
+//!    Short ranges are ignored, as they would likely match unrelated bytes.
+
+use crate::prelude::*;
+
+use crate::transport::Transport;
+use crate::transport::TransportEvent;
+use crate::transport::recording::Direction;
+use crate::transport::recording::Entry;
+use crate::transport::recording::Frame;
+use crate::transport::recording::Recording;
+
+use failure::Error;
+use futures::channel::mpsc::UnboundedSender;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::ops::Range;
+use utils::channel;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// Fields whose string values are generated anew in each session, so they may differ from the
+/// recorded ones even if they are not UUIDs.
+pub const GENERATED_FIELDS:&[&str] = &["clientId"];
+
+/// Differing byte ranges shorter than this are not substituted in the binary messages.
+const MIN_BINARY_SUBSTITUTION_LENGTH:usize = 4;
+
+/// Differing byte ranges separated by fewer equal bytes than this are merged into a single range,
+/// so randomly equal bytes do not split the substituted identifiers.
+const MAX_BINARY_SUBSTITUTION_GAP:usize = 4;
+
+
+
+// ===================
+// === ReplayError ===
+// ===================
+
+/// Errors emitted by the `ReplayTransport`.
+#[allow(missing_docs)]
+#[derive(Clone,Debug,Fail)]
+pub enum ReplayError {
+    #[fail(display = "Replayed session expected {} but {} was sent.", expected, actual)]
+    UnexpectedMessage {expected:String, actual:String},
+    #[fail(display = "Replayed session has already finished, but {} was sent.", _0)]
+    SessionFinished(String),
+    #[fail(display = "Cannot send message when socket is closed.")]
+    TransportClosed,
+}
+
+/// Checks if the string is a UUID in the hyphenated form, e.g.
+/// `8a3e5ff3-5f7f-4b35-bd37-a9d2d8c3d3b2`.
+fn is_uuid(string:&str) -> bool {
+    let hyphens = [8,13,18,23];
+    string.len() == 36 && string.char_indices().all(|(index,char)| {
+        if hyphens.contains(&index) { char == '-' } else { char.is_ascii_hexdigit() }
+    })
+}
+
+fn describe(frame:&Frame) -> String {
+    match frame {
+        Frame::Text(text)  => format!("`{}`",text),
+        Frame::Binary(bin) => format!("binary message of {} bytes",bin.len()),
+        other              => format!("{:?}",other),
+    }
+}
+
+
+
+// =====================
+// === Substitutions ===
+// =====================
+
+/// The differences between the recorded and actually sent messages, which are applied to the
+/// frames emitted by the `ReplayTransport`.
+#[derive(Clone,Debug,Default)]
+pub struct Substitutions {
+    /// Message ids, keyed by the serialized recorded id.
+    ids     : HashMap<String,Value>,
+    strings : HashMap<String,String>,
+    bytes   : Vec<(Vec<u8>,Vec<u8>)>,
+}
+
+impl Substitutions {
+    /// Compare the recorded JSON message with the actually sent one, remembering the differences.
+    ///
+    /// Fails if the messages differ in anything else than message ids, UUIDs and values of the
+    /// `GENERATED_FIELDS`.
+    fn learn_json(&mut self, recorded:&Value, actual:&Value) -> Result<(),()> {
+        self.learn_json_field(None,recorded,actual)
+    }
+
+    /// Compare the recorded and actual values of the given field (`None` for the message itself,
+    /// or for the elements of a top-level array).
+    fn learn_json_field
+    (&mut self, field:Option<&str>, recorded:&Value, actual:&Value) -> Result<(),()> {
+        match (recorded,actual) {
+            (Value::Object(recorded),Value::Object(actual)) => {
+                let same_keys = recorded.len() == actual.len()
+                    && recorded.keys().all(|key| actual.contains_key(key));
+                if !same_keys {
+                    return Err(())
+                }
+                let is_message = recorded.contains_key("jsonrpc");
+                for (key,recorded_value) in recorded {
+                    let actual_value = &actual[key];
+                    if is_message && key == "id" {
+                        self.learn_id(recorded_value,actual_value)
+                    } else {
+                        self.learn_json_field(Some(key),recorded_value,actual_value)?
+                    }
+                }
+                Ok(())
+            }
+            (Value::Array(recorded),Value::Array(actual)) => {
+                if recorded.len() != actual.len() {
+                    return Err(())
+                }
+                recorded.iter().zip(actual).try_for_each(|(r,a)| self.learn_json_field(field,r,a))
+            }
+            (Value::String(recorded),Value::String(actual)) if recorded != actual => {
+                let is_generated_field = field.map_or(false,|f| GENERATED_FIELDS.contains(&f));
+                let are_uuids          = is_uuid(recorded) && is_uuid(actual);
+                if is_generated_field || are_uuids {
+                    self.strings.insert(recorded.clone(),actual.clone());
+                    Ok(())
+                } else {
+                    Err(())
+                }
+            }
+            _ => if recorded == actual { Ok(()) } else { Err(()) }
+        }
+    }
+
+    fn learn_id(&mut self, recorded:&Value, actual:&Value) {
+        if recorded != actual {
+            self.ids.insert(recorded.to_string(),actual.clone());
+        }
+    }
+
+    /// Compare the recorded binary message with the actually sent one, remembering the differing
+    /// byte ranges. Messages of different lengths are not compared.
+    fn learn_binary(&mut self, recorded:&[u8], actual:&[u8]) {
+        if recorded.len() != actual.len() {
+            return
+        }
+        let mut ranges : Vec<Range<usize>> = default();
+        for (index,(r,a)) in recorded.iter().zip(actual).enumerate() {
+            if r != a {
+                match ranges.last_mut() {
+                    Some(last) if index - last.end < MAX_BINARY_SUBSTITUTION_GAP =>
+                        last.end = index + 1,
+                    _ => ranges.push(index..index + 1),
+                }
+            }
+        }
+        let long_enough = ranges.into_iter().filter(|r| r.len() >= MIN_BINARY_SUBSTITUTION_LENGTH);
+        for range in long_enough {
+            let substitution = (recorded[range.clone()].to_vec(),actual[range].to_vec());
+            if !self.bytes.contains(&substitution) {
+                self.bytes.push(substitution);
+            }
+        }
+    }
+
+    fn apply_json(&self, value:&mut Value) {
+        match value {
+            Value::Object(object) => {
+                let is_message = object.contains_key("jsonrpc");
+                for (key,value) in object.iter_mut() {
+                    if is_message && key == "id" {
+                        if let Some(id) = self.ids.get(&value.to_string()) {
+                            *value = id.clone();
+                        }
+                    } else {
+                        self.apply_json(value);
+                    }
+                }
+            }
+            Value::Array(array) => array.iter_mut().for_each(|value| self.apply_json(value)),
+            Value::String(string) => {
+                if let Some(substituted) = self.strings.get(string) {
+                    *string = substituted.clone();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_binary(&self, message:&mut [u8]) {
+        for (recorded,actual) in &self.bytes {
+            let mut index = 0;
+            while index + recorded.len() <= message.len() {
+                if message[index..].starts_with(recorded) {
+                    message[index..index + recorded.len()].copy_from_slice(actual);
+                    index += recorded.len();
+                } else {
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    /// Apply the remembered substitutions to the frame about to be emitted.
+    fn apply(&self, frame:Frame) -> Frame {
+        match frame {
+            Frame::Text(text) => match serde_json::from_str::<Value>(&text) {
+                Ok(mut json) => {
+                    self.apply_json(&mut json);
+                    Frame::Text(json.to_string())
+                }
+                // Not a JSON message, e.g. recorded garbage. Emitted as-is.
+                Err(_) => Frame::Text(text),
+            }
+            Frame::Binary(mut bin) => {
+                self.apply_binary(&mut bin);
+                Frame::Binary(bin)
+            }
+            other => other,
+        }
+    }
+}
+
+
+
+// ===========================
+// === ReplayTransportData ===
+// ===========================
+
+/// Replay transport shared data.
+#[derive(Debug,Default)]
+pub struct ReplayTransportData {
+    /// Events sink.
+    pub event_transmitter : Option<UnboundedSender<TransportEvent>>,
+    /// The recorded entries not replayed yet.
+    pub remaining         : VecDeque<Entry>,
+    /// The differences between the recorded and actually sent messages seen so far.
+    pub substitutions     : Substitutions,
+    /// Whether the replayed peer has closed the connection.
+    pub is_closed         : bool,
+}
+
+impl ReplayTransportData {
+    /// Emit all the received frames up to the next sent one.
+    fn emit_received(&mut self) {
+        while let Some(entry) = self.remaining.front() {
+            if entry.direction == Direction::Sent || self.event_transmitter.is_none() {
+                break
+            }
+            let entry = self.remaining.pop_front().unwrap();
+            let frame = self.substitutions.apply(entry.frame);
+            if frame == Frame::Closed {
+                self.is_closed = true;
+            }
+            if let Some(transmitter) = &self.event_transmitter {
+                channel::emit(transmitter,frame.into());
+            }
+        }
+    }
+
+    /// Match the sent frame against the next recorded one and replay the peer's reaction.
+    fn send(&mut self, sent:Frame) -> Result<(),Error> {
+        if self.is_closed {
+            return Err(ReplayError::TransportClosed.into())
+        }
+        let expected = match self.remaining.front() {
+            Some(entry) => &entry.frame,
+            None        => return Err(ReplayError::SessionFinished(describe(&sent)).into()),
+        };
+        let unexpected = || ReplayError::UnexpectedMessage {
+            expected : describe(expected),
+            actual   : describe(&sent),
+        };
+        match (expected,&sent) {
+            (Frame::Text(recorded),Frame::Text(actual)) => {
+                let recorded = serde_json::from_str::<Value>(recorded);
+                let actual   = serde_json::from_str::<Value>(actual);
+                if let (Ok(recorded),Ok(actual)) = (recorded,actual) {
+                    let mut substitutions = self.substitutions.clone();
+                    substitutions.learn_json(&recorded,&actual).map_err(|()| unexpected())?;
+                    self.substitutions = substitutions;
+                } else if expected != &sent {
+                    return Err(unexpected().into())
+                }
+            }
+            (Frame::Binary(recorded),Frame::Binary(actual)) =>
+                self.substitutions.learn_binary(recorded,actual),
+            _ => return Err(unexpected().into()),
+        }
+        self.remaining.pop_front();
+        self.emit_received();
+        Ok(())
+    }
+}
+
+
+
+// =======================
+// === ReplayTransport ===
+// =======================
+
+/// Shareable wrapper over `ReplayTransportData`.
+#[derive(Clone,CloneRef,Debug,Default)]
+pub struct ReplayTransport(Rc<RefCell<ReplayTransportData>>);
+
+impl Transport for ReplayTransport {
+    fn send_text(&mut self, message:&str) -> Result<(), Error> {
+        self.0.borrow_mut().send(Frame::Text(message.into()))
+    }
+
+    fn send_binary(&mut self, message:&[u8]) -> Result<(), Error> {
+        self.0.borrow_mut().send(Frame::Binary(message.into()))
+    }
+
+    fn set_event_transmitter(&mut self, transmitter:UnboundedSender<TransportEvent>) {
+        let mut data = self.0.borrow_mut();
+        data.event_transmitter = Some(transmitter);
+        data.emit_received();
+    }
+}
+
+impl ReplayTransport {
+    /// Create a new `ReplayTransport` playing back the given recording.
+    pub fn new(recording:Recording) -> ReplayTransport {
+        let remaining = recording.entries.into();
+        let data      = ReplayTransportData {remaining,..default()};
+        ReplayTransport(Rc::new(RefCell::new(data)))
+    }
+
+    /// Create a new `ReplayTransport` playing back the recording in the JSON Lines format.
+    ///
+    /// Panics if the recording is malformed.
+    pub fn from_json_lines(recording:&str) -> ReplayTransport {
+        let recording = Recording::from_json_lines(recording);
+        Self::new(recording.expect("failed to parse the recording"))
+    }
+
+    /// Checks if all the recorded entries have been replayed.
+    pub fn is_finished(&self) -> bool {
+        self.0.borrow().remaining.is_empty()
+    }
+
+    /// Panics if not all the recorded entries have been replayed.
+    pub fn expect_finished(&self) {
+        let remaining = &self.0.borrow().remaining;
+        if let Some(next) = remaining.front() {
+            let count = remaining.len();
+            let next  = describe(&next.frame);
+            panic!("the replayed session should have finished, but {} entries remain, the next \
+                one being {}",count,next)
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//! Module provides a `RecordingTransport` that wraps any `Transport` and logs every frame going
+//! through it, together with a timestamp.
+//!
+//! The logged session is stored as `Recording` in the JSON Lines format (one `Entry` per line),
+//! and can be played back later in tests by the `test_util::transport::replay::ReplayTransport`.
+//! In the browser, where there is no file system, the entries can be dumped to the console with
+//! `ConsoleSink`.
+
+use crate::prelude::*;
+
+use crate::transport::Transport;
+use crate::transport::TransportEvent;
+
+use failure::Error;
+use futures::channel::mpsc::UnboundedReceiver;
+use futures::channel::mpsc::UnboundedSender;
+use futures::channel::mpsc::unbounded;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde::Serialize;
+use std::io::Write;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+use utils::channel;
+
+
+
+// =============
+// === Entry ===
+// =============
+
+/// The side of the connection that has produced the frame.
+#[derive(Clone,Copy,Debug,Deserialize,Eq,PartialEq,Serialize)]
+pub enum Direction {
+    /// The frame was sent by the owner of the transport (e.g. the IDE).
+    Sent,
+    /// The frame was received from the peer (e.g. the Language Server).
+    Received,
+}
+
+/// A single recorded frame or connection event.
+#[derive(Clone,Debug,Deserialize,Eq,PartialEq,Serialize)]
+pub enum Frame {
+    /// A text message.
+    Text(String),
+    /// A binary message.
+    Binary(Vec<u8>),
+    /// The socket has been opened.
+    Opened,
+    /// The socket has been closed.
+    Closed,
+}
+
+impl From<&TransportEvent> for Frame {
+    fn from(event:&TransportEvent) -> Self {
+        match event {
+            TransportEvent::TextMessage(text)  => Frame::Text(text.clone()),
+            TransportEvent::BinaryMessage(bin) => Frame::Binary(bin.clone()),
+            TransportEvent::Opened             => Frame::Opened,
+            TransportEvent::Closed             => Frame::Closed,
+        }
+    }
+}
+
+impl From<Frame> for TransportEvent {
+    fn from(frame:Frame) -> Self {
+        match frame {
+            Frame::Text(text)  => TransportEvent::TextMessage(text),
+            Frame::Binary(bin) => TransportEvent::BinaryMessage(bin),
+            Frame::Opened      => TransportEvent::Opened,
+            Frame::Closed      => TransportEvent::Closed,
+        }
+    }
+}
+
+/// A single line of the `Recording`.
+#[derive(Clone,Debug,Deserialize,PartialEq,Serialize)]
+pub struct Entry {
+    /// Milliseconds elapsed since the recording has started.
+    pub time      : f64,
+    /// Which side of the connection has produced the frame.
+    pub direction : Direction,
+    /// The recorded frame.
+    pub frame     : Frame,
+}
+
+
+
+// =================
+// === Recording ===
+// =================
+
+/// A recorded session: all the frames that went through the `RecordingTransport`, in order.
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct Recording {
+    /// The recorded entries.
+    pub entries : Vec<Entry>,
+}
+
+impl Recording {
+    /// Parse the recording from the JSON Lines format. Empty lines are skipped.
+    pub fn from_json_lines(text:&str) -> serde_json::Result<Self> {
+        let lines   = text.lines().filter(|line| !line.trim().is_empty());
+        let entries = lines.map(serde_json::from_str).collect::<serde_json::Result<_>>()?;
+        Ok(Recording {entries})
+    }
+
+    /// Read the recording from the file written by `RecordingTransport`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path:impl AsRef<Path>) -> Result<Self,Error> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(Self::from_json_lines(&text)?)
+    }
+
+    /// Serialize the recording to the JSON Lines format.
+    pub fn to_json_lines(&self) -> String {
+        let to_line = |entry| serde_json::to_string(entry).expect("Failed to serialize entry.");
+        self.entries.iter().map(to_line).map(|line| line + "\n").collect()
+    }
+}
+
+
+
+// ===================
+// === ConsoleSink ===
+// ===================
+
+/// Sink writing each line to the browser console, prefixed with the label, so the recordings of
+/// several transports can be told apart. To get the recording, filter the console messages by the
+/// label, save them and strip the prefix.
+#[derive(Clone,Debug)]
+pub struct ConsoleSink {
+    label : String,
+    line  : Vec<u8>,
+}
+
+impl ConsoleSink {
+    /// Create a sink logging the lines with the given label.
+    pub fn new(label:impl Into<String>) -> Self {
+        let label = label.into();
+        let line  = default();
+        ConsoleSink {label,line}
+    }
+}
+
+impl Write for ConsoleSink {
+    fn write(&mut self, buf:&[u8]) -> std::io::Result<usize> {
+        for byte in buf {
+            if *byte == b'\n' {
+                let line    = String::from_utf8_lossy(&self.line);
+                let message = format!("{} {}",self.label,line);
+                ensogl_system_web::console::log_1(&message.into());
+                self.line.clear();
+            } else {
+                self.line.push(*byte);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+
+
+// ================
+// === Recorder ===
+// ================
+
+/// Writes the entries to the sink, as they happen.
+struct Recorder {
+    sink       : Box<dyn Write>,
+    start_time : f64,
+}
+
+impl Debug for Recorder {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        write!(f,"Recorder(start_time:{})",self.start_time)
+    }
+}
+
+impl Recorder {
+    fn new(sink:Box<dyn Write>) -> Self {
+        let start_time = ensogl_system_web::time_from_start();
+        Recorder {sink,start_time}
+    }
+
+    /// Record the frame. Panics if the sink fails, as a recording with missing frames would be
+    /// useless for replaying.
+    fn record(&mut self, direction:Direction, frame:Frame) {
+        let time  = ensogl_system_web::time_from_start() - self.start_time;
+        let entry = Entry {time,direction,frame};
+        let line  = serde_json::to_string(&entry).expect("Failed to serialize entry.");
+        writeln!(self.sink,"{}",line).expect("Failed to write the recorded entry.");
+        self.sink.flush().expect("Failed to flush the recorded entry.");
+    }
+}
+
+
+
+// ==========================
+// === RecordingTransport ===
+// ==========================
+
+/// A `Transport` that passes everything to the wrapped transport, recording all sent and received
+/// frames to the given sink.
+///
+/// The events of the wrapped transport are recorded and forwarded by the future returned from
+/// `runner`, which should be spawned alongside the client using this transport.
+#[derive(Debug)]
+pub struct RecordingTransport<T> {
+    inner             : T,
+    recorder          : Rc<RefCell<Recorder>>,
+    events            : Option<UnboundedReceiver<TransportEvent>>,
+    event_transmitter : Rc<RefCell<Option<UnboundedSender<TransportEvent>>>>,
+}
+
+impl<T:Transport> RecordingTransport<T> {
+    /// Wrap the transport, writing the recorded entries to the given sink.
+    pub fn new(mut inner:T, sink:impl Write + 'static) -> Self {
+        let (inner_transmitter,events) = unbounded();
+        inner.set_event_transmitter(inner_transmitter);
+        let recorder          = Rc::new(RefCell::new(Recorder::new(Box::new(sink))));
+        let events            = Some(events);
+        let event_transmitter = default();
+        RecordingTransport {inner,recorder,events,event_transmitter}
+    }
+
+    /// Wrap the transport, writing the recorded entries to the newly created file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new_to_file(inner:T, path:impl AsRef<Path>) -> Result<Self,Error> {
+        let file = std::fs::File::create(path)?;
+        Ok(Self::new(inner,file))
+    }
+
+    /// Wrap the transport, logging the recorded entries to the browser console. See `ConsoleSink`.
+    pub fn new_to_console(inner:T, label:impl Into<String>) -> Self {
+        Self::new(inner,ConsoleSink::new(label))
+    }
+
+    /// Returns a future that records the events of the wrapped transport and passes them to the
+    /// event transmitter. Must be called before the transport is passed to the client.
+    ///
+    /// Calling this method for the second time returns a future that finishes immediately.
+    pub fn runner(&mut self) -> impl Future<Output=()> {
+        let events            = self.events.take();
+        let recorder          = self.recorder.clone();
+        let event_transmitter = self.event_transmitter.clone();
+        async move {
+            if let Some(events) = events {
+                events.for_each(move |event| {
+                    recorder.borrow_mut().record(Direction::Received,Frame::from(&event));
+                    if let Some(transmitter) = &*event_transmitter.borrow() {
+                        channel::emit(transmitter,event);
+                    }
+                    futures::future::ready(())
+                }).await
+            }
+        }
+    }
+}
+
+impl<T:Transport> Transport for RecordingTransport<T> {
+    fn send_text(&mut self, message:&str) -> Result<(), Error> {
+        self.inner.send_text(message)?;
+        self.recorder.borrow_mut().record(Direction::Sent,Frame::Text(message.into()));
+        Ok(())
+    }
+
+    fn send_binary(&mut self, message:&[u8]) -> Result<(), Error> {
+        self.inner.send_binary(message)?;
+        self.recorder.borrow_mut().record(Direction::Sent,Frame::Binary(message.into()));
+        Ok(())
+    }
+
+    fn set_event_transmitter(&mut self, transmitter:UnboundedSender<TransportEvent>) {
+        *self.event_transmitter.borrow_mut() = Some(transmitter);
+    }
+}
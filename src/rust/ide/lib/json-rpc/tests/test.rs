@@ -5,6 +5,7 @@ use futures::FutureExt;
 use futures::Stream;
 use futures::task::LocalSpawnExt;
 use json_rpc::*;
+use json_rpc::api::LocalMethodCall;
 use json_rpc::api::RemoteMethodCall;
 use json_rpc::api::Result;
 use json_rpc::error::RpcError;
@@ -12,7 +13,14 @@ use json_rpc::error::HandlingError;
 use json_rpc::messages::Id;
 use json_rpc::messages::Message;
 use json_rpc::messages::Version;
+use json_rpc::test_util::timer::MockTimer;
 use json_rpc::test_util::transport::mock::MockTransport;
+use json_rpc::test_util::transport::replay::ReplayError;
+use json_rpc::test_util::transport::replay::ReplayTransport;
+use json_rpc::transport::recording::Direction;
+use json_rpc::transport::recording::Frame;
+use json_rpc::transport::recording::Recording;
+use json_rpc::transport::recording::RecordingTransport;
 use serde::Deserialize;
 use serde::Serialize;
 use std::future::Future;
@@ -50,6 +58,14 @@ impl RemoteMethodCall for MockRequest {
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 struct MockResponse { result:i64 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct MockConfirmation {text:String}
+
+impl LocalMethodCall for MockConfirmation {
+    const NAME:&'static str = "confirm";
+    type Returned           = bool;
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(tag = "method", content="params")]
 pub enum MockNotification {
@@ -64,6 +80,8 @@ type MockRequestMessage = messages::RequestMessage<MockRequest>;
 
 type MockResponseMessage = messages::ResponseMessage<MockResponse>;
 
+type MockConfirmationReply = messages::ResponseMessage<bool>;
+
 
 
 // ===================
@@ -90,6 +108,19 @@ impl Client {
         self.handler.open_request(input).map(|result| result.map(|r| r.result))
     }
 
+    pub fn pow_with_options(&mut self, i:i64, options:handler::RequestOptions)
+    -> impl Future<Output = Result<i64>> {
+        let input = serde_json::to_value(MockRequest { i }).unwrap();
+        let fut   = self.handler.open_request_with_options(MockRequest::NAME,&input,options);
+        fut.map(|result:Result<MockResponse>| result.map(|r| r.result))
+    }
+
+    pub fn pow_batch(&mut self, batch:&mut handler::Batch<MockNotification>, i:i64)
+    -> impl Future<Output = Result<i64>> {
+        let input = MockRequest { i };
+        batch.open_request(input).map(|result| result.map(|r| r.result))
+    }
+
     pub fn events_processor(&mut self) -> impl Future<Output = ()> {
         self.handler.runner()
     }
@@ -237,12 +268,15 @@ fn test_timeout_error() {
 #[test]
 fn test_disconnect_error() {
     let mut fixture = Fixture::new();
+    let gate        = fixture.client.handler.request_gate();
     let mut fut     = Box::pin(fixture.client.pow(8));
     fut.expect_pending(); // no reply nor relevant event
     fixture.transport.mock_connection_closed();
     fut.expect_pending(); // closing event not yet processed
+    assert_eq!(gate.disconnections(),0);
 
     fixture.pool.run_until_stalled();
+    assert_eq!(gate.disconnections(),1);
 
     let result = fut.expect_err();
     if let RpcError::LostConnection = result {} else {
@@ -258,6 +292,24 @@ fn test_sending_while_disconnected() {
     fut.expect_err();
 }
 
+#[test]
+fn test_reconnecting() {
+    let mut fixture = Fixture::new();
+    fixture.transport.mock_connection_closed();
+    fixture.pool.run_until_stalled();
+    assert!(matches!(fixture.client.events_stream.expect_next(),MockEvent::Closed));
+
+    fixture.transport.mock_connection_opened();
+    fixture.pool.run_until_stalled();
+    assert!(matches!(fixture.client.events_stream.expect_next(),MockEvent::Opened));
+
+    let mut fut = Box::pin(fixture.client.pow(8));
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    fixture.transport.mock_peer_json_message(pow_impl(request));
+    fixture.pool.run_until_stalled();
+    assert_eq!(fut.expect_ok(),64);
+}
+
 fn test_notification(mock_notif:MockNotification) {
     let mut fixture = Fixture::new();
     let message     = Message::new(mock_notif.clone());
@@ -300,3 +352,529 @@ fn test_handling_invalid_notification() {
         panic!("expected InvalidNotification error");
     }
 }
+
+
+#[test]
+fn test_batch_call() {
+    let mut fixture = Fixture::new();
+    let mut batch   = fixture.client.handler.batch();
+    let mut fut1    = Box::pin(fixture.client.pow_batch(&mut batch,2));
+    let mut fut2    = Box::pin(fixture.client.pow_batch(&mut batch,3));
+    let mut fut3    = Box::pin(fixture.client.pow_batch(&mut batch,4));
+    assert_eq!(batch.len(),3);
+    fixture.pool.run_until_stalled();
+    fixture.transport.with_mut_data(|data| assert!(data.sent_text_msgs.is_empty()));
+    batch.send();
+
+    // validate a single batch message sent
+    let requests = fixture.transport.expect_json_message::<Vec<MockRequestMessage>>();
+    let ids      = requests.iter().map(|req| req.id).collect_vec();
+    let inputs   = requests.iter().map(|req| req.i).collect_vec();
+    assert_eq!(ids,    vec![Id(0),Id(1),Id(2)]);
+    assert_eq!(inputs, vec![2,3,4]);
+
+    // reply with a mixed batch, in a different order
+    let mut requests = requests.into_iter();
+    let first        = requests.next().unwrap();
+    let second       = requests.next().unwrap();
+    let third        = requests.next().unwrap();
+    let error:MockResponseMessage = Message::new_error(second.id,5,"wrong!".into(),None);
+    let reply = vec![
+        serde_json::to_value(pow_impl(third)).unwrap(),
+        serde_json::to_value(error).unwrap(),
+        serde_json::to_value(pow_impl(first)).unwrap(),
+    ];
+    fixture.transport.mock_peer_json_message(reply);
+    fixture.pool.run_until_stalled();
+
+    assert_eq!(fut1.expect_ok(),4);
+    assert_eq!(fut3.expect_ok(),16);
+    if let RpcError::RemoteError(e) = fut2.expect_err() {
+        assert_eq!(e.code,5);
+    } else {
+        panic!("Expected an error to be RemoteError");
+    }
+}
+
+#[test]
+fn test_batch_with_notification_and_garbage() {
+    let mut fixture = Fixture::new();
+    let mut batch   = fixture.client.handler.batch();
+    let mut fut     = Box::pin(fixture.client.pow_batch(&mut batch,8));
+    batch.send();
+
+    let requests     = fixture.transport.expect_json_message::<Vec<MockRequestMessage>>();
+    let request      = requests.into_iter().next().unwrap();
+    let notification = MockNotification::Meow {text:"meow!".into()};
+    let reply        = vec![
+        serde_json::to_value(Message::new(notification.clone())).unwrap(),
+        serde_json::json!({"garbage":true}),
+        serde_json::to_value(pow_impl(request)).unwrap(),
+    ];
+    fixture.transport.mock_peer_json_message(reply);
+    fixture.pool.run_until_stalled();
+
+    assert_eq!(fut.expect_ok(),64);
+    assert_eq!(fixture.client.expect_notification(),notification);
+    if let HandlingError::InvalidMessage(_) = fixture.client.expect_handling_error() {} else {
+        panic!("Expected an error to be InvalidMessage");
+    }
+}
+
+#[test]
+fn test_dropped_batch() {
+    let mut fixture = Fixture::new();
+    let mut batch   = fixture.client.handler.batch();
+    let mut fut     = Box::pin(fixture.client.pow_batch(&mut batch,8));
+    fut.expect_pending();
+    drop(batch);
+
+    fixture.transport.with_mut_data(|data| assert!(data.sent_text_msgs.is_empty()));
+    if let RpcError::LostConnection = fut.expect_err() {} else {
+        panic!("Expected an error to be LostConnection");
+    }
+}
+
+#[test]
+fn test_sending_batch_while_disconnected() {
+    let mut fixture = Fixture::new();
+    fixture.transport.mock_connection_closed();
+    let mut batch = fixture.client.handler.batch();
+    let mut fut   = Box::pin(fixture.client.pow_batch(&mut batch,8));
+    batch.send();
+    fut.expect_err();
+}
+
+#[test]
+fn test_batch_timeout_starts_when_sent() {
+    let mut fixture = Fixture::new();
+    let timer       = MockTimer::new();
+    let timeout     = fixture.client.handler.timeout();
+    fixture.client.handler.set_timer(timer.timer());
+    let gate      = fixture.client.handler.request_gate();
+    let mut batch = fixture.client.handler.batch();
+    let mut fut   = Box::pin(fixture.client.pow_batch(&mut batch,8));
+    timer.advance(timeout);
+    fut.expect_pending();
+    gate.hold();
+    batch.send();
+    timer.advance(timeout);
+    fut.expect_pending();
+    fixture.transport.with_mut_data(|data| assert!(data.sent_text_msgs.is_empty()));
+
+    gate.release();
+    let requests = fixture.transport.expect_json_message::<Vec<MockRequestMessage>>();
+    assert_eq!(requests.len(),1);
+    fut.expect_pending();
+    timer.advance(timeout / 2);
+    fut.expect_pending();
+    timer.advance(timeout / 2);
+    if let RpcError::TimeoutError{..} = fut.expect_err() {} else {
+        panic!("Expected an error to be TimeoutError");
+    }
+}
+
+
+#[test]
+fn test_handling_request() {
+    let mut fixture = Fixture::new();
+    fixture.client.handler.set_request_handler(|input:MockConfirmation| {
+        futures::future::ready(Ok(input.text == "yes"))
+    });
+    let params = MockConfirmation {text:"yes".into()};
+    fixture.transport.mock_peer_request(Id(5),MockConfirmation::NAME,params);
+    fixture.pool.run_until_stalled();
+
+    let reply = fixture.transport.expect_json_message::<MockConfirmationReply>();
+    assert_eq!(reply.id,     Id(5));
+    assert_eq!(reply.result, messages::Result::new_success(true));
+}
+
+#[test]
+fn test_handling_request_with_delayed_reply() {
+    let mut fixture       = Fixture::new();
+    let (sender,receiver) = futures::channel::oneshot::channel::<bool>();
+    let receiver          = RefCell::new(Some(receiver));
+    fixture.client.handler.set_request_handler(move |_:MockConfirmation| {
+        let receiver = receiver.borrow_mut().take().unwrap();
+        receiver.map(|confirmed| Ok(confirmed.unwrap()))
+    });
+    let params = MockConfirmation {text:"Are you sure?".into()};
+    fixture.transport.mock_peer_request(Id(1),MockConfirmation::NAME,params);
+    fixture.pool.run_until_stalled();
+    fixture.transport.with_mut_data(|data| assert!(data.sent_text_msgs.is_empty()));
+
+    // Other messages are processed while waiting for the reply.
+    let mut fut = Box::pin(fixture.client.pow(3));
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    fixture.transport.mock_peer_json_message(pow_impl(request));
+    fixture.pool.run_until_stalled();
+    assert_eq!(fut.expect_ok(),9);
+
+    sender.send(false).unwrap();
+    fixture.pool.run_until_stalled();
+    let reply = fixture.transport.expect_json_message::<MockConfirmationReply>();
+    assert_eq!(reply.id,     Id(1));
+    assert_eq!(reply.result, messages::Result::new_success(false));
+}
+
+#[test]
+fn test_handling_request_with_error_reply() {
+    let mut fixture = Fixture::new();
+    fixture.client.handler.set_request_handler(|_:MockConfirmation| {
+        futures::future::ready(Err(RpcError::new_remote_error(7,"Denied.")))
+    });
+    let params = MockConfirmation {text:"yes".into()};
+    fixture.transport.mock_peer_request(Id(2),MockConfirmation::NAME,params);
+    fixture.transport.mock_peer_request(Id(3),MockConfirmation::NAME,vec![1,2,3]);
+    fixture.pool.run_until_stalled();
+
+    let reply = fixture.transport.expect_json_message::<MockConfirmationReply>();
+    assert_eq!(reply.id,     Id(2));
+    assert_eq!(reply.result, messages::Result::new_error_simple(7,"Denied.".into()));
+    let reply = fixture.transport.expect_json_message::<MockConfirmationReply>();
+    assert_eq!(reply.id, Id(3));
+    if let messages::Result::Error {error} = &reply.result {
+        assert_eq!(error.code,messages::INVALID_PARAMS_ERROR_CODE);
+    } else {
+        panic!("Expected an error reply, got {:?}.",reply.result);
+    }
+}
+
+#[test]
+fn test_handling_unexpected_request() {
+    let mut fixture = Fixture::new();
+    let params      = MockConfirmation {text:"yes".into()};
+    fixture.transport.mock_peer_request(Id(4),MockConfirmation::NAME,params);
+    fixture.pool.run_until_stalled();
+
+    let reply = fixture.transport.expect_json_message::<MockConfirmationReply>();
+    assert_eq!(reply.id, Id(4));
+    if let messages::Result::Error {error} = &reply.result {
+        assert_eq!(error.code,messages::METHOD_NOT_FOUND_ERROR_CODE);
+    } else {
+        panic!("Expected an error reply, got {:?}.",reply.result);
+    }
+    if let HandlingError::UnexpectedRequest(method) = fixture.client.expect_handling_error() {
+        assert_eq!(method,MockConfirmation::NAME);
+    } else {
+        panic!("Expected an error to be UnexpectedRequest");
+    }
+}
+
+
+#[test]
+fn test_dropped_request() {
+    let mut fixture = Fixture::new();
+    let fut         = fixture.client.pow(8);
+    drop(fut);
+
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    fixture.transport.mock_peer_json_message(pow_impl(request));
+    fixture.pool.run_until_stalled();
+
+    // The request was cancelled, so the reply does not match any ongoing request.
+    if let HandlingError::UnexpectedResponse(_) = fixture.client.expect_handling_error() {} else {
+        panic!("Expected an error to be UnexpectedResponse");
+    }
+}
+
+#[test]
+fn test_timeout_override() {
+    let mut fixture = Fixture::new();
+    let timer       = MockTimer::new();
+    fixture.client.handler.set_timer(timer.timer());
+    let timeout     = Duration::from_millis(10);
+    let options     = handler::RequestOptions {timeout:Some(timeout),..default()};
+    let mut fut     = Box::pin(fixture.client.pow_with_options(8,options));
+    fut.expect_pending();
+    timer.advance(timeout / 2);
+    fut.expect_pending();
+    timer.advance(timeout / 2);
+
+    if let RpcError::TimeoutError{millis} = fut.expect_err() {
+        assert_eq!(millis,10);
+    } else {
+        panic!("Expected an error to be TimeoutError");
+    }
+
+    // The timed out request is no longer pending.
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    fixture.transport.mock_peer_json_message(pow_impl(request));
+    fixture.pool.run_until_stalled();
+    if let HandlingError::UnexpectedResponse(_) = fixture.client.expect_handling_error() {} else {
+        panic!("Expected an error to be UnexpectedResponse");
+    }
+}
+
+#[test]
+fn test_retrying_request() {
+    let mut fixture = Fixture::new();
+    let timer       = MockTimer::new();
+    fixture.client.handler.set_timer(timer.timer());
+    let timeout     = Duration::from_millis(10);
+    let retry       = handler::RetryPolicy {
+        max_retries    : 2,
+        initial_delay  : timeout,
+        backoff_factor : 2.0,
+        max_delay      : timeout * 10,
+    };
+    let options = handler::RequestOptions {timeout:Some(timeout),retry:Some(retry)};
+    let mut fut = Box::pin(fixture.client.pow_with_options(8,options));
+    fut.expect_pending();
+    let first_request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    let first_id      = first_request.id;
+
+    timer.advance(timeout);
+    fut.expect_pending(); // waiting before retry
+    fixture.transport.with_mut_data(|data| assert!(data.sent_text_msgs.is_empty()));
+
+    // The late reply to the timed out request is not taken as the reply to the retried one.
+    fixture.transport.mock_peer_json_message(pow_impl(first_request));
+    fixture.pool.run_until_stalled();
+    if let HandlingError::UnexpectedResponse(_) = fixture.client.expect_handling_error() {} else {
+        panic!("Expected an error to be UnexpectedResponse");
+    }
+
+    timer.advance(timeout);
+    fut.expect_pending(); // retried
+    let second_request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    assert_ne!(first_id,second_request.id);
+    assert_eq!(second_request.i,8);
+
+    fixture.transport.mock_peer_json_message(pow_impl(second_request));
+    fixture.pool.run_until_stalled();
+    assert_eq!(fut.expect_ok(),64);
+}
+
+#[test]
+fn test_retrying_batch_request() {
+    let mut fixture = Fixture::new();
+    let timer       = MockTimer::new();
+    fixture.client.handler.set_timer(timer.timer());
+    let timeout     = Duration::from_millis(10);
+    let retry       = handler::RetryPolicy {
+        max_retries    : 1,
+        initial_delay  : timeout,
+        backoff_factor : 1.0,
+        max_delay      : timeout,
+    };
+    let options   = handler::RequestOptions {timeout:Some(timeout),retry:Some(retry)};
+    let mut batch = fixture.client.handler.batch();
+    let input     = serde_json::to_value(MockRequest {i:3}).unwrap();
+    let fut       = batch.open_request_with_options(MockRequest::NAME,&input,options);
+    let mut fut   = Box::pin(fut.map(|result:Result<MockResponse>| result.map(|r| r.result)));
+    batch.send();
+    fut.expect_pending();
+    let batch_requests = fixture.transport.expect_json_message::<Vec<MockRequestMessage>>();
+    assert_eq!(batch_requests.len(),1);
+
+    timer.advance(timeout);
+    fut.expect_pending(); // waiting before retry
+    timer.advance(timeout);
+    fut.expect_pending(); // retried alone
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    assert_ne!(request.id,batch_requests[0].id);
+    assert_eq!(request.i,3);
+
+    fixture.transport.mock_peer_json_message(pow_impl(request));
+    fixture.pool.run_until_stalled();
+    assert_eq!(fut.expect_ok(),9);
+}
+
+#[test]
+fn test_not_retrying_remote_error() {
+    let mut fixture = Fixture::new();
+    let options     = handler::RequestOptions {retry:Some(default()),..default()};
+    let mut fut     = Box::pin(fixture.client.pow_with_options(8,options));
+    fut.expect_pending();
+
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    let error:MockResponseMessage = Message::new_error(request.id,5,"wrong!".into(),None);
+    fixture.transport.mock_peer_json_message(error);
+    fixture.pool.run_until_stalled();
+
+    if let RpcError::RemoteError(_) = fut.expect_err() {} else {
+        panic!("Expected an error to be RemoteError");
+    }
+    fixture.transport.with_mut_data(|data| assert!(data.sent_text_msgs.is_empty()));
+}
+
+#[test]
+fn test_holding_requests() {
+    let mut fixture = Fixture::new();
+    let gate        = fixture.client.handler.request_gate();
+    gate.hold();
+    let mut held       = Box::pin(fixture.client.pow(2));
+    let mut batch      = fixture.client.handler.batch();
+    let mut held_batch = Box::pin(fixture.client.pow_batch(&mut batch,3));
+    batch.send();
+    // The request must be made while the exempt future is polled.
+    let handler        = fixture.client.handler.clone();
+    let request        = async move { handler.open_request(MockRequest {i:4}).await };
+    let mut exempt     = Box::pin(gate.exempt(request));
+    exempt.expect_pending();
+    held.expect_pending();
+    held_batch.expect_pending();
+
+    // Only the exempt request is sent.
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    assert_eq!(request.i,4);
+    fixture.transport.with_mut_data(|data| assert!(data.sent_text_msgs.is_empty()));
+    fixture.transport.mock_peer_json_message(pow_impl(request));
+    fixture.pool.run_until_stalled();
+    assert_eq!(exempt.expect_ok().result,16);
+
+    gate.release();
+    let batch_requests = fixture.transport.expect_json_message::<Vec<MockRequestMessage>>();
+    held.expect_pending();
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    assert_eq!(request.i,2);
+    fixture.transport.mock_peer_json_message(pow_impl(request));
+    for request in batch_requests {
+        fixture.transport.mock_peer_json_message(vec![pow_impl(request)]);
+    }
+    fixture.pool.run_until_stalled();
+    assert_eq!(held.expect_ok(),4);
+    assert_eq!(held_batch.expect_ok(),9);
+}
+
+/// Sink for the `RecordingTransport`, which can be read after the transport is moved to a client.
+#[derive(Clone,Debug,Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf:&[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_recording_and_replaying_session() {
+    let meow = MockNotification::Meow {text:"meow!".into()};
+
+    // Record the session.
+    let buffer        = SharedBuffer::default();
+    let mut mock      = MockTransport::new();
+    let mut transport = RecordingTransport::new(mock.clone(),buffer.clone());
+    let mut pool      = futures::executor::LocalPool::new();
+    pool.spawner().spawn_local(transport.runner()).unwrap();
+    let mut client    = Client::new(transport);
+    pool.spawner().spawn_local(client.events_processor()).unwrap();
+    let mut fut       = Box::pin(client.pow(8));
+    let request       = mock.expect_json_message::<MockRequestMessage>();
+    mock.mock_peer_json_message(pow_impl(request));
+    mock.mock_peer_json_message(Message::new(meow.clone()));
+    pool.run_until_stalled();
+    assert_eq!(fut.expect_ok(),64);
+    assert_eq!(client.expect_notification(),meow);
+
+    let recorded   = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+    let recording  = Recording::from_json_lines(&recorded).unwrap();
+    let directions = recording.entries.iter().map(|entry| entry.direction).collect_vec();
+    assert_eq!(directions,vec![Direction::Sent,Direction::Received,Direction::Received]);
+    assert!(recording.entries.iter().all(|entry| matches!(entry.frame,Frame::Text(_))));
+    assert_eq!(Recording::from_json_lines(&recording.to_json_lines()).unwrap(),recording);
+
+    // Replay the server side of the session.
+    let transport   = ReplayTransport::new(recording);
+    let mut client  = Client::new(transport.clone());
+    let mut pool    = futures::executor::LocalPool::new();
+    pool.spawner().spawn_local(client.events_processor()).unwrap();
+    let mut fut     = Box::pin(client.pow(8));
+    pool.run_until_stalled();
+    assert_eq!(fut.expect_ok(),64);
+    assert_eq!(client.expect_notification(),meow);
+    transport.expect_finished();
+}
+
+#[test]
+fn test_replaying_with_different_ids() {
+    let recording = r#"
+        {"time":0.0,"direction":"Received","frame":"Opened"}
+        {"time":1.0,"direction":"Sent","frame":{"Text":"{\"jsonrpc\":\"2.0\",\"id\":7,\"method\":\"pow\",\"params\":{\"i\":8}}"}}
+        {"time":2.0,"direction":"Received","frame":{"Text":"{\"jsonrpc\":\"2.0\",\"id\":7,\"result\":{\"result\":64}}"}}
+        {"time":3.0,"direction":"Received","frame":"Closed"}
+    "#;
+    let transport  = ReplayTransport::from_json_lines(recording);
+    let mut client = Client::new(transport.clone());
+    let mut pool   = futures::executor::LocalPool::new();
+    pool.spawner().spawn_local(client.events_processor()).unwrap();
+    let mut fut    = Box::pin(client.pow(8));
+    pool.run_until_stalled();
+    assert_eq!(fut.expect_ok(),64);
+    transport.expect_finished();
+    // The replayed peer has closed the connection.
+    client.pow(8).boxed_local().expect_err();
+}
+
+#[test]
+fn test_replaying_unexpected_message() {
+    let mut fixture = Fixture::new();
+    let mut fut     = Box::pin(fixture.client.pow(8));
+    let request     = fixture.transport.expect_text_message();
+    fixture.transport.mock_peer_json_message(pow_impl(serde_json::from_str(&request).unwrap()));
+    fixture.pool.run_until_stalled();
+    fut.expect_ok();
+
+    let mut recording = Recording::default();
+    recording.entries.push(json_rpc::transport::recording::Entry {
+        time      : 0.0,
+        direction : Direction::Sent,
+        frame     : Frame::Text(request),
+    });
+    let mut transport = ReplayTransport::new(recording);
+    let other_call    = Message::new_request(Id(0),"sqrt",MockRequest {i:8});
+    let other_call    = serde_json::to_string(&other_call).unwrap();
+    let error         = transport.send_text(&other_call).unwrap_err();
+    assert!(matches!(error.downcast::<ReplayError>(),Ok(ReplayError::UnexpectedMessage{..})));
+    // Only the ids and generated identifiers may differ, not the call parameters.
+    let other_input = Message::new_request(Id(0),"pow",MockRequest {i:9});
+    let other_input = serde_json::to_string(&other_input).unwrap();
+    let error       = transport.send_text(&other_input).unwrap_err();
+    assert!(matches!(error.downcast::<ReplayError>(),Ok(ReplayError::UnexpectedMessage{..})));
+    assert!(!transport.is_finished());
+}
+
+#[test]
+fn test_replaying_with_generated_identifiers() {
+    let recording = r#"
+        {"time":0.0,"direction":"Sent","frame":{"Text":"{\"jsonrpc\":\"2.0\",\"id\":0,\"method\":\"init\",\"params\":{\"clientId\":\"client-1\",\"contextId\":\"8a3e5ff3-5f7f-4b35-bd37-a9d2d8c3d3b2\",\"name\":\"Main\"}}"}}
+        {"time":1.0,"direction":"Received","frame":{"Text":"{\"jsonrpc\":\"2.0\",\"id\":0,\"result\":{\"clientId\":\"client-1\",\"contextId\":\"8a3e5ff3-5f7f-4b35-bd37-a9d2d8c3d3b2\",\"name\":\"Main\"}}"}}
+    "#;
+    let sent = serde_json::json!({
+        "jsonrpc" : "2.0",
+        "id"      : 5,
+        "method"  : "init",
+        "params"  : {
+            "clientId"  : "client-2",
+            "contextId" : "6f7d58dd-8ee8-44cf-9ab7-9f0454033641",
+            "name"      : "Main",
+        }
+    });
+    let mut transport            = ReplayTransport::from_json_lines(recording);
+    let (transmitter,mut events) = futures::channel::mpsc::unbounded();
+    transport.set_event_transmitter(transmitter);
+    transport.send_text(&sent.to_string()).unwrap();
+    transport.expect_finished();
+
+    let event = events.try_next().unwrap().expect("Expected an emitted reply.");
+    if let TransportEvent::TextMessage(text) = event {
+        let reply = serde_json::from_str::<serde_json::Value>(&text).unwrap();
+        assert_eq!(reply["id"],5);
+        assert_eq!(reply["result"],sent["params"]);
+    } else {
+        panic!("Expected a text message, got {:?}",event);
+    }
+
+    // Other fields must be the same as recorded.
+    let mut transport = ReplayTransport::from_json_lines(recording);
+    let mut sent      = sent;
+    sent["params"]["name"] = "Other".into();
+    let error = transport.send_text(&sent.to_string()).unwrap_err();
+    assert!(matches!(error.downcast::<ReplayError>(),Ok(ReplayError::UnexpectedMessage{..})));
+}
@@ -1,5 +1,6 @@
 //! This module contains all structures related to Searcher Controller.
 pub mod action;
+pub mod usage;
 
 use crate::prelude::*;
 
@@ -473,10 +474,14 @@ pub struct Searcher {
     ide              : controller::Ide,
     this_arg         : Rc<Option<ThisNode>>,
     position_in_code : Immutable<TextLocation>,
+    usage            : Rc<usage::Model>,
 }
 
 impl Searcher {
     /// Create new Searcher Controller.
+    ///
+    /// The `usage` history should be shared by all Searcher Controllers of the project, so the
+    /// suggestions committed in one are ranked higher in the next ones.
     pub async fn new
     ( parent         : impl AnyLogger
     , ide            : controller::Ide
@@ -484,9 +489,10 @@ impl Searcher {
     , method         : language_server::MethodPointer
     , mode           : Mode
     , selected_nodes : Vec<double_representation::node::Id>
+    , usage          : Rc<usage::Model>
     ) -> FallibleResult<Self> {
         let graph = controller::ExecutedGraph::new(&parent,project.clone_ref(),method).await?;
-        Self::new_from_graph_controller(parent,ide,project,graph,mode,selected_nodes)
+        Self::new_from_graph_controller(parent,ide,project,graph,mode,selected_nodes,usage)
     }
 
     /// Create new Searcher Controller, when you have Executed Graph Controller handy.
@@ -497,6 +503,7 @@ impl Searcher {
     , graph          : controller::ExecutedGraph
     , mode           : Mode
     , selected_nodes : Vec<double_representation::node::Id>
+    , usage          : Rc<usage::Model>
     ) -> FallibleResult<Self> {
         let logger   = Logger::sub(parent,"Searcher Controller");
         let database = project.suggestion_db();
@@ -511,7 +518,7 @@ impl Searcher {
         let position   = TextLocation::convert_span(module_ast.repr(),&def_span).end;
         let this_arg   = Rc::new(matches!(mode, Mode::NewNode{..}).and_option_from(|| ThisNode::new(selected_nodes,&graph.graph())));
         let ret        = Self {
-            logger,graph,this_arg,ide,
+            logger,graph,this_arg,ide,usage,
             data             : Rc::new(RefCell::new(data)),
             notifier         : default(),
             mode             : Immutable(mode),
@@ -720,7 +727,7 @@ impl Searcher {
 
         // We add the required imports before we create the node/edit its content. This way, we
         // avoid an intermediate state where imports would already be in use but not yet available.
-        match *self.mode {
+        let result = match *self.mode {
             Mode::NewNode {position} => {
                 self.add_required_imports()?;
                 let (expression,intended_method) = expr_and_method();
@@ -744,9 +751,31 @@ impl Searcher {
                 Ok(node_id)
             }
             mode => Err(CannotCommitExpression{mode}.into())
+        };
+        if result.is_ok() {
+            self.record_usage();
+        }
+        result
+    }
+
+    /// Record the use of all methods picked as suggestions in the committed input.
+    fn record_usage(&self) {
+        let data    = self.data.borrow();
+        let picked  = data.fragments_added_by_picking.iter();
+        let methods = picked.filter_map(|fragment| fragment.picked_suggestion.method_id());
+        for method in methods {
+            self.usage.record_use(method);
         }
     }
 
+    fn update_usage_boosts(&self, list:&action::List) {
+        list.set_usage_boosts(|action| match action {
+            Action::Suggestion(suggestion) =>
+                suggestion.method_id().map_or(0.0,|method| self.usage.boost(&method)),
+            _ => 0.0,
+        });
+    }
+
     /// Adds an example to the graph.
     ///
     /// The example piece of code will be inserted as a new function definition, and in current
@@ -922,6 +951,7 @@ impl Searcher {
             });
             actions.extend(entries);
         }
+        self.update_usage_boosts(&actions);
         actions.update_filtering(&self.data.borrow().input.pattern);
         Ok(actions)
     }
@@ -1224,8 +1254,9 @@ pub mod test {
             ide.expect_current_project().returning_st(move || project.clone_ref());
             ide.expect_manage_projects().returning_st(move || Err(ProjectOperationsNotSupported.into()));
             let module_name = QualifiedName::from_segments(PROJECT_NAME, &[MODULE_NAME]).unwrap();
+            let usage    = Rc::new(usage::Model::new_in_memory(&logger));
             let searcher = Searcher {
-                graph,logger,database,
+                graph,logger,database,usage,
                 ide              : Rc::new(ide),
                 data             : default(),
                 notifier         : default(),
@@ -1811,6 +1842,57 @@ pub mod test {
         assert_eq!(module.ast().repr(), expected_code);
     }
 
+    #[wasm_bindgen_test]
+    fn committing_node_records_usage() {
+        let Fixture{test:_test,searcher,entry4,..} = Fixture::new();
+        let parser        = Parser::new_or_panic();
+        let method        = entry4.method_id().unwrap();
+        let picked_method = FragmentAddedByPickingSuggestion {
+            id                : CompletedFragmentId::Function,
+            picked_suggestion : entry4,
+        };
+        with(searcher.data.borrow_mut(), |mut data| {
+            data.fragments_added_by_picking.push(picked_method);
+            data.input = ParsedInput::new("Test.testMethod1".to_string(),&parser).unwrap();
+        });
+        assert_eq!(searcher.usage.boost(&method),0.0);
+
+        searcher.commit_node().unwrap();
+        let history = searcher.usage.history();
+        assert_eq!(history.tick,1);
+        assert_eq!(history.records.len(),1);
+        assert_eq!(history.records[0].method,method);
+        assert!(searcher.usage.boost(&method) > 0.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn usage_boost_reorders_action_list() {
+        let Fixture{test:_test,searcher,entry4,..} = Fixture::new();
+        let parser = Parser::new_or_panic();
+        // The same method displayed in the list, but defined in another module.
+        let entry11 = model::suggestion_database::Entry {
+            module : "Test.Other".to_owned().try_into().unwrap(),
+            ..(*entry4).clone()
+        };
+        searcher.database.put_entry(11,entry11);
+        let entry11 = searcher.database.lookup(11).unwrap();
+        with(searcher.data.borrow_mut(), |mut data| {
+            data.input = ParsedInput::new("testMethod1".to_string(),&parser).unwrap();
+        });
+        let suggestions = |searcher:&Searcher| {
+            let response = Ok(completion_response(&[4,11]));
+            let list     = searcher.make_action_list(vec![response]).unwrap();
+            list.to_action_vec().into_iter().filter_map(|action| match action {
+                Action::Suggestion(suggestion) => Some(suggestion),
+                _                              => None,
+            }).collect_vec()
+        };
+        assert_eq!(suggestions(&searcher),vec![entry4.clone(),entry11.clone()]);
+
+        searcher.usage.record_use(entry11.method_id().unwrap());
+        assert_eq!(suggestions(&searcher),vec![entry11,entry4]);
+    }
+
     #[wasm_bindgen_test]
     fn initialized_data_when_editing_node() {
         let Fixture{test:_test,searcher,entry4,..} = Fixture::new();
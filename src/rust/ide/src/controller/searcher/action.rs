@@ -67,7 +67,8 @@ impl Display for Action {
 // === List Entry ===
 // ==================
 
-/// Information how the list entry matches the filtering pattern.
+/// Information how the list entry matches the filtering pattern. The score of the subsequence
+/// includes the entry's usage boost.
 #[allow(missing_docs)]
 #[derive(Clone,Debug,PartialEq)]
 pub enum MatchInfo {
@@ -79,8 +80,10 @@ pub enum MatchInfo {
 #[allow(missing_docs)]
 #[derive(Clone,Debug)]
 pub struct ListEntry {
-    pub match_info : MatchInfo,
-    pub action     : Action,
+    pub match_info  : MatchInfo,
+    pub action      : Action,
+    /// The bonus added to the matching score, see `controller::searcher::usage`.
+    pub usage_boost : f32,
 }
 
 impl ListEntry {
@@ -92,8 +95,11 @@ impl ListEntry {
             fuzzly::find_best_subsequence(self.action.to_string(),pattern,metric)
         });
         self.match_info = match subsequence {
-            Some(subsequence) => MatchInfo::Matches {subsequence},
-            None              => MatchInfo::DoesNotMatch,
+            Some(mut subsequence) => {
+                subsequence.score += self.usage_boost;
+                MatchInfo::Matches {subsequence}
+            }
+            None => MatchInfo::DoesNotMatch,
         };
     }
 
@@ -116,7 +122,8 @@ impl From<Action> for ListEntry {
     fn from(action:Action) -> Self {
         let subsequence = default();
         let match_info  = MatchInfo::Matches {subsequence};
-        ListEntry {match_info,action}
+        let usage_boost = 0.0;
+        ListEntry {match_info,action,usage_boost}
     }
 }
 
@@ -178,6 +185,14 @@ impl List {
         self.matching.set(0..matches_end);
     }
 
+    /// Set the usage boosts of all entries. The new boosts are taken into account by the next
+    /// `update_filtering` call.
+    pub fn set_usage_boosts(&self, boost:impl Fn(&Action) -> f32) {
+        for entry in self.entries.borrow_mut().iter_mut() {
+            entry.usage_boost = boost(&entry.action);
+        }
+    }
+
     /// Length of the actions list.
     pub fn len(&self) -> usize { self.entries.borrow().len() }
 
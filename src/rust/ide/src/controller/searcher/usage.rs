@@ -0,0 +1,330 @@
+//! The history of suggestions used by the user, boosting the ranking of the frequently and recently
+//! picked ones in the Searcher.
+//!
+//! The time in the history is measured in committed suggestions: every commit increments the
+//! history's `tick`. The frequency of a method decays exponentially with the number of commits
+//! since it was last used, according to the `DecayPolicy`. The history is persisted by a `Store`,
+//! which is usually the project metadata (see `ProjectMetadataStore`).
+use crate::prelude::*;
+
+use crate::model::module::MethodId;
+use crate::model::traits::*;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+
+
+// ===================
+// === DecayPolicy ===
+// ===================
+
+/// Parameters of the usage decay and of the boost of ranking scores.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct DecayPolicy {
+    /// After this many commits, the frequency of not used method is halved.
+    pub half_life        : f32,
+    /// The weight of the frequency component of the boost. The component grows logarithmically
+    /// with the frequency.
+    pub frequency_weight : f32,
+    /// The weight of the recency component of the boost. The component is 1.0 for the method
+    /// committed most recently and decays with the `half_life`.
+    pub recency_weight   : f32,
+    /// The upper bound of the boost. The weighted components are squashed below it, so the boost
+    /// only reorders similarly good matches instead of outweighing the matching score. It should be
+    /// comparable with the score of a single matched char, see `fuzzly::metric`.
+    pub max_boost        : f32,
+    /// The maximum number of remembered methods. The ones with the lowest frequency are forgotten
+    /// first.
+    pub max_records      : usize,
+}
+
+impl Default for DecayPolicy {
+    fn default() -> Self {
+        Self {
+            half_life        : 50.0,
+            frequency_weight : 1.0,
+            recency_weight   : 0.5,
+            max_boost        : 1.0,
+            max_records      : 256,
+        }
+    }
+}
+
+impl DecayPolicy {
+    /// The factor by which values decay after `age` commits.
+    pub fn decay(&self, age:u64) -> f32 {
+        0.5_f32.powf(age as f32 / self.half_life)
+    }
+}
+
+
+
+// ===============
+// === History ===
+// ===============
+
+/// The usage of a single method.
+#[derive(Clone,Debug,Deserialize,PartialEq,Serialize)]
+pub struct Record {
+    /// The used method.
+    pub method    : MethodId,
+    /// The number of uses, decayed up to the `last_used` tick.
+    pub frequency : f32,
+    /// The tick of the last use.
+    pub last_used : u64,
+}
+
+/// The usage history, as persisted in the project metadata.
+#[derive(Clone,Debug,Default,Deserialize,PartialEq,Serialize)]
+pub struct History {
+    /// The number of all commits recorded so far.
+    #[serde(default)]
+    pub tick    : u64,
+    /// The usage of methods.
+    #[serde(default)]
+    pub records : Vec<Record>,
+}
+
+impl History {
+    /// Record the use of the method.
+    pub fn record_use(&mut self, method:MethodId, policy:&DecayPolicy) {
+        self.tick += 1;
+        let tick = self.tick;
+        match self.records.iter_mut().find(|record| record.method == method) {
+            Some(record) => {
+                record.frequency = record.frequency * policy.decay(tick - record.last_used) + 1.0;
+                record.last_used = tick;
+            }
+            None => self.records.push(Record {method,frequency:1.0,last_used:tick}),
+        }
+        if self.records.len() > policy.max_records {
+            let frequency = |record:&Record| self.frequency(record,policy);
+            let weakest   = self.records.iter().position_min_by(|lhs,rhs| {
+                frequency(lhs).partial_cmp(&frequency(rhs)).unwrap_or(std::cmp::Ordering::Equal)
+            });
+            if let Some(index) = weakest {
+                self.records.remove(index);
+            }
+        }
+    }
+
+    /// The boost of the ranking score of the method, between 0.0 and `policy.max_boost`. Never
+    /// used methods have no boost.
+    pub fn boost(&self, method:&MethodId, policy:&DecayPolicy) -> f32 {
+        self.records.iter().find(|record| &record.method == method).map_or(0.0,|record| {
+            let frequency = self.frequency(record,policy);
+            let recency   = policy.decay(self.tick - record.last_used);
+            let weighted  = policy.frequency_weight * frequency.ln_1p()
+                          + policy.recency_weight * recency;
+            policy.max_boost * weighted / (1.0 + weighted)
+        })
+    }
+
+    /// The frequency of the record decayed up to the current tick.
+    fn frequency(&self, record:&Record, policy:&DecayPolicy) -> f32 {
+        record.frequency * policy.decay(self.tick - record.last_used)
+    }
+}
+
+
+
+// =============
+// === Store ===
+// =============
+
+/// A persistent storage of the usage history.
+pub trait Store : Debug {
+    /// Load the stored history.
+    fn load(&self) -> History;
+
+    /// Replace the stored history.
+    fn save(&self, history:&History) -> FallibleResult;
+}
+
+impl<T:Store> Store for Rc<T> {
+    fn load(&self) -> History {
+        self.deref().load()
+    }
+
+    fn save(&self, history:&History) -> FallibleResult {
+        self.deref().save(history)
+    }
+}
+
+
+// === InMemoryStore ===
+
+/// The store keeping the history in memory only. Useful for tests.
+#[derive(Debug,Default)]
+pub struct InMemoryStore {
+    history : RefCell<History>,
+}
+
+impl Store for InMemoryStore {
+    fn load(&self) -> History {
+        self.history.borrow().clone()
+    }
+
+    fn save(&self, history:&History) -> FallibleResult {
+        *self.history.borrow_mut() = history.clone();
+        Ok(())
+    }
+}
+
+
+// === ProjectMetadataStore ===
+
+/// The store keeping the history in the project metadata of the main module.
+#[derive(Debug)]
+pub struct ProjectMetadataStore {
+    main_module : model::Module,
+}
+
+impl ProjectMetadataStore {
+    /// Constructor.
+    pub fn new(main_module:model::Module) -> Self {
+        Self {main_module}
+    }
+}
+
+impl Store for ProjectMetadataStore {
+    fn load(&self) -> History {
+        self.main_module.with_project_metadata(|metadata| metadata.searcher_usage.clone())
+    }
+
+    fn save(&self, history:&History) -> FallibleResult {
+        self.main_module.update_project_metadata(|metadata| {
+            metadata.searcher_usage = history.clone()
+        })
+    }
+}
+
+
+
+// =============
+// === Model ===
+// =============
+
+/// The usage history model, shared between subsequent Searcher Controllers.
+#[derive(Debug)]
+pub struct Model {
+    logger  : Logger,
+    store   : Box<dyn Store>,
+    policy  : DecayPolicy,
+    history : RefCell<History>,
+}
+
+impl Model {
+    /// Create a model with the history loaded from the store.
+    pub fn new(parent:impl AnyLogger, store:impl Store + 'static, policy:DecayPolicy) -> Self {
+        let logger  = Logger::sub(parent,"Searcher Usage");
+        let history = RefCell::new(store.load());
+        let store   = Box::new(store);
+        Self {logger,store,policy,history}
+    }
+
+    /// Create a model with an empty history kept in memory.
+    pub fn new_in_memory(parent:impl AnyLogger) -> Self {
+        Self::new(parent,InMemoryStore::default(),default())
+    }
+
+    /// Record the use of the method and save the history.
+    pub fn record_use(&self, method:MethodId) {
+        self.history.borrow_mut().record_use(method,&self.policy);
+        if let Err(err) = self.store.save(&self.history.borrow()) {
+            error!(self.logger, "Failed to save the searcher usage history: {err}");
+        }
+    }
+
+    /// The boost of the ranking score of the method. See `History::boost`.
+    pub fn boost(&self, method:&MethodId) -> f32 {
+        self.history.borrow().boost(method,&self.policy)
+    }
+
+    /// The current history.
+    pub fn history(&self) -> History {
+        self.history.borrow().clone()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::model::module::QualifiedName;
+    use crate::model::module::TypeQualifiedName;
+
+    fn method(name:&str) -> MethodId {
+        MethodId {
+            module          : QualifiedName::from_text("Test.Main").unwrap(),
+            defined_on_type : TypeQualifiedName::from_text("Test.Main").unwrap(),
+            name            : name.to_owned(),
+        }
+    }
+
+    #[test]
+    fn boosting_frequent_and_recent_methods() {
+        let policy      = DecayPolicy {half_life:2.0,..default()};
+        let mut history = History::default();
+        history.record_use(method("frequent"),&policy);
+        history.record_use(method("frequent"),&policy);
+        history.record_use(method("frequent"),&policy);
+        history.record_use(method("recent"),&policy);
+        let boost    = |history:&History, name| history.boost(&method(name),&policy);
+        let frequent = boost(&history,"frequent");
+        let recent   = boost(&history,"recent");
+        assert_eq!(history.tick,4);
+        assert_eq!(boost(&history,"unused"),0.0);
+        assert!(recent > 0.0);
+        assert!(frequent > recent);
+
+        for _ in 0..10 {
+            history.record_use(method("recent"),&policy);
+        }
+        assert!(boost(&history,"frequent") < frequent);
+        assert!(boost(&history,"recent") > boost(&history,"frequent"));
+    }
+
+    #[test]
+    fn bounding_boost() {
+        let policy      = DecayPolicy::default();
+        let mut history = History::default();
+        for _ in 0..1000 {
+            history.record_use(method("frequent"),&policy);
+        }
+        let boost = history.boost(&method("frequent"),&policy);
+        assert!(boost > 0.5 * policy.max_boost);
+        assert!(boost < policy.max_boost);
+    }
+
+    #[test]
+    fn forgetting_rare_methods() {
+        let policy      = DecayPolicy {max_records:2,..default()};
+        let mut history = History::default();
+        history.record_use(method("first"),&policy);
+        history.record_use(method("first"),&policy);
+        history.record_use(method("second"),&policy);
+        history.record_use(method("third"),&policy);
+        let methods = history.records.iter().map(|record| record.method.name.as_str()).collect_vec();
+        assert_eq!(methods,vec!["first","third"]);
+    }
+
+    #[test]
+    fn persisting_history() {
+        let logger = Logger::new("test");
+        let store  = Rc::new(InMemoryStore::default());
+        let model  = Model::new(&logger,store.clone_ref(),default());
+        model.record_use(method("foo"));
+        assert_eq!(store.load(),model.history());
+        let reloaded = Model::new(&logger,store,default());
+        assert_eq!(reloaded.boost(&method("foo")),model.boost(&method("foo")));
+        assert!(reloaded.boost(&method("foo")) > 0.0);
+    }
+}
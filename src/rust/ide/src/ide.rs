@@ -25,6 +25,9 @@ pub use initializer::Initializer;
 pub const BACKEND_DISCONNECTED_MESSAGE:&str =
     "Connection to the backend has been lost. Please try restarting IDE.";
 
+/// Text that shows up in the statusbar when the lost backend connection has been reestablished.
+pub const BACKEND_RECONNECTED_MESSAGE:&str = "Connection to the backend has been restored.";
+
 const ALIVE_LOG_INTERVAL_SEC:u64 = 60;
 
 
@@ -9,6 +9,7 @@ use crate::transport::web::WebSocket;
 
 use enso_protocol::project_manager;
 use enso_protocol::project_manager::ProjectName;
+use enso_protocol::traits::*;
 use uuid::Uuid;
 use ensogl::application::Application;
 use ensogl::system::web;
@@ -134,8 +135,8 @@ impl Initializer {
     /// executor.
     pub async fn setup_project_manager
     (&self, endpoint:&str) -> FallibleResult<Rc<dyn project_manager::API>> {
-        let transport           = WebSocket::new_opened(self.logger.clone_ref(),endpoint).await?;
-        let mut project_manager = project_manager::Client::new(transport);
+        let transport       = WebSocket::new_opened(self.logger.clone_ref(),endpoint).await?;
+        let project_manager = project_manager::Client::new(transport);
         project_manager.set_timeout(std::time::Duration::from_secs(PROJECT_MANAGER_TIMEOUT_SEC));
         executor::global::spawn(project_manager.runner());
         Ok(Rc::new(project_manager))
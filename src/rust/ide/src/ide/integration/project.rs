@@ -11,6 +11,8 @@ use crate::controller::graph::Connections;
 use crate::controller::graph::NodeTrees;
 use crate::controller::searcher::action::MatchInfo;
 use crate::controller::searcher::Actions;
+use crate::controller::searcher::usage::Model as SearcherUsage;
+use crate::controller::searcher::usage::ProjectMetadataStore;
 use crate::controller::upload;
 use crate::controller::upload::NodeFromDroppedFileHandler;
 use crate::model::execution_context::ComputedValueInfo;
@@ -195,6 +197,7 @@ struct Model {
     text                    : controller::Text,
     ide                     : controller::Ide,
     searcher                : RefCell<Option<controller::Searcher>>,
+    searcher_usage          : Rc<controller::searcher::usage::Model>,
     project                 : model::Project,
     main_module             : model::Module,
     node_views              : RefCell<BiMap<ast::Id,graph_editor::NodeId>>,
@@ -432,6 +435,8 @@ impl Integration {
             let message = match notification {
                 model::project::Notification::ConnectionLost(_) =>
                     crate::BACKEND_DISCONNECTED_MESSAGE,
+                model::project::Notification::ConnectionRestored(_) =>
+                    crate::BACKEND_RECONNECTED_MESSAGE,
             };
             let message = ide_view::status_bar::event::Label::from(message);
             status_bar.add_event(message);
@@ -453,22 +458,22 @@ impl Integration {
 
     fn connect_frp_to_graph_controller_notifications
     (&self, frp_endpoint : frp::Source<Option<controller::graph::executed::Notification>>) {
-        let stream = self.model.graph.subscribe();
         let logger = self.model.logger.clone_ref();
-        self.spawn_sync_stream_handler(stream, move |notification,_model| {
+        let stream = self.model.graph.subscribe().map(move |notification| {
             info!(logger,"Processing notification {notification:?}");
-            frp_endpoint.emit(&Some(notification));
-        })
+            Some(notification)
+        });
+        executor::global::spawn(frp::future::forward(&frp_endpoint,stream));
     }
 
     fn connect_frp_text_controller_notifications
     (&self, frp_endpoint : frp::Source<Option<controller::text::Notification>>) {
-        let stream  = self.model.text.subscribe();
-        let logger  = self.model.logger.clone_ref();
-        self.spawn_sync_stream_handler(stream, move |notification,_model| {
+        let logger = self.model.logger.clone_ref();
+        let stream = self.model.text.subscribe().map(move |notification| {
             info!(logger,"Processing notification {notification:?}");
-            frp_endpoint.emit(&Some(notification));
+            Some(notification)
         });
+        executor::global::spawn(frp::future::forward(&frp_endpoint,stream));
     }
 
     /// Convert a function being a method of GraphEditorIntegratedWithControllerModel to a closure
@@ -521,8 +526,11 @@ impl Model {
         let error_visualizations    = default();
         let searcher                = default();
         let prompt_was_shown        = default();
+        let usage_store             = ProjectMetadataStore::new(main_module.clone_ref());
+        let searcher_usage          = SearcherUsage::new(&logger,usage_store,default());
+        let searcher_usage          = Rc::new(searcher_usage);
         let this                    = Model
-            {logger,view,graph,text,ide,searcher,project,main_module,node_views
+            {logger,view,graph,text,ide,searcher,searcher_usage,project,main_module,node_views
             ,node_view_by_expression,expression_views,expression_types,connection_views,code_view
             ,visualizations,error_visualizations,prompt_was_shown};
 
@@ -1725,8 +1733,9 @@ impl Model {
         }).collect_vec();
         let controller = self.graph.clone_ref();
         let ide        = self.ide.clone_ref();
+        let usage      = self.searcher_usage.clone_ref();
         let searcher   = controller::Searcher::new_from_graph_controller
-            (&self.logger,ide,&self.project,controller,mode,selected_nodes)?;
+            (&self.logger,ide,&self.project,controller,mode,selected_nodes,usage)?;
         executor::global::spawn(searcher.subscribe().for_each(f!([weak_self](notification) {
             if let Some(this) = weak_self.upgrade() {
                 this.handle_searcher_notification(notification);
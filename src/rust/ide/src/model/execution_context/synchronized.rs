@@ -3,6 +3,7 @@
 use crate::prelude::*;
 
 use crate::model::execution_context::ComputedValueInfoRegistry;
+use crate::model::execution_context::ExpressionId;
 use crate::model::execution_context::LocalCall;
 use crate::model::execution_context::Visualization;
 use crate::model::execution_context::VisualizationUpdateData;
@@ -13,6 +14,46 @@ use enso_protocol::language_server;
 use enso_protocol::language_server::ExpressionUpdates;
 
 
+// ==============
+// === Errors ===
+// ==============
+
+/// Failure of restoring the execution context, telling the step at which it failed.
+#[derive(Debug,Fail)]
+#[allow(missing_docs)]
+pub enum RestoringFailed {
+    #[fail(display = "Failed to create the execution context again: {}", cause)]
+    Creating {
+        #[cause]
+        cause : failure::Error,
+    },
+    #[fail(display = "Failed to push the root frame to the recreated execution context {}: {}"
+        , id, cause)]
+    PushingRootFrame {
+        id    : model::execution_context::Id,
+        #[cause]
+        cause : failure::Error,
+    },
+    #[fail(display = "Failed to push the call {} to the recreated execution context {}: {}"
+        , call, id, cause)]
+    PushingLocalCall {
+        id    : model::execution_context::Id,
+        call  : ExpressionId,
+        #[cause]
+        cause : failure::Error,
+    },
+    #[fail(display = "Failed to attach the visualization {} to the recreated execution context \
+        {}: {}", visualization, id, cause)]
+    AttachingVisualization {
+        id            : model::execution_context::Id,
+        visualization : VisualizationId,
+        #[cause]
+        cause         : failure::Error,
+    },
+}
+
+
+
 // ==========================
 // === Synchronized Model ===
 // ==========================
@@ -21,7 +62,8 @@ use enso_protocol::language_server::ExpressionUpdates;
 /// from LS once dropped.
 #[derive(Debug)]
 pub struct ExecutionContext {
-    id              : model::execution_context::Id,
+    /// The id assigned by the Language Server. Changes when the context is recreated by `restore`.
+    id              : Cell<model::execution_context::Id>,
     model           : model::execution_context::Plain,
     language_server : Rc<language_server::Connection>,
     logger          : Logger,
@@ -30,7 +72,7 @@ pub struct ExecutionContext {
 impl ExecutionContext {
     /// The unique identifier of this execution context.
     pub fn id(&self) -> model::execution_context::Id {
-        self.id
+        self.id.get()
     }
 
     /// Create new ExecutionContext. It will be created in LanguageServer and the ExplicitCall
@@ -50,7 +92,7 @@ impl ExecutionContext {
             let logger = Logger::sub(&parent,iformat!{"ExecutionContext {id}"});
             let model  = model::execution_context::Plain::new(&logger,root_definition);
             info!(logger, "Created. Id: {id}.");
-            let this = Self {id,model,language_server,logger };
+            let this = Self {id:Cell::new(id),model,language_server,logger};
             this.push_root_frame().await?;
             info!(this.logger, "Pushed root frame.");
             Ok(this)
@@ -65,7 +107,7 @@ impl ExecutionContext {
         let call = language_server::ExplicitCall {method_pointer,this_argument_expression,
             positional_arguments_expressions};
         let frame  = language_server::StackItem::ExplicitCall(call);
-        let result = self.language_server.push_to_execution_context(&self.id,&frame);
+        let result = self.language_server.push_to_execution_context(&self.id(),&frame);
         result.map(|res| res.map_err(|err| err.into()))
     }
 
@@ -76,7 +118,7 @@ impl ExecutionContext {
     async fn detach_visualization_inner
     (&self, vis:Visualization) -> FallibleResult<Visualization> {
         let vis_id = vis.id;
-        let exe_id = self.id;
+        let exe_id = self.id();
         let ast_id = vis.ast_id;
         let ls     = self.language_server.clone_ref();
         let logger = self.logger.clone_ref();
@@ -88,6 +130,54 @@ impl ExecutionContext {
         Ok(vis)
     }
 
+    /// Create the context again in the Language Server, e.g. after the connection has been
+    /// reestablished and the previous session's contexts are gone, and restore its state (see
+    /// `recreate` and `restore_state`).
+    pub async fn restore(&self) -> Result<(),RestoringFailed> {
+        self.recreate().await?;
+        self.restore_state().await
+    }
+
+    /// Create the context again in the Language Server. Returns the new id of the context, under
+    /// which it must be registered again to receive updates. It should be registered before its
+    /// state is restored with `restore_state`, as the updates may come as soon as it is done.
+    pub async fn recreate(&self) -> Result<model::execution_context::Id,RestoringFailed> {
+        info!(self.logger, "Recreating the context.");
+        let created = self.language_server.client.create_execution_context().await;
+        let created = created.map_err(|err| RestoringFailed::Creating {cause:err.into()})?;
+        let id      = created.context_id;
+        self.id.set(id);
+        info!(self.logger, "Recreated. New id: {id}.");
+        Ok(id)
+    }
+
+    /// Push the whole call stack to the context recreated with `recreate` and attach all the
+    /// visualizations again. Stops at the first failed step, leaving the context partially
+    /// restored.
+    pub async fn restore_state(&self) -> Result<(),RestoringFailed> {
+        let id = self.id();
+        self.push_root_frame().await.map_err(|cause| RestoringFailed::PushingRootFrame {id,cause})?;
+        let local_calls = self.model.stack_items().collect_vec();
+        for local_call in local_calls {
+            let call   = local_call.call;
+            let frame  = language_server::LocalCall{expression_id:call};
+            let frame  = language_server::StackItem::LocalCall(frame);
+            let pushed = self.language_server.push_to_execution_context(&id,&frame).await;
+            pushed.map_err(|err| RestoringFailed::PushingLocalCall {id,call,cause:err.into()})?;
+        }
+        for vis in self.model.all_visualizations_info() {
+            let config        = vis.config(id);
+            let visualization = vis.id;
+            let attached      = self.language_server.attach_visualisation(&vis.id,&vis.ast_id
+                ,&config).await;
+            attached.map_err(|err| {
+                RestoringFailed::AttachingVisualization {id,visualization,cause:err.into()}
+            })?;
+        }
+        info!(self.logger, "Restored the call stack and visualizations.");
+        Ok(())
+    }
+
     /// Handles the update about expressions being computed.
     pub fn handle_expression_updates
     (&self, notification:ExpressionUpdates) -> FallibleResult {
@@ -127,7 +217,7 @@ impl model::execution_context::API for ExecutionContext {
             let expression_id = stack_item.call;
             let call          = language_server::LocalCall{expression_id};
             let frame         = language_server::StackItem::LocalCall(call);
-            self.language_server.push_to_execution_context(&self.id,&frame).await?;
+            self.language_server.push_to_execution_context(&self.id(),&frame).await?;
             self.model.push(stack_item);
             Ok(())
         }.boxed_local()
@@ -138,7 +228,7 @@ impl model::execution_context::API for ExecutionContext {
             // We do pop first, because we want to call any ls method if the operation is impossible
             // in the plain model.
             let frame  = self.model.pop()?;
-            let result = self.language_server.pop_from_execution_context(&self.id).await;
+            let result = self.language_server.pop_from_execution_context(&self.id()).await;
             if let Err(err) = result {
                 self.model.push(frame);
                 Err(err.into())
@@ -155,7 +245,7 @@ impl model::execution_context::API for ExecutionContext {
         //  We must register our visualization in the model first, because Language server can send
         //  us visualization updates through the binary socket before confirming that visualization
         //  has been successfully attached.
-        let config = vis.config(self.id);
+        let config = vis.config(self.id());
         let stream = self.model.attach_visualization(vis.clone());
         async move {
             let result = self.language_server.attach_visualisation(&vis.id,&vis.ast_id,&config).await;
@@ -180,7 +270,7 @@ impl model::execution_context::API for ExecutionContext {
     (&self, id:VisualizationId, expression:Option<String>, module:Option<module::QualifiedName>)
     -> BoxFuture<FallibleResult> {
         let result     = self.model.modify_visualization(id,expression,module);
-        let new_config = self.model.visualization_config(id,self.id);
+        let new_config = self.model.visualization_config(id,self.id());
         async move {
             result?;
             self.language_server.modify_visualisation(&id,&new_config?).await?;
@@ -197,7 +287,7 @@ impl model::execution_context::API for ExecutionContext {
 
 impl Drop for ExecutionContext {
     fn drop(&mut self) {
-        let id     = self.id;
+        let id     = self.id();
         let ls     = self.language_server.clone_ref();
         let logger = self.logger.clone_ref();
         executor::global::spawn(async move {
@@ -226,6 +316,7 @@ pub mod test {
 
     use enso_protocol::language_server::CapabilityRegistration;
     use enso_protocol::language_server::response::CreateExecutionContext;
+    use json_rpc::error::RpcError;
     use json_rpc::expect_call;
     use utils::test::ExpectTuple;
     use utils::test::stream::StreamTestExt;
@@ -258,7 +349,7 @@ pub mod test {
         }
 
         /// What is expected server's response to a successful creation of this context.
-        fn expected_creation_response(data:&MockData) -> CreateExecutionContext {
+        pub fn expected_creation_response(data:&MockData) -> CreateExecutionContext {
             let context_id = data.context_id;
             let can_modify =
                 CapabilityRegistration::create_can_modify_execution_context(context_id);
@@ -312,7 +403,7 @@ pub mod test {
     #[test]
     fn creating_context() {
         let f = Fixture::new();
-        assert_eq!(f.data.context_id, f.context.id);
+        assert_eq!(f.data.context_id, f.context.id());
         let name_in_data      = f.data.module_qualified_name();
         let name_in_ctx_model = QualifiedName::try_from(&f.context.model.entry_point);
         assert_eq!(name_in_data, name_in_ctx_model.unwrap());
@@ -455,4 +546,105 @@ pub mod test {
             context.modify_visualization(vis_id,expression,module).await.unwrap();
         });
     }
+
+    #[test]
+    fn restoring_context() {
+        let data   = MockData::new();
+        let new_id = model::execution_context::Id::new_v4();
+        let call   = model::execution_context::ExpressionId::new_v4();
+        let vis    = Visualization {
+            id                   : model::execution_context::VisualizationId::new_v4(),
+            ast_id               : model::execution_context::ExpressionId::new_v4(),
+            expression           : "".to_string(),
+            visualisation_module : data.module_qualified_name(),
+        };
+        let mut ls   = language_server::MockClient::default();
+        let response = Fixture::expected_creation_response(&data);
+        let restored = CreateExecutionContext {context_id:new_id,..response.clone()};
+        for (id,response) in vec![(data.context_id,response),(new_id,restored)] {
+            let root_frame = language_server::ExplicitCall {
+                method_pointer                   : data.main_method_pointer(),
+                this_argument_expression         : None,
+                positional_arguments_expressions : vec![]
+            };
+            let root_item  = language_server::StackItem::ExplicitCall(root_frame);
+            let local_call = language_server::LocalCall{expression_id:call};
+            let local_item = language_server::StackItem::LocalCall(local_call);
+            let vis_id     = vis.id;
+            let ast_id     = vis.ast_id;
+            let config     = vis.config(id);
+            expect_call!(ls.create_execution_context()                 => Ok(response));
+            expect_call!(ls.push_to_execution_context(id,root_item)    => Ok(()));
+            expect_call!(ls.push_to_execution_context(id,local_item)   => Ok(()));
+            expect_call!(ls.attach_visualisation(vis_id,ast_id,config) => Ok(()));
+        }
+        expect_call!(ls.destroy_execution_context(new_id) => Ok(()));
+        ls.require_all_calls();
+
+        let mut test   = TestWithLocalPoolExecutor::set_up();
+        let connection = language_server::Connection::new_mock_rc(ls);
+        let method     = data.main_method_pointer();
+        let context    = ExecutionContext::create(Logger::new("Test"),connection,method);
+        let context    = test.expect_completion(context).unwrap();
+        test.run_task(async move {
+            let definition = data.main_method_pointer();
+            context.push(LocalCall {call,definition}).await.unwrap();
+            context.attach_visualization(vis.clone()).await.unwrap();
+            context.restore().await.unwrap();
+            assert_eq!(context.id(),new_id);
+            assert_eq!(context.active_visualizations(),vec![vis.id]);
+        });
+    }
+
+    #[test]
+    fn restoring_context_reports_failed_step() {
+        let data   = MockData::new();
+        let new_id = model::execution_context::Id::new_v4();
+        let vis    = Visualization {
+            id                   : model::execution_context::VisualizationId::new_v4(),
+            ast_id               : model::execution_context::ExpressionId::new_v4(),
+            expression           : "".to_string(),
+            visualisation_module : data.module_qualified_name(),
+        };
+        let mut ls   = language_server::MockClient::default();
+        let response = Fixture::expected_creation_response(&data);
+        let restored = CreateExecutionContext {context_id:new_id,..response.clone()};
+        let vis_id   = vis.id;
+        let ast_id   = vis.ast_id;
+        for (id,response) in vec![(data.context_id,response),(new_id,restored)] {
+            let root_frame = language_server::ExplicitCall {
+                method_pointer                   : data.main_method_pointer(),
+                this_argument_expression         : None,
+                positional_arguments_expressions : vec![]
+            };
+            let root_item = language_server::StackItem::ExplicitCall(root_frame);
+            expect_call!(ls.create_execution_context()              => Ok(response));
+            expect_call!(ls.push_to_execution_context(id,root_item) => Ok(()));
+        }
+        let config = vis.config(data.context_id);
+        expect_call!(ls.attach_visualisation(vis_id,ast_id,config) => Ok(()));
+        let config = vis.config(new_id);
+        let error  = RpcError::LostConnection;
+        expect_call!(ls.attach_visualisation(vis_id,ast_id,config) => Err(error));
+        expect_call!(ls.destroy_execution_context(new_id) => Ok(()));
+        ls.require_all_calls();
+
+        let mut test   = TestWithLocalPoolExecutor::set_up();
+        let connection = language_server::Connection::new_mock_rc(ls);
+        let method     = data.main_method_pointer();
+        let context    = ExecutionContext::create(Logger::new("Test"),connection,method);
+        let context    = test.expect_completion(context).unwrap();
+        test.run_task(async move {
+            context.attach_visualization(vis.clone()).await.unwrap();
+            let error = context.restore().await.unwrap_err();
+            match error {
+                RestoringFailed::AttachingVisualization {id,visualization,..} => {
+                    assert_eq!(id,new_id);
+                    assert_eq!(visualization,vis_id);
+                }
+                error => panic!("Unexpected error: {}",error),
+            }
+            assert_eq!(context.id(),new_id);
+        });
+    }
 }
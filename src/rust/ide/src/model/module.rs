@@ -321,6 +321,9 @@ pub struct ProjectMetadata {
     /// The execution context of the displayed graph editor.
     #[serde(default,deserialize_with="utils::serde::deserialize_or_default")]
     pub call_stack : Vec<model::execution_context::LocalCall>,
+    /// The history of suggestions picked in the searcher.
+    #[serde(default,deserialize_with="utils::serde::deserialize_or_default")]
+    pub searcher_usage : controller::searcher::usage::History,
 }
 
 /// Metadata that belongs to ide.
@@ -19,11 +19,26 @@ use enso_protocol::types::Sha3_224;
 use enso_protocol::language_server;
 use enso_protocol::language_server::TextEdit;
 use flo_stream::Subscriber;
+use futures::channel::mpsc::UnboundedReceiver;
+use futures::channel::mpsc::UnboundedSender;
+use futures::channel::oneshot;
+use futures::future::Either;
 use parser::api::SourceFile;
 use parser::Parser;
 
 
 
+// ==============
+// === Errors ===
+// ==============
+
+#[allow(missing_docs)]
+#[derive(Clone,Debug,Fail)]
+#[fail(display="Failed to reopen the file of module {}.",_0)]
+pub struct ReopeningFailed(String);
+
+
+
 // =======================
 // === Content Summary ===
 // =======================
@@ -105,6 +120,16 @@ impl LanguageServerContent {
     }
 }
 
+/// The event handled by the module's runner.
+#[derive(Debug)]
+enum RunnerEvent {
+    /// The module has been changed.
+    Notification(Notification),
+    /// The module's file should be opened again in the Language Server. The result is sent once
+    /// it is done.
+    Reopen(oneshot::Sender<FallibleResult>),
+}
+
 
 
 // ===========================
@@ -123,6 +148,7 @@ pub struct Module {
     model           : model::module::Plain,
     language_server : Rc<language_server::Connection>,
     logger          : Logger,
+    reopen_requests : UnboundedSender<oneshot::Sender<FallibleResult>>,
 }
 
 
@@ -151,13 +177,27 @@ impl Module {
         let digest  = opened.current_version;
         let summary = ContentSummary {digest,end_of_file};
         let model   = model::module::Plain::new(&logger,path,source.ast,source.metadata,repository);
-        let this    = Rc::new(Module {model,language_server,logger});
+        let (reopen_requests,reopen_receiver) = futures::channel::mpsc::unbounded();
+        let this    = Rc::new(Module {model,language_server,logger,reopen_requests});
         let content = this.model.serialized_content()?;
         let first_invalidation = this.full_invalidation(&summary,content);
-        executor::global::spawn(Self::runner(this.clone_ref(),summary,first_invalidation));
+        let runner  = Self::runner(this.clone_ref(),summary,first_invalidation,reopen_receiver);
+        executor::global::spawn(runner);
         Ok(this)
     }
 
+    /// Schedule opening the module's file again in the Language Server, e.g. after the connection
+    /// has been reestablished. If the file's content differs from the module's, it is replaced.
+    ///
+    /// The returned future completes once the file is reopened. If the module is dropped before,
+    /// there is nothing to reopen and it completes successfully.
+    pub fn reopen_file(&self) -> impl Future<Output=FallibleResult> {
+        info!(self.logger,"Scheduling reopening the module file.");
+        let (sender,receiver) = oneshot::channel();
+        utils::channel::emit(&self.reopen_requests,sender);
+        receiver.map(|result| result.unwrap_or(Ok(())))
+    }
+
     /// Create a module mock.
     pub fn mock(model:model::module::Plain) -> Rc<Self> {
         let logger = Logger::new(iformat!("Mocked Module {model.path()}"));
@@ -165,7 +205,8 @@ impl Module {
         client.expect.close_text_file(|_| Ok(()));
         // We don't expect any other call, because we don't execute `runner()`.
         let language_server = language_server::Connection::new_mock_rc(client);
-        Rc::new(Module{model,language_server,logger})
+        let reopen_requests = futures::channel::mpsc::unbounded().0;
+        Rc::new(Module{model,language_server,logger,reopen_requests})
     }
 }
 
@@ -235,31 +276,99 @@ impl API for Module {
 
 impl Module {
     /// The asynchronous task scheduled during struct creation which listens for all module changes
-    /// and send proper updates to Language Server.
+    /// and send proper updates to Language Server. It also reopens the module's file when requested
+    /// by `reopen_file`.
     async fn runner
     ( self               : Rc<Self>
     , initial_ls_content : ContentSummary
-    , first_invalidation : impl Future<Output=FallibleResult<ParsedContentSummary>>) {
-        let first_invalidation = first_invalidation.await;
-        let mut ls_content     = self.new_ls_content_info(initial_ls_content,first_invalidation);
-        let mut subscriber     = self.model.subscribe();
-        let weak               = Rc::downgrade(&self);
+    , first_invalidation : impl Future<Output=FallibleResult<ParsedContentSummary>>
+    , reopen_requests    : UnboundedReceiver<oneshot::Sender<FallibleResult>>) {
+        let first_invalidation  = first_invalidation.await;
+        let mut ls_content      = self.new_ls_content_info(initial_ls_content,first_invalidation);
+        let mut notifications   = self.model.subscribe();
+        let mut reopen_requests = reopen_requests;
+        let weak                = Rc::downgrade(&self);
         drop(self);
 
         loop {
-            let notification = subscriber.next().await;
-            let this = weak.upgrade();
-            match (notification,this) {
-                (Some(notification),Some(this)) => {
+            let notification = notifications.next().map(|n| n.map(RunnerEvent::Notification));
+            let reopen       = reopen_requests.next().map(|r| r.map(RunnerEvent::Reopen));
+            let event        = futures::future::select(notification,reopen).await.factor_first().0;
+            let this         = weak.upgrade();
+            match (event,this) {
+                (Some(RunnerEvent::Notification(notification)),Some(this)) => {
                     debug!(this.logger,"Processing a notification: {notification:?}");
-                    let result = this.handle_notification(&ls_content,notification).await;
-                    ls_content = this.new_ls_content_info(ls_content.summary().clone(),result)
+                    let handled = this.handle_notification(&ls_content,notification).boxed_local();
+                    // While the session is restored after reconnecting, the edits are held back
+                    // until the module is reopened, so the reopening cannot wait for them.
+                    match futures::future::select(handled,reopen_requests.next()).await {
+                        Either::Left((result,_)) => {
+                            let summary = ls_content.summary().clone();
+                            ls_content  = this.new_ls_content_info(summary,result);
+                        }
+                        Either::Right((Some(done),handled)) => {
+                            // The held edit is dropped, as the reopening compares the whole content
+                            // of the module and the file anyway.
+                            drop(handled);
+                            ls_content = this.reopen_and_report(ls_content,done).await;
+                        }
+                        Either::Right((None,handled)) => {
+                            let result  = handled.await;
+                            let summary = ls_content.summary().clone();
+                            ls_content  = this.new_ls_content_info(summary,result);
+                        }
+                    }
+                }
+                (Some(RunnerEvent::Reopen(done)),Some(this)) => {
+                    ls_content = this.reopen_and_report(ls_content,done).await;
                 }
                 _ => break,
             }
         }
     }
 
+    /// Reopen the module's file (see `reopen`) and send the result to the requester. Returns the
+    /// new Language Server content summary.
+    async fn reopen_and_report
+    (&self, ls_content:LanguageServerContent, done:oneshot::Sender<FallibleResult>)
+    -> LanguageServerContent {
+        // The file is reopened while the session is restored after reconnecting, so its requests
+        // cannot be held back until the restoration is finished.
+        let gate       = self.language_server.request_gate();
+        let ls_content = gate.exempt(self.reopen(ls_content)).await;
+        let result     = match &ls_content {
+            LanguageServerContent::Synchronized(_)    => Ok(()),
+            LanguageServerContent::Desynchronized(_) =>
+                Err(ReopeningFailed(self.path().to_string()).into()),
+        };
+        // The requester might not wait for the result anymore.
+        let _ = done.send(result);
+        ls_content
+    }
+
+    /// Open the module's file in the Language Server and make sure its content is the same as the
+    /// module's. Returns the new Language Server content summary.
+    async fn reopen(&self, ls_content:LanguageServerContent) -> LanguageServerContent {
+        let file_path = self.path().file_path().clone();
+        info!(self.logger,"Reopening module file {file_path}.");
+        let opened = match self.language_server.client.open_text_file(&file_path).await {
+            Ok(opened) => opened,
+            Err(err)   => {
+                error!(self.logger,"Failed to reopen module file {file_path}: {err}");
+                return LanguageServerContent::Desynchronized(ls_content.summary().clone())
+            }
+        };
+        let end_of_file = TextLocation::at_document_end(&opened.content);
+        let summary     = ContentSummary {digest:opened.current_version,end_of_file};
+        let result      = match self.model.serialized_content() {
+            Ok(file) if file.content == opened.content =>
+                Ok(ParsedContentSummary::from_source(&file)),
+            Ok(file) => self.full_invalidation(&summary,file).await,
+            Err(err) => Err(err),
+        };
+        self.new_ls_content_info(summary,result)
+    }
+
     /// Get the updated Language Server content summary basing on result of some updating function
     /// (`handle_notification` or `full_invalidation`. If the result is Error, then we assume that
     /// any change was not applied to Language Server state, and mark the state as `Desynchronized`,
@@ -441,8 +550,9 @@ pub mod test {
     use enso_protocol::language_server::MockClient;
     use enso_protocol::language_server::Position;
     use enso_protocol::language_server::TextRange;
+    use enso_protocol::traits::*;
     use json_rpc::error::RpcError;
-    use utils::test::ExpectTuple;
+    use utils::test::traits::*;
     use wasm_bindgen_test::wasm_bindgen_test;
 
 
@@ -666,4 +776,95 @@ pub mod test {
         };
         Runner::run(test);
     }
+
+    #[wasm_bindgen_test]
+    fn reopening_file() {
+        // After reconnecting, the Language Server has the file's content as it was before the
+        // module was opened, so the whole module's content should be sent again.
+        let initial_code = "main =\n    println \"Hello World!\"";
+        let mut data     = crate::test::mock::Unified::new();
+        data.set_code(initial_code);
+
+        let test = |runner:&mut Runner| {
+            let edit_handler = LsClientSetup::new_for_mock_data(&data);
+            let mut fixture  = data.fixture_customize(|data,client,_| {
+                data.expect_opening_module(client);
+                data.expect_closing_module(client);
+                // Opening module and metadata generation.
+                edit_handler.expect_full_invalidation(client);
+                // Reopening module.
+                let ls_state = edit_handler.clone();
+                client.expect.open_text_file(move |_| {
+                    let content         = initial_code.to_string();
+                    let current_version = Sha3_224::new(content.as_bytes());
+                    ls_state.current_ls_content.set(content.clone());
+                    ls_state.current_ls_version.set(current_version.clone());
+                    let write_capability = None;
+                    Ok(language_server::response::OpenTextFile
+                        {content,current_version,write_capability})
+                });
+                edit_handler.expect_full_invalidation(client);
+            });
+
+            let module = fixture.synchronized_module();
+            runner.perhaps_run_until_stalled(&mut fixture);
+            let mut reopened = module.reopen_file().boxed_local();
+            runner.perhaps_run_until_stalled(&mut fixture);
+            reopened.expect_ok();
+        };
+        Runner::run(test);
+    }
+
+    #[wasm_bindgen_test]
+    fn reopening_file_while_edits_are_held() {
+        // While the session is restored after reconnecting, the edits wait for the request gate,
+        // but the file is reopened without waiting for them.
+        let initial_code = "main =\n    println \"Hello World!\"";
+        let mut data     = crate::test::mock::Unified::new();
+        data.set_code(initial_code);
+        let edit_handler = LsClientSetup::new_for_mock_data(&data);
+        let mut fixture  = data.fixture_customize(|data,client,_| {
+            let gate = client.request_gate();
+            data.expect_opening_module(client);
+            data.expect_closing_module(client);
+            // Opening module and metadata generation.
+            edit_handler.expect_full_invalidation(client);
+            // The edit made while the gate is held.
+            let edit_gate = gate.clone_ref();
+            client.expect.apply_text_file_edit(move |_| {
+                assert!(!edit_gate.is_open());
+                Ok(())
+            });
+            // Reopening module.
+            let ls_state = edit_handler.clone();
+            client.expect.open_text_file(move |_| {
+                assert!(gate.is_held());
+                assert!(gate.is_open());
+                let content         = initial_code.to_string();
+                let current_version = Sha3_224::new(content.as_bytes());
+                ls_state.current_ls_content.set(content.clone());
+                ls_state.current_ls_version.set(current_version.clone());
+                let write_capability = None;
+                Ok(language_server::response::OpenTextFile
+                    {content,current_version,write_capability})
+            });
+            edit_handler.expect_full_invalidation(client);
+        });
+
+        let (module,controller) = fixture.synchronized_module_w_controller();
+        fixture.run_until_stalled();
+        let gate = fixture.project.json_rpc().request_gate();
+        gate.hold();
+        let change = TextChange {
+            replaced : text::Index::new(20)..text::Index::new(24),
+            inserted : "Test 2".to_string(),
+        };
+        controller.apply_code_change(change).unwrap();
+        fixture.run_until_stalled();
+        let mut reopened = module.reopen_file().boxed_local();
+        fixture.run_until_stalled();
+        reopened.expect_ok();
+        gate.release();
+        fixture.run_until_stalled();
+    }
 }
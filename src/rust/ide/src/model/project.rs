@@ -156,7 +156,9 @@ pub type Synchronized = synchronized::Project;
 #[derive(Clone,Copy,Debug,PartialEq)]
 pub enum Notification {
     /// One of the backend connections has been lost.
-    ConnectionLost(BackendConnection)
+    ConnectionLost(BackendConnection),
+    /// One of the backend connections has been reestablished and its session state restored.
+    ConnectionRestored(BackendConnection),
 }
 
 /// Denotes one of backend connections used by a project.
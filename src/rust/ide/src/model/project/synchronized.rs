@@ -17,7 +17,10 @@ use enso_protocol::language_server::CapabilityRegistration;
 use enso_protocol::language_server::MethodPointer;
 use enso_protocol::project_manager;
 use enso_protocol::project_manager::MissingComponentAction;
+use enso_protocol::traits::*;
 use flo_stream::Subscriber;
+use json_rpc::Transport;
+use json_rpc::transport::recording::RecordingTransport;
 use parser::Parser;
 
 
@@ -84,6 +87,30 @@ impl ExecutionContextsRegistry {
     pub fn insert(&self, context:Rc<execution_context::Synchronized>) {
         self.0.borrow_mut().insert(context.id(),context);
     }
+
+    /// Recreates all the registered contexts in the Language Server, e.g. after the connection has
+    /// been reestablished. As the contexts get new ids, they are registered again under them as
+    /// soon as they are created, so the updates coming while their state is restored are not lost.
+    ///
+    /// All the contexts are restored even if some of them fail, the first error is returned. A
+    /// context which could not be created again stays registered under its old id.
+    pub async fn restore_all(&self) -> FallibleResult {
+        let contexts   = self.0.borrow().values().collect_vec();
+        let mut result = Ok(());
+        for context in contexts {
+            let old_id   = context.id();
+            let restored = match context.recreate().await {
+                Ok(_) => {
+                    self.0.borrow_mut().remove(&old_id);
+                    self.insert(context.clone_ref());
+                    context.restore_state().await
+                }
+                Err(error) => Err(error),
+            };
+            result = result.and(restored.map_err(|error| error.into()));
+        }
+        result
+    }
 }
 
 
@@ -135,6 +162,25 @@ impl Display for UnsupportedEngineVersion {
 
 
 
+// === Session Recording ===
+
+/// Label of the console messages with the recorded Language Server JSON-RPC session.
+pub const JSON_SESSION_LABEL:&str = "[recorded json session]";
+
+/// Label of the console messages with the recorded Language Server binary session.
+pub const BINARY_SESSION_LABEL:&str = "[recorded binary session]";
+
+/// Wrap the transport so the whole session is logged to the console, for replaying it in tests.
+/// Used when the IDE is started with the `record_session` option. See
+/// `json_rpc::transport::recording::ConsoleSink`.
+fn recorded<T:Transport>(transport:T, label:&str) -> RecordingTransport<T> {
+    let mut transport = RecordingTransport::new_to_console(transport,label);
+    crate::executor::global::spawn(transport.runner());
+    transport
+}
+
+
+
 // === Data ===
 
 /// A structure containing the project's properties.
@@ -150,6 +196,8 @@ pub struct Properties {
 
 // == Model ==
 
+type ModuleRegistry = model::registry::Registry<module::Path,module::Synchronized>;
+
 /// Project Model.
 #[allow(missing_docs)]
 #[derive(Derivative)]
@@ -160,7 +208,7 @@ pub struct Project {
     pub project_manager     : Option<Rc<dyn project_manager::API>>,
     pub language_server_rpc : Rc<language_server::Connection>,
     pub language_server_bin : Rc<binary::Connection>,
-    pub module_registry     : Rc<model::registry::Registry<module::Path,module::Synchronized>>,
+    pub module_registry     : Rc<ModuleRegistry>,
     pub execution_contexts  : Rc<ExecutionContextsRegistry>,
     pub visualization       : controller::Visualization,
     pub suggestion_db       : Rc<SuggestionDatabase>,
@@ -229,8 +277,18 @@ impl Project {
         let client_id = Uuid::new_v4();
         let json_ws   = WebSocket::new_opened(&parent,&language_server_rpc).await?;
         let binary_ws = WebSocket::new_opened(&parent,&language_server_bin).await?;
-        let client_json   = language_server::Client::new(json_ws);
-        let client_binary = binary::Client::new(&parent,binary_ws);
+        let record        = enso_args::ARGS.record_session.unwrap_or(false);
+        let client_json   = if record {
+            language_server::Client::new(recorded(json_ws,JSON_SESSION_LABEL))
+        } else {
+            language_server::Client::new(json_ws)
+        };
+        let client_binary = if record {
+            binary::Client::new(&parent,recorded(binary_ws,BINARY_SESSION_LABEL))
+        } else {
+            binary::Client::new(&parent,binary_ws)
+        };
+        client_json.set_retry_policy(Some(default()));
         crate::executor::global::spawn(client_json.runner());
         crate::executor::global::spawn(client_binary.runner());
         let connection_json = language_server::Connection::new(client_json,client_id)
@@ -269,6 +327,9 @@ impl Project {
         let logger                  = self.logger.clone_ref();
         let publisher               = self.notifications.clone_ref();
         let weak_execution_contexts = Rc::downgrade(&self.execution_contexts);
+        let weak_connection         = Rc::downgrade(&self.language_server_bin);
+        let weak_json_connection    = Rc::downgrade(&self.language_server_rpc);
+        let connection_lost         = Cell::new(false);
         move |event| {
             debug!(logger, "Received an event from the binary protocol: {event:?}");
             use enso_protocol::binary::client::Event;
@@ -287,14 +348,31 @@ impl Project {
                         already dropped.");
                     }
                 }
+                Event::Opened => {
+                    // The socket reports being opened also when the connection is first set up,
+                    // while the session needs to be restored only after it has been lost.
+                    if connection_lost.replace(false) {
+                        if let Some(connection) = weak_connection.upgrade() {
+                            let which   = model::project::BackendConnection::LanguageServerBinary;
+                            // The binary session is bound to the JSON-RPC one, so the latter must
+                            // be restored first, if it was lost as well.
+                            let json_session_restored = weak_json_connection.upgrade().map(|json| {
+                                json.request_gate().released()
+                            });
+                            let restore = async move {
+                                futures::future::OptionFuture::from(json_session_restored).await;
+                                connection.client.init(connection.client_id).await
+                            };
+                            Self::spawn_session_restoration(&logger,&publisher,which,restore);
+                        }
+                    }
+                }
                 Event::Closed => {
                     error!(logger,"Lost binary connection with the Language Server!");
+                    connection_lost.set(true);
                     let which        = model::project::BackendConnection::LanguageServerBinary;
                     let notification = model::project::Notification::ConnectionLost(which);
                     publisher.notify(notification);
-                    // TODO [wmu]
-                    //   The connection should be reestablished, see
-                    //   https://github.com/enso-org/ide/issues/145
                 }
                 Event::Error(error) => {
                     error!(logger,"Error emitted by the binary data connection: {error}.");
@@ -319,6 +397,9 @@ impl Project {
         let publisher               = self.notifications.clone_ref();
         let weak_execution_contexts = Rc::downgrade(&self.execution_contexts);
         let weak_suggestion_db      = Rc::downgrade(&self.suggestion_db);
+        let weak_connection         = Rc::downgrade(&self.language_server_rpc);
+        let weak_module_registry    = Rc::downgrade(&self.module_registry);
+        let connection_lost         = Cell::new(false);
         move |event| {
             debug!(logger, "Received an event from the json-rpc protocol: {event:?}");
             use enso_protocol::language_server::Event;
@@ -347,14 +428,36 @@ impl Project {
                         suggestion_db.apply_update_event(update);
                     }
                 }
+                Event::Opened => {
+                    // See the comment in the `binary_event_handler`.
+                    if connection_lost.replace(false) {
+                        let connection         = weak_connection.upgrade();
+                        let module_registry    = weak_module_registry.upgrade();
+                        let execution_contexts = weak_execution_contexts.upgrade();
+                        let suggestion_db      = weak_suggestion_db.upgrade();
+                        if let (Some(connection),Some(module_registry),Some(execution_contexts)
+                               ,Some(suggestion_db)) =
+                            (connection,module_registry,execution_contexts,suggestion_db) {
+                            let which   = model::project::BackendConnection::LanguageServerJson;
+                            let restore = async move {
+                                Self::restore_json_session(&connection,&module_registry
+                                    ,&execution_contexts,&suggestion_db).await
+                            };
+                            Self::spawn_session_restoration(&logger,&publisher,which,restore);
+                        }
+                    }
+                }
                 Event::Closed => {
                     error!(logger,"Lost JSON-RPC connection with the Language Server!");
+                    connection_lost.set(true);
+                    // The requests are held back until the session is restored, as the Language
+                    // Server would reject them before.
+                    if let Some(connection) = weak_connection.upgrade() {
+                        connection.request_gate().hold();
+                    }
                     let which        = model::project::BackendConnection::LanguageServerJson;
                     let notification = model::project::Notification::ConnectionLost(which);
                     publisher.notify(notification);
-                    // TODO [wmu]
-                    //  The connection should be reestablished, 
-                    //  see https://github.com/enso-org/ide/issues/145
                 }
                 Event::Error(error) => {
                     error!(logger,"Error emitted by the JSON-RPC data connection: {error}.");
@@ -366,8 +469,70 @@ impl Project {
     }
 
     fn acquire_suggestion_db_updates_capability(&self) -> impl Future<Output=json_rpc::Result<()>> {
+        Self::acquire_suggestion_db_updates_capability_on(&self.language_server_rpc)
+    }
+
+    fn acquire_suggestion_db_updates_capability_on
+    (language_server:&language_server::Connection) -> impl Future<Output=json_rpc::Result<()>> {
         let capability = CapabilityRegistration::create_receives_suggestions_database_updates();
-        self.language_server_rpc.acquire_capability(&capability.method,&capability.register_options)
+        language_server.acquire_capability(&capability.method,&capability.register_options)
+    }
+
+    /// Spawns the task restoring the session after the backend connection has been reestablished.
+    /// The `ConnectionRestored` notification is emitted once the task succeeds.
+    fn spawn_session_restoration
+    ( logger    : &Logger
+    , publisher : &notification::Publisher<model::project::Notification>
+    , which     : model::project::BackendConnection
+    , restore   : impl Future<Output=FallibleResult> + 'static
+    ) {
+        info!(logger,"Connection {which:?} reestablished, restoring the session.");
+        let logger    = logger.clone_ref();
+        let publisher = publisher.clone_ref();
+        crate::executor::global::spawn(async move {
+            match restore.await {
+                Ok(()) => {
+                    let notification = model::project::Notification::ConnectionRestored(which);
+                    publisher.notify(notification);
+                }
+                Err(error) => error!(logger,"Failed to restore the {which:?} session: {error}."),
+            }
+        });
+    }
+
+    /// Restores the Language Server session state after the JSON-RPC connection has been
+    /// reestablished: initializes the protocol, acquires the capabilities again, reloads the
+    /// suggestion database, reopens the loaded modules and recreates the execution contexts.
+    ///
+    /// The other requests are held back by the client's `RequestGate` since the connection was
+    /// lost, including the retries of the requests which were in flight then. They are sent once
+    /// the restoration finishes, also if it failed, so they do not wait forever. However, if the
+    /// connection has been lost again meanwhile, they are held back until the next restoration.
+    async fn restore_json_session
+    ( language_server    : &language_server::Connection
+    , module_registry    : &ModuleRegistry
+    , execution_contexts : &ExecutionContextsRegistry
+    , suggestion_db      : &SuggestionDatabase
+    ) -> FallibleResult {
+        let gate           = language_server.request_gate();
+        let disconnections = gate.disconnections();
+        let restore        = gate.exempt(async {
+            let client_id = language_server.client_id;
+            language_server.client.init_protocol_connection(&client_id).await?;
+            Self::acquire_suggestion_db_updates_capability_on(language_server).await?;
+            suggestion_db.reload(language_server).await?;
+            let modules  = module_registry.loaded();
+            let reopened = modules.iter().map(|module| module.reopen_file()).collect_vec();
+            for result in futures::future::join_all(reopened).await {
+                result?;
+            }
+            execution_contexts.restore_all().await
+        });
+        let result = restore.await;
+        if gate.disconnections() == disconnections {
+            gate.release();
+        }
+        result
     }
 
     fn load_module(&self, path:module::Path)
@@ -472,8 +637,17 @@ mod test {
     use enso_protocol::language_server::response;
     use json_rpc::expect_call;
     use enso_protocol::language_server::Notification::ExpressionUpdates;
+    use mockall::predicate::eq;
     use utils::test::traits::*;
     use futures::SinkExt;
+    use futures::channel::mpsc::UnboundedSender;
+    use json_rpc::error::RpcError;
+    use json_rpc::test_util::transport::replay::ReplayTransport;
+    use json_rpc::transport::recording::Direction;
+    use json_rpc::transport::recording::Entry;
+    use json_rpc::transport::recording::Frame;
+    use json_rpc::transport::recording::Recording;
+    use language_server::SuggestionId;
 
 
     #[allow(unused)]
@@ -550,6 +724,218 @@ mod test {
         });
     }
 
+    /// The suggestion database with a single atom entry.
+    fn suggestions_with_atom(id:SuggestionId, name:&str) -> response::GetSuggestionDatabase {
+        let suggestion = language_server::types::SuggestionEntry::Atom {
+            name          : name.to_owned(),
+            module        : "TestProject.TestModule".to_owned(),
+            arguments     : vec![],
+            return_type   : "TestAtom".to_owned(),
+            documentation : None,
+            external_id   : None,
+        };
+        let entries = vec![language_server::SuggestionsDatabaseEntry {id,suggestion}];
+        response::GetSuggestionDatabase {entries,current_version:1}
+    }
+
+    #[wasm_bindgen_test]
+    fn restore_session_after_reconnecting() {
+        use crate::model::project::Notification;
+        use crate::model::project::BackendConnection::*;
+
+        let mut f = Fixture::new(|ls_json| {
+            let capability = CapabilityRegistration::create_receives_suggestions_database_updates();
+            let method        = capability.method;
+            let options       = capability.register_options;
+            let client_id     = Uuid::default();
+            let init_response = response::InitProtocolConnection {content_roots:vec![]};
+            // The updates sent while the connection was lost are gone, so the whole suggestion
+            // database is taken again.
+            let suggestions   = suggestions_with_atom(1,"Reloaded");
+            expect_call!(ls_json.init_protocol_connection(client_id) => Ok(init_response));
+            expect_call!(ls_json.acquire_capability(method,options)  => Ok(()));
+            expect_call!(ls_json.get_suggestions_database()          => Ok(suggestions));
+            ls_json.require_all_calls();
+        }, |ls_binary| {
+            let init = futures::future::ready(Ok(())).boxed_local();
+            ls_binary.expect_init().with(eq(Uuid::default())).times(1).return_once(|_| init);
+        });
+        let mut events = f.project.subscribe().boxed_local();
+
+        // Opening the connection for the first time does not need restoring anything.
+        f.json_events_sender.unbounded_send(json_rpc::Event::Opened).unwrap();
+        f.binary_events_sender.unbounded_send(binary::Event::Opened).unwrap();
+        f.test.run_until_stalled();
+        events.expect_pending();
+
+        f.json_events_sender.unbounded_send(json_rpc::Event::Closed).unwrap();
+        f.json_events_sender.unbounded_send(json_rpc::Event::Opened).unwrap();
+        f.test.run_until_stalled();
+        assert_eq!(events.expect_next(),Notification::ConnectionLost(LanguageServerJson));
+        assert_eq!(events.expect_next(),Notification::ConnectionRestored(LanguageServerJson));
+        assert_eq!(f.project.suggestion_db.lookup(1).unwrap().name,"Reloaded");
+
+        f.binary_events_sender.unbounded_send(binary::Event::Closed).unwrap();
+        f.binary_events_sender.unbounded_send(binary::Event::Opened).unwrap();
+        f.test.run_until_stalled();
+        assert_eq!(events.expect_next(),Notification::ConnectionLost(LanguageServerBinary));
+        assert_eq!(events.expect_next(),Notification::ConnectionRestored(LanguageServerBinary));
+        events.expect_pending();
+    }
+
+    #[wasm_bindgen_test]
+    fn hold_requests_while_restoring_session() {
+        use crate::model::project::Notification;
+        use crate::model::project::BackendConnection::*;
+
+        let suggestions = response::GetSuggestionDatabase {entries:vec![],current_version:1};
+        let expected    = suggestions.clone();
+        let mut f = Fixture::new(|ls_json| {
+            let capability = CapabilityRegistration::create_receives_suggestions_database_updates();
+            let method        = capability.method;
+            let options       = capability.register_options;
+            let client_id     = Uuid::default();
+            let init_response = response::InitProtocolConnection {content_roots:vec![]};
+            expect_call!(ls_json.init_protocol_connection(client_id) => Ok(init_response));
+            expect_call!(ls_json.acquire_capability(method,options)  => Ok(()));
+            // The request held back, then the one reloading the database during restoration.
+            expect_call!(ls_json.get_suggestions_database()          => Ok(suggestions.clone()));
+            expect_call!(ls_json.get_suggestions_database()          => Ok(suggestions));
+            ls_json.require_all_calls();
+        }, |ls_binary| {
+            let init = futures::future::ready(Ok(())).boxed_local();
+            ls_binary.expect_init().with(eq(Uuid::default())).times(1).return_once(|_| init);
+        });
+        let mut events = f.project.subscribe().boxed_local();
+        f.json_events_sender.unbounded_send(json_rpc::Event::Opened).unwrap();
+        f.binary_events_sender.unbounded_send(binary::Event::Opened).unwrap();
+        f.test.run_until_stalled();
+
+        f.json_events_sender.unbounded_send(json_rpc::Event::Closed).unwrap();
+        f.test.run_until_stalled();
+        assert_eq!(events.expect_next(),Notification::ConnectionLost(LanguageServerJson));
+
+        // The binary session is not restored before the JSON-RPC one.
+        f.binary_events_sender.unbounded_send(binary::Event::Closed).unwrap();
+        f.binary_events_sender.unbounded_send(binary::Event::Opened).unwrap();
+        f.test.run_until_stalled();
+        assert_eq!(events.expect_next(),Notification::ConnectionLost(LanguageServerBinary));
+        events.expect_pending();
+
+        // The requests made before the session is restored wait for it.
+        let json_rpc    = f.project.json_rpc();
+        let mut request = json_rpc.get_suggestions_database();
+        request.expect_pending();
+
+        f.json_events_sender.unbounded_send(json_rpc::Event::Opened).unwrap();
+        f.test.run_until_stalled();
+        assert_eq!(events.expect_next(),Notification::ConnectionRestored(LanguageServerJson));
+        assert_eq!(events.expect_next(),Notification::ConnectionRestored(LanguageServerBinary));
+        assert_eq!(request.expect_ok(),expected);
+        events.expect_pending();
+    }
+
+    #[wasm_bindgen_test]
+    fn lose_connection_while_restoring_session() {
+        use crate::model::project::Notification;
+        use crate::model::project::BackendConnection::*;
+
+        let suggestions   = response::GetSuggestionDatabase {entries:vec![],current_version:1};
+        let expected      = suggestions.clone();
+        let events_sender = Rc::new(RefCell::new(None));
+        let mut f = Fixture::new(|ls_json| {
+            let capability = CapabilityRegistration::create_receives_suggestions_database_updates();
+            let method        = capability.method;
+            let options       = capability.register_options;
+            let client_id     = Uuid::default();
+            let init_response = response::InitProtocolConnection {content_roots:vec![]};
+            // The first restoration fails, as the connection is lost again meanwhile. The closed
+            // transport is recorded as by the `json_rpc::Handler`.
+            let sender = events_sender.clone_ref();
+            let gate   = ls_json.request_gate();
+            ls_json.expect.init_protocol_connection(move |_| {
+                let sender:&Option<UnboundedSender<language_server::Event>> = &sender.borrow();
+                gate.record_disconnection();
+                sender.as_ref().unwrap().unbounded_send(json_rpc::Event::Closed).unwrap();
+                Err(RpcError::LostConnection)
+            });
+            expect_call!(ls_json.init_protocol_connection(client_id) => Ok(init_response));
+            expect_call!(ls_json.acquire_capability(method,options)  => Ok(()));
+            // The request held back, then the one reloading the database during restoration.
+            expect_call!(ls_json.get_suggestions_database()          => Ok(suggestions.clone()));
+            expect_call!(ls_json.get_suggestions_database()          => Ok(suggestions));
+            ls_json.require_all_calls();
+        }, |_| {});
+        events_sender.replace(Some(f.json_events_sender.clone()));
+        let mut events = f.project.subscribe().boxed_local();
+        f.json_events_sender.unbounded_send(json_rpc::Event::Opened).unwrap();
+        f.test.run_until_stalled();
+
+        f.json_events_sender.unbounded_send(json_rpc::Event::Closed).unwrap();
+        f.json_events_sender.unbounded_send(json_rpc::Event::Opened).unwrap();
+        f.test.run_until_stalled();
+        assert_eq!(events.expect_next(),Notification::ConnectionLost(LanguageServerJson));
+        assert_eq!(events.expect_next(),Notification::ConnectionLost(LanguageServerJson));
+        events.expect_pending();
+
+        // The failed restoration does not let the requests through, as the session is lost.
+        let json_rpc    = f.project.json_rpc();
+        assert!(json_rpc.request_gate().is_held());
+        let mut request = json_rpc.get_suggestions_database();
+        f.test.run_until_stalled();
+        request.expect_pending();
+
+        f.json_events_sender.unbounded_send(json_rpc::Event::Opened).unwrap();
+        f.test.run_until_stalled();
+        assert_eq!(events.expect_next(),Notification::ConnectionRestored(LanguageServerJson));
+        assert!(!json_rpc.request_gate().is_held());
+        assert_eq!(request.expect_ok(),expected);
+    }
+
+    #[wasm_bindgen_test]
+    fn restore_execution_contexts() {
+        use execution_context::synchronized::test::Fixture as ExecutionFixture;
+
+        let data       = execution_context::plain::test::MockData::new();
+        let old_id     = data.context_id;
+        let new_id     = execution_context::Id::new_v4();
+        let registry   = Rc::new(ExecutionContextsRegistry::default());
+        let response   = ExecutionFixture::expected_creation_response(&data);
+        let restored   = response::CreateExecutionContext {context_id:new_id,..response.clone()};
+        let root_frame = language_server::ExplicitCall {
+            method_pointer                   : data.main_method_pointer(),
+            this_argument_expression         : None,
+            positional_arguments_expressions : vec![]
+        };
+        let root_item  = language_server::StackItem::ExplicitCall(root_frame);
+        let mut ls     = language_server::MockClient::default();
+        expect_call!(ls.create_execution_context()                  => Ok(response));
+        expect_call!(ls.push_to_execution_context(old_id,root_item) => Ok(()));
+        expect_call!(ls.create_execution_context()                  => Ok(restored));
+        // The context is already registered under the new id when its stack is restored.
+        let weak_registry = Rc::downgrade(&registry);
+        ls.expect.push_to_execution_context(move |id,_| {
+            let registry = weak_registry.upgrade().unwrap();
+            assert_eq!(*id,new_id);
+            assert!(registry.with_context(new_id,|_| Ok(())).is_ok());
+            assert!(registry.with_context(old_id,|_| Ok(())).is_err());
+            Ok(())
+        });
+        expect_call!(ls.destroy_execution_context(new_id) => Ok(()));
+        ls.require_all_calls();
+
+        let mut test   = TestWithLocalPoolExecutor::set_up();
+        let connection = language_server::Connection::new_mock_rc(ls);
+        let method     = data.main_method_pointer();
+        let context    = execution_context::Synchronized::create(Logger::new("Test"),connection
+            ,method);
+        let context    = Rc::new(test.expect_completion(context).unwrap());
+        registry.insert(context.clone_ref());
+        test.expect_completion(registry.restore_all()).unwrap();
+        assert!(registry.with_context(new_id,|_| Ok(())).is_ok());
+        assert!(registry.with_context(old_id,|_| Ok(())).is_err());
+    }
+
     #[wasm_bindgen_test]
     fn obtain_module_controller() {
         let path         = module::Path::from_mock_module_name("TestModule");
@@ -570,6 +956,67 @@ mod test {
         });
     }
 
+    /// The binary session of initializing the connection: the `InitSession` request and the
+    /// success reply. The ids differ from the replayed ones, which are substituted by the
+    /// `ReplayTransport`.
+    fn binary_init_session() -> Recording {
+        use enso_protocol::binary::message::FromServerPayloadOwned;
+        use enso_protocol::binary::message::MessageFromServer;
+        use enso_protocol::binary::message::MessageToServer;
+        use enso_protocol::binary::message::ToServerPayload;
+        use enso_protocol::binary::serialization::SerializableRoot;
+
+        let client_id = Uuid::new_v4();
+        let request   = MessageToServer::new(ToServerPayload::InitSession {client_id});
+        let mut reply = MessageFromServer::new(FromServerPayloadOwned::Success {});
+        reply.correlation_id = Some(request.message_id);
+        let entry = |direction,message:Vec<u8>| {
+            Entry {time:0.0,direction,frame:Frame::Binary(message)}
+        };
+        let request = request.with_serialized(|data| entry(Direction::Sent,data.to_vec()));
+        let reply   = reply.with_serialized(|data| entry(Direction::Received,data.to_vec()));
+        Recording {entries:vec![request,reply]}
+    }
+
+    /// Replays a recorded Language Server session, checking that the project model makes the same
+    /// calls during initialization and handles the recorded replies and notifications.
+    ///
+    /// To capture the session again, start the IDE with the `record_session` option, save the
+    /// console messages labeled with `JSON_SESSION_LABEL` and strip the label. The binary session
+    /// is only initialized, see `binary_init_session`.
+    #[wasm_bindgen_test]
+    fn replay_recorded_session() {
+        let mut test          = TestWithLocalPoolExecutor::set_up();
+        let logger            = Logger::new("Test");
+        let recording         = include_str!("synchronized/session.jsonl");
+        let json_transport    = ReplayTransport::from_json_lines(recording);
+        let binary_transport  = ReplayTransport::new(binary_init_session());
+        let json_client       = language_server::Client::new(json_transport.clone_ref());
+        let binary_client     = binary::Client::new(&logger,binary_transport.clone_ref());
+        crate::executor::global::spawn(json_client.runner());
+        crate::executor::global::spawn(binary_client.runner());
+
+        let client_id         = Uuid::new_v4();
+        let json_connection   = language_server::Connection::new(json_client,client_id);
+        let json_connection   = test.expect_completion(json_connection.boxed_local()).unwrap();
+        let binary_connection = binary::Connection::new(binary_client,client_id);
+        let binary_connection = test.expect_completion(binary_connection.boxed_local()).unwrap();
+        let json_connection   = Rc::new(json_connection);
+        let binary_connection = Rc::new(binary_connection);
+        let engine_version    = semver::Version::new(0,2,1);
+        let project_fut       = Project::new(logger,None,json_connection,binary_connection
+            ,engine_version,Uuid::new_v4(),DEFAULT_PROJECT_NAME).boxed_local();
+        let project = test.expect_completion(project_fut).unwrap();
+        json_transport.expect_finished();
+        binary_transport.expect_finished();
+
+        let project_root = Uuid::parse_str("6f7d58dd-8ee8-44cf-9ab7-9f0454033641").unwrap();
+        assert_eq!(project.json_rpc().project_root().id,project_root);
+        // The first entry comes with the database, the second one with the update notification.
+        assert_eq!(project.suggestion_db.lookup(1).unwrap().name,"Foo");
+        assert_eq!(project.suggestion_db.lookup(2).unwrap().name,"Bar");
+    }
+
     fn mock_calls_for_opening_text_file
     (client:&language_server::MockClient, path:language_server::Path, content:&str) {
         let content          = content.to_string();
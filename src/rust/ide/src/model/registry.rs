@@ -126,6 +126,16 @@ where K : Clone + Eq + Hash {
 
     }
 
+    /// Get handles to all the items which are already loaded. Items being loaded are skipped.
+    pub fn loaded(&self) -> Vec<Rc<V>> {
+        let registry = self.registry.borrow();
+        let entries  = registry.values();
+        entries.filter_map(|entry| match entry {
+            Entry::Loaded(state) => Some(state),
+            Entry::Loading(_)    => None,
+        }).collect()
+    }
+
     async fn load<F,E>(&self, key:K, loader:F) -> Result<Rc<V>,E>
     where F : Future<Output=Result<Rc<V>,E>> {
         let publisher = Publisher::default();
@@ -159,6 +169,8 @@ mod test {
 
     use crate::executor::test_utils::TestWithLocalPoolExecutor;
 
+    use utils::test::ExpectTuple;
+
     type ModulePath = model::module::Path;
     type Registry   = super::Registry<ModulePath,model::module::Plain>;
 
@@ -174,10 +186,13 @@ mod test {
             let state    = Rc::new(model::module::Plain::new(logger,path.clone(),ast,default(),urm));
             let registry = Registry::default();
             let expected = state.clone_ref();
+            assert!(registry.loaded().is_empty());
 
             let loader = async move { Ok(state) };
             let module = registry.get_or_load(path.clone(),loader).await.unwrap();
             assert!(Rc::ptr_eq(&expected,&module));
+            let (loaded,) = registry.loaded().expect_tuple();
+            assert!(Rc::ptr_eq(&expected,&loaded));
 
             let loader = async move { unreachable!("Should not call loader second time!") };
             let module = registry.get_or_load(path,loader).await.unwrap();
@@ -93,15 +93,8 @@ impl SuggestionDatabase {
 
     /// Create a new database model from response received from the Language Server.
     fn from_ls_response(response:language_server::response::GetSuggestionDatabase) -> Self {
-        let logger      = Logger::new("SuggestionDatabase");
-        let mut entries = HashMap::new();
-        for ls_entry in response.entries {
-            let id = ls_entry.id;
-            match Entry::from_ls_entry(ls_entry.suggestion) {
-                Ok(entry) => { entries.insert(id, Rc::new(entry)); },
-                Err(err)  => { error!(logger,"Discarded invalid entry {id}: {err}"); },
-            }
-        }
+        let logger  = Logger::new("SuggestionDatabase");
+        let entries = Self::entries_from_ls_response(&logger,response.entries);
         //TODO[ao]: This is a temporary solution. Eventually, we should gather examples from the
         //          available modules documentation. (https://github.com/enso-org/ide/issues/1011)
         let examples = example::EXAMPLES.iter().cloned().map(Rc::new).collect_vec();
@@ -114,6 +107,31 @@ impl SuggestionDatabase {
         }
     }
 
+    fn entries_from_ls_response
+    (logger:&Logger, ls_entries:Vec<language_server::SuggestionsDatabaseEntry>)
+    -> HashMap<entry::Id,Rc<Entry>> {
+        let mut entries = HashMap::new();
+        for ls_entry in ls_entries {
+            let id = ls_entry.id;
+            match Entry::from_ls_entry(ls_entry.suggestion) {
+                Ok(entry) => { entries.insert(id, Rc::new(entry)); },
+                Err(err)  => { error!(logger,"Discarded invalid entry {id}: {err}"); },
+            }
+        }
+        entries
+    }
+
+    /// Replace the whole content of the database with the one taken from the Language Server, e.g.
+    /// after the connection has been reestablished, as the updates sent meanwhile were lost.
+    pub async fn reload(&self, language_server:&language_server::Connection) -> FallibleResult {
+        let response = language_server.client.get_suggestions_database().await?;
+        let entries  = Self::entries_from_ls_response(&self.logger,response.entries);
+        *self.entries.borrow_mut() = entries;
+        self.version.set(response.current_version);
+        self.notifications.notify(Notification::Updated);
+        Ok(())
+    }
+
     /// Subscribe for notifications about changes in the database.
     pub fn subscribe(&self) -> Subscriber<Notification> {
         self.notifications.subscribe()
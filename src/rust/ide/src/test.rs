@@ -271,9 +271,10 @@ pub mod mock {
             let data           = self.clone();
             let selected_nodes = Vec::new();
             let searcher_mode  = controller::searcher::Mode::NewNode {position:None};
+            let searcher_usage = Rc::new(controller::searcher::usage::Model::new_in_memory(&logger));
             let searcher       = controller::Searcher::new_from_graph_controller(&logger
                 ,ide.clone_ref(),&project,executed_graph.clone_ref(),searcher_mode,selected_nodes
-                ).unwrap();
+                ,searcher_usage).unwrap();
             Fixture
                 {logger,executor,data,module,graph,executed_graph,execution,suggestion_db,project
                 ,searcher,ide}
@@ -832,3 +832,24 @@ impl display::Object for Node {
         &self.model.display_object
     }
 }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_shaders_are_valid() {
+        check_shape_system_shaders(&
+            [ ("background"  , background::ShapeSystem::shader_for)
+            , ("backdrop"    , backdrop::ShapeSystem::shader_for)
+            , ("drag_area"   , drag_area::ShapeSystem::shader_for)
+            , ("error_shape" , error_shape::ShapeSystem::shader_for)
+            ]);
+    }
+}
@@ -16,6 +16,7 @@ use enso_frp as frp;
 use ensogl::Animation;
 use ensogl::application;
 use ensogl::application::Application;
+use ensogl::application::keymap;
 use ensogl::application::shortcut;
 use ensogl::display;
 use ensogl::display::shape::*;
@@ -23,6 +24,7 @@ use ensogl::DEPRECATED_Animation;
 use ensogl::system::web;
 use ensogl::system::web::dom;
 use ensogl_theme::Theme as Theme;
+use wasm_bindgen_futures::JsFuture;
 
 
 
@@ -51,6 +53,10 @@ ensogl::define_endpoints! {
         show_prompt(),
         /// Hide the prompt
         hide_prompt(),
+        /// Log all conflicts between registered shortcuts.
+        debug_report_shortcut_conflicts(),
+        /// Load the user keymap again from the URL given by the `keymap` startup argument.
+        reload_keymap(),
     }
 
     Output {
@@ -263,6 +269,30 @@ impl Model {
     fn on_fullscreen_clicked(&self) {
         js::fullscreen();
     }
+
+    /// Load the user keymap from the URL given by the `keymap` startup argument, replacing the
+    /// current one. Does nothing if the argument was not given.
+    fn reload_keymap(&self) {
+        if let Some(url) = ARGS.keymap.clone() {
+            let logger    = self.logger.clone_ref();
+            let shortcuts = self.app.shortcuts.clone_ref();
+            wasm_bindgen_futures::spawn_local(async move {
+                let content = JsFuture::from(js::fetch_text(&url)).await;
+                let content = content.ok().and_then(|content| content.as_string());
+                let keymap  = content.map(|content| keymap::Keymap::from_file(&url,&content));
+                match keymap {
+                    None             => error!(&logger,"Cannot fetch the keymap from {url}."),
+                    Some(Err(error)) => error!(&logger,"Cannot load the keymap: {error}"),
+                    Some(Ok(keymap)) => {
+                        for error in shortcuts.set_keymap(keymap) {
+                            warning!(&logger,"Cannot apply the keymap: {error}");
+                        }
+                        info!(&logger,"Loaded the keymap from {url}.");
+                    }
+                }
+            });
+        }
+    }
 }
 
 
@@ -284,6 +314,20 @@ mod js {
     }
 
 
+    #[wasm_bindgen(inline_js="
+    export function fetchText(url) {
+        return fetch(url).then(response => {
+            if (!response.ok) { throw new Error(`${response.status} ${response.statusText}`) }
+            return response.text()
+        })
+    }")]
+    extern "C" {
+        #[allow(unsafe_code)]
+        #[wasm_bindgen(js_name="fetchText")]
+        pub fn fetch_text(url:&str) -> js_sys::Promise;
+    }
+
+
     #[wasm_bindgen(inline_js="
     export function fullscreen() {
         try {
@@ -495,6 +539,12 @@ impl View {
             eval frp.style ((style) model.set_style(style.clone()));
 
 
+            // === Debug ===
+
+            eval_ frp.debug_report_shortcut_conflicts (model.app.shortcuts.report_conflicts());
+            eval_ frp.reload_keymap (model.reload_keymap());
+
+
             // === Fullscreen Visualization ===
 
             // TODO[ao]: All DOM elements in visualizations ale displayed below canvas, because
@@ -551,6 +601,7 @@ impl View {
         }
         init.emit(());
         std::mem::forget(prompt_visibility);
+        model.reload_keymap();
 
         Self{model,frp}
     }
@@ -592,6 +643,8 @@ impl application::View for View {
           , (Press   , "is_searcher_opened" , "escape"          , "close_searcher")
           , (Press   , ""                   , "tab"             , "hide_prompt")
           , (Press   , ""                   , "cmd alt shift t" , "toggle_style")
+          , (Press   , ""                   , "cmd alt shift k" , "debug_report_shortcut_conflicts")
+          , (Press   , ""                   , "cmd alt shift r" , "reload_keymap")
           , (Press   , ""                   , "cmd s"           , "save_module")
           , (Press   , ""                   , "cmd z"           , "undo")
           , (Press   , ""                   , "cmd y"           , "redo")
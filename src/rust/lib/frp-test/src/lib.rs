@@ -0,0 +1,317 @@
+//! Marble testing harness for FRP networks.
+//!
+//! The harness allows describing the input events of `Source` nodes and the expected output events
+//! of any stream as marble diagrams (see the `marbles` module to learn about the notation). The
+//! network is then run frame by frame and the recorded output is compared with the expectations.
+//! In case of a mismatch, both diagrams are printed one below another with the first differing
+//! frame marked. The harness does not depend on a browser, so it can be used in plain
+//! `cargo test`.
+//!
+//! ```
+//! use enso_frp as frp;
+//! use enso_frp_test::Test;
+//!
+//! frp::new_network! { network
+//!     def source = source::<usize>();
+//!     def count  = source.count();
+//! }
+//! let mut test = Test::new();
+//! test.input(&source,"a--a-a",&[('a',0)]);
+//! let output = test.output(&count);
+//! test.run();
+//! output.assert_eq("1--2-3",&[('1',1),('2',2),('3',3)]);
+//! ```
+//!
+//! Time-dependent nodes can be tested by providing a `ManualClock`, which will be advanced by a
+//! constant duration before every frame. See `Test::with_clock` to learn more.
+
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unsafe_code)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+pub mod marbles;
+
+pub use marbles::Frames;
+
+use crate::prelude::*;
+
+use enso_frp as frp;
+use frp::clock::ManualClock;
+use frp::clock::Time;
+use frp::stream::CallStack;
+use frp::stream::EventEmitter;
+use frp::stream::EventInput;
+use frp::stream::EventOutput;
+use frp::stream::WeakEventConsumer;
+
+/// Set of often used types and functions.
+pub mod prelude {
+    pub use enso_prelude::*;
+}
+
+
+
+// ============
+// === Test ===
+// ============
+
+/// Marble test of an FRP network. Register the inputs and outputs, run the test, and then check
+/// the recorded outputs.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct Test {
+    frame       : Rc<Cell<usize>>,
+    frame_count : Rc<Cell<usize>>,
+    #[derivative(Debug="ignore")]
+    inputs      : Vec<Box<dyn Fn(usize)>>,
+    length      : usize,
+    clock       : Option<(ManualClock,Time)>,
+}
+
+impl Test {
+    /// Constructor.
+    pub fn new() -> Self {
+        let frame       = default();
+        let frame_count = default();
+        let inputs      = default();
+        let length      = default();
+        let clock       = default();
+        Self {frame,frame_count,inputs,length,clock}
+    }
+
+    /// Constructor of a test driving the provided clock. The clock is advanced by `frame_duration`
+    /// at the beginning of every frame except the first one, before any input event is emitted.
+    pub fn with_clock(clock:&ManualClock, frame_duration:Time) -> Self {
+        let clock = Some((clock.clone_ref(),frame_duration));
+        Self {clock,..Self::new()}
+    }
+
+    /// Register the events which should be emitted by the source. See the `marbles` module to
+    /// learn about the diagram notation.
+    pub fn input<T:frp::Data>
+    (&mut self, source:&frp::Source<T>, diagram:&str, values:&[(char,T)]) {
+        let frames = marbles::parse(diagram,values);
+        let source = source.clone_ref();
+        self.length = self.length.max(frames.len());
+        self.inputs.push(Box::new(move |frame| {
+            for event in frames.get(frame).into_iter().flatten() {
+                source.emit(event);
+            }
+        }));
+    }
+
+    /// Start recording events of the stream. The events are grouped by the frame they were emitted
+    /// in. Events emitted before the test was run are recorded in the first frame.
+    pub fn output<S:EventOutput>(&self, stream:&S) -> Recorder<frp::Output<S>> {
+        let frame       = self.frame.clone_ref();
+        let frame_count = self.frame_count.clone_ref();
+        let events      = default();
+        let data        = Rc::new(RecorderData {frame,frame_count,events});
+        let input       = RecorderInput {data:Rc::downgrade(&data)};
+        stream.register_target(EventInput::new(input));
+        Recorder {data}
+    }
+
+    /// Run all frames described by the registered inputs.
+    pub fn run(&mut self) {
+        self.run_frames(self.length)
+    }
+
+    /// Run the given number of frames. Useful if the output is expected to change after the last
+    /// input event, for example, when testing time-dependent nodes.
+    pub fn run_frames(&mut self, count:usize) {
+        for frame in 0..count {
+            self.frame.set(frame);
+            self.frame_count.set(frame + 1);
+            if let Some((clock,frame_duration)) = &self.clock {
+                if frame > 0 {
+                    clock.advance(*frame_duration);
+                }
+            }
+            for input in &self.inputs {
+                input(frame)
+            }
+        }
+    }
+}
+
+impl Default for Test {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+
+// ================
+// === Recorder ===
+// ================
+
+/// Recorded events of a single stream. See `Test::output` to learn more.
+#[derive(Debug)]
+pub struct Recorder<T> {
+    data : Rc<RecorderData<T>>,
+}
+
+#[derive(Debug)]
+struct RecorderData<T> {
+    frame       : Rc<Cell<usize>>,
+    frame_count : Rc<Cell<usize>>,
+    events      : RefCell<Vec<(usize,T)>>,
+}
+
+impl<T:Clone> Recorder<T> {
+    /// All recorded events, grouped by frames. The number of frames is equal to the number of
+    /// frames run so far.
+    pub fn frames(&self) -> Frames<T> {
+        let mut frames = vec![vec![];self.data.frame_count.get()];
+        for (frame,event) in self.data.events.borrow().iter() {
+            if *frame >= frames.len() {
+                frames.resize(*frame + 1,vec![]);
+            }
+            frames[*frame].push(event.clone());
+        }
+        frames
+    }
+
+    /// All recorded events, in the order they were emitted.
+    pub fn events(&self) -> Vec<T> {
+        self.data.events.borrow().iter().map(|(_,event)| event.clone()).collect()
+    }
+}
+
+impl<T:Clone+Debug+PartialEq> Recorder<T> {
+    /// Check the recorded events against the expected marble diagram.
+    ///
+    /// # Panics
+    /// Panics with a readable diff of both diagrams if the events do not match.
+    pub fn assert_eq(&self, expected:&str, values:&[(char,T)]) {
+        let mut expected = marbles::parse(expected,values);
+        let mut actual   = self.frames();
+        let length       = expected.len().max(actual.len());
+        expected.resize(length,vec![]);
+        actual.resize(length,vec![]);
+        if let Some(first_mismatch) = (0..length).find(|i| expected[*i] != actual[*i]) {
+            panic!("{}",diff(&expected,&actual,values,first_mismatch))
+        }
+    }
+}
+
+/// Describe the mismatch between the expected and actual events. Both diagrams are aligned frame
+/// by frame.
+fn diff<T:Debug+PartialEq>
+(expected:&[Vec<T>], actual:&[Vec<T>], values:&[(char,T)], first_mismatch:usize) -> String {
+    let mut expected_line = String::new();
+    let mut actual_line   = String::new();
+    let mut marker_line   = String::new();
+    for (frame,(expected,actual)) in expected.iter().zip(actual).enumerate() {
+        let expected_cell = marbles::render_frame(expected,values);
+        let actual_cell   = marbles::render_frame(actual,values);
+        let width         = expected_cell.chars().count().max(actual_cell.chars().count());
+        let marker        = if frame == first_mismatch {"^"} else {""};
+        expected_line.push_str(&format!("{:width$}",expected_cell,width=width));
+        actual_line.push_str(&format!("{:width$}",actual_cell,width=width));
+        marker_line.push_str(&format!("{:width$}",marker,width=width));
+    }
+    let expected_events = &expected[first_mismatch];
+    let actual_events   = &actual[first_mismatch];
+    let mut out = String::from("FRP output does not match the expected marble diagram.\n");
+    out.push_str(&format!("  expected: {}\n",expected_line));
+    out.push_str(&format!("  actual:   {}\n",actual_line));
+    out.push_str(&format!("            {}\n",marker_line.trim_end()));
+    out.push_str(&format!("  frame {}: expected {:?}, got {:?}",first_mismatch,expected_events,
+        actual_events));
+    out
+}
+
+
+// === RecorderInput ===
+
+/// Weak connection between the recorded stream and the recorder.
+#[derive(Debug)]
+struct RecorderInput<T> {
+    data : Weak<RecorderData<T>>,
+}
+
+impl<T:Clone> WeakEventConsumer<T> for RecorderInput<T> {
+    fn on_event_if_exists(&self, _stack:CallStack, value:&T) -> bool {
+        self.data.upgrade().map(|data| {
+            data.events.borrow_mut().push((data.frame.get(),value.clone()));
+        }).is_some()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGITS : &[(char,usize)] = &[('0',0),('1',1),('2',2),('3',3),('4',4)];
+
+    #[test]
+    fn map() {
+        frp::new_network! { network
+            def source  = source::<usize>();
+            def doubled = source.map(|t| t * 2);
+        }
+        let mut test = Test::new();
+        test.input(&source,"1-(12)-",DIGITS);
+        let output = test.output(&doubled);
+        test.run();
+        output.assert_eq("2-(24)-",DIGITS);
+        assert_eq!(output.events(),vec![2,2,4]);
+    }
+
+    #[test]
+    fn multiple_inputs() {
+        frp::new_network! { network
+            def source1 = source::<usize>();
+            def source2 = source::<usize>();
+            def sum     = source1.all_with(&source2,|a,b| a + b);
+        }
+        let mut test = Test::new();
+        test.input(&source1,"1---2",DIGITS);
+        test.input(&source2,"--1--",DIGITS);
+        let output = test.output(&sum);
+        test.run();
+        output.assert_eq("1-2-3",DIGITS);
+    }
+
+    #[test]
+    fn delay() {
+        let clock = ManualClock::new();
+        frp::new_network! { network
+            def source  = source::<usize>();
+            def delayed = source.delay(&clock,20.0);
+        }
+        let mut test = Test::with_clock(&clock,10.0);
+        test.input(&source,"1-2",DIGITS);
+        let output = test.output(&delayed);
+        test.run_frames(6);
+        output.assert_eq("--1-2-",DIGITS);
+    }
+
+    #[test]
+    #[should_panic(expected="frame 2: expected [2], got []")]
+    fn mismatch() {
+        frp::new_network! { network
+            def source = source::<usize>();
+            def gated  = source.filter(|t| *t != 2);
+        }
+        let mut test = Test::new();
+        test.input(&source,"1-2",DIGITS);
+        let output = test.output(&gated);
+        test.run();
+        output.assert_eq("1-2",DIGITS);
+    }
+}
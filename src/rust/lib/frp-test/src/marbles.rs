@@ -0,0 +1,130 @@
+//! Marble diagrams – a textual notation of event sequences.
+//!
+//! Every character of a diagram describes a single frame. The `-` character means that no event
+//! happens in the frame, and any other character means a single event with the value assigned to
+//! that character. Several events happening in the same frame can be grouped with parentheses.
+//! Spaces are ignored and can be used for aligning diagrams. For example, the diagram `a-(bc)-a`
+//! describes five frames: event `a` in the first one, events `b` and `c` in the third one, and
+//! event `a` again in the last one.
+
+use crate::prelude::*;
+
+
+
+// ==============
+// === Frames ===
+// ==============
+
+/// Events of a single stream, grouped by frames.
+pub type Frames<T> = Vec<Vec<T>>;
+
+/// Character denoting a frame without events.
+pub const EMPTY_FRAME : char = '-';
+
+/// Character used in rendered diagrams for values which have no character assigned.
+pub const UNKNOWN_VALUE : char = '?';
+
+
+
+// =============
+// === Parse ===
+// =============
+
+/// Parse the marble diagram. The `values` assign event values to diagram characters.
+///
+/// # Panics
+/// Panics if the diagram is malformed or uses a character which has no value assigned, as this is
+/// always a bug in the test definition.
+pub fn parse<T:Clone>(diagram:&str, values:&[(char,T)]) -> Frames<T> {
+    let mut frames = Frames::new();
+    let mut group  = None;
+    for symbol in diagram.chars() {
+        match symbol {
+            ' ' => {},
+            EMPTY_FRAME if group.is_some() =>
+                panic!("Empty frame inside of a group in marble diagram `{}`.",diagram),
+            EMPTY_FRAME => frames.push(vec![]),
+            '(' if group.is_some() => panic!("Nested group in marble diagram `{}`.",diagram),
+            '(' => group = Some(vec![]),
+            ')' => match group.take() {
+                Some(events) => frames.push(events),
+                None         => panic!("Unmatched `)` in marble diagram `{}`.",diagram),
+            },
+            _ => {
+                let value = values.iter().find(|(s,_)| *s == symbol).map(|(_,v)| v.clone());
+                let value = value.unwrap_or_else(|| {
+                    panic!("No value assigned to `{}` in marble diagram `{}`.",symbol,diagram)
+                });
+                match &mut group {
+                    Some(events) => events.push(value),
+                    None         => frames.push(vec![value]),
+                }
+            }
+        }
+    }
+    if group.is_some() {
+        panic!("Unclosed group in marble diagram `{}`.",diagram)
+    }
+    frames
+}
+
+
+
+// ==============
+// === Render ===
+// ==============
+
+/// Render a single frame as a part of the marble diagram. Values without character assigned are
+/// rendered as `UNKNOWN_VALUE`.
+pub fn render_frame<T:PartialEq>(events:&[T], values:&[(char,T)]) -> String {
+    let symbol = |event:&T| {
+        values.iter().find(|(_,v)| v == event).map(|(s,_)| *s).unwrap_or(UNKNOWN_VALUE)
+    };
+    match events {
+        []      => EMPTY_FRAME.to_string(),
+        [event] => symbol(event).to_string(),
+        _       => format!("({})",events.iter().map(symbol).collect::<String>()),
+    }
+}
+
+/// Render frames as a marble diagram. See `render_frame` to learn more.
+pub fn render<T:PartialEq>(frames:&[Vec<T>], values:&[(char,T)]) -> String {
+    frames.iter().map(|events| render_frame(events,values)).collect()
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALUES : &[(char,usize)] = &[('a',1),('b',2),('c',3)];
+
+    #[test]
+    fn parsing() {
+        assert_eq!(parse("a-(bc) -a",VALUES),vec![vec![1],vec![],vec![2,3],vec![],vec![1]]);
+        assert_eq!(parse("",VALUES),Frames::<usize>::new());
+    }
+
+    #[test]
+    #[should_panic]
+    fn parsing_unknown_value() {
+        parse("a-d",VALUES);
+    }
+
+    #[test]
+    #[should_panic]
+    fn parsing_unclosed_group() {
+        parse("a-(bc",VALUES);
+    }
+
+    #[test]
+    fn rendering() {
+        let frames = vec![vec![1],vec![],vec![2,3],vec![7]];
+        assert_eq!(render(&frames,VALUES),"a-(bc)?");
+    }
+}
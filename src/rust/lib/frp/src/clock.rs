@@ -0,0 +1,273 @@
+//! Time sources for time-dependent FRP nodes, like `delay`, `debounce`, `throttle`, or
+//! `sample_every`.
+//!
+//! Time-dependent nodes do not use any timers on their own. Instead, they are driven by a clock,
+//! which is just an FRP stream of `Time` values. Every event emitted by the clock is interpreted as
+//! the current time. In EnsoGL applications, the scene frame time stream is a natural clock. In
+//! tests, the `ManualClock` can be used to advance the time by hand, which allows testing networks
+//! natively, without a browser.
+
+use crate::prelude::*;
+
+use crate::data::watch;
+use crate::node::*;
+use crate::nodes::OwnedSource;
+use crate::stream;
+use crate::stream::CallStack;
+use crate::stream::EventEmitter;
+use crate::stream::EventInput;
+use crate::stream::EventOutput;
+use crate::stream::Stream;
+use crate::stream::ValueProvider;
+
+
+
+// ============
+// === Time ===
+// ============
+
+/// Time in milliseconds.
+pub type Time = f32;
+
+/// Any stream of `Time` values which can drive time-dependent FRP nodes. The emitted values should
+/// never decrease.
+pub trait Clock = EventOutput<Output=Time>;
+
+
+
+// ===================
+// === ManualClock ===
+// ===================
+
+/// A clock which is advanced by hand. The time starts at zero.
+#[derive(Clone,CloneRef,Debug)]
+pub struct ManualClock {
+    source : OwnedSource<Time>,
+    time   : Rc<Cell<Time>>,
+}
+
+impl ManualClock {
+    /// Constructor.
+    pub fn new() -> Self {
+        let source = OwnedSource::new("manual_clock");
+        let time   = default();
+        Self {source,time}
+    }
+
+    /// The current time.
+    pub fn now(&self) -> Time {
+        self.time.get()
+    }
+
+    /// Set the current time and emit a tick.
+    pub fn set(&self, time:Time) {
+        self.time.set(time);
+        self.source.emit(time);
+    }
+
+    /// Move the time forward by `delta` milliseconds and emit a tick.
+    pub fn advance(&self, delta:Time) {
+        self.set(self.now() + delta)
+    }
+
+    /// The stream of ticks of this clock.
+    pub fn stream(&self) -> Stream<Time> {
+        (&self.source).into()
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HasOutput for ManualClock {
+    type Output = Time;
+}
+
+impl HasId for ManualClock {
+    fn id(&self) -> Id {
+        self.source.id()
+    }
+}
+
+impl EventEmitter for ManualClock {
+    fn emit_event(&self, stack:CallStack, value:&Time) {
+        self.source.emit_event(stack,value)
+    }
+
+    fn register_target(&self, target:EventInput<Time>) {
+        self.source.register_target(target)
+    }
+
+    fn unregister_target(&self, consumer:Id) {
+        self.source.unregister_target(consumer)
+    }
+
+    fn register_watch(&self) -> watch::Handle {
+        self.source.register_watch()
+    }
+}
+
+impl ValueProvider for ManualClock {
+    fn value(&self) -> Time {
+        self.now()
+    }
+}
+
+
+
+// =====================
+// === ClockConsumer ===
+// =====================
+
+/// Implementors of this trait consume clock ticks in addition to their regular input events.
+pub trait ClockConsumer {
+    /// Callback for a new clock tick.
+    fn on_tick(&self, stack:CallStack, time:Time);
+}
+
+/// Weak event target forwarding clock ticks to the `ClockConsumer` implementation of a node. It is
+/// needed because the node already consumes events of its regular input, which may be of the same
+/// type as the clock ticks.
+#[derive(Debug)]
+struct TickInput<Def:HasOutputStatic> {
+    node : stream::WeakNode<Def>,
+}
+
+impl<Def> stream::WeakEventConsumer<Time> for TickInput<Def>
+where Def:HasOutputStatic, stream::Node<Def>:ClockConsumer {
+    fn on_event_if_exists(&self, stack:CallStack, time:&Time) -> bool {
+        self.node.upgrade().map(|node| node.on_tick(stack,*time)).is_some()
+    }
+
+    fn consumer_id(&self) -> Option<Id> {
+        Some(self.node.id())
+    }
+}
+
+/// Register the node as a target of the clock ticks. The connection is weak, so it does not
+/// influence the lifetime of the node.
+pub fn connect<C,Def>(clock:&C, node:&stream::Node<Def>)
+where C:Clock, Def:HasOutputStatic, stream::Node<Def>:ClockConsumer {
+    let node = node.downgrade();
+    clock.register_target(EventInput::new(TickInput {node}));
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use crate as frp;
+    use crate::prelude::*;
+    use super::ManualClock;
+
+    fn recorder() -> Rc<RefCell<Vec<usize>>> {
+        default()
+    }
+
+    #[test]
+    fn delay() {
+        let clock    = ManualClock::new();
+        let received = recorder();
+        frp::new_network! { network
+            source  <- source::<usize>();
+            delayed <- source.delay(&clock,100.0);
+            eval delayed ((t) received.borrow_mut().push(*t));
+        }
+        source.emit(1_usize);
+        clock.advance(50.0);
+        source.emit(2_usize);
+        clock.advance(49.0);
+        assert_eq!(*received.borrow(),Vec::<usize>::new());
+        clock.advance(1.0);
+        assert_eq!(*received.borrow(),vec![1]);
+        clock.advance(100.0);
+        assert_eq!(*received.borrow(),vec![1,2]);
+    }
+
+    #[test]
+    fn debounce() {
+        let clock    = ManualClock::new();
+        let received = recorder();
+        frp::new_network! { network
+            source    <- source::<usize>();
+            debounced <- source.debounce(&clock,100.0);
+            eval debounced ((t) received.borrow_mut().push(*t));
+        }
+        source.emit(1_usize);
+        clock.advance(50.0);
+        source.emit(2_usize);
+        clock.advance(50.0);
+        source.emit(3_usize);
+        clock.advance(99.0);
+        assert_eq!(*received.borrow(),Vec::<usize>::new());
+        clock.advance(1.0);
+        assert_eq!(*received.borrow(),vec![3]);
+        clock.advance(1000.0);
+        assert_eq!(*received.borrow(),vec![3]);
+    }
+
+    #[test]
+    fn throttle() {
+        let clock    = ManualClock::new();
+        let received = recorder();
+        frp::new_network! { network
+            source    <- source::<usize>();
+            throttled <- source.throttle(&clock,100.0);
+            eval throttled ((t) received.borrow_mut().push(*t));
+        }
+        source.emit(1_usize);
+        assert_eq!(*received.borrow(),vec![1]);
+        clock.advance(10.0);
+        source.emit(2_usize);
+        source.emit(3_usize);
+        clock.advance(10.0);
+        assert_eq!(*received.borrow(),vec![1]);
+        clock.advance(80.0);
+        assert_eq!(*received.borrow(),vec![1,3]);
+        clock.advance(100.0);
+        source.emit(4_usize);
+        assert_eq!(*received.borrow(),vec![1,3,4]);
+    }
+
+    #[test]
+    fn sample_every() {
+        let clock    = ManualClock::new();
+        let received = recorder();
+        frp::new_network! { network
+            source  <- source::<usize>();
+            sampled <- source.sample_every(&clock,100.0);
+            eval sampled ((t) received.borrow_mut().push(*t));
+        }
+        source.emit(1_usize);
+        clock.set(0.0);
+        clock.advance(50.0);
+        assert_eq!(*received.borrow(),Vec::<usize>::new());
+        clock.advance(50.0);
+        assert_eq!(*received.borrow(),vec![1]);
+        source.emit(2_usize);
+        clock.advance(100.0);
+        assert_eq!(*received.borrow(),vec![1,2]);
+    }
+
+    #[test]
+    fn dropping_network_disconnects_clock() {
+        let clock    = ManualClock::new();
+        let received = recorder();
+        frp::new_network! { network
+            source  <- source::<usize>();
+            delayed <- source.delay(&clock,100.0);
+            eval delayed ((t) received.borrow_mut().push(*t));
+        }
+        source.emit(1_usize);
+        drop(network);
+        clock.advance(100.0);
+        assert_eq!(*received.borrow(),Vec::<usize>::new());
+    }
+}
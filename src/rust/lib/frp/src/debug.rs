@@ -1,142 +1,121 @@
-//! This module defines FRP Graphviz bindings. It allows visualizing the FRP network as Graphviz
-//! diagram.
+//! Introspection of live FRP networks. A network, together with all of its bridge networks, can be
+//! dumped as Graphviz Dot code or as a JSON graph. Every dumped node contains its label, its type,
+//! and its current output value if the node is used as a behavior.
 
-#![allow(missing_docs)]
+use crate::prelude::*;
 
-//! WARNING
-//! THIS MODULE IS IN A VERY WORK-IN-PROGRESS STATE. IT WILL BE CHANGED SOON.
+use crate::network::*;
 
+use serde::Serialize;
 
-use crate::prelude::*;
-//use crate::DataType;
-//
 
 
 // ================
-// === Graphviz ===
+// === NodeInfo ===
 // ================
 
-/// Visualization data for a nodes.
-#[derive(Debug,Clone)]
-pub struct VizNode {
-    variant    : String,
-    label      : String,
+/// Debug information about a single FRP node.
+#[derive(Clone,Debug,Serialize)]
+pub struct NodeInfo {
+    /// Unique identifier of the node.
+    pub id    : usize,
+    /// Label of the node. Nodes defined with the `frp::extend!` macro use their definition path and
+    /// line as the label.
+    pub label : String,
+    /// Type of the node, like `Map` or `Sampler`.
+    #[serde(rename="type")]
+    pub tp    : String,
+    /// Debug representation of the current output value. Available only for nodes used as
+    /// behaviors.
+    pub value : Option<String>,
 }
 
-impl VizNode {
-    /// Constructor
-    pub fn new(variant:String, label:String) -> Self {
-        VizNode {variant,label}
+impl NodeInfo {
+    /// Constructor.
+    pub fn new(node:&dyn Item) -> Self {
+        let id    = node.id().into();
+        let label = node.label().into();
+        let tp    = node.output_type_label();
+        let value = node.debug_value();
+        Self {id,label,tp,value}
+    }
+
+    /// The last segment of the label. The module path is skipped, as it is usually the same for
+    /// all nodes in the network.
+    pub fn short_label(&self) -> &str {
+        self.label.rsplit("::").next().unwrap_or(&self.label)
     }
 }
 
 
-/// Visualization data for a link between nodes.
-#[derive(Debug,Clone)]
-pub struct VizLink {
-    source_display_id : usize,
-    target_display_id : usize,
-//    message_type      : DataType,
-    data_type         : String,
-}
 
-impl VizLink {
-    /// Constructor.
-    pub fn new
-    (source_display_id:usize, target_display_id:usize, data_type:String)
-     -> Self {
-        Self {source_display_id,target_display_id,data_type}
-    }
+// ================
+// === LinkInfo ===
+// ================
+
+/// Debug information about a connection between two FRP nodes.
+#[derive(Clone,Copy,Debug,Serialize)]
+pub struct LinkInfo {
+    /// Identifier of the node emitting events or providing the value.
+    pub source : usize,
+    /// Identifier of the node receiving events or sampling the value.
+    pub target : usize,
+    /// Kind of the connection.
+    #[serde(rename="type")]
+    pub tp     : LinkType,
 }
 
 
-/// Graphviz FRP system visualizer.
-#[derive(Debug,Default)]
-pub struct Graphviz {
-    nodes  : HashMap<usize,VizNode>,
-    labels : HashMap<usize,String>,
-    links  : Vec<VizLink>,
+
+// ===================
+// === NetworkInfo ===
+// ===================
+
+/// Debug information about an FRP network and all of its bridge networks. Links may point to nodes
+/// of other networks, which are not a part of the dump. Such nodes are drawn as external ones.
+#[derive(Clone,Debug,Default,Serialize)]
+pub struct NetworkInfo {
+    /// Label of the network.
+    pub label   : String,
+    /// All nodes registered in the network.
+    pub nodes   : Vec<NodeInfo>,
+    /// All links targeting or originating in nodes of the network.
+    pub links   : Vec<LinkInfo>,
+    /// Bridge networks registered in this network.
+    pub bridges : Vec<NetworkInfo>,
 }
 
-impl Graphviz {
-    /// Defines a new node.
-    pub fn add_node<Tp:Str,Label:Str>
-    (&mut self, id:usize, tp:Tp, label:Label) {
-        let tp    = tp.into();
-        let label = label.into();
-        self.nodes.insert(id,VizNode::new(tp,label.clone()));
-        self.labels.insert(id,label);
+impl NetworkInfo {
+    /// Serialize the dump to a pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Serialization of network dump cannot fail.")
     }
-//
-//    /// Defines a new link between nodes.
-//    pub fn add_link<S:Str>
-//    (&mut self, source:usize, target:usize, message_type:DataType, data_type:S) {
-//        let link = VizLink::new(source,target,message_type,data_type.into());
-//        self.links.push(link);
-//    }
-//
-//    /// Checks if a node with the given id is already registered in the node map.
-//    pub fn contains(&mut self, id:usize) -> bool {
-//        self.nodes.contains_key(&id)
-//    }
-//
-//    /// Takes a set of nodes and outputs a map from `display_id` to a particular node. In case the
-//    /// `display_id` points to several nodes, the node types `Hold` and `Recursive` has weaker
-//    /// preference.
-//    fn create_node_map(&self) -> HashMap<usize,VizNode> {
-//        let mut node_map : HashMap<usize,VizNode> = default();
-//        for node in self.nodes.values() {
-//            let entry        = node_map.entry(node.display_id);
-//            let merged_entry = entry.and_modify(|node2|{
-//                let variant = &node2.variant;
-//                if variant == "Hold" || variant == "Recursive" {
-//                    *node2 = node.clone();
-//                }
-//            });
-//            merged_entry.or_insert_with(|| node.clone());
-//        }
-//        node_map
-//    }
-//
-//    /// Outputs a Graphviz Dot code.
-    pub fn to_code(&self) -> String {
-        let mut code = String::default();
-//        let node_map = self.create_node_map();
-//
-        for (idx,node) in self.nodes.iter() {
-            let color = match node.variant.as_str() {
-                "Toggle"  => "534666",
-                "Gate"    => "e69d45",
-                "Hold"    => "308695",
-                "Lambda"  => "d45769",
-                "Lambda2" => "d45769",
-                _         => "455054",
+
+    /// Generate Graphviz Dot code. Every network is drawn as a separate cluster. Event links are
+    /// drawn with solid lines, behavior links with dashed lines, and links which are both event and
+    /// behavior links with bold lines.
+    pub fn to_graphviz(&self) -> String {
+        let mut code       = String::new();
+        let mut known      = HashSet::new();
+        let mut cluster_id = 0;
+        self.write_cluster(&mut code,&mut known,&mut cluster_id);
+        let mut links = Vec::new();
+        self.collect_links(&mut links);
+        for link in &links {
+            for id in &[link.source,link.target] {
+                if known.insert(*id) {
+                    code.push_str(&iformat!("\nn{id} {EXTERNAL_NODE_STYLE}"));
+                }
+            }
+        }
+        for link in &links {
+            let style = match link.tp {
+                LinkType::Event    => "",
+                LinkType::Behavior => " [style=\"dashed\"]",
+                LinkType::Mixed    => " [style=\"bold\"]",
             };
-            let fill    = iformat!("[fillcolor=\"#{color}\"]");
-            let spacing = "<br/><FONT POINT-SIZE=\"5\"> </FONT><br/>";
-            let variant = iformat!("<FONT POINT-SIZE=\"9\">{node.variant}</FONT>");
-            let label   = iformat!("[label=< {node.label} {spacing} {variant} >]");
-            let line    = iformat!("\n{idx} {fill} {label}");
-            code.push_str(&line);
+            code.push_str(&iformat!("\nn{link.source} -> n{link.target}{style}"));
         }
-//
-//        for link in &self.links {
-//            let source    = &link.source_display_id;
-//            let target    = &link.target_display_id;
-//            let data_type = &link.data_type;
-//            let not_loop  = source != target;
-//            if not_loop {
-//                let style = match link.message_type {
-//                    DataType::Behavior => "[style=\"dashed\"]",
-//                    _ => ""
-//                };
-//                let label = if data_type == "()" { "" } else { &data_type };
-//                let label = iformat!("[label=\"  {label}\"]");
-//                let line  = iformat!("\n{source} -> {target} {style} {label}");
-//                code.push_str(&line);
-//            }
-//        }
-//
         let fonts      = "[fontname=\"Helvetica Neue\" fontsize=11]";
         let node_shape = "[shape=box penwidth=0 margin=0.12 style=\"rounded,filled\"]";
         let node_style = "[fontcolor=white fillcolor=\"#5397dc\"]";
@@ -145,54 +124,155 @@ impl Graphviz {
         let nodes_cfg  = iformat!("node {fonts} {node_shape} {node_style};");
         let edges_cfg  = iformat!("edge {fonts} {edge_style};");
         iformat!("digraph G {{ \n{graph_cfg} \n{nodes_cfg} \n{edges_cfg} \n{code} \n}}")
-//        todo!()
     }
-}
 
-impl From<Graphviz> for String {
-    fn from(cfg:Graphviz) -> String {
-        cfg.to_code()
+    fn write_cluster(&self, code:&mut String, known:&mut HashSet<usize>, cluster_id:&mut usize) {
+        let label = escape_html(&self.label);
+        code.push_str(&iformat!("\nsubgraph cluster_{cluster_id} {{ label=<{label}>;"));
+        *cluster_id += 1;
+        for node in &self.nodes {
+            if known.insert(node.id) {
+                let color   = node_color(&node.tp);
+                let label   = escape_html(node.short_label());
+                let tp      = escape_html(&node.tp);
+                let tp      = iformat!("<br/><FONT POINT-SIZE=\"9\">{tp}</FONT>");
+                let value   = node.value.as_ref().map(|value| {
+                    let value = escape_html(&truncate(value,MAX_VALUE_LENGTH));
+                    iformat!("<br/><FONT POINT-SIZE=\"9\">= {value}</FONT>")
+                }).unwrap_or_default();
+                let fill    = iformat!("[fillcolor=\"#{color}\"]");
+                let label   = iformat!("[label=< {label} {tp} {value} >]");
+                code.push_str(&iformat!("\nn{node.id} {fill} {label}"));
+            }
+        }
+        for bridge in &self.bridges {
+            bridge.write_cluster(code,known,cluster_id);
+        }
+        code.push_str("\n}");
+    }
+
+    fn collect_links(&self, links:&mut Vec<LinkInfo>) {
+        links.extend(self.links.iter().copied());
+        for bridge in &self.bridges {
+            bridge.collect_links(links);
+        }
     }
 }
 
 
+// === Utils ===
 
-// =======================
-// === GraphvizBuilder ===
-// =======================
+/// Values longer than this are truncated in the Graphviz output.
+const MAX_VALUE_LENGTH : usize = 40;
 
-/// Trait for every node which can be visualized.
-pub trait GraphvizBuilder {
-    /// Adds the current object to the builder.
-    fn graphviz_build(&self, builder:&mut Graphviz);
+/// Style of nodes which are not a part of the dump, but are connected to its nodes.
+const EXTERNAL_NODE_STYLE : &str =
+    "[label=\"external\" style=\"rounded,dashed\" fontcolor=\"#455054\"]";
 
-    /// Converts the current object to Graphviz Dot syntax.
-    fn to_graphviz(&self) -> String {
-        let mut builder = Graphviz::default();
-        self.graphviz_build(&mut builder);
-        builder.into()
+fn node_color(tp:&str) -> &'static str {
+    match tp {
+        "Source"  => "308695",
+        "Sampler" => "534666",
+        "Toggle"  => "534666",
+        "Gate"    => "e69d45",
+        "GateNot" => "e69d45",
+        "Map"     => "d45769",
+        "Map2"    => "d45769",
+        "Map3"    => "d45769",
+        "Map4"    => "d45769",
+        _         => "455054",
     }
+}
 
-    /// Converts the current object to Graphviz and displays it in a new tab in a web browser.
-    fn display_graphviz(&self) {
-        let code = self.to_graphviz();
-        let url  = percent_encoding::utf8_percent_encode(&code,percent_encoding::NON_ALPHANUMERIC);
-        let url  = format!("https://dreampuf.github.io/GraphvizOnline/#{}",url);
-        crate::web::window().open_with_url_and_target(&url,"_blank").unwrap();
+fn truncate(text:&str, max_length:usize) -> String {
+    if text.chars().count() <= max_length {
+        text.into()
+    } else {
+        text.chars().take(max_length).collect::<String>() + "…"
     }
 }
 
-pub fn display_graphviz(viz:Graphviz) {
-    let code : String = viz.into();
-    let url  = percent_encoding::utf8_percent_encode(&code,percent_encoding::NON_ALPHANUMERIC);
-    let url  = format!("https://dreampuf.github.io/GraphvizOnline/#{}",url);
+fn escape_html(text:&str) -> String {
+    text.replace('&',"&amp;").replace('<',"&lt;").replace('>',"&gt;").replace('"',"&quot;")
+}
+
+
+
+// ===============
+// === Display ===
+// ===============
+
+/// Open the provided Graphviz Dot code in a new tab in a web browser.
+pub fn display_graphviz(code:&str) {
+    let url = percent_encoding::utf8_percent_encode(code,percent_encoding::NON_ALPHANUMERIC);
+    let url = format!("https://dreampuf.github.io/GraphvizOnline/#{}",url);
     crate::web::window().open_with_url_and_target(&url,"_blank").unwrap();
 }
 
 
-impl<T> GraphvizBuilder for T
-where T:ContentRef, Content<T>:GraphvizBuilder {
-    default fn graphviz_build(&self, builder:&mut Graphviz) {
-        self.content().graphviz_build(builder)
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use crate as frp;
+
+    #[test]
+    fn network_dump() {
+        frp::new_network! { network
+            def source   = source::<usize>();
+            def behavior = source::<bool>();
+            def gated    = source.gate(&behavior);
+            def count    = gated.count();
+        }
+        behavior.emit(true);
+        let dump = network.introspect();
+        assert_eq!(dump.label,"network");
+        assert_eq!(dump.nodes.len(),4);
+        let node_with_label = |name:&str| {
+            dump.nodes.iter().find(|node| node.short_label().starts_with(name)).unwrap()
+        };
+        let source   = node_with_label("source:");
+        let behavior = node_with_label("behavior:");
+        let gated    = node_with_label("gated:");
+        let count    = node_with_label("count:");
+        assert_eq!(source.tp,"Source");
+        assert_eq!(gated.tp,"Gate");
+        assert_eq!(behavior.value.as_deref(),Some("true"));
+        assert_eq!(count.value,None);
+        let link = |src:usize, tgt:usize| {
+            dump.links.iter().find(|l| l.source == src && l.target == tgt).map(|l| l.tp)
+        };
+        assert_eq!(link(source.id,gated.id),Some(frp::LinkType::Event));
+        assert_eq!(link(behavior.id,gated.id),Some(frp::LinkType::Behavior));
+        assert_eq!(link(gated.id,count.id),Some(frp::LinkType::Event));
+
+        let dot = network.to_graphviz();
+        assert!(dot.starts_with("digraph G {"));
+        assert!(dot.contains(&format!("n{} -> n{} [style=\"dashed\"]",behavior.id,gated.id)));
+        let json : serde_json::Value = serde_json::from_str(&network.to_json()).unwrap();
+        assert_eq!(json["nodes"].as_array().map(|nodes| nodes.len()),Some(4));
+    }
+
+    #[test]
+    fn bridge_network_dump() {
+        frp::new_network! { network1
+            def source = source::<()>();
+        }
+        frp::new_network! { network2
+            def count = source.count();
+        }
+        frp::new_bridge_network! { [network1,network2] bridge
+            _any <- any_(source,count);
+        }
+        let dump = network1.introspect();
+        assert_eq!(dump.nodes.len(),1);
+        assert_eq!(dump.links.len(),2);
+        assert_eq!(dump.bridges.len(),1);
+        assert_eq!(dump.bridges[0].label,"bridge");
+        assert_eq!(dump.bridges[0].nodes.len(),1);
+        assert!(network1.to_graphviz().contains("external"));
     }
 }
@@ -0,0 +1,299 @@
+//! Bridge between FRP streams and asynchronous code.
+//!
+//! FRP events can be consumed as a `futures::Stream` (see `to_stream`) or awaited one by one (see
+//! `next_event`). In the other direction, items of a `futures::Stream` or the result of a `Future`
+//! can be emitted by an FRP `Source` (see `forward` and `forward_future`).
+//!
+//! The bridge never extends the lifetime of FRP nodes. When the FRP node is dropped (for example,
+//! because its network was dropped), the `futures::Stream` created from it ends, and all `Forward`
+//! futures emitting to it finish without polling their input anymore. Dropping the
+//! `futures::Stream` disconnects it from the FRP node.
+
+use crate::prelude::*;
+
+use crate::node::*;
+use crate::nodes::Source;
+use crate::stream::CallStack;
+use crate::stream::EventInput;
+use crate::stream::EventOutput;
+use crate::stream::WeakEventConsumer;
+
+use futures::FutureExt;
+use futures::StreamExt;
+use futures::stream::LocalBoxStream;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+
+
+
+// ===============
+// === Channel ===
+// ===============
+
+/// State shared between an FRP node and the asynchronous task observing it.
+#[derive(Debug)]
+struct Channel<T> {
+    queue  : RefCell<VecDeque<T>>,
+    waker  : RefCell<Option<Waker>>,
+    closed : Cell<bool>,
+}
+
+impl<T> Channel<T> {
+    fn new() -> Self {
+        let queue  = default();
+        let waker  = default();
+        let closed = default();
+        Self {queue,waker,closed}
+    }
+
+    fn register_waker(&self, waker:&Waker) {
+        *self.waker.borrow_mut() = Some(waker.clone());
+    }
+
+    fn wake(&self) {
+        if let Some(waker) = self.waker.borrow_mut().take() {
+            waker.wake()
+        }
+    }
+
+    fn close(&self) {
+        self.closed.set(true);
+        self.wake();
+    }
+}
+
+/// Connect a new channel to the FRP node. If `buffer` is set, all events are stored in the channel
+/// queue, otherwise the channel is used only to observe the lifetime of the node.
+fn connect<T:EventOutput>(src:&T, buffer:bool) -> Rc<Channel<Output<T>>> {
+    let channel = Rc::new(Channel::new());
+    let input   = ChannelInput {channel:Rc::downgrade(&channel),buffer};
+    src.register_target(EventInput::new(input));
+    channel
+}
+
+
+// === ChannelInput ===
+
+/// Weak connection between an FRP node and a channel. It is owned by the node, so it is dropped
+/// together with the node, which closes the channel.
+#[derive(Debug)]
+struct ChannelInput<T> {
+    channel : Weak<Channel<T>>,
+    buffer  : bool,
+}
+
+impl<T:Clone> WeakEventConsumer<T> for ChannelInput<T> {
+    fn on_event_if_exists(&self, _stack:CallStack, value:&T) -> bool {
+        self.channel.upgrade().map(|channel| {
+            if self.buffer {
+                channel.queue.borrow_mut().push_back(value.clone());
+                channel.wake();
+            }
+        }).is_some()
+    }
+}
+
+impl<T> Drop for ChannelInput<T> {
+    fn drop(&mut self) {
+        if let Some(channel) = self.channel.upgrade() {
+            channel.close()
+        }
+    }
+}
+
+
+
+// ===================
+// === EventStream ===
+// ===================
+
+/// A `futures::Stream` of events emitted by an FRP node. Events are buffered until polled. The
+/// stream ends after all buffered events were consumed and the FRP node was dropped.
+#[derive(Debug)]
+pub struct EventStream<T> {
+    channel : Rc<Channel<T>>,
+}
+
+impl<T> futures::Stream for EventStream<T> {
+    type Item = T;
+
+    fn poll_next(self:Pin<&mut Self>, cx:&mut Context<'_>) -> Poll<Option<T>> {
+        let channel = &self.channel;
+        match channel.queue.borrow_mut().pop_front() {
+            Some(value)                  => Poll::Ready(Some(value)),
+            None if channel.closed.get() => Poll::Ready(None),
+            None                         => {
+                channel.register_waker(cx.waker());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Create a `futures::Stream` of all events emitted by the FRP node from now on.
+pub fn to_stream<T:EventOutput>(src:&T) -> EventStream<Output<T>> {
+    let channel = connect(src,true);
+    EventStream {channel}
+}
+
+/// Create a future resolving to the next event emitted by the FRP node, or to `None` if the node
+/// was dropped before emitting any event. The node is observed from the moment this function is
+/// called, not from the first poll of the future.
+pub fn next_event<T:EventOutput>(src:&T) -> impl Future<Output=Option<Output<T>>> {
+    let mut stream = to_stream(src);
+    async move { stream.next().await }
+}
+
+
+
+// ===============
+// === Forward ===
+// ===============
+
+/// A future emitting all items of a `futures::Stream` by an FRP source. It finishes when the
+/// stream ends or when the source is dropped, whichever happens first. The future has to be
+/// spawned on an executor to make progress.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct Forward<T> {
+    source  : Source<T>,
+    #[derivative(Debug="ignore")]
+    stream  : LocalBoxStream<'static,T>,
+    channel : Rc<Channel<T>>,
+}
+
+impl<T:Data> Future for Forward<T> {
+    type Output = ();
+
+    fn poll(mut self:Pin<&mut Self>, cx:&mut Context<'_>) -> Poll<()> {
+        self.channel.register_waker(cx.waker());
+        loop {
+            if self.channel.closed.get() {
+                return Poll::Ready(())
+            }
+            match self.stream.poll_next_unpin(cx) {
+                Poll::Ready(Some(value)) => self.source.emit(value),
+                Poll::Ready(None)        => return Poll::Ready(()),
+                Poll::Pending            => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Create a future emitting all items of the stream by the FRP source. See `Forward` to learn
+/// more.
+pub fn forward<T,S>(source:&Source<T>, stream:S) -> Forward<T>
+where T:Data, S:'static+futures::Stream<Item=T> {
+    let channel = connect(source,false);
+    let source  = source.clone_ref();
+    let stream  = stream.boxed_local();
+    Forward {source,stream,channel}
+}
+
+/// Create a future emitting the result of the provided future by the FRP source. Nothing is emitted
+/// if the source is dropped before the future resolves.
+pub fn forward_future<T,F>(source:&Source<T>, future:F) -> Forward<T>
+where T:Data, F:'static+Future<Output=T> {
+    forward(source,future.into_stream())
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use crate as frp;
+    use super::*;
+
+    use futures::channel::mpsc;
+    use futures::channel::oneshot;
+    use futures::executor::LocalPool;
+    use futures::task::LocalSpawnExt;
+
+    #[test]
+    fn frp_to_stream() {
+        frp::new_network! { network
+            def source  = source::<usize>();
+            def doubled = source.map(|t| t * 2);
+        }
+        let mut stream = to_stream(&doubled);
+        let mut pool   = LocalPool::new();
+        source.emit(1_usize);
+        source.emit(2_usize);
+        assert_eq!(pool.run_until(stream.next()),Some(2));
+        assert_eq!(pool.run_until(stream.next()),Some(4));
+        source.emit(3_usize);
+        drop(network);
+        assert_eq!(pool.run_until(stream.next()),Some(6));
+        assert_eq!(pool.run_until(stream.next()),None);
+    }
+
+    #[test]
+    fn awaiting_next_event() {
+        frp::new_network! { network
+            def source = source::<usize>();
+        }
+        let mut pool     = LocalPool::new();
+        let received     = Rc::new(Cell::new(None));
+        let next         = next_event(&source);
+        let received_ref = received.clone_ref();
+        pool.spawner().spawn_local(async move { received_ref.set(next.await) }).unwrap();
+        pool.run_until_stalled();
+        assert_eq!(received.get(),None);
+        source.emit(5_usize);
+        pool.run_until_stalled();
+        assert_eq!(received.get(),Some(Some(5)));
+        let next = next_event(&source);
+        drop(network);
+        assert_eq!(pool.run_until(next),None);
+    }
+
+    #[test]
+    fn stream_to_frp() {
+        let received = Rc::new(RefCell::new(vec![]));
+        frp::new_network! { network
+            def source = source::<usize>();
+            eval source ((t) received.borrow_mut().push(*t));
+        }
+        let mut pool           = LocalPool::new();
+        let (sender,receiver)  = mpsc::unbounded();
+        let finished           = Rc::new(Cell::new(false));
+        let finished_ref       = finished.clone_ref();
+        let forward            = forward(&source,receiver);
+        pool.spawner().spawn_local(forward.map(move |_| finished_ref.set(true))).unwrap();
+        sender.unbounded_send(1).unwrap();
+        sender.unbounded_send(2).unwrap();
+        pool.run_until_stalled();
+        assert_eq!(*received.borrow(),vec![1,2]);
+        drop(network);
+        pool.run_until_stalled();
+        assert!(finished.get());
+        assert!(sender.unbounded_send(3).is_err());
+    }
+
+    #[test]
+    fn future_to_frp() {
+        let received = Rc::new(RefCell::new(vec![]));
+        frp::new_network! { network
+            def source = source::<usize>();
+            eval source ((t) received.borrow_mut().push(*t));
+        }
+        let mut pool           = LocalPool::new();
+        let (sender,receiver)  = oneshot::channel::<usize>();
+        let forward            = forward_future(&source,receiver.map(|t| t.unwrap_or_default()));
+        pool.spawner().spawn_local(forward).unwrap();
+        pool.run_until_stalled();
+        assert_eq!(*received.borrow(),Vec::<usize>::new());
+        sender.send(7).unwrap();
+        pool.run_until_stalled();
+        assert_eq!(*received.borrow(),vec![7]);
+        drop(network);
+    }
+}
@@ -156,19 +156,26 @@
 
 #![feature(associated_type_defaults)]
 #![feature(specialization)]
+#![feature(test)]
 #![feature(trait_alias)]
 #![feature(unboxed_closures)]
 
 #![recursion_limit="512"]
 
+extern crate test;
+
+pub mod clock;
 pub mod debug;
 pub mod data;
+pub mod future;
 pub mod io;
 pub mod macros;
 pub mod network;
 pub mod node;
 pub mod nodes;
 pub mod stream;
+pub mod tracing;
+pub mod transaction;
 
 pub use network::*;
 pub use node::*;
@@ -210,6 +217,192 @@ mod network_mode_tests {
         source.emit(());
         assert_eq!(sampler.value(),2);
     }
+
+    #[test]
+    fn switch_latest() {
+        frp::new_network! { network1
+            def inner1 = source::<usize>();
+        }
+        frp::new_network! { network2
+            def inner2 = source::<usize>();
+        }
+        frp::new_network! { network
+            def outer   = source::<frp::Stream<usize>>();
+            def latest  = outer.switch_latest();
+            def sampler = latest.sampler();
+            def count   = latest.count();
+            def count   = count.sampler();
+        }
+        inner1.emit(1_usize);
+        assert_eq!(count.value(),0);
+        outer.emit(frp::Stream::from(&inner1));
+        inner1.emit(2_usize);
+        assert_eq!(sampler.value(),2);
+        assert_eq!(network1.introspect().links.len(),1);
+        outer.emit(frp::Stream::from(&inner2));
+        assert_eq!(network1.introspect().links.len(),0);
+        inner1.emit(3_usize);
+        assert_eq!(sampler.value(),2);
+        inner2.emit(4_usize);
+        assert_eq!(sampler.value(),4);
+        assert_eq!(count.value(),2);
+        drop(network2);
+        inner2.emit(5_usize);
+        assert_eq!(count.value(),2);
+        drop(network1);
+        drop(network);
+    }
+
+    #[test]
+    fn flat_map_of_streams_owned_by_other_networks() {
+        use frp::prelude::*;
+        let received = Rc::new(RefCell::new(vec![]));
+        frp::new_network! { inner_network
+            def inner1 = source::<usize>();
+            def inner2 = source::<usize>();
+        }
+        let inners = vec![frp::Stream::from(&inner1),frp::Stream::from(&inner2)];
+        frp::new_network! { network
+            def select = source::<usize>();
+            def latest = select.flat_map(move |index| inners[*index].clone_ref());
+            eval latest ((t) received.borrow_mut().push(*t));
+        }
+        select.emit(0);
+        inner1.emit(1);
+        select.emit(1);
+        inner1.emit(2);
+        inner2.emit(3);
+        assert_eq!(*received.borrow(),vec![1,3]);
+
+        // The streams of a dropped network never emit, and switching to them is safe.
+        drop(inner_network);
+        inner2.emit(4);
+        select.emit(0);
+        inner1.emit(5);
+        assert_eq!(*received.borrow(),vec![1,3]);
+
+        frp::new_network! { inner_network
+            def inner3 = source::<usize>();
+        }
+        let inner3_stream = frp::Stream::from(&inner3);
+        frp::extend! { network
+            def select3 = source::<()>();
+            def latest3 = select3.flat_map(move |_| inner3_stream.clone_ref());
+            eval latest3 ((t) received.borrow_mut().push(*t));
+        }
+        select3.emit(());
+        inner3.emit(6);
+        assert_eq!(*received.borrow(),vec![1,3,6]);
+
+        // When the network of the `flat_map` node is dropped, the inner stream keeps working, but
+        // its events are not passed anywhere.
+        drop(network);
+        inner3.emit(7);
+        assert_eq!(*received.borrow(),vec![1,3,6]);
+        drop(inner_network);
+    }
+
+    #[test]
+    fn flat_map_network() {
+        use frp::prelude::*;
+        let received   = Rc::new(RefCell::new(vec![]));
+        let inners     = Rc::new(RefCell::new(vec![]));
+        let inners_ref = inners.clone_ref();
+        let make_inner = move |_:&usize| {
+            let bridge = frp::BridgeNetwork::new("inner");
+            let inner  = bridge.network().unwrap();
+            let source = inner.source::<usize>("source");
+            inners_ref.borrow_mut().push((bridge.clone_ref(),source.clone_ref()));
+            (bridge,frp::Stream::from(&source))
+        };
+        frp::new_network! { network
+            def select = source::<usize>();
+            def latest = select.flat_map_network(make_inner);
+            eval latest ((t) received.borrow_mut().push(*t));
+        }
+        select.emit(0);
+        let (bridge1,inner1) = inners.borrow()[0].clone();
+        inner1.emit(1);
+        assert_eq!(*received.borrow(),vec![1]);
+
+        select.emit(1);
+        let (bridge2,inner2) = inners.borrow()[1].clone();
+        assert!(bridge1.network().is_none());
+        assert!(inner1.upgrade().is_none());
+        inner2.emit(2);
+        assert_eq!(*received.borrow(),vec![1,2]);
+
+        drop(network);
+        assert!(bridge2.network().is_none());
+        assert!(inner2.upgrade().is_none());
+    }
+
+    #[test]
+    fn keyed_collection() {
+        use frp::prelude::*;
+        let built      = Rc::new(Cell::new(0));
+        let received   = Rc::new(RefCell::new(vec![]));
+        let built_ref  = built.clone_ref();
+        let make_child = move |network:&frp::Network, key:&usize, item:&frp::Stream<usize>| {
+            built_ref.set(built_ref.get() + 1);
+            let key = *key;
+            network.map("child",item,move |t| key * 100 + *t)
+        };
+        frp::new_network! { network
+            def items = source::<Vec<(usize,usize)>>();
+            def out   = items.keyed_collection(make_child);
+            eval out ((t) received.borrow_mut().push(*t));
+        }
+        items.emit(vec![(1,1),(2,2)]);
+        assert_eq!(built.get(),2);
+        assert_eq!(*received.borrow(),vec![(1,101),(2,202)]);
+        received.borrow_mut().clear();
+        items.emit(vec![(1,1),(2,3),(3,3)]);
+        assert_eq!(built.get(),3);
+        assert_eq!(*received.borrow(),vec![(2,203),(3,303)]);
+        received.borrow_mut().clear();
+        items.emit(vec![(3,4)]);
+        items.emit(vec![(1,1),(3,4)]);
+        assert_eq!(built.get(),4);
+        assert_eq!(*received.borrow(),vec![(3,304),(1,101)]);
+    }
+
+    #[test]
+    fn keyed_collection_child_networks() {
+        use frp::prelude::*;
+        use frp::transaction::Propagation;
+        let children     = Rc::new(RefCell::new(vec![]));
+        let children_ref = children.clone_ref();
+        let make_child   = move |network:&frp::Network, _:&usize, item:&frp::Stream<usize>| {
+            children_ref.borrow_mut().push(network.downgrade());
+            network.map("child",item,|t| *t)
+        };
+        frp::new_network! { network
+            def items = source::<Vec<(usize,usize)>>();
+            def _out  = items.keyed_collection(make_child);
+        }
+        let tracer = network.enable_tracing(100);
+        items.emit(vec![(1,1)]);
+        let child = children.borrow()[0].upgrade().unwrap();
+        assert!(child.tracer().is_some());
+        assert!(tracer.trace().events.iter().any(|event| event.label == "child"));
+        assert_eq!(network.introspect().bridges.len(),1);
+
+        network.set_propagation(Propagation::Transactional);
+        assert_eq!(child.propagation(),Propagation::Transactional);
+        network.set_propagation(Propagation::Push);
+        network.disable_tracing();
+        assert!(child.tracer().is_none());
+        drop(child);
+
+        items.emit(vec![(2,2)]);
+        assert!(children.borrow()[0].upgrade().is_none());
+        assert_eq!(network.introspect().bridges.len(),1);
+        let child = children.borrow()[1].clone_ref();
+        assert!(child.upgrade().is_some());
+        drop(network);
+        assert!(child.upgrade().is_none());
+    }
 }
 
 #[cfg(test)]
@@ -5,6 +5,12 @@ use crate::stream;
 use crate::stream::Stream;
 use crate::node::*;
 use crate::debug;
+use crate::stream::Schedulable;
+use crate::stream::Traceable;
+use crate::tracing::Tracer;
+use crate::transaction::Propagation;
+
+use serde::Serialize;
 
 
 
@@ -39,8 +45,12 @@ pub struct WeakNetwork {
 }
 
 /// Network item.
-pub trait Item : HasId + HasLabel + stream::HasOutputTypeLabel {}
-impl<T> Item for T where T : HasId + HasLabel + stream::HasOutputTypeLabel {}
+pub trait Item
+    : HasId + HasLabel + stream::HasOutputTypeLabel + stream::InputBehaviors
+    + stream::Introspection + stream::Traceable + stream::Schedulable {}
+impl<T> Item for T
+where T : HasId + HasLabel + stream::HasOutputTypeLabel + stream::InputBehaviors
+        + stream::Introspection + stream::Traceable + stream::Schedulable {}
 
 /// Internal data of `Network`.
 #[derive(Derivative)]
@@ -53,7 +63,9 @@ pub struct NetworkData {
     links   : RefCell<HashMap<Id,Link>>,
     bridges : RefCell<Vec<BridgeNetwork>>,
     /// Used as a convenient storage of data associated with network, like animation instances.
-    storage : RefCell<Vec<Box<dyn Any>>>,
+    storage     : RefCell<Vec<Box<dyn Any>>>,
+    tracer      : RefCell<Option<Tracer>>,
+    propagation : Cell<Propagation>,
 }
 
 
@@ -62,12 +74,14 @@ pub struct NetworkData {
 impl NetworkData {
     /// Constructor.
     pub fn new(label:impl Into<String>) -> Self {
-        let label   = label.into();
-        let nodes   = default();
-        let links   = default();
-        let bridges = default();
-        let storage = default();
-        Self {label,nodes,links,bridges,storage}
+        let label       = label.into();
+        let nodes       = default();
+        let links       = default();
+        let bridges     = default();
+        let storage     = default();
+        let tracer      = default();
+        let propagation = default();
+        Self {label,nodes,links,bridges,storage,tracer,propagation}
     }
 }
 
@@ -104,6 +118,8 @@ impl Network {
     /// Register the node and return it's weak reference.
     pub fn register_raw<T:HasOutputStatic>(&self, node:stream::Node<T>) -> stream::WeakNode<T> {
         let weak = node.downgrade();
+        node.set_tracer(self.tracer());
+        node.set_propagation(self.propagation());
         let node = Box::new(node);
         self.data.nodes.borrow_mut().push(node);
         weak
@@ -112,6 +128,8 @@ impl Network {
     /// Register the node and return a new `Stream` reference.
     pub fn register<Def:HasOutputStatic>(&self, node:stream::Node<Def>) -> Stream<Output<Def>> {
         let stream = node.clone_ref().into();
+        node.set_tracer(self.tracer());
+        node.set_propagation(self.propagation());
         let node   = Box::new(node);
         self.data.nodes.borrow_mut().push(node);
         stream
@@ -122,18 +140,118 @@ impl Network {
         self.data.links.borrow_mut().insert(target,link);
     }
 
-    /// Registers the provided bridge network as child of this network.
+    /// Registers the provided bridge network as child of this network. The bridges which were
+    /// already destroyed are forgotten.
     pub fn register_bridge_network(&self, sub_network:&BridgeNetwork) {
-        self.data.bridges.borrow_mut().push(sub_network.clone_ref())
+        let mut bridges = self.data.bridges.borrow_mut();
+        bridges.retain(|bridge| bridge.network().is_some());
+        bridges.push(sub_network.clone_ref())
+    }
+
+    /// Creates a new network, registered as a bridge network of this one. It inherits the tracer
+    /// and the propagation mode of this network. It is destroyed together with this network, or
+    /// earlier, by `BridgeNetwork::destroy`.
+    pub fn new_child_network(&self, label:impl Into<String>) -> BridgeNetwork {
+        let network = Network::new(label);
+        network.set_tracer(self.tracer());
+        network.set_propagation(self.propagation());
+        let bridge  = BridgeNetwork::from(network);
+        self.register_bridge_network(&bridge);
+        bridge
+    }
+
+    /// Collect the debug information about this network and all of its bridge networks, including
+    /// the current values of behaviors. Consists of all nodes and all links originating in the
+    /// nodes of this network or targeting its nodes as behaviors.
+    pub fn introspect(&self) -> debug::NetworkInfo {
+        let label = self.data.label.clone();
+        let nodes = self.data.nodes.borrow();
+        let mut links : HashMap<(Id,Id),LinkType> = default();
+        let mut add_link = |source:Id, target:Id, tp:LinkType| {
+            links.entry((source,target)).and_modify(|t| *t = t.merge(tp)).or_insert(tp);
+        };
+        for node in nodes.iter() {
+            let id = node.id();
+            node.event_targets().into_iter().for_each(|tgt| add_link(id,tgt,LinkType::Event));
+            node.input_behaviors().into_iter().for_each(|link| add_link(link.source,id,link.tp));
+        }
+        for (target,link) in self.data.links.borrow().iter() {
+            add_link(link.source,*target,link.tp);
+        }
+        let mut links = links.into_iter().map(|((source,target),tp)| {
+            debug::LinkInfo {source:source.into(),target:target.into(),tp}
+        }).collect_vec();
+        links.sort_by_key(|link| (link.source,link.target));
+        let bridges = self.data.bridges.borrow().iter().filter_map(|t| t.introspect()).collect();
+        let nodes   = nodes.iter().map(|node| debug::NodeInfo::new(node.as_ref())).collect();
+        debug::NetworkInfo {label,nodes,links,bridges}
+    }
+
+    /// Dump the network as Graphviz Dot code. See `introspect` to learn more.
+    pub fn to_graphviz(&self) -> String {
+        self.introspect().to_graphviz()
     }
 
-    /// Draw the network using GraphViz.
+    /// Dump the network as a JSON graph. See `introspect` to learn more.
+    pub fn to_json(&self) -> String {
+        self.introspect().to_json()
+    }
+
+    /// Draw the network using GraphViz and display it in a new browser tab.
     pub fn draw(&self) {
-        let mut viz = debug::Graphviz::default();
-        self.data.nodes.borrow().iter().for_each(|node| {
-            viz.add_node(node.id().into(),node.output_type_label(),node.label());
-        });
-        debug::display_graphviz(viz);
+        debug::display_graphviz(&self.to_graphviz());
+    }
+
+    /// Start recording all events passing through the nodes of this network and its bridge
+    /// networks, including nodes registered later. Only the last `capacity` events are kept. If
+    /// tracing was already enabled, the previous tracer is replaced. See the `tracing` module to
+    /// learn more.
+    pub fn enable_tracing(&self, capacity:usize) -> Tracer {
+        let tracer = Tracer::new(capacity);
+        self.set_tracer(Some(tracer.clone_ref()));
+        tracer
+    }
+
+    /// Stop recording events. The events recorded so far are still available in the tracer
+    /// returned by `enable_tracing`.
+    pub fn disable_tracing(&self) {
+        self.set_tracer(None)
+    }
+
+    /// The tracer recording events of this network, if tracing is enabled.
+    pub fn tracer(&self) -> Option<Tracer> {
+        self.data.tracer.borrow().as_ref().map(|tracer| tracer.clone_ref())
+    }
+
+    /// Set the mode of event propagation of all nodes of this network and its bridge networks,
+    /// including nodes registered later. See the `transaction` module to learn more.
+    pub fn set_propagation(&self, propagation:Propagation) {
+        self.data.propagation.set(propagation);
+        for node in self.data.nodes.borrow().iter() {
+            node.set_propagation(propagation);
+        }
+        for network in self.bridge_networks() {
+            network.set_propagation(propagation);
+        }
+    }
+
+    /// The mode of event propagation of this network.
+    pub fn propagation(&self) -> Propagation {
+        self.data.propagation.get()
+    }
+
+    fn set_tracer(&self, tracer:Option<Tracer>) {
+        for node in self.data.nodes.borrow().iter() {
+            node.set_tracer(tracer.as_ref().map(|t| t.clone_ref()));
+        }
+        for network in self.bridge_networks() {
+            network.set_tracer(tracer.as_ref().map(|t| t.clone_ref()));
+        }
+        *self.data.tracer.borrow_mut() = tracer;
+    }
+
+    fn bridge_networks(&self) -> Vec<Network> {
+        self.data.bridges.borrow().iter().filter_map(|bridge| bridge.network()).collect()
     }
 }
 
@@ -176,9 +294,21 @@ impl BridgeNetwork {
         Self {data}
     }
 
-    fn destroy(&self) {
+    /// Drops the network before its parent networks are dropped.
+    pub fn destroy(&self) {
         *self.data.borrow_mut() = None
     }
+
+    /// The network, if it was not destroyed yet.
+    pub fn network(&self) -> Option<Network> {
+        self.data.borrow().as_ref().map(|network| network.clone_ref())
+    }
+
+    /// Collect the debug information about the network. Returns `None` if the bridge was already
+    /// destroyed.
+    pub fn introspect(&self) -> Option<debug::NetworkInfo> {
+        self.data.borrow().as_ref().map(|network| network.introspect())
+    }
 }
 
 impl From<Network> for BridgeNetwork {
@@ -226,6 +356,13 @@ impl Link {
 }
 
 /// Type of the link between FRP nodes.
-#[derive(Debug,Clone,Copy)]
+#[derive(Debug,Clone,Copy,Eq,PartialEq,Serialize)]
 #[allow(missing_docs)]
 pub enum LinkType {Event,Behavior,Mixed}
+
+impl LinkType {
+    /// Type of two links between the same nodes, merged into one.
+    pub fn merge(self, other:LinkType) -> LinkType {
+        if self == other { self } else { LinkType::Mixed }
+    }
+}
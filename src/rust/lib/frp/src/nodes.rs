@@ -9,6 +9,10 @@
 
 use crate::prelude::*;
 
+use crate::clock;
+use crate::clock::Clock;
+use crate::clock::ClockConsumer;
+use crate::clock::Time;
 use crate::data::watch;
 use crate::network::*;
 use crate::node::*;
@@ -20,6 +24,7 @@ use crate::stream::OwnedStream;
 use crate::stream;
 use enso_generics as generics;
 use enso_generics::traits::*;
+use std::collections::VecDeque;
 
 
 
@@ -536,6 +541,87 @@ impl Network {
               ,&Output<T7>,&Output<T8>)->T {
         self.register(OwnedAllWith8::new(label,t1,t2,t3,t4,t5,t6,t7,t8,f))
     }
+
+
+    // === Higher Order ===
+
+    /// Follows the latest stream emitted by the input. Every event of the most recently received
+    /// inner stream is passed to the output, while the previously received inner streams are
+    /// unsubscribed from. The inner streams are referenced weakly, so dropping the network
+    /// owning the current inner stream just silences the output. Please note that switching to a
+    /// new inner stream does not emit anything on its own.
+    pub fn switch_latest<T,X>(&self, label:Label, src:&T) -> Stream<X>
+    where T:EventOutput<Output=Stream<X>>, X:Data {
+        self.register(OwnedSwitchLatest::new(label,src))
+    }
+
+    /// Maps every incoming event to a stream and follows the latest of them. See `switch_latest`
+    /// to learn more.
+    pub fn flat_map<T,F,X>(&self, label:Label, src:&T, f:F) -> Stream<X>
+    where T:EventOutput, X:Data, F:'static+Fn(&Output<T>)->Stream<X> {
+        let inner = self.map(label,src,f);
+        self.switch_latest(label,&inner)
+    }
+
+    /// Like `flat_map`, but every inner stream is created together with the network owning it.
+    /// The node owns the network of the latest inner stream, and destroys it when it switches to
+    /// the next one or when it is dropped. Create the networks with `Network::new_child_network`
+    /// to destroy them together with this network as well.
+    pub fn flat_map_network<T,F,X>(&self, label:Label, src:&T, f:F) -> Stream<X>
+    where T:EventOutput, X:Data, F:'static+Fn(&Output<T>)->(BridgeNetwork,Stream<X>) {
+        self.register(OwnedFlatMapNetwork::new(label,src,f))
+    }
+
+
+    // === Collections ===
+
+    /// Maintains a sub-network for every key of the incoming list of `(key,item)` pairs. When a new
+    /// key appears, a new network is created and passed to the `builder`, together with the key
+    /// and a stream of the item values. The stream returned by the `builder` is the output of the
+    /// child, and its events are passed to the output of this node, tagged with the key. When an
+    /// item changes, its new value is emitted in the child network. When a key disappears, its
+    /// network is dropped. Use it to build "for each" components, like lists of child views.
+    ///
+    /// The child networks are bridge networks of this network, see `Network::new_child_network`.
+    pub fn keyed_collection<T,K,Item,F,Out>
+    (&self, label:Label, src:&T, builder:F) -> Stream<(K,Out)>
+    where T:EventOutput<Output=Vec<(K,Item)>>, K:Data+Eq+Hash, Item:Data+PartialEq, Out:Data,
+          F:'static+Fn(&Network,&K,&Stream<Item>)->Stream<Out> {
+        self.register(OwnedKeyedCollection::new(label,src,self,builder))
+    }
+
+
+    // === Time ===
+
+    /// Delays every incoming event by `duration` milliseconds. The events are emitted on the first
+    /// clock tick at or after their due time, in the order they were received.
+    pub fn delay<T,C>(&self, label:Label, src:&T, clock:&C, duration:Time) -> Stream<Output<T>>
+    where T:EventOutput, C:Clock {
+        self.register(OwnedDelay::new(label,src,clock,duration))
+    }
+
+    /// Emits the last incoming event after no new events were received for `duration`
+    /// milliseconds. Useful for reacting to the user input only after the user stopped typing.
+    pub fn debounce<T,C>(&self, label:Label, src:&T, clock:&C, duration:Time) -> Stream<Output<T>>
+    where T:EventOutput, C:Clock {
+        self.register(OwnedDebounce::new(label,src,clock,duration))
+    }
+
+    /// Passes at most one event per `duration` milliseconds. The first event is passed immediately.
+    /// If more events arrive during the window, the last of them is emitted as soon as the window
+    /// ends.
+    pub fn throttle<T,C>(&self, label:Label, src:&T, clock:&C, duration:Time) -> Stream<Output<T>>
+    where T:EventOutput, C:Clock {
+        self.register(OwnedThrottle::new(label,src,clock,duration))
+    }
+
+    /// Samples the behavior every `period` milliseconds. The first sample is emitted one period
+    /// after the first clock tick.
+    pub fn sample_every<T,C>
+    (&self, label:Label, behavior:&T, clock:&C, period:Time) -> Stream<Output<T>>
+    where T:EventOutput, C:Clock {
+        self.register(OwnedSampleEvery::new(label,behavior,clock,period))
+    }
 }
 
 
@@ -2622,3 +2708,534 @@ impl<T1,T2,T3,T4,T5,T6,T7,T8,F> Debug for AllWith8Data<T1,T2,T3,T4,T5,T6,T7,T8,F
         write!(f,"AllWith8Data")
     }
 }
+
+
+
+// ====================
+// === SwitchLatest ===
+// ====================
+
+#[derive(Debug)]
+pub struct SwitchLatestData <T:EventOutput> {
+    src    : T,
+    switch : Switch<Output<T>>,
+}
+pub type OwnedSwitchLatest <T> = stream::Node     <SwitchLatestData<T>>;
+pub type SwitchLatest      <T> = stream::WeakNode <SwitchLatestData<T>>;
+
+impl<T,X> HasOutput for SwitchLatestData<T>
+where T:EventOutput<Output=Stream<X>>, X:Data {
+    type Output = X;
+}
+
+impl<T,X> OwnedSwitchLatest<T>
+where T:EventOutput<Output=Stream<X>>, X:Data {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T) -> Self {
+        let src    = src1.clone_ref();
+        let switch = default();
+        let def    = SwitchLatestData {src,switch};
+        Self::construct_and_connect(label,src1,def)
+    }
+}
+
+impl<T,X> stream::EventConsumer<Stream<X>> for OwnedSwitchLatest<T>
+where T:EventOutput<Output=Stream<X>>, X:Data {
+    fn on_event(&self, _stack:CallStack, inner:&Stream<X>) {
+        self.switch.switch_to(self.downgrade(),inner,None);
+    }
+}
+
+impl<T,X> Switching for SwitchLatestData<T>
+where T:EventOutput<Output=Stream<X>>, X:Data {
+    fn switch(&self) -> &Switch<Stream<X>> {
+        &self.switch
+    }
+}
+
+
+
+// ======================
+// === FlatMapNetwork ===
+// ======================
+
+pub struct FlatMapNetworkData <T,F,X> {
+    src    : T,
+    f      : F,
+    switch : Switch<Stream<X>>,
+}
+pub type OwnedFlatMapNetwork <T,F,X> = stream::Node     <FlatMapNetworkData<T,F,X>>;
+pub type FlatMapNetwork      <T,F,X> = stream::WeakNode <FlatMapNetworkData<T,F,X>>;
+
+impl<T,F,X> HasOutput for FlatMapNetworkData<T,F,X>
+where T:EventOutput, X:Data, F:'static+Fn(&Output<T>)->(BridgeNetwork,Stream<X>) {
+    type Output = X;
+}
+
+impl<T,F,X> OwnedFlatMapNetwork<T,F,X>
+where T:EventOutput, X:Data, F:'static+Fn(&Output<T>)->(BridgeNetwork,Stream<X>) {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, f:F) -> Self {
+        let src    = src1.clone_ref();
+        let switch = default();
+        let def    = FlatMapNetworkData {src,f,switch};
+        Self::construct_and_connect(label,src1,def)
+    }
+}
+
+impl<T,F,X> stream::EventConsumer<Output<T>> for OwnedFlatMapNetwork<T,F,X>
+where T:EventOutput, X:Data, F:'static+Fn(&Output<T>)->(BridgeNetwork,Stream<X>) {
+    fn on_event(&self, _stack:CallStack, event:&Output<T>) {
+        let (network,inner) = (self.f)(event);
+        self.switch.switch_to(self.downgrade(),&inner,Some(network));
+    }
+}
+
+impl<T,F,X> Switching for FlatMapNetworkData<T,F,X>
+where T:EventOutput, X:Data, F:'static+Fn(&Output<T>)->(BridgeNetwork,Stream<X>) {
+    fn switch(&self) -> &Switch<Stream<X>> {
+        &self.switch
+    }
+}
+
+impl<T,F,X> Debug for FlatMapNetworkData<T,F,X> {
+    fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f,"FlatMapNetworkData")
+    }
+}
+
+
+
+// ==============
+// === Switch ===
+// ==============
+
+/// The state of a node following the latest of its inner streams. It unsubscribes from the
+/// previous inner stream and destroys the network owning it, if any, on every switch. The network
+/// of the current inner stream is destroyed when the node is dropped.
+#[derive(Debug)]
+struct Switch<S> {
+    /// Incremented on every switch. Connections to the inner streams tagged with older generations
+    /// ignore their events. They are normally removed right away on switch, but a connection which
+    /// is notified during the switch can be removed only when its stream emits the next event.
+    generation : Cell<usize>,
+    current    : RefCell<S>,
+    network    : RefCell<Option<BridgeNetwork>>,
+}
+
+impl<S:Default> Default for Switch<S> {
+    fn default() -> Self {
+        let generation = default();
+        let current    = default();
+        let network    = default();
+        Self {generation,current,network}
+    }
+}
+
+impl<X:Data> Switch<Stream<X>> {
+    /// Connects the `node` to the `inner` stream instead of the previous one.
+    fn switch_to<Def>
+    (&self, node:stream::WeakNode<Def>, inner:&Stream<X>, network:Option<BridgeNetwork>)
+    where Def:Switching<Output=X> {
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        let previous = self.current.replace(inner.clone_ref());
+        previous.unregister_target(node.id());
+        let previous_network = self.network.replace(network);
+        if let Some(network) = previous_network {
+            network.destroy();
+        }
+        inner.register_target(stream::EventInput::new(SwitchInput {node,generation}));
+    }
+}
+
+impl<S> Drop for Switch<S> {
+    fn drop(&mut self) {
+        if let Some(network) = self.network.borrow().as_ref() {
+            network.destroy();
+        }
+    }
+}
+
+/// Definitions of nodes following the latest of their inner streams.
+trait Switching : 'static + HasOutput {
+    /// The state of switching between the inner streams.
+    fn switch(&self) -> &Switch<Stream<Self::Output>>;
+}
+
+/// Connection between a switching node and one of its inner streams. It unsubscribes itself when
+/// the node is dropped or switched to another stream.
+#[derive(Debug)]
+struct SwitchInput<Def:HasOutputStatic> {
+    node       : stream::WeakNode<Def>,
+    generation : usize,
+}
+
+impl<Def:Switching> stream::WeakEventConsumer<Output<Def>> for SwitchInput<Def> {
+    fn on_event_if_exists(&self, stack:CallStack, value:&Output<Def>) -> bool {
+        self.node.upgrade().map(|node| {
+            let is_current = node.switch().generation.get() == self.generation;
+            if is_current {
+                node.emit_event(stack,value);
+            }
+            is_current
+        }) == Some(true)
+    }
+
+    fn consumer_id(&self) -> Option<Id> {
+        Some(self.node.id())
+    }
+}
+
+
+
+// =======================
+// === KeyedCollection ===
+// =======================
+
+pub struct KeyedCollectionData <T,K,Item,F> {
+    src      : T,
+    builder  : F,
+    /// The network this node is registered in, the parent of the child networks.
+    network  : WeakNetwork,
+    children : RefCell<HashMap<K,KeyedChild<Item>>>,
+}
+pub type OwnedKeyedCollection <T,K,Item,F> = stream::Node     <KeyedCollectionData<T,K,Item,F>>;
+pub type KeyedCollection      <T,K,Item,F> = stream::WeakNode <KeyedCollectionData<T,K,Item,F>>;
+
+/// Sub-network of a single key of `KeyedCollection`. The `input` emits the item values. The
+/// network is destroyed when the child is dropped.
+#[derive(Debug)]
+struct KeyedChild<Item> {
+    network : BridgeNetwork,
+    input   : Source<Item>,
+    item    : Item,
+}
+
+impl<Item> Drop for KeyedChild<Item> {
+    fn drop(&mut self) {
+        self.network.destroy();
+    }
+}
+
+impl<T,K,Item,F,Out> HasOutput for KeyedCollectionData<T,K,Item,F>
+where T:EventOutput<Output=Vec<(K,Item)>>, K:Data+Eq+Hash, Item:Data+PartialEq, Out:Data,
+      F:'static+Fn(&Network,&K,&Stream<Item>)->Stream<Out> {
+    type Output = (K,Out);
+}
+
+impl<T,K,Item,F,Out> OwnedKeyedCollection<T,K,Item,F>
+where T:EventOutput<Output=Vec<(K,Item)>>, K:Data+Eq+Hash, Item:Data+PartialEq, Out:Data,
+      F:'static+Fn(&Network,&K,&Stream<Item>)->Stream<Out> {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, network:&Network, builder:F) -> Self {
+        let src      = src1.clone_ref();
+        let network  = network.downgrade();
+        let children = default();
+        let def      = KeyedCollectionData {src,builder,network,children};
+        Self::construct_and_connect(label,src1,def)
+    }
+
+    /// Creates the network of a new child. It must not be called while `children` are borrowed,
+    /// as the `builder` may access this node.
+    fn new_child(&self, key:&K, item:&Item) -> KeyedChild<Item> {
+        let label   = self.label();
+        let network = match self.network.upgrade() {
+            Some(parent) => parent.new_child_network(label),
+            // The parent is being dropped, so the child would be dropped right away.
+            None         => BridgeNetwork::new(label),
+        };
+        let child   = network.network().expect("The new child network was destroyed.");
+        let input   = child.source::<Item>(label);
+        let output  = (self.builder)(&child,key,&input.clone_ref().into());
+        let node    = self.downgrade();
+        let key     = key.clone();
+        output.register_target(stream::EventInput::new(KeyedCollectionInput {node,key}));
+        let item    = item.clone();
+        KeyedChild {network,input,item}
+    }
+}
+
+impl<T,K,Item,F,Out> stream::EventConsumer<Vec<(K,Item)>> for OwnedKeyedCollection<T,K,Item,F>
+where T:EventOutput<Output=Vec<(K,Item)>>, K:Data+Eq+Hash, Item:Data+PartialEq, Out:Data,
+      F:'static+Fn(&Network,&K,&Stream<Item>)->Stream<Out> {
+    fn on_event(&self, _stack:CallStack, items:&Vec<(K,Item)>) {
+        // The children are created and dropped, and the items are emitted, while `children` are not
+        // borrowed, so the child networks can safely access this node. The items are emitted after
+        // all the children are updated.
+        let removed = {
+            let mut children = self.children.borrow_mut();
+            let keys : HashSet<&K> = items.iter().map(|(key,_)| key).collect();
+            let removed_keys = children.keys().filter(|k| !keys.contains(k)).cloned().collect_vec();
+            removed_keys.iter().filter_map(|key| children.remove(key)).collect_vec()
+        };
+        drop(removed);
+        let mut updates = vec![];
+        for (key,item) in items {
+            let mut children = self.children.borrow_mut();
+            if let Some(child) = children.get_mut(key) {
+                if child.item != *item {
+                    child.item = item.clone();
+                    updates.push((child.input.clone_ref(),item.clone()));
+                }
+            } else {
+                drop(children);
+                let child = self.new_child(key,item);
+                updates.push((child.input.clone_ref(),item.clone()));
+                self.children.borrow_mut().insert(key.clone(),child);
+            }
+        }
+        for (input,item) in updates {
+            input.emit(item);
+        }
+    }
+}
+
+impl<T,K,Item,F> Debug for KeyedCollectionData<T,K,Item,F> {
+    fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f,"KeyedCollectionData")
+    }
+}
+
+/// Connection between `KeyedCollection` node and the output of one of its children. The child
+/// network is owned by the node, so the connection is dropped together with the child.
+struct KeyedCollectionInput<T,K,Item,F> where KeyedCollectionData<T,K,Item,F>:HasOutputStatic {
+    node : KeyedCollection<T,K,Item,F>,
+    key  : K,
+}
+
+impl<T,K,Item,F,Out> stream::WeakEventConsumer<Out> for KeyedCollectionInput<T,K,Item,F>
+where T:EventOutput<Output=Vec<(K,Item)>>, K:Data+Eq+Hash, Item:Data+PartialEq, Out:Data,
+      F:'static+Fn(&Network,&K,&Stream<Item>)->Stream<Out> {
+    fn on_event_if_exists(&self, stack:CallStack, value:&Out) -> bool {
+        self.node.upgrade().map(|node| {
+            node.emit_event(stack,&(self.key.clone(),value.clone()))
+        }).is_some()
+    }
+
+    fn consumer_id(&self) -> Option<Id> {
+        Some(self.node.id())
+    }
+}
+
+
+
+// =============
+// === Delay ===
+// =============
+
+#[derive(Debug)]
+pub struct DelayData <T:EventOutput,C> {
+    src      : T,
+    clock    : watch::Ref<C>,
+    duration : Time,
+    queue    : RefCell<VecDeque<(Time,Output<T>)>>,
+}
+pub type OwnedDelay <T,C> = stream::Node     <DelayData<T,C>>;
+pub type Delay      <T,C> = stream::WeakNode <DelayData<T,C>>;
+
+impl<T:EventOutput,C> HasOutput for DelayData<T,C> {
+    type Output = Output<T>;
+}
+
+impl<T:EventOutput,C:Clock> OwnedDelay<T,C> {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, clock1:&C, duration:Time) -> Self {
+        let src   = src1.clone_ref();
+        let clock = watch_stream(clock1);
+        let queue = default();
+        let def   = DelayData {src,clock,duration,queue};
+        let this  = Self::construct_and_connect(label,src1,def);
+        clock::connect(clock1,&this);
+        this
+    }
+}
+
+impl<T:EventOutput,C:Clock> stream::EventConsumer<Output<T>> for OwnedDelay<T,C> {
+    fn on_event(&self, _stack:CallStack, event:&Output<T>) {
+        let due = self.clock.value() + self.duration;
+        self.queue.borrow_mut().push_back((due,event.clone()));
+    }
+}
+
+impl<T:EventOutput,C:Clock> ClockConsumer for OwnedDelay<T,C> {
+    fn on_tick(&self, stack:CallStack, time:Time) {
+        loop {
+            let is_due = self.queue.borrow().front().map(|(due,_)| *due <= time) == Some(true);
+            if !is_due { break }
+            let event = self.queue.borrow_mut().pop_front();
+            if let Some((_,event)) = event {
+                self.emit_event(stack,&event);
+            }
+        }
+    }
+}
+
+
+
+// ================
+// === Debounce ===
+// ================
+
+#[derive(Debug)]
+pub struct DebounceData <T:EventOutput,C> {
+    src      : T,
+    clock    : watch::Ref<C>,
+    duration : Time,
+    pending  : RefCell<Option<(Time,Output<T>)>>,
+}
+pub type OwnedDebounce <T,C> = stream::Node     <DebounceData<T,C>>;
+pub type Debounce      <T,C> = stream::WeakNode <DebounceData<T,C>>;
+
+impl<T:EventOutput,C> HasOutput for DebounceData<T,C> {
+    type Output = Output<T>;
+}
+
+impl<T:EventOutput,C:Clock> OwnedDebounce<T,C> {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, clock1:&C, duration:Time) -> Self {
+        let src     = src1.clone_ref();
+        let clock   = watch_stream(clock1);
+        let pending = default();
+        let def     = DebounceData {src,clock,duration,pending};
+        let this    = Self::construct_and_connect(label,src1,def);
+        clock::connect(clock1,&this);
+        this
+    }
+}
+
+impl<T:EventOutput,C:Clock> stream::EventConsumer<Output<T>> for OwnedDebounce<T,C> {
+    fn on_event(&self, _stack:CallStack, event:&Output<T>) {
+        let due = self.clock.value() + self.duration;
+        *self.pending.borrow_mut() = Some((due,event.clone()));
+    }
+}
+
+impl<T:EventOutput,C:Clock> ClockConsumer for OwnedDebounce<T,C> {
+    fn on_tick(&self, stack:CallStack, time:Time) {
+        let is_due = self.pending.borrow().as_ref().map(|(due,_)| *due <= time) == Some(true);
+        if is_due {
+            let event = self.pending.borrow_mut().take();
+            if let Some((_,event)) = event {
+                self.emit_event(stack,&event);
+            }
+        }
+    }
+}
+
+
+
+// ================
+// === Throttle ===
+// ================
+
+#[derive(Debug)]
+pub struct ThrottleData <T:EventOutput,C> {
+    src        : T,
+    clock      : watch::Ref<C>,
+    duration   : Time,
+    /// The time at which the current throttling window ends. `None` if no event was passed yet.
+    window_end : Cell<Option<Time>>,
+    pending    : RefCell<Option<Output<T>>>,
+}
+pub type OwnedThrottle <T,C> = stream::Node     <ThrottleData<T,C>>;
+pub type Throttle      <T,C> = stream::WeakNode <ThrottleData<T,C>>;
+
+impl<T:EventOutput,C> HasOutput for ThrottleData<T,C> {
+    type Output = Output<T>;
+}
+
+impl<T:EventOutput,C:Clock> OwnedThrottle<T,C> {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, clock1:&C, duration:Time) -> Self {
+        let src        = src1.clone_ref();
+        let clock      = watch_stream(clock1);
+        let window_end = default();
+        let pending    = default();
+        let def        = ThrottleData {src,clock,duration,window_end,pending};
+        let this       = Self::construct_and_connect(label,src1,def);
+        clock::connect(clock1,&this);
+        this
+    }
+
+    fn is_window_open(&self, time:Time) -> bool {
+        self.window_end.get().map(|end| time < end) == Some(true)
+    }
+}
+
+impl<T:EventOutput,C:Clock> stream::EventConsumer<Output<T>> for OwnedThrottle<T,C> {
+    fn on_event(&self, stack:CallStack, event:&Output<T>) {
+        let time = self.clock.value();
+        if self.is_window_open(time) {
+            *self.pending.borrow_mut() = Some(event.clone());
+        } else {
+            self.window_end.set(Some(time + self.duration));
+            self.emit_event(stack,event);
+        }
+    }
+}
+
+impl<T:EventOutput,C:Clock> ClockConsumer for OwnedThrottle<T,C> {
+    fn on_tick(&self, stack:CallStack, time:Time) {
+        if !self.is_window_open(time) {
+            let event = self.pending.borrow_mut().take();
+            if let Some(event) = event {
+                self.window_end.set(Some(time + self.duration));
+                self.emit_event(stack,&event);
+            }
+        }
+    }
+}
+
+
+
+// ===================
+// === SampleEvery ===
+// ===================
+
+#[derive(Debug)]
+pub struct SampleEveryData <T,C> {
+    behavior    : watch::Ref<T>,
+    clock       : C,
+    period      : Time,
+    next_sample : Cell<Option<Time>>,
+}
+pub type OwnedSampleEvery <T,C> = stream::Node     <SampleEveryData<T,C>>;
+pub type SampleEvery      <T,C> = stream::WeakNode <SampleEveryData<T,C>>;
+
+impl<T:EventOutput,C> HasOutput for SampleEveryData<T,C> {
+    type Output = Output<T>;
+}
+
+impl<T:EventOutput,C:Clock> OwnedSampleEvery<T,C> {
+    /// Constructor.
+    pub fn new(label:Label, behavior:&T, clock1:&C, period:Time) -> Self {
+        let behavior    = watch_stream(behavior);
+        let clock       = clock1.clone_ref();
+        let next_sample = default();
+        let def         = SampleEveryData {behavior,clock,period,next_sample};
+        Self::construct_and_connect(label,clock1,def)
+    }
+}
+
+impl<T:EventOutput,C:Clock> stream::EventConsumer<Time> for OwnedSampleEvery<T,C> {
+    fn on_event(&self, stack:CallStack, time:&Time) {
+        let time = *time;
+        match self.next_sample.get() {
+            None => self.next_sample.set(Some(time + self.period)),
+            Some(next_sample) => if next_sample <= time {
+                let next_sample = next_sample + self.period;
+                // Skipping the missed samples if the clock jumped by more than one period.
+                let next_sample = if next_sample <= time {time + self.period} else {next_sample};
+                self.next_sample.set(Some(next_sample));
+                self.emit_event(stack,&self.behavior.value());
+            }
+        }
+    }
+}
+
+impl<T:EventOutput,C> stream::InputBehaviors for SampleEveryData<T,C> {
+    fn input_behaviors(&self) -> Vec<Link> {
+        vec![Link::behavior(&self.behavior)]
+    }
+}
@@ -4,6 +4,10 @@ use crate::prelude::*;
 use crate::network::*;
 use crate::node::*;
 use crate::data::watch;
+use crate::tracing::Tracer;
+use crate::transaction;
+use crate::transaction::Propagation;
+use crate::transaction::Rank;
 
 
 
@@ -29,13 +33,15 @@ pub type CallStack<'a> = &'a OwnedCallStack;
 pub trait CallStackOps : Default + Display {
     /// Create a sub stack trace.
     fn sub(&self, label:Label) -> Self;
+    /// Labels of all nodes on the stack, starting with the node which emitted the first event.
+    fn labels(&self) -> Vec<Label>;
 }
 
 
 // === Enabled ===
 
 /// A call stack trace for FRP events.
-#[derive(Debug,Default)]
+#[derive(Clone,Debug,Default)]
 pub struct EnabledCallStack {
     stack: Vec<Label>
 }
@@ -45,6 +51,10 @@ impl CallStackOps for EnabledCallStack {
         let stack = self.stack.to_vec().pushed(label);
         Self {stack}
     }
+
+    fn labels(&self) -> Vec<Label> {
+        self.stack.clone()
+    }
 }
 
 impl Display for EnabledCallStack {
@@ -66,6 +76,10 @@ impl CallStackOps for DisabledCallStack {
     fn sub(&self, _label:Label) -> Self {
         *self
     }
+
+    fn labels(&self) -> Vec<Label> {
+        vec![]
+    }
 }
 
 impl Display for DisabledCallStack {
@@ -88,6 +102,34 @@ pub trait HasOutputTypeLabel {
 
 
 
+// =====================
+// === Introspection ===
+// =====================
+
+/// Runtime information about FRP nodes. Used for debugging purposes only.
+pub trait Introspection {
+    /// Identifiers of all nodes this node emits events to.
+    fn event_targets(&self) -> Vec<Id>;
+
+    /// Debug representation of the current output value. It is available only if the value is
+    /// cached, which is the case for nodes used as behaviors.
+    fn debug_value(&self) -> Option<String>;
+}
+
+/// Nodes which can propagate events in different modes. See the `transaction` module to learn more.
+pub trait Schedulable {
+    /// Set the mode of propagation of events received by this node.
+    fn set_propagation(&self, propagation:Propagation);
+}
+
+/// Nodes which can record their events. See the `tracing` module to learn more.
+pub trait Traceable {
+    /// Set the tracer recording all events emitted by this node. Passing `None` disables tracing.
+    fn set_tracer(&self, tracer:Option<Tracer>);
+}
+
+
+
 // ======================
 // === InputBehaviors ===
 // ======================
@@ -120,6 +162,10 @@ pub trait EventEmitter : HasOutput {
     fn emit_event(&self, stack:CallStack, value:&Self::Output);
     /// Register new event target. All emitted events will be send to every registered target.
     fn register_target(&self, target:EventInput<Output<Self>>);
+    /// Unregister all event targets consumed by the node of the given id. The targets which are
+    /// being notified at the moment of the call cannot be removed, so they have to unsubscribe
+    /// themselves when they receive the next event.
+    fn unregister_target(&self, consumer:Id);
     /// Register that someone is watching value of this node.
     fn register_watch(&self) -> watch::Handle;
 }
@@ -142,6 +188,11 @@ pub trait WeakEventConsumer<T> {
     /// Callback for a new incoming event. Returns true if the event was consumed or false if it was
     /// not. Not consuming an event means that the event receiver was already dropped.
     fn on_event_if_exists(&self, stack:CallStack, value:&T) -> bool;
+
+    /// Identifier of the node consuming the events. Used for debugging purposes only.
+    fn consumer_id(&self) -> Option<Id> {
+        None
+    }
 }
 
 
@@ -170,6 +221,14 @@ pub struct EventInput<Input> {
     data : Rc<dyn WeakEventConsumer<Input>>
 }
 
+impl<Input> EventInput<Input> {
+    /// Constructor from any weak event consumer. Use it to connect targets which are not regular
+    /// FRP nodes, or nodes consuming events of several streams of the same type.
+    pub fn new<T:'static+WeakEventConsumer<Input>>(consumer:T) -> Self {
+        Self {data:Rc::new(consumer)}
+    }
+}
+
 impl<Def,Input> From<WeakNode<Def>> for EventInput<Input>
 where Def:HasOutputStatic, Node<Def>:EventConsumer<Input> {
     fn from(node:WeakNode<Def>) -> Self {
@@ -218,6 +277,9 @@ pub struct NodeData<Out=()> {
     during_call   : Cell<bool>,
     watch_counter : watch::Counter,
     label         : Label,
+    tracer        : RefCell<Option<Tracer>>,
+    rank          : Rank,
+    propagation   : Cell<Propagation>,
 }
 
 impl<Out:Default> NodeData<Out> {
@@ -228,7 +290,15 @@ impl<Out:Default> NodeData<Out> {
         let value_cache   = default();
         let during_call   = default();
         let watch_counter = default();
-        Self {targets,new_targets,value_cache,during_call,watch_counter,label}
+        let tracer        = default();
+        let rank          = transaction::next_rank();
+        let propagation   = default();
+        Self {targets,new_targets,value_cache,during_call,watch_counter,label,tracer,rank,
+              propagation}
+    }
+
+    fn is_transactional(&self) -> bool {
+        self.propagation.get() == Propagation::Transactional
     }
 
     fn use_caching(&self) -> bool {
@@ -236,12 +306,27 @@ impl<Out:Default> NodeData<Out> {
     }
 }
 
+impl<Out:Debug> NodeData<Out> {
+    fn event_targets(&self) -> Vec<Id> {
+        let targets     = self.targets.try_borrow();
+        let new_targets = self.new_targets.try_borrow();
+        let targets     = targets.iter().flat_map(|t| t.iter());
+        let new_targets = new_targets.iter().flat_map(|t| t.iter());
+        targets.chain(new_targets).filter_map(|target| target.data.consumer_id()).collect()
+    }
+
+    fn debug_value(&self) -> Option<String> {
+        let value = self.value_cache.try_borrow().ok()?;
+        self.use_caching().as_some_from(|| format!("{:?}",*value))
+    }
+}
+
 impl<Out:Data> HasOutput for NodeData<Out> {
     type Output = Out;
 }
 
-impl<Out:Data> EventEmitter for NodeData<Out> {
-    fn emit_event(&self, stack:CallStack, value:&Out) {
+impl<Out:Data> NodeData<Out> {
+    fn propagate(&self, stack:CallStack, value:&Out) {
         let new_stack = stack.sub(self.label);
         if self.during_call.get() {
             let logger : Logger = Logger::new("frp");
@@ -250,6 +335,9 @@ impl<Out:Data> EventEmitter for NodeData<Out> {
             })
         } else {
             self.during_call.set(true);
+            if let Some(tracer) = &*self.tracer.borrow() {
+                tracer.record(self.label,value,&new_stack);
+            }
             if self.use_caching() {
                 *self.value_cache.borrow_mut() = value.clone();
             }
@@ -264,6 +352,16 @@ impl<Out:Data> EventEmitter for NodeData<Out> {
             self.during_call.set(false);
         }
     }
+}
+
+impl<Out:Data> EventEmitter for NodeData<Out> {
+    fn emit_event(&self, stack:CallStack, value:&Out) {
+        if self.is_transactional() {
+            transaction::transaction(|| self.propagate(stack,value))
+        } else {
+            self.propagate(stack,value)
+        }
+    }
 
     fn register_target(&self,target:EventInput<Out>) {
         if self.during_call.get() {
@@ -273,6 +371,16 @@ impl<Out:Data> EventEmitter for NodeData<Out> {
         }
     }
 
+    fn unregister_target(&self, consumer:Id) {
+        let is_other = |target:&EventInput<Out>| target.data.consumer_id() != Some(consumer);
+        if let Ok(mut targets) = self.targets.try_borrow_mut() {
+            targets.retain(is_other);
+        }
+        if let Ok(mut new_targets) = self.new_targets.try_borrow_mut() {
+            new_targets.retain(is_other);
+        }
+    }
+
     fn register_watch(&self) -> watch::Handle {
         self.watch_counter.new_watch()
     }
@@ -418,6 +526,13 @@ impl<Out> Stream<Out> {
     }
 }
 
+/// A stream which is not connected to any node. It never emits events.
+impl<Out> Default for Stream<Out> {
+    fn default() -> Self {
+        Self {data:Weak::new()}
+    }
+}
+
 impl<Def> From<WeakNode<Def>> for Stream<Def::Output>
 where Def:HasOutputStatic {
     fn from(node:WeakNode<Def>) -> Self {
@@ -472,6 +587,10 @@ impl<Out:Data> EventEmitter for OwnedStream<Out> {
         self.data.register_target(target)
     }
 
+    fn unregister_target(&self, consumer:Id) {
+        self.data.unregister_target(consumer)
+    }
+
     fn register_watch(&self) -> watch::Handle {
         self.data.register_watch()
     }
@@ -486,6 +605,10 @@ impl<Out:Data> EventEmitter for Stream<Out> {
         self.upgrade().for_each(|t| t.register_target(target))
     }
 
+    fn unregister_target(&self, consumer:Id) {
+        self.upgrade().for_each(|t| t.unregister_target(consumer))
+    }
+
     fn register_watch(&self) -> watch::Handle {
         self.upgrade().map(|t| t.register_watch()).unwrap() // FIXME
     }
@@ -493,23 +616,51 @@ impl<Out:Data> EventEmitter for Stream<Out> {
 
 impl<Def:HasOutputStatic> EventEmitter for Node<Def>  {
     fn emit_event (&self, stack:CallStack, value:&Output<Def>) {self.stream.emit_event(stack,value)}
-    fn register_target (&self,tgt:EventInput<Output<Self>>)    {self.stream.register_target(tgt)}
-    fn register_watch  (&self) -> watch::Handle                {self.stream.register_watch()}
+    fn register_target   (&self,tgt:EventInput<Output<Self>>)  {self.stream.register_target(tgt)}
+    fn unregister_target (&self,id:Id)                         {self.stream.unregister_target(id)}
+    fn register_watch    (&self) -> watch::Handle              {self.stream.register_watch()}
 }
 
 impl<Def:HasOutputStatic> EventEmitter for WeakNode<Def> {
     fn emit_event (&self, stack:CallStack, value:&Output<Def>) {self.stream.emit_event(stack,value)}
-    fn register_target (&self,tgt:EventInput<Output<Self>>)    {self.stream.register_target(tgt)}
-    fn register_watch  (&self) -> watch::Handle                {self.stream.register_watch()}
+    fn register_target   (&self,tgt:EventInput<Output<Self>>)  {self.stream.register_target(tgt)}
+    fn unregister_target (&self,id:Id)                         {self.stream.unregister_target(id)}
+    fn register_watch    (&self) -> watch::Handle              {self.stream.register_watch()}
 }
 
 
 // === WeakEventConsumer ===
 
 impl<Def,T> WeakEventConsumer<T> for WeakNode<Def>
-where Def:HasOutputStatic, Node<Def>:EventConsumer<T> {
+where Def:HasOutputStatic, Node<Def>:EventConsumer<T>, T:'static+Clone {
     fn on_event_if_exists(&self, stack:CallStack, value:&T) -> bool {
-        self.upgrade().map(|node| {node.on_event(stack,value);}).is_some()
+        self.upgrade().map(|node| {
+            let data = &node.stream.data;
+            if data.is_transactional() {
+                let weak  = self.clone_ref();
+                let stack = stack.clone();
+                let value = value.clone();
+                transaction::transaction(|| {
+                    let scheduled = transaction::schedule(data.rank, move || {
+                        if let Some(node) = weak.upgrade() {
+                            node.on_event(&stack,&value)
+                        }
+                    });
+                    if !scheduled {
+                        let logger : Logger = Logger::new("frp");
+                        warning!(logger,"Encountered a loop in the reactive dataflow.", || {
+                            warning!(logger,"{data.label}");
+                        })
+                    }
+                });
+            } else {
+                node.on_event(stack,value);
+            }
+        }).is_some()
+    }
+
+    fn consumer_id(&self) -> Option<Id> {
+        Some(self.id())
     }
 }
 
@@ -590,13 +741,42 @@ impl<Def> HasOutputTypeLabel for Node<Def>
 }
 
 
+// === Introspection ===
+
+impl<Def:HasOutputStatic> Introspection for Node<Def> {
+    fn event_targets(&self) -> Vec<Id> {
+        self.stream.data.event_targets()
+    }
+
+    fn debug_value(&self) -> Option<String> {
+        self.stream.data.debug_value()
+    }
+}
+
+// === Schedulable ===
+
+impl<Def:HasOutputStatic> Schedulable for Node<Def> {
+    fn set_propagation(&self, propagation:Propagation) {
+        self.stream.data.propagation.set(propagation)
+    }
+}
+
+
+// === Traceable ===
+
+impl<Def:HasOutputStatic> Traceable for Node<Def> {
+    fn set_tracer(&self, tracer:Option<Tracer>) {
+        *self.stream.data.tracer.borrow_mut() = tracer;
+    }
+}
+
+
 // === InputBehaviors ===
 
 impl<Def:HasOutputStatic> InputBehaviors for Node<Def>
 where Def:InputBehaviors {
     fn input_behaviors(&self) -> Vec<Link> {
-        vec![] // FIXME
-//        self.data.input_behaviors()
+        self.definition.input_behaviors()
     }
 }
 
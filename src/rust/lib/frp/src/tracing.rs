@@ -0,0 +1,317 @@
+//! Event tracing and replaying of FRP networks.
+//!
+//! When tracing is enabled for a network (see `Network::enable_tracing`), every event passing
+//! through any of its nodes is recorded by a `Tracer`, together with the label of the node, the
+//! debug representation of the event value, and the call stack (available only if the
+//! `stack-trace` feature is enabled). Only the last events are kept in a ring buffer, so tracing
+//! can be left enabled for a long time. The recorded `Trace` can be saved to a JSON file.
+//!
+//! The `Replay` tool feeds the events emitted by `Source` nodes in a recorded trace back into a
+//! freshly built network. Sources are matched by their labels, so the network has to be built by
+//! the same code as the traced one. This allows reproducing user-reported bugs offline.
+
+use crate::prelude::*;
+
+use crate::node::*;
+use crate::nodes::Source;
+use crate::stream::CallStack;
+use crate::stream::CallStackOps;
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::str::FromStr;
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// Errors which can occur when loading or replaying a trace.
+#[derive(Debug,Fail,From)]
+pub enum TraceError {
+    /// Reading or writing the trace file failed.
+    #[fail(display="Unable to access the trace file: {}.",_0)]
+    Io(std::io::Error),
+    /// The trace is not a valid JSON trace.
+    #[fail(display="Malformed trace: {}.",_0)]
+    Format(serde_json::Error),
+    /// The recorded value could not be restored from its debug representation.
+    #[fail(display="Unable to parse the value `{}` of the source `{}`.",value,label)]
+    #[allow(missing_docs)]
+    Parse { label:String, value:String },
+}
+
+
+
+// ==================
+// === TraceEvent ===
+// ==================
+
+/// A single event recorded by a `Tracer`.
+#[derive(Clone,Debug,Deserialize,PartialEq,Serialize)]
+pub struct TraceEvent {
+    /// Index of the event since the tracer was created. Gaps between indexes of subsequent events
+    /// in a trace are never present, while a non-zero index of the first event means that older
+    /// events were dropped from the ring buffer.
+    pub index      : usize,
+    /// Label of the node which emitted the event.
+    pub label      : String,
+    /// Debug representation of the event value.
+    pub value      : String,
+    /// Labels of nodes the event passed through, starting with the node which emitted the first
+    /// event. Empty if the `stack-trace` feature is disabled.
+    pub call_stack : Vec<String>,
+}
+
+
+
+// =============
+// === Trace ===
+// =============
+
+/// A sequence of recorded events, starting with the oldest one.
+#[derive(Clone,Debug,Default,Deserialize,PartialEq,Serialize)]
+pub struct Trace {
+    /// The recorded events.
+    pub events : Vec<TraceEvent>,
+}
+
+impl Trace {
+    /// Serialize the trace to a pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Serialization of a trace cannot fail.")
+    }
+
+    /// Deserialize the trace from JSON.
+    pub fn from_json(json:&str) -> Result<Self,TraceError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Save the trace as a JSON file.
+    pub fn save(&self, path:impl AsRef<Path>) -> Result<(),TraceError> {
+        Ok(std::fs::write(path,self.to_json())?)
+    }
+
+    /// Load the trace from a JSON file.
+    pub fn load(path:impl AsRef<Path>) -> Result<Self,TraceError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
+
+
+
+// ==============
+// === Tracer ===
+// ==============
+
+/// Recorder of FRP events. Keeps the last `capacity` events in a ring buffer.
+#[derive(Clone,CloneRef,Debug)]
+pub struct Tracer {
+    data : Rc<TracerData>,
+}
+
+#[derive(Debug)]
+struct TracerData {
+    capacity   : usize,
+    events     : RefCell<VecDeque<TraceEvent>>,
+    next_index : Cell<usize>,
+}
+
+impl Tracer {
+    /// Constructor.
+    pub fn new(capacity:usize) -> Self {
+        let events     = RefCell::new(VecDeque::with_capacity(capacity));
+        let next_index = default();
+        let data       = Rc::new(TracerData {capacity,events,next_index});
+        Self {data}
+    }
+
+    /// Record a new event. If the buffer is full, the oldest event is dropped.
+    pub fn record(&self, label:Label, value:&dyn Debug, stack:CallStack) {
+        let index = self.data.next_index.get();
+        self.data.next_index.set(index + 1);
+        if self.data.capacity > 0 {
+            let label      = label.into();
+            let value      = format!("{:?}",value);
+            let call_stack = stack.labels().into_iter().map(|label| label.into()).collect();
+            let mut events = self.data.events.borrow_mut();
+            if events.len() == self.data.capacity {
+                events.pop_front();
+            }
+            events.push_back(TraceEvent {index,label,value,call_stack});
+        }
+    }
+
+    /// The events recorded so far.
+    pub fn trace(&self) -> Trace {
+        let events = self.data.events.borrow().iter().cloned().collect();
+        Trace {events}
+    }
+
+    /// Remove all recorded events.
+    pub fn clear(&self) {
+        self.data.events.borrow_mut().clear()
+    }
+}
+
+
+
+// ==============
+// === Replay ===
+// ==============
+
+/// Feeds the recorded events of `Source` nodes back into a network. Every source has to be bound
+/// together with a parser of the debug representation of its values. Events of nodes which were
+/// not bound are ignored, including all events derived from the replayed ones, as they will be
+/// emitted by the network again.
+#[derive(Derivative,Default)]
+#[derivative(Debug)]
+pub struct Replay {
+    #[derivative(Debug="ignore")]
+    sources : HashMap<String,Box<dyn Fn(&str)->bool>>,
+}
+
+impl Replay {
+    /// Constructor.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// Bind the source to recorded events with the same label. The `parse` function should restore
+    /// the value from its debug representation, returning `None` if it is not possible. Does
+    /// nothing if the source was already dropped.
+    pub fn bind<T,F>(&mut self, source:&Source<T>, parse:F)
+    where T:Data, F:'static+Fn(&str)->Option<T> {
+        if let Some(node) = source.upgrade() {
+            let label  = node.label().to_string();
+            let source = source.clone_ref();
+            self.sources.insert(label,Box::new(move |value| {
+                parse(value).map(|value| source.emit(value)).is_some()
+            }));
+        }
+    }
+
+    /// Bind the source to recorded events with the same label. The values are parsed with
+    /// `FromStr`, which restores the debug representation of numbers and booleans.
+    pub fn bind_parsed<T:Data+FromStr>(&mut self, source:&Source<T>) {
+        self.bind(source,|value| value.parse().ok())
+    }
+
+    /// Emit the events of all bound sources in the recorded order. Returns the number of emitted
+    /// events. Stops at the first value which cannot be parsed.
+    pub fn run(&self, trace:&Trace) -> Result<usize,TraceError> {
+        let mut count = 0;
+        for event in &trace.events {
+            if let Some(emit) = self.sources.get(&event.label) {
+                if !emit(&event.value) {
+                    let label = event.label.clone();
+                    let value = event.value.clone();
+                    return Err(TraceError::Parse {label,value})
+                }
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use crate as frp;
+    use super::*;
+
+    fn network() -> (frp::Network,frp::Source<usize>,frp::Source<bool>,frp::Sampler<usize>) {
+        frp::new_network! { network
+            def source  = source::<usize>();
+            def enabled = source::<bool>();
+            def gated   = source.gate(&enabled);
+            def sampler = gated.sampler();
+        }
+        (network,source,enabled,sampler)
+    }
+
+    #[test]
+    fn recording() {
+        let (network,source,enabled,_) = network();
+        let tracer = network.enable_tracing(100);
+        enabled.emit(true);
+        source.emit(2_usize);
+        let trace  = tracer.trace();
+        let labels = trace.events.iter().map(|event| event.label.rsplit("::").next().unwrap());
+        let labels = labels.map(|label| label.split(':').next().unwrap()).collect_vec();
+        assert_eq!(labels,vec!["enabled","source","gated","sampler"]);
+        assert_eq!(trace.events[1].value,"2");
+        assert_eq!(trace.events.iter().map(|event| event.index).collect_vec(),vec![0,1,2,3]);
+        if cfg!(feature="stack-trace") {
+            assert_eq!(trace.events[3].call_stack.len(),3);
+        }
+        network.disable_tracing();
+        source.emit(3_usize);
+        assert_eq!(tracer.trace().events.len(),4);
+    }
+
+    #[test]
+    fn ring_buffer() {
+        let (network,source,_,_) = network();
+        let tracer = network.enable_tracing(3);
+        for i in 0..10_usize {
+            source.emit(i);
+        }
+        let trace = tracer.trace();
+        assert_eq!(trace.events.iter().map(|event| event.index).collect_vec(),vec![7,8,9]);
+        assert_eq!(trace.events[2].value,"9");
+        tracer.clear();
+        assert_eq!(tracer.trace(),Trace::default());
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let (network,source,_,_) = network();
+        let tracer = network.enable_tracing(10);
+        source.emit(1_usize);
+        let trace = tracer.trace();
+        assert_eq!(Trace::from_json(&trace.to_json()).unwrap(),trace);
+        assert!(Trace::from_json("[").is_err());
+    }
+
+    #[test]
+    fn replay() {
+        let (network,source,enabled,sampler) = network();
+        let tracer = network.enable_tracing(100);
+        source.emit(1_usize);
+        enabled.emit(true);
+        source.emit(2_usize);
+        source.emit(3_usize);
+        enabled.emit(false);
+        source.emit(4_usize);
+        assert_eq!(sampler.value(),3);
+        let trace = tracer.trace();
+
+        let (_network,source,enabled,sampler) = network();
+        let mut replay = Replay::new();
+        replay.bind_parsed(&source);
+        replay.bind_parsed(&enabled);
+        assert_eq!(replay.run(&trace).unwrap(),6);
+        assert_eq!(sampler.value(),3);
+    }
+
+    #[test]
+    fn replay_parse_error() {
+        let (network,source,_,_) = network();
+        let tracer = network.enable_tracing(100);
+        source.emit(1_usize);
+        let (_network,source,_,_) = network();
+        let mut replay = Replay::new();
+        replay.bind(&source,|_| None);
+        assert!(matches!(replay.run(&tracer.trace()),Err(TraceError::Parse{..})));
+    }
+}
@@ -0,0 +1,348 @@
+//! Glitch-free transactional propagation of events.
+//!
+//! By default, events are pushed through the network depth-first: every node processes an event
+//! as soon as it is emitted by any of its inputs. If an input fans out and the branches reconverge,
+//! the node where they meet sees intermediate states. For example, a node defined as
+//! `all_with(&source.map(f),&source.map(g),h)` fires twice for every event of `source`, and the
+//! first time it uses the new value of one branch and the old value of the other one.
+//!
+//! In the transactional mode (see `Network::set_propagation`), an event entering the network starts
+//! a transaction. Instead of being processed immediately, events received by nodes of the network
+//! are scheduled, and the scheduled nodes are processed in the topological order. All transactional
+//! networks share the transaction, so networks connected together should use the same mode. An
+//! event passing from a network in the push mode to a transactional one is processed in a separate
+//! transaction for every node receiving it. If a node receives several events in a single
+//! transaction, only the last one is processed, so every node fires at most once per transaction
+//! and sees consistent values of all of its inputs. In particular, simultaneous events merged by
+//! `any` are reduced to the one which was received last.
+//!
+//! The topological order is approximated by the node creation order, which is always correct for
+//! nodes connected at construction time. Connections created later, like the ones made with
+//! `any_mut` or by higher-order nodes, can deliver an event to a node which has already fired in
+//! the current transaction. Such an event is dropped with a warning, the same way as events
+//! reentering a node in the push mode, because otherwise a feedback loop would never finish the
+//! transaction.
+//! Event targets which are not FRP nodes, like the streams created by the `future` module, are
+//! notified immediately.
+
+use crate::prelude::*;
+
+use std::collections::BTreeMap;
+
+
+
+// ============
+// === Rank ===
+// ============
+
+/// Position of a node in the topological order. Nodes created later have higher ranks.
+pub type Rank = usize;
+
+thread_local! {
+    static NEXT_RANK : Cell<Rank> = Cell::new(0);
+}
+
+/// Get the rank for a newly created node.
+pub fn next_rank() -> Rank {
+    NEXT_RANK.with(|next| {
+        let rank = next.get();
+        next.set(rank + 1);
+        rank
+    })
+}
+
+
+
+// ===================
+// === Propagation ===
+// ===================
+
+/// Mode of event propagation in a network.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub enum Propagation {
+    /// Every event is processed as soon as it is emitted, depth-first.
+    Push,
+    /// Events are processed in the topological order, each node at most once per transaction.
+    Transactional,
+}
+
+impl Default for Propagation {
+    fn default() -> Self {
+        Self::Push
+    }
+}
+
+
+
+// =================
+// === Scheduler ===
+// =================
+
+thread_local! {
+    static SCHEDULER : Scheduler = default();
+}
+
+/// Queue of nodes waiting to be processed in the current transaction. It is shared by all networks
+/// working in the transactional mode, so an event passing through several of them is processed in
+/// a single transaction.
+#[derive(Derivative,Default)]
+#[derivative(Debug)]
+struct Scheduler {
+    running : Cell<bool>,
+    #[derivative(Debug="ignore")]
+    pending : RefCell<BTreeMap<Rank,Box<dyn FnOnce()>>>,
+    /// Nodes which have already been processed in the current transaction.
+    fired   : RefCell<HashSet<Rank>>,
+}
+
+impl Scheduler {
+    fn next_task(&self) -> Option<Box<dyn FnOnce()>> {
+        let mut pending = self.pending.borrow_mut();
+        let rank        = pending.keys().next().copied()?;
+        self.fired.borrow_mut().insert(rank);
+        pending.remove(&rank)
+    }
+
+    /// Finish the transaction. Pending tasks are discarded, so a transaction interrupted by a panic
+    /// does not leak them to the next one.
+    fn finish(&self) {
+        self.pending.borrow_mut().clear();
+        self.fired.borrow_mut().clear();
+        self.running.set(false);
+    }
+}
+
+/// Finishes the transaction when dropped, also while unwinding from a panicking task.
+struct TransactionGuard;
+
+impl Drop for TransactionGuard {
+    fn drop(&mut self) {
+        SCHEDULER.with(|scheduler| scheduler.finish());
+    }
+}
+
+/// Check whether a transaction is in progress.
+pub fn is_running() -> bool {
+    SCHEDULER.with(|scheduler| scheduler.running.get())
+}
+
+/// Schedule the node of the given rank to be processed in the current transaction. If the node was
+/// already scheduled, its previous task is replaced. If the node has already been processed in the
+/// current transaction, the task is dropped and `false` is returned.
+pub fn schedule(rank:Rank, task:impl FnOnce()+'static) -> bool {
+    SCHEDULER.with(|scheduler| {
+        let fired = scheduler.fired.borrow().contains(&rank);
+        if !fired {
+            scheduler.pending.borrow_mut().insert(rank,Box::new(task));
+        }
+        !fired
+    })
+}
+
+/// Run the function in a transaction. If no transaction is in progress, a new one is started, and
+/// all nodes scheduled by the function are processed before returning. Otherwise, the function is
+/// just a part of the current transaction.
+pub fn transaction(f:impl FnOnce()) {
+    if is_running() {
+        f()
+    } else {
+        SCHEDULER.with(|scheduler| scheduler.running.set(true));
+        let _guard = TransactionGuard;
+        f();
+        while let Some(task) = SCHEDULER.with(|scheduler| scheduler.next_task()) {
+            task()
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use crate as frp;
+    use crate::prelude::*;
+    use super::Propagation;
+
+    fn diamond(propagation:Propagation) -> Vec<(usize,usize)> {
+        let received = Rc::new(RefCell::new(vec![]));
+        frp::new_network! { network
+            def source  = source::<usize>();
+            def left    = source.map(|t| *t);
+            def right   = source.map(|t| *t * 10);
+            def both    = left.all_with(&right,|l,r| (*l,*r));
+            eval both ((t) received.borrow_mut().push(*t));
+        }
+        network.set_propagation(propagation);
+        source.emit(1_usize);
+        source.emit(2_usize);
+        let out = received.borrow().clone();
+        out
+    }
+
+    #[test]
+    fn push_propagation_glitches() {
+        assert_eq!(diamond(Propagation::Push),vec![(1,0),(1,10),(2,10),(2,20)]);
+    }
+
+    #[test]
+    fn transactional_propagation() {
+        assert_eq!(diamond(Propagation::Transactional),vec![(1,10),(2,20)]);
+    }
+
+    #[test]
+    fn nodes_registered_after_switching_mode() {
+        let received = Rc::new(RefCell::new(vec![]));
+        let network  = frp::Network::new("network");
+        network.set_propagation(Propagation::Transactional);
+        frp::extend! { network
+            def source = source::<usize>();
+            def double = source.map(|t| *t * 2);
+            merged <- any(source,double);
+            eval merged ((t) received.borrow_mut().push(*t));
+        }
+        source.emit(1_usize);
+        assert_eq!(*received.borrow(),vec![2]);
+        network.set_propagation(Propagation::Push);
+        source.emit(2_usize);
+        assert_eq!(*received.borrow(),vec![2,4,2]);
+    }
+
+    #[test]
+    fn recursive_network() {
+        let received = Rc::new(RefCell::new(vec![]));
+        let network  = frp::Network::new("network");
+        network.set_propagation(Propagation::Transactional);
+        frp::extend! { network
+            def source = source::<usize>();
+            looped     <- any_mut::<usize>();
+            looped     <+ source;
+            def next   = looped.map(|t| *t + 1);
+            looped     <+ next;
+            eval next ((t) received.borrow_mut().push(*t));
+        }
+        source.emit(1_usize);
+        source.emit(5_usize);
+        assert_eq!(*received.borrow(),vec![2,6]);
+    }
+
+    #[test]
+    fn transaction_after_panic() {
+        let received = Rc::new(RefCell::new(vec![]));
+        let network  = frp::Network::new("network");
+        network.set_propagation(Propagation::Transactional);
+        frp::extend! { network
+            def source = source::<usize>();
+            def output = source.map(|t| if *t == 0 { panic!("Test panic.") } else { *t });
+            eval output ((t) received.borrow_mut().push(*t));
+        }
+        let emit   = std::panic::AssertUnwindSafe(|| source.emit(0_usize));
+        let result = std::panic::catch_unwind(emit);
+        assert!(result.is_err());
+        assert!(!super::is_running());
+        source.emit(1_usize);
+        assert_eq!(*received.borrow(),vec![1]);
+    }
+
+    #[test]
+    fn events_from_other_networks() {
+        let received = Rc::new(RefCell::new(vec![]));
+        frp::new_network! { network1
+            def source = source::<usize>();
+        }
+        frp::new_network! { network2
+            def left  = source.map(|t| *t + 1);
+            def right = source.map(|t| *t + 2);
+            def sum   = left.all_with(&right,|l,r| l + r);
+            eval sum ((t) received.borrow_mut().push(*t));
+        }
+        network2.set_propagation(Propagation::Transactional);
+        source.emit(1_usize);
+        assert_eq!(*received.borrow(),vec![2,5]);
+        network1.set_propagation(Propagation::Transactional);
+        source.emit(2_usize);
+        assert_eq!(*received.borrow(),vec![2,5,7]);
+    }
+}
+
+
+
+// ==================
+// === Benchmarks ===
+// ==================
+
+#[cfg(test)]
+mod benchmarks {
+    use crate as frp;
+    use super::Propagation;
+    use test::Bencher;
+
+    const EVENTS : usize = 100;
+
+    /// A chain of diamonds. Every diamond doubles the number of events in the push mode, so the
+    /// chain is kept short.
+    fn diamonds(propagation:Propagation, bencher:&mut Bencher) {
+        frp::new_network! { network
+            def source = source::<usize>();
+            def a1     = source.map(|t| t + 1);
+            def a2     = source.map(|t| t + 2);
+            def a      = a1.all_with(&a2,|x,y| x + y);
+            def b1     = a.map(|t| t + 1);
+            def b2     = a.map(|t| t + 2);
+            def b      = b1.all_with(&b2,|x,y| x + y);
+            def c1     = b.map(|t| t + 1);
+            def c2     = b.map(|t| t + 2);
+            def c      = c1.all_with(&c2,|x,y| x + y);
+            def out    = c.sampler();
+        }
+        network.set_propagation(propagation);
+        bencher.iter(|| {
+            for i in 0..test::black_box(EVENTS) {
+                source.emit(i);
+            }
+            out.value()
+        });
+    }
+
+    /// A linear chain of maps, where both modes process the same number of events.
+    fn chain(propagation:Propagation, bencher:&mut Bencher) {
+        frp::new_network! { network
+            def source = source::<usize>();
+            def a      = source.map(|t| t + 1);
+            def b      = a.map(|t| t + 1);
+            def c      = b.map(|t| t + 1);
+            def d      = c.map(|t| t + 1);
+            def out    = d.sampler();
+        }
+        network.set_propagation(propagation);
+        bencher.iter(|| {
+            for i in 0..test::black_box(EVENTS) {
+                source.emit(i);
+            }
+            out.value()
+        });
+    }
+
+    #[bench]
+    fn diamonds_push(bencher:&mut Bencher) {
+        diamonds(Propagation::Push,bencher)
+    }
+
+    #[bench]
+    fn diamonds_transactional(bencher:&mut Bencher) {
+        diamonds(Propagation::Transactional,bencher)
+    }
+
+    #[bench]
+    fn chain_push(bencher:&mut Bencher) {
+        chain(Propagation::Push,bencher)
+    }
+
+    #[bench]
+    fn chain_transactional(bencher:&mut Bencher) {
+        chain(Propagation::Transactional,bencher)
+    }
+}
@@ -0,0 +1,201 @@
+//! Scoring patterns against items having several named fields.
+//!
+//! Search entries usually have more than one text describing them: a name, a module, some
+//! documentation, etc. The pattern is matched against each field separately, and the score of every
+//! matching field is multiplied by the field's weight, so the hits in the name can be ranked above
+//! the hits in documentation. The best weighted field represents the whole item.
+
+use crate::prelude::*;
+
+use crate::metric::Metric;
+use crate::score::Subsequence;
+use crate::score::find_best_subsequence;
+use crate::score::matches;
+
+
+
+// =============
+// === Field ===
+// =============
+
+/// A single named field of a searched item.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub struct Field<'a> {
+    /// The name of the field, used to look up its weight.
+    pub name : &'a str,
+    /// The field's content.
+    pub text : &'a str,
+}
+
+impl<'a> Field<'a> {
+    /// Constructor.
+    pub fn new(name:&'a str, text:&'a str) -> Self {
+        Self {name,text}
+    }
+}
+
+
+
+// ===============
+// === Weights ===
+// ===============
+
+/// Weights of fields, identified by their names. Fields without a weight set explicitly have the
+/// default weight. Fields with non-positive weights are not matched at all.
+#[derive(Clone,Debug)]
+pub struct Weights {
+    weights        : HashMap<String,f32>,
+    default_weight : f32,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl Weights {
+    /// Constructor.
+    pub fn new(default_weight:f32) -> Self {
+        let weights = default();
+        Self {weights,default_weight}
+    }
+
+    /// Set the weight of the field.
+    pub fn set(&mut self, field:impl Into<String>, weight:f32) {
+        self.weights.insert(field.into(),weight);
+    }
+
+    /// Builder-style version of `set`.
+    pub fn with(mut self, field:impl Into<String>, weight:f32) -> Self {
+        self.set(field,weight);
+        self
+    }
+
+    /// The weight of the field.
+    pub fn get(&self, field:&str) -> f32 {
+        self.weights.get(field).copied().unwrap_or(self.default_weight)
+    }
+}
+
+
+
+// ==================
+// === FieldMatch ===
+// ==================
+
+/// The pattern match in the best field of an item.
+#[derive(Clone,Debug,PartialEq)]
+pub struct FieldMatch {
+    /// The name of the best field.
+    pub field       : String,
+    /// The weighted score, used to compare items.
+    pub score       : f32,
+    /// The best subsequence of the field's text, with the unweighted score.
+    pub subsequence : Subsequence,
+}
+
+impl FieldMatch {
+    /// Ranges of chars of the field's text matching the pattern, useful for highlighting.
+    pub fn char_ranges(&self) -> Vec<Range<usize>> {
+        self.subsequence.char_ranges()
+    }
+
+    /// Compare weighted scores of the matches. See also `Subsequence::compare_scores`.
+    pub fn compare_scores(&self, rhs:&FieldMatch) -> std::cmp::Ordering {
+        let as_subsequence = |field_match:&FieldMatch| {
+            Subsequence {score:field_match.score, indices:default()}
+        };
+        as_subsequence(self).compare_scores(&as_subsequence(rhs))
+    }
+}
+
+/// Find the field best matching the pattern, according to the `metric` and field's `weights`.
+///
+/// Returns `None` if the pattern matches no field. From the fields with equal weighted scores, the
+/// first one is returned.
+pub fn find_best_field<'a>
+( fields  : impl IntoIterator<Item=Field<'a>>
+, pattern : impl Str
+, metric  : impl Metric
+, weights : &Weights
+) -> Option<FieldMatch> {
+    let pattern = pattern.as_ref();
+    let mut best : Option<FieldMatch> = None;
+    for field in fields {
+        let weight = weights.get(field.name);
+        if weight > 0.0 && matches(field.text,pattern) {
+            if let Some(subsequence) = find_best_subsequence(field.text,pattern,&metric) {
+                let score     = subsequence.score * weight;
+                let field     = field.name.to_string();
+                let candidate = FieldMatch {field,score,subsequence};
+                let is_better = best.as_ref().map_or(true,|best| {
+                    candidate.compare_scores(best) == std::cmp::Ordering::Greater
+                });
+                if is_better {
+                    best = Some(candidate);
+                }
+            }
+        }
+    }
+    best
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn weights() -> Weights {
+        Weights::new(0.5).with("name",2.0).with("documentation",0.25).with("tags",0.0)
+    }
+
+    #[test]
+    fn weights_lookup() {
+        let weights = weights();
+        assert_eq!(weights.get("name")         ,2.0);
+        assert_eq!(weights.get("documentation"),0.25);
+        assert_eq!(weights.get("module")       ,0.5);
+        assert_eq!(Weights::default().get("name"),1.0);
+    }
+
+    #[test]
+    fn finding_best_field() {
+        let metric  = crate::metric::default;
+        let filter  = vec!
+            [ Field::new("name"         , "filter")
+            , Field::new("self_type"    , "Table")
+            , Field::new("documentation", "Select rows where the filter is true.")
+            ];
+        let select  = vec!
+            [ Field::new("name"         , "select_columns")
+            , Field::new("self_type"    , "Table")
+            , Field::new("documentation", "Select columns, unlike filter.")
+            ];
+        let filter_match = find_best_field(filter,"filter",metric(),&weights()).unwrap();
+        let select_match = find_best_field(select,"filter",metric(),&weights()).unwrap();
+        assert_eq!(filter_match.field,"name");
+        assert_eq!(filter_match.char_ranges(),vec![0..6]);
+        assert_eq!(select_match.field,"documentation");
+        assert_eq!(select_match.char_ranges(),vec![23..29]);
+        assert_eq!(filter_match.compare_scores(&select_match),std::cmp::Ordering::Greater);
+
+        let expected = find_best_subsequence("filter","filter",metric()).unwrap();
+        assert_eq!(filter_match.subsequence,expected);
+        assert_eq!(filter_match.score,expected.score * 2.0);
+    }
+
+    #[test]
+    fn ignoring_fields() {
+        let metric = crate::metric::default;
+        let fields = vec![Field::new("name","map"),Field::new("tags","filter")];
+        assert_eq!(find_best_field(fields.clone(),"fil",metric(),&weights()),None);
+        assert_eq!(find_best_field(fields,"fil",metric(),&Weights::default()).unwrap().field,"tags");
+        assert_eq!(find_best_field(vec![],"fil",metric(),&weights()),None);
+    }
+}
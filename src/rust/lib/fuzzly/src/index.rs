@@ -0,0 +1,342 @@
+//! An index of texts allowing fast fuzzy search in large candidate sets.
+//!
+//! The text matches the pattern only if it contains all pattern's chars, so the `Index` keeps, for
+//! each char, the list of texts containing it. Searching starts from intersecting the lists of the
+//! pattern chars, and only the remaining candidates are checked and scored. N-gram indexes are not
+//! used, because the pattern chars do not need to be adjacent in the matched text.
+//!
+//! When the user types the pattern, the `Search` structure keeps the matches of the previous
+//! pattern. If the previous pattern is a subsequence of the new one (for example, when the pattern
+//! was extended), only the previous matches need to be re-filtered.
+
+use crate::prelude::*;
+
+use crate::metric::Metric;
+use crate::score::Subsequence;
+use crate::score::find_best_subsequence;
+use crate::score::matches;
+
+use std::iter::FromIterator;
+
+
+
+// =============
+// === Index ===
+// =============
+
+/// Identifier of an entry in the `Index`, being its insertion order.
+pub type EntryId = usize;
+
+/// An index of texts with associated items. See the module docs to learn more.
+#[derive(Clone,Debug)]
+pub struct Index<T> {
+    entries : Vec<(String,T)>,
+    chars   : HashMap<char,Vec<EntryId>>,
+}
+
+impl<T> Default for Index<T> {
+    fn default() -> Self {
+        let entries = default();
+        let chars   = default();
+        Self {entries,chars}
+    }
+}
+
+impl<T> Index<T> {
+    /// Constructor.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// Add a new entry, returning its id.
+    pub fn insert(&mut self, text:impl Into<String>, item:T) -> EntryId {
+        let id   = self.entries.len();
+        let text = text.into();
+        for ch in text.chars().map(|ch| ch.to_ascii_lowercase()).unique() {
+            self.chars.entry(ch).or_default().push(id);
+        }
+        self.entries.push((text,item));
+        id
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the index has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The text of the entry.
+    pub fn text(&self, id:EntryId) -> Option<&str> {
+        self.entries.get(id).map(|(text,_)| text.as_str())
+    }
+
+    /// The item of the entry.
+    pub fn item(&self, id:EntryId) -> Option<&T> {
+        self.entries.get(id).map(|(_,item)| item)
+    }
+
+    /// Ids of all entries containing all chars of the pattern, ignoring the ASCII case. These are
+    /// the only entries which may match the pattern.
+    pub fn candidates(&self, pattern:&str) -> Vec<EntryId> {
+        let chars = pattern.chars().map(|ch| ch.to_ascii_lowercase()).unique();
+        let lists = chars.map(|ch| self.chars.get(&ch).map_or(&[][..],|ids| ids.as_slice()));
+        let mut lists = lists.collect_vec();
+        lists.sort_by_key(|ids| ids.len());
+        match lists.split_first() {
+            None               => (0..self.len()).collect(),
+            Some((first,rest)) => first.iter().copied().filter(|id| {
+                rest.iter().all(|ids| ids.binary_search(id).is_ok())
+            }).collect(),
+        }
+    }
+
+    /// Ids of all entries matching the pattern, in the insertion order.
+    pub fn matching(&self, pattern:&str) -> Vec<EntryId> {
+        self.filter(self.candidates(pattern),pattern)
+    }
+
+    /// Find the `limit` best matches of the pattern. The scores are the same as returned by
+    /// `find_best_subsequence` for every entry, and matches with equal scores are ordered by
+    /// their ids.
+    pub fn search(&self, pattern:&str, metric:impl Metric, limit:usize) -> Vec<Match> {
+        self.best_matches(self.matching(pattern),pattern,metric,limit)
+    }
+
+    fn filter(&self, ids:impl IntoIterator<Item=EntryId>, pattern:&str) -> Vec<EntryId> {
+        ids.into_iter().filter(|id| matches(&self.entries[*id].0,pattern)).collect()
+    }
+
+    fn best_matches
+    (&self, ids:Vec<EntryId>, pattern:&str, metric:impl Metric, limit:usize) -> Vec<Match> {
+        let found = ids.into_iter().filter_map(|id| {
+            let subsequence = find_best_subsequence(&self.entries[id].0,pattern,&metric)?;
+            Some(Match {id,subsequence})
+        });
+        let mut found = found.collect_vec();
+        let order     = |lhs:&Match, rhs:&Match| {
+            rhs.subsequence.compare_scores(&lhs.subsequence).then(lhs.id.cmp(&rhs.id))
+        };
+        if found.len() > limit && limit > 0 {
+            found.select_nth_unstable_by(limit - 1,order);
+        }
+        found.truncate(limit);
+        found.sort_by(order);
+        found
+    }
+}
+
+impl<S:Into<String>,T> FromIterator<(S,T)> for Index<T> {
+    fn from_iter<I:IntoIterator<Item=(S,T)>>(iter:I) -> Self {
+        let mut index = Self::new();
+        for (text,item) in iter {
+            index.insert(text,item);
+        }
+        index
+    }
+}
+
+
+
+// =============
+// === Match ===
+// =============
+
+/// A single search result.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Match {
+    /// The id of the matched entry.
+    pub id          : EntryId,
+    /// The best subsequence of the entry's text matching the pattern.
+    pub subsequence : Subsequence,
+}
+
+
+
+// ==============
+// === Search ===
+// ==============
+
+/// An incremental search in an `Index`. It remembers the matches of the last pattern, so the
+/// search for the next pattern re-filters them only, if possible. The entries added to the index
+/// after the last search are also taken into account.
+///
+/// The search should be used with a single index only.
+#[derive(Clone,Debug,Default)]
+pub struct Search {
+    pattern  : String,
+    matching : Vec<EntryId>,
+    /// The number of entries in the index during the last search.
+    indexed  : usize,
+}
+
+impl Search {
+    /// Constructor.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// The last searched pattern.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Ids of all entries matching the last pattern.
+    pub fn matching(&self) -> &[EntryId] {
+        &self.matching
+    }
+
+    /// Set the new pattern and update the matching entries.
+    pub fn set_pattern<T>(&mut self, index:&Index<T>, pattern:&str) -> &[EntryId] {
+        let refinement = self.indexed > 0 && matches(pattern,&self.pattern);
+        self.matching  = if refinement {
+            let previous = std::mem::take(&mut self.matching);
+            let added    = self.indexed..index.len();
+            index.filter(previous.into_iter().chain(added),pattern)
+        } else {
+            index.matching(pattern)
+        };
+        self.pattern = pattern.into();
+        self.indexed = index.len();
+        &self.matching
+    }
+
+    /// Find the `limit` best matches of the last pattern. See `Index::search` to learn more.
+    pub fn best_matches<T>(&self, index:&Index<T>, metric:impl Metric, limit:usize) -> Vec<Match> {
+        index.best_matches(self.matching.clone(),&self.pattern,metric,limit)
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn index() -> Index<usize> {
+        let names = ["filter","fold","map","flatten","Filter Column","reverse","to_text"];
+        names.iter().enumerate().map(|(item,name)| (*name,item)).collect()
+    }
+
+    #[test]
+    fn finding_candidates() {
+        let index = index();
+        assert_eq!(index.candidates("fl")   ,vec![0,1,3,4]);
+        assert_eq!(index.candidates("lf")   ,vec![0,1,3,4]);
+        assert_eq!(index.matching("lf")     ,Vec::<EntryId>::new());
+        assert_eq!(index.candidates("FLT")  ,vec![0,3,4]);
+        assert_eq!(index.candidates("")     ,(0..7).collect_vec());
+        assert_eq!(index.candidates("q")    ,Vec::<EntryId>::new());
+        assert_eq!(index.text(4)            ,Some("Filter Column"));
+        assert_eq!(index.item(4)            ,Some(&4));
+    }
+
+    #[test]
+    fn searching_keeps_scores() {
+        let index  = index();
+        let metric = crate::metric::default;
+        let found  = index.search("ft",metric(),2);
+        assert_eq!(found.len(),2);
+        for result in &found {
+            let text     = index.text(result.id).unwrap();
+            let expected = find_best_subsequence(text,"ft",metric()).unwrap();
+            assert_eq!(result.subsequence,expected);
+        }
+        let all  = index.search("ft",metric(),10);
+        assert_eq!(all.len(),3);
+        assert_eq!(all[..2],found[..]);
+        assert!(all.windows(2).all(|t| t[0].subsequence.score >= t[1].subsequence.score));
+        assert!(index.search("ft",metric(),0).is_empty());
+    }
+
+    #[test]
+    fn incremental_search() {
+        let mut index  = index();
+        let mut search = Search::new();
+        assert_eq!(search.set_pattern(&index,"f")  ,&[0,1,3,4]);
+        assert_eq!(search.set_pattern(&index,"fl") ,&[0,1,3,4]);
+        index.insert("fill",7);
+        assert_eq!(search.set_pattern(&index,"fil"),&[0,4,7]);
+        assert_eq!(search.set_pattern(&index,"fi") ,&[0,4,7]);
+        assert_eq!(search.set_pattern(&index,"t")  ,&[0,3,4,6]);
+        assert_eq!(search.pattern(),"t");
+        let found = search.best_matches(&index,crate::metric::default(),1);
+        assert_eq!(found,index.search("t",crate::metric::default(),1));
+    }
+}
+
+
+
+// ==================
+// === Benchmarks ===
+// ==================
+
+#[cfg(test)]
+mod benchmarks {
+    use super::*;
+
+    use crate::score::benchmarks::suggestion_database;
+    use test::Bencher;
+
+    const LIMIT : usize = 10;
+
+    /// Patterns typed by the user, one keystroke at a time.
+    const TYPED : &[&str] = &["f","fi","fil","filt","filte","filter","filterc","filtercol"];
+
+    /// The suggestion database of ten projects, having over twenty thousand entries.
+    fn large_database() -> Vec<String> {
+        let names = (0..10).cartesian_product(suggestion_database());
+        names.map(|(project,name)| format!("Project{}.{}",project,name)).collect()
+    }
+
+    fn large_index() -> Index<()> {
+        large_database().into_iter().map(|name| (name,())).collect()
+    }
+
+    #[bench]
+    fn linear_search(bencher:&mut Bencher) {
+        let database = large_database();
+        bencher.iter(|| {
+            TYPED.iter().map(|pattern| {
+                let found = database.iter().enumerate().filter_map(|(id,name)| {
+                    let metric      = crate::metric::default();
+                    let subsequence = find_best_subsequence(name,pattern,metric)?;
+                    Some(Match {id,subsequence})
+                });
+                let mut found = found.collect_vec();
+                found.sort_by(|lhs,rhs| rhs.subsequence.compare_scores(&lhs.subsequence));
+                found.truncate(LIMIT);
+                found
+            }).collect_vec()
+        });
+    }
+
+    #[bench]
+    fn indexed_search(bencher:&mut Bencher) {
+        let index = large_index();
+        bencher.iter(|| {
+            TYPED.iter().map(|pattern| {
+                index.search(pattern,crate::metric::default(),LIMIT)
+            }).collect_vec()
+        });
+    }
+
+    #[bench]
+    fn incremental_search(bencher:&mut Bencher) {
+        let index = large_index();
+        bencher.iter(|| {
+            let mut search = Search::new();
+            TYPED.iter().map(|pattern| {
+                search.set_pattern(&index,pattern);
+                search.best_matches(&index,crate::metric::default(),LIMIT)
+            }).collect_vec()
+        });
+    }
+}
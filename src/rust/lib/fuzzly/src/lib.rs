@@ -7,7 +7,19 @@
 //!
 //! The metrics used for scoring may be adjusted by implementing `Metric` trait, or by customizing
 //! parameters of metrics defined in `metric` module.
+//!
+//! The `find_best_subsequence_with_typos` function is a typo-tolerant variant of the matching,
+//! finding `filter` also for patterns like `fitler`. When matching many texts with the same
+//! pattern, use `TypoTolerantPattern`, which generates the pattern's corrections only once. See the
+//! `typo` module to learn more.
+//!
+//! Items described by several texts (like a name, a module and documentation) may be scored with
+//! `find_best_field`, weighting the scores of particular fields. See the `field` module.
+//!
+//! For large sets of names, the `Index` structure finds the best matches without checking every
+//! name, and `index::Search` re-filters the previous matches when the pattern is being typed.
 #![feature(option_result_contains)]
+#![feature(test)]
 
 #![warn(missing_docs)]
 #![warn(trivial_casts)]
@@ -18,13 +30,23 @@
 #![warn(missing_copy_implementations)]
 #![warn(missing_debug_implementations)]
 
+extern crate test;
+
+pub mod field;
+pub mod index;
 pub mod subsequence_graph;
 pub mod metric;
 pub mod score;
+pub mod typo;
 
 pub use enso_prelude as prelude;
+pub use field::find_best_field;
+pub use index::Index;
 pub use metric::Metric;
 pub use subsequence_graph::Graph as SubsequenceGraph;
 pub use score::Subsequence;
 pub use score::matches;
 pub use score::find_best_subsequence;
+pub use score::find_best_subsequence_with_typos;
+pub use score::matches_with_typos;
+pub use score::TypoTolerantPattern;
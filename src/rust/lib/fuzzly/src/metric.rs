@@ -2,6 +2,7 @@
 use crate::prelude::*;
 
 use crate::subsequence_graph;
+use crate::typo::Typo;
 
 
 
@@ -21,6 +22,11 @@ pub trait Metric {
     /// How good is the edge on the path on the Subsequence Graph.
     fn measure_edge(&self, edge:subsequence_graph::Edge, text:&str, pattern:&str) -> f32;
 
+    /// How good is the typo in the pattern. Measured only by the typo-tolerant matching (see
+    /// `find_best_subsequence_with_typos`) for each typo corrected in the `pattern`, so most
+    /// metrics return negative values, if any.
+    fn measure_typo(&self, _typo:Typo, _text:&str, _pattern:&str) -> f32 { 0.0 }
+
     /// Return a new metric being a sum of this and `rhs`.
     fn sum<Rhs:Metric>(self, rhs:Rhs) -> Sum<Self,Rhs> where Self:Sized { Sum(self, rhs) }
 }
@@ -33,7 +39,7 @@ pub trait Metric {
 
 /// The default metric, recommended by this library
 pub fn default() -> impl Metric {
-    SubsequentLettersBonus::default().sum(CaseMatchBonus::default())
+    SubsequentLettersBonus::default().sum(CaseMatchBonus::default()).sum(TypoPenalty::default())
 }
 
 
@@ -62,6 +68,30 @@ impl<M1:Metric, M2:Metric> Metric for Sum<M1,M2> {
         let right            = right.measure_edge(edge,text,pattern);
         left + right
     }
+
+    fn measure_typo(&self, typo:Typo, text:&str, pattern:&str) -> f32 {
+        let Self(left,right) = self;
+        let left             = left.measure_typo(typo,text,pattern);
+        let right            = right.measure_typo(typo,text,pattern);
+        left + right
+    }
+}
+
+
+// === Reference ===
+
+impl<M:Metric> Metric for &M {
+    fn measure_vertex(&self, vertex:subsequence_graph::Vertex, text:&str, pattern:&str) -> f32 {
+        M::measure_vertex(self,vertex,text,pattern)
+    }
+
+    fn measure_edge(&self, edge:subsequence_graph::Edge, text:&str, pattern:&str) -> f32 {
+        M::measure_edge(self,edge,text,pattern)
+    }
+
+    fn measure_typo(&self, typo:Typo, text:&str, pattern:&str) -> f32 {
+        M::measure_typo(self,typo,text,pattern)
+    }
 }
 
 
@@ -137,3 +167,32 @@ impl Metric for CaseMatchBonus {
 
     fn measure_edge(&self, _:subsequence_graph::Edge, _:&str, _:&str) -> f32 { 0.0 }
 }
+
+
+// === TypoPenalty ===
+
+/// A metric which lowers the score of matches for every typo corrected in the pattern. The default
+/// penalty equals the best possible measure of a single edge in `SubsequentLettersBonus`, so a
+/// typo costs at least as much as the letter it corrects may gain, and the matches with typos are
+/// ranked below the similar matches without them.
+#[derive(Copy,Clone,Debug)]
+pub struct TypoPenalty {
+    /// A score subtracted for each typo.
+    pub penalty_per_typo : f32,
+}
+
+impl Default for TypoPenalty {
+    fn default() -> Self {
+        TypoPenalty {
+            penalty_per_typo : 1.0,
+        }
+    }
+}
+
+impl Metric for TypoPenalty {
+    fn measure_vertex(&self, _:subsequence_graph::Vertex, _:&str, _:&str) -> f32 { 0.0 }
+
+    fn measure_edge(&self, _:subsequence_graph::Edge, _:&str, _:&str) -> f32 { 0.0 }
+
+    fn measure_typo(&self, _:Typo, _:&str, _:&str) -> f32 { -self.penalty_per_typo }
+}
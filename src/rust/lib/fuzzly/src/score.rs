@@ -4,6 +4,7 @@ use crate::prelude::*;
 
 use crate::metric::Metric;
 use crate::subsequence_graph;
+use crate::typo;
 use crate::SubsequenceGraph;
 
 use std::collections::hash_map::Entry;
@@ -172,6 +173,19 @@ impl Subsequence {
         else if self.score > rhs.score                    { std::cmp::Ordering::Greater }
         else                                              { std::cmp::Ordering::Equal   }
     }
+
+    /// Ranges of adjacent `indices`, useful for highlighting the matched chars. The ranges are
+    /// expressed in chars, not bytes.
+    pub fn char_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges = Vec::<Range<usize>>::new();
+        for &index in &self.indices {
+            match ranges.last_mut() {
+                Some(range) if range.end == index => range.end = index + 1,
+                _                                 => ranges.push(index..index+1),
+            }
+        }
+        ranges
+    }
 }
 
 /// Find best subsequence in `text` which case-insensitively equals to `pattern` in terms of given
@@ -216,6 +230,103 @@ pub fn find_best_subsequence
 
 
 
+
+// ===========================
+// === TypoTolerantPattern ===
+// ===========================
+
+/// A pattern matched with tolerance for typos. See the `typo` module docs to learn more about
+/// typos.
+///
+/// The corrections of the pattern are generated once, on construction, so a single instance should
+/// be used for matching all texts with the same pattern. Matching a text which does not match the
+/// pattern exactly checks all corrections (at most `typo::MAX_CORRECTIONS`) and runs the full
+/// subsequence search for every one matching the text.
+#[derive(Clone,Debug)]
+pub struct TypoTolerantPattern {
+    pattern     : String,
+    corrections : Vec<typo::Correction>,
+}
+
+impl TypoTolerantPattern {
+    /// Constructor tolerating up to `max_typos` typos (see `typo::max_typos`).
+    pub fn new(pattern:impl Str, max_typos:usize) -> Self {
+        let pattern     = pattern.into();
+        let corrections = typo::corrections(&pattern,max_typos);
+        TypoTolerantPattern {pattern,corrections}
+    }
+
+    /// The pattern as typed, without corrections.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Fast-check if the pattern or any of its corrections matches text.
+    pub fn matches(&self, text:impl Str) -> bool {
+        let text = text.as_ref();
+        matches(text,&self.pattern) || self.corrections.iter().any(|correction| {
+            matches(text,&correction.pattern)
+        })
+    }
+
+    /// Find best subsequence in `text` which case-insensitively equals to the pattern in terms of
+    /// given `metric`, tolerating typos.
+    ///
+    /// If `text` matches the pattern without typos, the result is the same as of
+    /// `find_best_subsequence`. Otherwise, the best subsequence is searched for every correction
+    /// matching `text`, and its score is adjusted by the measures of all corrected typos (see
+    /// `Metric::measure_typo`). The correction with the best adjusted score is picked. Returns
+    /// `None` if no correction matches `text`.
+    pub fn find_best_subsequence
+    (&self, text:impl Str, metric:impl Metric) -> Option<Subsequence> {
+        let text    = text.as_ref();
+        let pattern = self.pattern.as_str();
+        if matches(text,pattern) {
+            find_best_subsequence(text,pattern,metric)
+        } else {
+            let corrections = self.corrections.iter();
+            let matching    = corrections.filter(|correction| matches(text,&correction.pattern));
+            let found       = matching.filter_map(|correction| {
+                let mut subsequence = find_best_subsequence(text,&correction.pattern,&metric)?;
+                let typos           = correction.typos.iter();
+                let typos_score     = typos.map(|t| metric.measure_typo(*t,text,pattern));
+                subsequence.score  += typos_score.sum::<f32>();
+                Some(subsequence)
+            });
+            found.max_by(|lhs,rhs| lhs.compare_scores(rhs))
+        }
+    }
+}
+
+/// Fast-check if the pattern matches text, tolerating up to `max_typos` typos in the pattern. See
+/// the `typo` module docs to learn more about typos.
+///
+/// The corrections of the pattern are generated on every call; use `TypoTolerantPattern` when
+/// matching many texts.
+pub fn matches_with_typos(text:impl Str, pattern:impl Str, max_typos:usize) -> bool {
+    let text    = text.as_ref();
+    let pattern = pattern.as_ref();
+    matches(text,pattern) || TypoTolerantPattern::new(pattern,max_typos).matches(text)
+}
+
+/// Find best subsequence in `text` which case-insensitively equals to `pattern` in terms of given
+/// `metric`, tolerating up to `max_typos` typos in the pattern. See
+/// `TypoTolerantPattern::find_best_subsequence`.
+///
+/// The corrections of the pattern are generated on every call; use `TypoTolerantPattern` when
+/// matching many texts.
+pub fn find_best_subsequence_with_typos
+(text:impl Str, pattern:impl Str, metric:impl Metric, max_typos:usize) -> Option<Subsequence> {
+    let text    = text.as_ref();
+    let pattern = pattern.as_ref();
+    if matches(text,pattern) {
+        find_best_subsequence(text,pattern,metric)
+    } else {
+        TypoTolerantPattern::new(pattern,max_typos).find_best_subsequence(text,metric)
+    }
+}
+
+
 // =============
 // === Tests ===
 // =============
@@ -309,4 +420,138 @@ mod test {
         let text    = "";
         assert_eq!(find_best_subsequence(text,pattern,mock_metric::Sum::default()), None);
     }
+
+    #[test]
+    fn matching_with_typos() {
+        assert!( matches_with_typos("filter", "fitler", 1));
+        assert!( matches_with_typos("filter", "fxlter", 1));
+        assert!( matches_with_typos("filter", "flter" , 0));
+        assert!(!matches_with_typos("filter", "fitler", 0));
+        assert!(!matches_with_typos("filter", "fitlre", 1));
+        assert!( matches_with_typos("filter", "fitlre", 2));
+        assert!(!matches_with_typos("filter", "fx"    , 2));
+    }
+
+    #[test]
+    fn finding_best_subsequence_with_typos() {
+        let metric  = crate::metric::default;
+        let exact   = find_best_subsequence("filter","flter",metric()).unwrap();
+        let typo    = find_best_subsequence_with_typos("filter","flter",metric(),1).unwrap();
+        assert_eq!(typo,exact);
+
+        let typo    = find_best_subsequence_with_typos("filter","fitler",metric(),1).unwrap();
+        let correct = find_best_subsequence("filter","filter",metric()).unwrap();
+        assert_eq!(typo.indices,vec![0,1,2,3,4,5]);
+        assert!(typo.score < correct.score);
+        assert!(typo.score > exact.score);
+        assert_eq!(find_best_subsequence_with_typos("filter","fitler",metric(),0),None);
+        assert_eq!(find_best_subsequence_with_typos("map","fitler",metric(),2),None);
+    }
+
+    #[test]
+    fn typo_tolerant_pattern() {
+        let metric  = crate::metric::default;
+        let pattern = TypoTolerantPattern::new("fitler",1);
+        assert!( pattern.matches("filter"));
+        assert!( pattern.matches("fitler"));
+        assert!(!pattern.matches("map"));
+        for &text in &["filter","fitler","filter_map","map"] {
+            let expected = find_best_subsequence_with_typos(text,"fitler",metric(),1);
+            assert_eq!(pattern.find_best_subsequence(text,metric()),expected);
+        }
+    }
+
+    #[test]
+    fn char_ranges_of_subsequence() {
+        let subsequence = Subsequence {score:0.0, indices:vec![0,1,2,5,7,8]};
+        assert_eq!(subsequence.char_ranges(),vec![0..3,5..6,7..9]);
+        assert!(Subsequence::default().char_ranges().is_empty());
+    }
+}
+
+
+
+// ==================
+// === Benchmarks ===
+// ==================
+
+#[cfg(test)]
+pub(crate) mod benchmarks {
+    use super::*;
+
+    use test::Bencher;
+
+    /// Names of entries similar to the ones stored in the suggestion database of a project with
+    /// the standard library imported.
+    pub(crate) fn suggestion_database() -> Vec<String> {
+        let modules = ["Base","Text","Vector","Map","Table","Json","Http","File","Time","Math"];
+        let verbs   = ["get","set","to","from","find","filter","map","fold","sort","join","split"
+                      ,"parse","read","write","index","each"];
+        let nouns   = ["text","value","column","row","index","key","element","case","path","line"
+                      ,"name","size","date","number"];
+        let names   = modules.iter().cartesian_product(&verbs).cartesian_product(&nouns);
+        names.map(|((module,verb),noun)| format!("{}.{}_{}",module,verb,noun)).collect()
+    }
+
+    /// Match all entries of the `suggestion_database` as the searcher does.
+    ///
+    /// With typos, the corrections are generated once per iteration, as once per query in the
+    /// searcher. Then, for every entry, up to `typo::MAX_CORRECTIONS` fast checks are made, and the
+    /// full subsequence search is run for every correction matching the entry. Therefore the typo
+    /// tolerance costs the most for the patterns which do not match exactly, and its cost grows
+    /// with the number of corrections, i.e. with the pattern length and the number of typos.
+    fn bench_matching(bencher:&mut Bencher, pattern:&str, max_typos:Option<usize>) {
+        let database = suggestion_database();
+        bencher.iter(|| {
+            let metric = crate::metric::default;
+            let found  = match max_typos {
+                None => database.iter().filter_map(|name| {
+                    matches(name,pattern).and_option_from(|| {
+                        find_best_subsequence(name,pattern,metric())
+                    })
+                }).count(),
+                Some(max_typos) => {
+                    let pattern = TypoTolerantPattern::new(pattern,max_typos);
+                    database.iter().filter_map(|name| {
+                        pattern.matches(name).and_option_from(|| {
+                            pattern.find_best_subsequence(name,metric())
+                        })
+                    }).count()
+                }
+            };
+            found
+        });
+    }
+
+    #[bench]
+    fn subsequence_matching(bencher:&mut Bencher) {
+        bench_matching(bencher,"fltrcol",None);
+    }
+
+    #[bench]
+    fn typo_tolerant_matching_of_correct_pattern(bencher:&mut Bencher) {
+        bench_matching(bencher,"fltrcol",Some(2));
+    }
+
+    #[bench]
+    fn typo_tolerant_matching_with_one_typo(bencher:&mut Bencher) {
+        bench_matching(bencher,"fitlercol",Some(1));
+    }
+
+    #[bench]
+    fn typo_tolerant_matching_with_two_typos(bencher:&mut Bencher) {
+        bench_matching(bencher,"fitlercolmun",Some(2));
+    }
+
+    /// The pattern is long enough to be bounded by `typo::MAX_CORRECTIONS`.
+    #[bench]
+    fn typo_tolerant_matching_of_long_pattern(bencher:&mut Bencher) {
+        bench_matching(bencher,"tabel.fitler_colmun",Some(3));
+    }
+
+    /// Generating the corrections alone, done once per query.
+    #[bench]
+    fn generating_corrections_of_long_pattern(bencher:&mut Bencher) {
+        bencher.iter(|| typo::corrections("tabel.fitler_colmun",3).len());
+    }
 }
@@ -0,0 +1,558 @@
+//! Scoring how given text matches the given pattern.
+
+use crate::prelude::*;
+use std::ops::Range;
+
+use crate::metric::Metric;
+use crate::subsequence_graph;
+use crate::typo;
+use crate::SubsequenceGraph;
+
+use std::collections::hash_map::Entry;
+
+
+
+// =====================
+// === VerticesScore ===
+// =====================
+
+/// The description of path which finishes at some specific vertex.
+#[derive(Clone,Copy,Debug)]
+struct InputPath {
+    value : f32,
+    from  : subsequence_graph::Vertex,
+}
+
+/// The score of single vertex in graph.
+///
+/// The score is a sum of measure of the vertex alone, and the best score of input path.
+/// The `best_input_path` is updated during the scoring algorithm run. See the `score_match`
+/// function.
+#[derive(Copy,Clone,Debug,Default)]
+struct VertexScore {
+    my_measure      : f32,
+    best_input_path : Option<InputPath>,
+}
+
+impl VertexScore {
+    fn new(my_measure:f32) -> Self {
+        let best_input_path = default();
+        VertexScore {my_measure,best_input_path}
+    }
+
+    fn update_input_path(&mut self, candidate:InputPath) {
+        let new_score = match self.best_input_path.take() {
+            Some(score) if score.value < candidate.value => candidate,
+            Some(score)                                  => score,
+            None                                         => candidate,
+        };
+        self.best_input_path = Some(new_score)
+    }
+
+    fn score(&self) -> f32 {
+        self.my_measure + self.best_input_path.map_or(0.0, |s| s.value)
+    }
+}
+
+/// All graph's vertices' scores.
+///
+/// Used in the `score_match` function.
+#[derive(Debug,Default)]
+struct VerticesScores(HashMap<subsequence_graph::Vertex,VertexScore>);
+
+impl VerticesScores {
+    fn init_vertex(&mut self, vertex:subsequence_graph::Vertex, measure:f32) {
+        let Self(scores) = self;
+        scores.insert(vertex,VertexScore::new(measure));
+    }
+
+    fn update_input_path(&mut self, edge:subsequence_graph::Edge, value:f32) {
+        let Self(scores)                     = self;
+        let subsequence_graph::Edge{from,to} = edge;
+        let candidate                        = InputPath{value,from};
+        match scores.entry(to) {
+            Entry::Occupied(mut entry) => { entry.get_mut().update_input_path(candidate) }
+            Entry::Vacant(entry)   => {
+                let mut vertex = VertexScore::default();
+                vertex.update_input_path(candidate);
+                entry.insert(vertex);
+            }
+        }
+    }
+
+    fn get_score(&self, vertex:subsequence_graph::Vertex) -> f32 {
+        let Self(scores) = self;
+        scores.get(&vertex).map(|v| v.score()).unwrap_or(0.0)
+    }
+
+    fn best_vertex
+    (&self, vertices:impl Iterator<Item=subsequence_graph::Vertex>)
+    -> Option<subsequence_graph::Vertex> {
+        let pairs     = vertices.map(|v| (v,self.get_score(v)));
+        let best_pair = pairs.fold(None, |prev,(vertex,score)| {
+            match prev {
+                Some((_,prev_score)) if score > prev_score => Some((vertex,score)),
+                Some(prev)                                 => Some(prev),
+                None                                       => Some((vertex,score)),
+            }
+        });
+        best_pair.map(|(vertex,_)| vertex)
+    }
+
+    fn best_path_rev(&self, end:subsequence_graph::Vertex) -> BestPathRevIter {
+        BestPathRevIter {
+            scores      : self,
+            next_vertex : Some(end),
+        }
+    }
+}
+
+struct BestPathRevIter<'a> {
+    scores      : &'a VerticesScores,
+    next_vertex : Option<subsequence_graph::Vertex>
+}
+
+impl<'a> Iterator for BestPathRevIter<'a> {
+    type Item = subsequence_graph::Vertex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = std::mem::take(&mut self.next_vertex);
+        self.next_vertex = (|| {
+            let VerticesScores(scores) = self.scores;
+            Some(scores.get(&next?)?.best_input_path?.from)
+        })();
+        next
+    }
+}
+
+
+
+// ===================
+// === Score Match ===
+// ===================
+
+/// Fast-check if the pattern matches text.
+///
+/// This is faster way than calling `score_match(text,pattern,metric).is_some()`, therefore it's
+/// recommended to call this function before scoring when we are not sure if the pattern actually
+/// matches the text.
+pub fn matches(text:impl Str, pattern:impl Str) -> bool {
+    let mut pattern_chars     = pattern.as_ref().chars();
+    let mut next_pattern_char = pattern_chars.next();
+    for text_char in text.as_ref().chars() {
+        match next_pattern_char {
+            Some(ch) if ch.eq_ignore_ascii_case(&text_char) => {
+                next_pattern_char = pattern_chars.next()
+            },
+            Some(_) => {},
+            None    => { break; }
+        }
+    }
+    next_pattern_char.is_none()
+}
+
+/// The result of `find_best_subsequence` function.
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct Subsequence {
+    /// The score of found subsequence.
+    pub score:f32,
+    /// Indices of `text`'s chars which belong to the subsequence.
+    pub indices:Vec<usize>
+}
+
+impl Subsequence {
+    /// Compare scores of subsequences.
+    ///
+    /// The `f32` does not implement total ordering, however that does not help when we want to
+    /// sort items by their matching score. Therefore this function assumes that all NaNs are the
+    /// lowest values.
+    pub fn compare_scores(&self, rhs:&Subsequence) -> std::cmp::Ordering {
+        if      self.score.is_nan() && rhs.score.is_nan() { std::cmp::Ordering::Equal   }
+        else if self.score.is_nan()                       { std::cmp::Ordering::Less    }
+        else if rhs.score.is_nan()                        { std::cmp::Ordering::Greater }
+        else if self.score < rhs.score                    { std::cmp::Ordering::Less    }
+        else if self.score > rhs.score                    { std::cmp::Ordering::Greater }
+        else                                              { std::cmp::Ordering::Equal   }
+    }
+
+    /// Ranges of adjacent `indices`, useful for highlighting the matched chars. The ranges are
+    /// expressed in chars, not bytes.
+    pub fn char_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges = Vec::<Range<usize>>::new();
+        for &index in &self.indices {
+            match ranges.last_mut() {
+                Some(range) if range.end == index => range.end = index + 1,
+                _                                 => ranges.push(index..index+1),
+            }
+        }
+        ranges
+    }
+}
+
+/// Find best subsequence in `text` which case-insensitively equals to `pattern` in terms of given
+/// `metric`.
+///
+/// Returns `None` if `text` does not match `pattern`. Empty `pattern` gives 0.0 score.
+///
+/// ## Algorithm specification
+///
+/// In essence, it looks through all possible subsequences of `text` being the `pattern` and pick
+/// the one with the best score. Not directly (because there may be a lot of such subsequences), but
+/// by building the `SubsequenceGraph` and computing best score for each vertex. See
+/// `SubsequenceGraph` docs for detailed description of the graph.
+pub fn find_best_subsequence
+(text:impl Str, pattern:impl Str, metric:impl Metric) -> Option<Subsequence> {
+    let text    = text.as_ref();
+    let pattern = pattern.as_ref();
+    if pattern.is_empty() {
+        Some(default())
+    } else {
+        let last_layer = pattern.chars().count() - 1;
+        let mut scores = VerticesScores::default();
+        let graph      = SubsequenceGraph::new(text,pattern);
+        for vertex in &graph.vertices {
+            let measure = metric.measure_vertex(*vertex,text,pattern);
+            scores.init_vertex(*vertex,measure);
+        }
+        for edge in &graph.edges {
+            let from_score  = scores.get_score(edge.from);
+            let input_score = from_score + metric.measure_edge(*edge,text,pattern);
+            scores.update_input_path(*edge,input_score);
+        }
+        let end_vertices  = graph.vertices_in_layer(last_layer).cloned();
+        let best_vertex   = scores.best_vertex(end_vertices)?;
+        let score         = scores.get_score(best_vertex);
+        let best_path_rev = scores.best_path_rev(best_vertex);
+        let mut indices   = best_path_rev.map(|v| v.position_in_text).collect_vec();
+        indices.reverse();
+        Some(Subsequence {score,indices})
+    }
+}
+
+
+
+
+// ===========================
+// === TypoTolerantPattern ===
+// ===========================
+
+/// A pattern matched with tolerance for typos. See the `typo` module docs to learn more about
+/// typos.
+///
+/// The corrections of the pattern are generated once, on construction, so a single instance should
+/// be used for matching all texts with the same pattern. Matching a text which does not match the
+/// pattern exactly checks all corrections (at most `typo::MAX_CORRECTIONS`) and runs the full
+/// subsequence search for every one matching the text.
+#[derive(Clone,Debug)]
+pub struct TypoTolerantPattern {
+    pattern     : String,
+    corrections : Vec<typo::Correction>,
+}
+
+impl TypoTolerantPattern {
+    /// Constructor tolerating up to `max_typos` typos (see `typo::max_typos`).
+    pub fn new(pattern:impl Str, max_typos:usize) -> Self {
+        let pattern     = pattern.into();
+        let corrections = typo::corrections(&pattern,max_typos);
+        TypoTolerantPattern {pattern,corrections}
+    }
+
+    /// The pattern as typed, without corrections.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Fast-check if the pattern or any of its corrections matches text.
+    pub fn matches(&self, text:impl Str) -> bool {
+        let text = text.as_ref();
+        matches(text,&self.pattern) || self.corrections.iter().any(|correction| {
+            matches(text,&correction.pattern)
+        })
+    }
+
+    /// Find best subsequence in `text` which case-insensitively equals to the pattern in terms of
+    /// given `metric`, tolerating typos.
+    ///
+    /// If `text` matches the pattern without typos, the result is the same as of
+    /// `find_best_subsequence`. Otherwise, the best subsequence is searched for every correction
+    /// matching `text`, and its score is adjusted by the measures of all corrected typos (see
+    /// `Metric::measure_typo`). The correction with the best adjusted score is picked. Returns
+    /// `None` if no correction matches `text`.
+    pub fn find_best_subsequence
+    (&self, text:impl Str, metric:impl Metric) -> Option<Subsequence> {
+        let text    = text.as_ref();
+        let pattern = self.pattern.as_str();
+        if matches(text,pattern) {
+            find_best_subsequence(text,pattern,metric)
+        } else {
+            let corrections = self.corrections.iter();
+            let matching    = corrections.filter(|correction| matches(text,&correction.pattern));
+            let found       = matching.filter_map(|correction| {
+                let mut subsequence = find_best_subsequence(text,&correction.pattern,&metric)?;
+                let typos           = correction.typos.iter();
+                let typos_score     = typos.map(|t| metric.measure_typo(*t,text,pattern));
+                subsequence.score  += typos_score.sum::<f32>();
+                Some(subsequence)
+            });
+            found.max_by(|lhs,rhs| lhs.compare_scores(rhs))
+        }
+    }
+}
+
+/// Fast-check if the pattern matches text, tolerating up to `max_typos` typos in the pattern. See
+/// the `typo` module docs to learn more about typos.
+///
+/// The corrections of the pattern are generated on every call; use `TypoTolerantPattern` when
+/// matching many texts.
+pub fn matches_with_typos(text:impl Str, pattern:impl Str, max_typos:usize) -> bool {
+    let text    = text.as_ref();
+    let pattern = pattern.as_ref();
+    matches(text,pattern) || TypoTolerantPattern::new(pattern,max_typos).matches(text)
+}
+
+/// Find best subsequence in `text` which case-insensitively equals to `pattern` in terms of given
+/// `metric`, tolerating up to `max_typos` typos in the pattern. See
+/// `TypoTolerantPattern::find_best_subsequence`.
+///
+/// The corrections of the pattern are generated on every call; use `TypoTolerantPattern` when
+/// matching many texts.
+pub fn find_best_subsequence_with_typos
+(text:impl Str, pattern:impl Str, metric:impl Metric, max_typos:usize) -> Option<Subsequence> {
+    let text    = text.as_ref();
+    let pattern = pattern.as_ref();
+    if matches(text,pattern) {
+        find_best_subsequence(text,pattern,metric)
+    } else {
+        TypoTolerantPattern::new(pattern,max_typos).find_best_subsequence(text,metric)
+    }
+}
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    mod mock_metric {
+        use super::*;
+
+        use crate::metric;
+
+        #[derive(Debug,Default)]
+        pub struct WordIndex;
+
+        impl Metric for WordIndex {
+            fn measure_vertex
+            (&self, vertex:subsequence_graph::Vertex, _text:&str, _pattern:&str) -> f32 {
+                vertex.position_in_text as f32
+            }
+
+            fn measure_edge(&self, _:subsequence_graph::Edge, _:&str, _:&str) -> f32 { 0.0 }
+        }
+
+        #[derive(Debug,Default)]
+        pub struct SquareEdgeLength;
+
+        impl Metric for SquareEdgeLength {
+            fn measure_vertex(&self, _:subsequence_graph::Vertex, _:&str, _:&str) -> f32 { 0.0 }
+
+            fn measure_edge(&self, edge:subsequence_graph::Edge, _text:&str, _pattern:&str) -> f32 {
+                (edge.to.position_in_text - edge.from.position_in_text).pow(2) as f32
+            }
+        }
+
+        pub type Sum = metric::Sum<WordIndex,SquareEdgeLength>;
+    }
+
+    #[test]
+    fn matches_test() {
+        assert!( matches("abba", "aba"));
+        assert!( matches("abba", "ba" ));
+        assert!( matches("abba", ""   ));
+        assert!(!matches("abba", "abc"));
+        assert!(!matches("abba", "baa"));
+        assert!(!matches(""    , "ba" ));
+    }
+
+    #[test]
+    fn finding_best_subsequence() {
+        let pattern = "abc";
+        let text = "aabxbacc";
+
+        let expected = Subsequence {
+            score   : 12.0,
+            indices : vec![1,4,7] // Always pick the latest character possible
+        };
+        assert_eq!(find_best_subsequence(text,pattern,mock_metric::WordIndex), Some(expected));
+
+        let expected = Subsequence {
+            score   : 29.0,
+            indices : vec![0,2,7] // Prefer the long edges
+        };
+        assert_eq!(find_best_subsequence(text,pattern,mock_metric::SquareEdgeLength), Some(expected));
+
+        let expected = Subsequence {
+            score   : 38.0,
+            indices : vec![0,2,7] // The edges metric should have more impact
+        };
+        assert_eq!(find_best_subsequence(text,pattern,mock_metric::Sum::default()), Some(expected));
+    }
+
+    #[test]
+    fn finding_best_subsequence_when_does_not_match() {
+        let pattern = "abc";
+        let text    = "aabxbyy";
+        assert_eq!(find_best_subsequence(text,pattern,mock_metric::Sum::default()), None);
+    }
+
+    #[test]
+    fn finding_best_subsequence_corner_cases() {
+        let pattern = "";
+        let text    = "any";
+        let expected = Subsequence {
+            score   : 0.0,
+            indices : vec![],
+        };
+        assert_eq!(find_best_subsequence(text,pattern,mock_metric::Sum::default()), Some(expected));
+        let pattern = "any";
+        let text    = "";
+        assert_eq!(find_best_subsequence(text,pattern,mock_metric::Sum::default()), None);
+    }
+
+    #[test]
+    fn matching_with_typos() {
+        assert!( matches_with_typos("filter", "fitler", 1));
+        assert!( matches_with_typos("filter", "fxlter", 1));
+        assert!( matches_with_typos("filter", "flter" , 0));
+        assert!(!matches_with_typos("filter", "fitler", 0));
+        assert!(!matches_with_typos("filter", "fitlre", 1));
+        assert!( matches_with_typos("filter", "fitlre", 2));
+        assert!(!matches_with_typos("filter", "fx"    , 2));
+    }
+
+    #[test]
+    fn finding_best_subsequence_with_typos() {
+        let metric  = crate::metric::default;
+        let exact   = find_best_subsequence("filter","flter",metric()).unwrap();
+        let typo    = find_best_subsequence_with_typos("filter","flter",metric(),1).unwrap();
+        assert_eq!(typo,exact);
+
+        let typo    = find_best_subsequence_with_typos("filter","fitler",metric(),1).unwrap();
+        let correct = find_best_subsequence("filter","filter",metric()).unwrap();
+        assert_eq!(typo.indices,vec![0,1,2,3,4,5]);
+        assert!(typo.score < correct.score);
+        assert!(typo.score > exact.score);
+        assert_eq!(find_best_subsequence_with_typos("filter","fitler",metric(),0),None);
+        assert_eq!(find_best_subsequence_with_typos("map","fitler",metric(),2),None);
+    }
+
+    #[test]
+    fn typo_tolerant_pattern() {
+        let metric  = crate::metric::default;
+        let pattern = TypoTolerantPattern::new("fitler",1);
+        assert!( pattern.matches("filter"));
+        assert!( pattern.matches("fitler"));
+        assert!(!pattern.matches("map"));
+        for &text in &["filter","fitler","filter_map","map"] {
+            let expected = find_best_subsequence_with_typos(text,"fitler",metric(),1);
+            assert_eq!(pattern.find_best_subsequence(text,metric()),expected);
+        }
+    }
+
+    #[test]
+    fn char_ranges_of_subsequence() {
+        let subsequence = Subsequence {score:0.0, indices:vec![0,1,2,5,7,8]};
+        assert_eq!(subsequence.char_ranges(),vec![0..3,5..6,7..9]);
+        assert!(Subsequence::default().char_ranges().is_empty());
+    }
+}
+
+
+
+// ==================
+// === Benchmarks ===
+// ==================
+
+#[cfg(test)]
+pub(crate) mod benchmarks {
+    use super::*;
+
+    use ::test::Bencher;
+
+    /// Names of entries similar to the ones stored in the suggestion database of a project with
+    /// the standard library imported.
+    pub(crate) fn suggestion_database() -> Vec<String> {
+        let modules = ["Base","Text","Vector","Map","Table","Json","Http","File","Time","Math"];
+        let verbs   = ["get","set","to","from","find","filter","map","fold","sort","join","split"
+                      ,"parse","read","write","index","each"];
+        let nouns   = ["text","value","column","row","index","key","element","case","path","line"
+                      ,"name","size","date","number"];
+        let names   = modules.iter().cartesian_product(&verbs).cartesian_product(&nouns);
+        names.map(|((module,verb),noun)| format!("{}.{}_{}",module,verb,noun)).collect()
+    }
+
+    /// Match all entries of the `suggestion_database` as the searcher does.
+    ///
+    /// With typos, the corrections are generated once per iteration, as once per query in the
+    /// searcher. Then, for every entry, up to `typo::MAX_CORRECTIONS` fast checks are made, and the
+    /// full subsequence search is run for every correction matching the entry. Therefore the typo
+    /// tolerance costs the most for the patterns which do not match exactly, and its cost grows
+    /// with the number of corrections, i.e. with the pattern length and the number of typos.
+    fn bench_matching(bencher:&mut Bencher, pattern:&str, max_typos:Option<usize>) {
+        let database = suggestion_database();
+        bencher.iter(|| {
+            let metric = crate::metric::default;
+            let found  = match max_typos {
+                None => database.iter().filter_map(|name| {
+                    matches(name,pattern).and_option_from(|| {
+                        find_best_subsequence(name,pattern,metric())
+                    })
+                }).count(),
+                Some(max_typos) => {
+                    let pattern = TypoTolerantPattern::new(pattern,max_typos);
+                    database.iter().filter_map(|name| {
+                        pattern.matches(name).and_option_from(|| {
+                            pattern.find_best_subsequence(name,metric())
+                        })
+                    }).count()
+                }
+            };
+            found
+        });
+    }
+
+    #[bench]
+    fn subsequence_matching(bencher:&mut Bencher) {
+        bench_matching(bencher,"fltrcol",None);
+    }
+
+    #[bench]
+    fn typo_tolerant_matching_of_correct_pattern(bencher:&mut Bencher) {
+        bench_matching(bencher,"fltrcol",Some(2));
+    }
+
+    #[bench]
+    fn typo_tolerant_matching_with_one_typo(bencher:&mut Bencher) {
+        bench_matching(bencher,"fitlercol",Some(1));
+    }
+
+    #[bench]
+    fn typo_tolerant_matching_with_two_typos(bencher:&mut Bencher) {
+        bench_matching(bencher,"fitlercolmun",Some(2));
+    }
+
+    /// The pattern is long enough to be bounded by `typo::MAX_CORRECTIONS`.
+    #[bench]
+    fn typo_tolerant_matching_of_long_pattern(bencher:&mut Bencher) {
+        bench_matching(bencher,"tabel.fitler_colmun",Some(3));
+    }
+
+    /// Generating the corrections alone, done once per query.
+    #[bench]
+    fn generating_corrections_of_long_pattern(bencher:&mut Bencher) {
+        bencher.iter(|| typo::corrections("tabel.fitler_colmun",3).len());
+    }
+}
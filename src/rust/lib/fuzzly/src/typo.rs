@@ -0,0 +1,204 @@
+//! Typo-tolerant matching support.
+//!
+//! The pattern matches the text if it is a subsequence of the text, so the text may contain any
+//! number of characters missing in the pattern. Therefore, measuring how far is the pattern from
+//! matching the text with the Damerau-Levenshtein distance, only two kinds of edits are needed:
+//! * skipping a pattern char, which covers both chars inserted to the pattern and chars substituted
+//!   with other ones (the substituted char is then "inserted" into the text for free),
+//! * transposing two adjacent pattern chars.
+//!
+//! The *corrections* of the pattern are all patterns obtained by applying up to given number of
+//! such edits (typos). The typo-tolerant matching (see `find_best_subsequence_with_typos`) looks
+//! for the best subsequence among the corrections matching the text.
+//!
+//! The number of corrections grows exponentially with the number of typos: a pattern of _n_ chars
+//! has about _2n_ corrections with one typo and _4n²_ with two typos. Each of them is checked
+//! against every matched text, so the corrections are generated once per pattern (see
+//! `score::TypoTolerantPattern`) and their number is bounded by `MAX_CORRECTIONS`.
+
+use crate::prelude::*;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The minimum number of pattern chars per single tolerated typo. Short patterns cannot contain
+/// typos, as after correcting them they would match almost anything.
+pub const CHARS_PER_TYPO : usize = 3;
+
+/// The maximum number of corrections of a single pattern. If the corrections with the next number
+/// of typos would exceed it, none of them are generated, so long patterns tolerate fewer typos.
+pub const MAX_CORRECTIONS : usize = 1024;
+
+
+
+// ============
+// === Typo ===
+// ============
+
+/// A single typo in a pattern. Positions are indices of chars in the original pattern.
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
+pub enum Typo {
+    /// The char at the given position should be skipped.
+    Skipped {
+        /// Position of the char.
+        position : usize
+    },
+    /// The char at the given position was swapped with the next one.
+    Transposed {
+        /// Position of the first of the swapped chars.
+        position : usize
+    },
+}
+
+
+
+// ==================
+// === Correction ===
+// ==================
+
+/// A corrected pattern together with the typos which were corrected.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct Correction {
+    /// The corrected pattern.
+    pub pattern : String,
+    /// The corrected typos.
+    pub typos   : Vec<Typo>,
+}
+
+/// The maximum number of typos which are tolerated in the pattern, being the `limit` lowered
+/// according to `CHARS_PER_TYPO`.
+pub fn max_typos(pattern:&str, limit:usize) -> usize {
+    limit.min(pattern.chars().count() / CHARS_PER_TYPO)
+}
+
+/// All corrections of the pattern with at least one and at most `limit` typos (see `max_typos`),
+/// ordered by the number of typos. Every corrected pattern is returned once, with the minimum
+/// number of typos. The original pattern is not included. If the corrections with the next number
+/// of typos would exceed `MAX_CORRECTIONS`, their generation is stopped as soon as the bound is
+/// reached, and only the corrections with fewer typos are returned.
+pub fn corrections(pattern:&str, limit:usize) -> Vec<Correction> {
+    let mut seen   = HashSet::<String>::new();
+    let mut result = Vec::new();
+    let mut level  = vec![Candidate {chars:pattern.chars().enumerate().collect(),typos:default()}];
+    seen.insert(pattern.into());
+    for _ in 0..max_typos(pattern,limit) {
+        let budget = MAX_CORRECTIONS - result.len();
+        match next_level(&level,&mut seen,budget) {
+            Some(next) => {
+                result.extend(next.iter().map(Candidate::correction));
+                level = next;
+            }
+            None => break,
+        }
+    }
+    result
+}
+
+/// A corrected pattern, with the positions of its chars in the original pattern.
+#[derive(Clone,Debug)]
+struct Candidate {
+    chars : Vec<(usize,char)>,
+    typos : Vec<Typo>,
+}
+
+impl Candidate {
+    fn pattern(&self) -> String {
+        self.chars.iter().map(|(_,ch)| ch).collect()
+    }
+
+    fn correction(&self) -> Correction {
+        Correction {pattern:self.pattern(),typos:self.typos.clone()}
+    }
+
+    fn with_typo(&self, chars:Vec<(usize,char)>, typo:Typo) -> Self {
+        let typos = self.typos.iter().copied().chain(std::iter::once(typo)).collect();
+        Self {chars,typos}
+    }
+}
+
+/// The not yet `seen` corrections of the `level` candidates with one more typo. Returns `None` as
+/// soon as their number exceeds the `budget`, without generating the rest of them.
+fn next_level
+(level:&[Candidate], seen:&mut HashSet<String>, budget:usize) -> Option<Vec<Candidate>> {
+    let mut next = Vec::new();
+    for candidate in level {
+        let chars = &candidate.chars;
+        for index in 0..chars.len() {
+            let mut skipped  = chars.clone();
+            let (position,_) = skipped.remove(index);
+            let mut edits    = vec![candidate.with_typo(skipped,Typo::Skipped {position})];
+            if index + 1 < chars.len() {
+                let mut transposed = chars.clone();
+                transposed.swap(index,index+1);
+                let position = chars[index].0.min(chars[index+1].0);
+                edits.push(candidate.with_typo(transposed,Typo::Transposed {position}));
+            }
+            next.extend(edits.into_iter().filter(|edit| seen.insert(edit.pattern())));
+            if next.len() > budget {
+                return None;
+            }
+        }
+    }
+    Some(next)
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn limiting_typos() {
+        assert_eq!(max_typos("ab"    ,2),0);
+        assert_eq!(max_typos("abc"   ,2),1);
+        assert_eq!(max_typos("abcdef",2),2);
+        assert_eq!(max_typos("abcdef",1),1);
+        assert!(corrections("ab",2).is_empty());
+    }
+
+    #[test]
+    fn generating_corrections() {
+        let corrections = corrections("abc",2);
+        let expected    = vec!
+            [ ("bc" , Typo::Skipped    {position:0})
+            , ("bac", Typo::Transposed {position:0})
+            , ("ac" , Typo::Skipped    {position:1})
+            , ("acb", Typo::Transposed {position:1})
+            , ("ab" , Typo::Skipped    {position:2})
+            ];
+        let expected = expected.into_iter().map(|(pattern,typo)| {
+            Correction {pattern:pattern.into(),typos:vec![typo]}
+        }).collect_vec();
+        assert_eq!(corrections,expected);
+    }
+
+    #[test]
+    fn generating_corrections_with_many_typos() {
+        let corrections = corrections("abcdef",2);
+        let with_typos  = |count| corrections.iter().filter(|t| t.typos.len() == count).count();
+        assert_eq!(with_typos(1),11);
+        assert!(with_typos(2) > 0);
+        let fixed = corrections.iter().find(|t| t.pattern == "bacdfe").unwrap();
+        let typos = vec![Typo::Transposed {position:0},Typo::Transposed {position:4}];
+        assert_eq!(fixed.typos,typos);
+        assert!(corrections.iter().all(|t| t.pattern != "abcdef"));
+    }
+
+    #[test]
+    fn limiting_corrections() {
+        let pattern     = "abcdefghijklmnopqrst";
+        let corrections = corrections(pattern,3);
+        assert!(corrections.len() <= MAX_CORRECTIONS);
+        assert!(corrections.iter().any(|t| t.typos.len() == 2));
+        assert!(corrections.iter().all(|t| t.typos.len() <= 2));
+        assert_eq!(corrections,super::corrections(pattern,2));
+    }
+}
@@ -14,6 +14,10 @@
 
 extern crate test;
 
+pub mod sequence;
+
+pub use sequence::PendingSequence;
+
 use enso_automata::*;
 use enso_prelude::*;
 use ensogl_system_web as web;
@@ -100,7 +104,7 @@ pub use ActionType::*;
 #[allow(missing_docs)]
 pub trait Registry<T> : Default {
     /// Add a new action mapping. `The expr` needs to be a list of keys separated by space, like
-    /// "ctrl shift a".
+    /// "ctrl shift a". It can also describe a sequence of strokes, like "ctrl k ctrl s".
     fn add (&self, action_type:ActionType, expr:impl AsRef<str>, action:impl Into<T>);
 
     /// Get a list of items registered for the action that just happened. It might include items
@@ -114,6 +118,16 @@ pub trait Registry<T> : Default {
     /// Some engines might implement a separate optimization stage. This is intended to force the
     /// optimization at a given point in time. Used mainly in benchmarks.
     fn optimize (&self) {}
+
+    /// Set the maximum time in milliseconds between subsequent strokes of multi-stroke shortcuts,
+    /// like "ctrl k ctrl s". See the `sequence` module to learn more.
+    fn set_sequence_timeout (&self, timeout:f32);
+
+    /// Multi-stroke shortcuts which were partially matched and are waiting for the next stroke.
+    fn pending_sequences (&self) -> Vec<PendingSequence<T>>;
+
+    /// Remove all action mappings. The sequence timeout is preserved.
+    fn clear (&self);
 }
 
 
@@ -142,6 +156,7 @@ pub struct AutomataRegistryModel<T> {
     action_map    : HashMap<ActionType,HashMap<nfa::State,T>>,
     press_times   : HashMap<dfa::State,f32>,
     release_times : HashMap<dfa::State,f32>,
+    sequences     : sequence::SequenceMatcher<T>,
 }
 
 
@@ -170,15 +185,33 @@ impl<T> AutomataRegistryModel<T> {
         let action_map    = default();
         let press_times   = default();
         let release_times = default();
+        let sequences     = default();
         Self {dirty,nfa,dfa,states,connections,always_state,current,pressed,action_map,press_times
-            ,release_times}
+            ,release_times,sequences}
     }
 }
 
 impl<T:Clone> AutomataRegistryModel<T> {
+    /// Remove all shortcut definitions. The state of pressed keys is preserved, the current DFA
+    /// state is restored from it once the automata are rebuilt.
+    fn clear(&mut self) {
+        let Self {nfa,dfa,states,connections,always_state,action_map,..} = Self::new();
+        self.dirty        = true;
+        self.nfa          = nfa;
+        self.dfa          = dfa;
+        self.states       = states;
+        self.connections  = connections;
+        self.always_state = always_state;
+        self.action_map   = action_map;
+        self.sequences.clear();
+    }
+
     fn add(&mut self, action_type:ActionType, expr:impl AsRef<str>, action:impl Into<T>) {
+        let expr = expr.as_ref();
+        if !expr.starts_with('-') && sequence::strokes(expr).len() > 1 {
+            return self.sequences.add(action_type,expr,action.into())
+        }
         self.dirty = true;
-        let expr   = expr.as_ref();
         let end_state = if let Some(key) = expr.strip_prefix('-') {
             let key = format!("-{}",key.trim().to_lowercase());
             let sym = Symbol::new_named(hash(&key),key);
@@ -210,6 +243,27 @@ impl<T:Clone> AutomataRegistryModel<T> {
     }
 }
 
+impl<T:Clone+Ord+Hash> AutomataRegistryModel<T> {
+    /// Groups of different actions of the same type which are triggered by the same input. Every
+    /// DFA state is checked, so actions registered for different expressions are reported if the
+    /// expressions can match the same set of pressed keys, like "ctrl a" and "ctrl-left a".
+    fn overlapping_actions(&mut self) -> Vec<(ActionType,Vec<T>)> {
+        self.optimize();
+        let action_types = [Press,PressAndRepeat,Release,DoublePress,DoubleClick];
+        let mut groups   = HashSet::<(ActionType,Vec<T>)>::new();
+        for nfa_states in &self.dfa.sources {
+            for action_type in &action_types {
+                let actions = nfa_states.iter().filter_map(|t| self.get_action(*action_type,*t));
+                let actions = actions.sorted().dedup().collect_vec();
+                if actions.len() > 1 {
+                    groups.insert((*action_type,actions));
+                }
+            }
+        }
+        groups.into_iter().sorted_by(|a,b| a.1.cmp(&b.1)).collect()
+    }
+}
+
 impl<T:Clone> AutomataRegistryModel<T> {
     /// The `keys` para contains list of (key name, is side-aware key) items.
     fn add_key_permutations(&mut self, source:nfa::State, keys:Vec<&str>) -> nfa::State {
@@ -330,7 +384,7 @@ impl<T:Clone> AutomataRegistryModel<T> {
             actions.extend(nfa_states.iter().filter_map(|t|self.get_action(action2,*t)));
         }
         if press {
-            self.pressed.insert(input);
+            self.pressed.insert(input.clone());
             self.press_times.insert(focus_state,new_time);
         } else {
             self.pressed.remove(&input);
@@ -340,19 +394,23 @@ impl<T:Clone> AutomataRegistryModel<T> {
             }
             self.reset_to_known_state();
         }
+        actions.extend(self.sequences.on_event(&input,press,&self.pressed));
         actions
     }
 
     fn reset_to_known_state(&mut self) {
         if self.current.is_invalid() {
-            let path = self.pressed.iter().sorted().cloned().collect_vec();
-            self.current = Dfa::START_STATE;
-            for p in path {
-                self.current = self.dfa.next_state(self.current, &Symbol::from(hash(&p)));
-            }
+            self.current = self.pressed_keys_state();
         }
     }
 
+    /// The DFA state reached by pressing the currently pressed keys in the alphabetical order.
+    fn pressed_keys_state(&self) -> dfa::State {
+        self.pressed.iter().sorted().fold(Dfa::START_STATE, |state,key| {
+            self.dfa.next_state(state,&Symbol::from(hash(key)))
+        })
+    }
+
     fn get_action(&self, action_type:ActionType, state:nfa::State) -> Option<T> {
         self.action_map.get(&action_type).and_then(|m|m.get(&state).cloned())
     }
@@ -361,8 +419,7 @@ impl<T:Clone> AutomataRegistryModel<T> {
         if self.dirty {
             self.dirty         = false;
             self.dfa           = (&self.nfa).into();
-            self.current       =  Dfa::START_STATE;
-            self.pressed       = default();
+            self.current       = self.pressed_keys_state();
             self.press_times   = default();
             self.release_times = default();
         }
@@ -426,6 +483,15 @@ impl<T:Clone> AutomataRegistry<T> {
     }
 }
 
+impl<T:Clone+Ord+Hash> AutomataRegistry<T> {
+    /// Groups of different actions of the same type which are triggered by the same input. Please
+    /// note that only one action can be registered for a given expression and action type, so
+    /// actions registered for the same expression are not reported.
+    pub fn overlapping_actions(&self) -> Vec<(ActionType,Vec<T>)> {
+        self.rc.borrow_mut().overlapping_actions()
+    }
+}
+
 impl<T:Clone> Registry<T> for AutomataRegistry<T> {
     fn add(&self, action_type:ActionType, expr:impl AsRef<str>, action:impl Into<T>) {
         self.rc.borrow_mut().add(action_type,expr,action)
@@ -444,6 +510,18 @@ impl<T:Clone> Registry<T> for AutomataRegistry<T> {
     fn optimize(&self) {
         self.rc.borrow_mut().optimize();
     }
+
+    fn set_sequence_timeout(&self, timeout:f32) {
+        self.rc.borrow_mut().sequences.set_timeout(timeout)
+    }
+
+    fn pending_sequences(&self) -> Vec<PendingSequence<T>> {
+        self.rc.borrow().sequences.pending()
+    }
+
+    fn clear(&self) {
+        self.rc.borrow_mut().clear()
+    }
 }
 
 
@@ -464,6 +542,7 @@ pub struct HashSetRegistryModel<T> {
     release_times : HashMap<String,f32>,
     side_keys     : HashMap<String,Vec<String>>,
     key_aliases   : HashMap<String,String>,
+    sequences     : sequence::SequenceMatcher<T>,
 }
 
 impl<T> HashSetRegistryModel<T> {
@@ -474,17 +553,11 @@ impl<T> HashSetRegistryModel<T> {
         let pressed       = default();
         let press_times   = default();
         let release_times = default();
-        let side_keys     = default();
+        let side_keys     = side_keys();
         let key_aliases   = key_aliases();
-        Self {current_expr,actions,pressed,press_times,release_times,side_keys,key_aliases} . init()
-    }
-
-    fn init(mut self) -> Self {
-        for key in SIDE_KEYS {
-            let alts = vec![format!("{}-left",key),format!("{}-right",key),(*key).to_string()];
-            self.side_keys.insert((*key).to_string(),alts);
-        }
-        self
+        let sequences     = default();
+        Self {current_expr,actions,pressed,press_times,release_times,side_keys,key_aliases
+            ,sequences}
     }
 
     fn current_expr(&self) -> String {
@@ -493,10 +566,19 @@ impl<T> HashSetRegistryModel<T> {
 }
 
 impl<T:HashSetRegistryItem> HashSetRegistryModel<T> {
+    /// Remove all shortcut definitions. The state of pressed keys is preserved.
+    pub fn clear(&mut self) {
+        self.actions = default();
+        self.sequences.clear();
+    }
+
     /// Add a new shortcut definition.
     pub fn add(&mut self, action_type:ActionType, input:impl AsRef<str>, action:impl Into<T>) {
         let input  = input.as_ref();
         let action = action.into();
+        if !input.starts_with('-') && sequence::strokes(input).len() > 1 {
+            return self.sequences.add(action_type,input,action)
+        }
         let exprs  = self.possible_exprs(input);
         let map    = self.actions.entry(action_type).or_default();
         for expr in exprs {
@@ -511,11 +593,12 @@ impl<T:HashSetRegistryItem> HashSetRegistryModel<T> {
         let repeat = if press { exists } else { !exists };
         if !repeat {
             let out = self.process_event(Release);
-            if press { self.pressed.insert(input); }
+            if press { self.pressed.insert(input.clone()); }
             else     { self.pressed.remove(&input); }
             self.current_expr = self.current_expr();
             out.extended(self.process_event(Press))
                .extended(self.process_event(PressAndRepeat))
+               .extended(self.sequences.on_event(&input,press,&self.pressed))
         } else {
             if press {
                 self.process_event(PressAndRepeat)
@@ -558,44 +641,67 @@ impl<T:HashSetRegistryItem> HashSetRegistryModel<T> {
         self.on_event(input,false)
     }
 
-    /// Return all possible expressions with sorted keys for a given input expression. For example,
-    /// for the input expression "cmd a", it will return ["a cmd", "a cmd-left", "a cmd-right"].
     fn possible_exprs(&self, expr:impl AsRef<str>) -> Vec<String> {
-        let mut out = Vec::<String>::new();
-        let expr    = expr.as_ref();
-        let chunks  = expr.split(' ').map(|t| t.trim()).filter(|t|!t.is_empty());
-        let keys    = chunks.map(|t| self.key_aliases.get(t).map(|t|t.as_ref()).unwrap_or(t));
-        for key in keys.sorted() {
-            match self.side_keys.get(key) {
-                Some(alts) => {
-                    if out.is_empty() {
-                        out.extend(alts.iter().cloned());
-                    } else {
-                        let local_out = mem::take(&mut out);
-                        for alt in alts {
-                            out.extend(local_out.iter().map(|expr| format!("{} {}",expr,alt)));
-                        }
+        possible_exprs(&self.side_keys,&self.key_aliases,expr)
+    }
+}
+
+impl<T> Default for HashSetRegistryModel<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Return all possible expressions with sorted keys for a given input expression. For example,
+/// for the input expression "cmd a", it will return ["a cmd", "a cmd-left", "a cmd-right"].
+fn possible_exprs
+( side_keys   : &HashMap<String,Vec<String>>
+, key_aliases : &HashMap<String,String>
+, expr        : impl AsRef<str>
+) -> Vec<String> {
+    let mut out = Vec::<String>::new();
+    let expr    = expr.as_ref();
+    let chunks  = expr.split(' ').map(|t| t.trim()).filter(|t|!t.is_empty());
+    let keys    = chunks.map(|t| key_aliases.get(t).map(|t|t.as_ref()).unwrap_or(t));
+    for key in keys.sorted() {
+        match side_keys.get(key) {
+            Some(alts) => {
+                if out.is_empty() {
+                    out.extend(alts.iter().cloned());
+                } else {
+                    let local_out = mem::take(&mut out);
+                    for alt in alts {
+                        out.extend(local_out.iter().map(|expr| format!("{} {}",expr,alt)));
                     }
-                },
-                None => {
-                    if out.is_empty() {
-                        out.push(key.into());
-                    } else {
-                        for el in out.iter_mut() {
-                            *el = format!("{} {}", el, key);
-                        }
+                }
+            },
+            None => {
+                if out.is_empty() {
+                    out.push(key.into());
+                } else {
+                    for el in out.iter_mut() {
+                        *el = format!("{} {}", el, key);
                     }
                 }
             }
         }
-        out
     }
+    out
 }
 
-impl<T> Default for HashSetRegistryModel<T> {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Replace all key aliases in the expression with the names of the keys, so for example, the
+/// expression "cmd option a" becomes "ctrl alt a" on Linux and Windows.
+pub fn resolve_aliases(expr:impl AsRef<str>) -> String {
+    let aliases = key_aliases();
+    let chunks  = expr.as_ref().split(' ').map(|t| t.trim()).filter(|t|!t.is_empty());
+    chunks.map(|t| aliases.get(t).map(|t|t.as_ref()).unwrap_or(t)).join(" ")
+}
+
+fn side_keys() -> HashMap<String,Vec<String>> {
+    SIDE_KEYS.iter().map(|key| {
+        let alts = vec![format!("{}-left",key),format!("{}-right",key),(*key).to_string()];
+        ((*key).to_string(),alts)
+    }).collect()
 }
 
 fn key_aliases() -> HashMap<String,String> {
@@ -662,6 +768,18 @@ impl<T:HashSetRegistryItem> Registry<T> for HashSetRegistry<T> {
     fn on_release(&self, input:impl AsRef<str>) -> Vec<T> {
         self.rc.borrow_mut().on_release(input)
     }
+
+    fn set_sequence_timeout(&self, timeout:f32) {
+        self.rc.borrow_mut().sequences.set_timeout(timeout)
+    }
+
+    fn pending_sequences(&self) -> Vec<PendingSequence<T>> {
+        self.rc.borrow().sequences.pending()
+    }
+
+    fn clear(&self) {
+        self.rc.borrow_mut().clear()
+    }
 }
 
 
@@ -818,6 +936,136 @@ mod tests {
     }
 
 
+    // === Multi-Stroke Sequences ===
+
+    #[test] fn automata_registry_strokes() { strokes::<AutomataRegistry<i32>>(); }
+    #[test] fn hash_set_registry_strokes() { strokes::<HashSetRegistry<i32>>(); }
+    fn strokes<T:Registry<i32>>() -> T {
+        let nothing  = Vec::<i32>::new();
+        let registry = <T>::default();
+        registry.add(Press   , "ctrl k"        , 0);
+        registry.add(Press   , "ctrl k ctrl s" , 1);
+        registry.add(Release , "ctrl k ctrl t" , 2);
+        registry.set_sequence_timeout(500.0);
+        // Strokes pressed without releasing the modifier.
+        assert_eq!(registry.on_press("ctrl-left"),nothing);
+        assert_eq!(registry.on_press("k"),vec![0]);
+        assert_eq!(registry.on_release("k"),nothing);
+        let remaining = registry.pending_sequences().into_iter().map(|t| t.remaining).collect_vec();
+        assert_eq!(remaining,vec![vec!["ctrl s".to_string()],vec!["ctrl t".to_string()]]);
+        assert_eq!(registry.on_press("s"),vec![1]);
+        assert_eq!(registry.on_release("s"),nothing);
+        assert_eq!(registry.pending_sequences(),vec![]);
+        // Strokes pressed separately.
+        assert_eq!(registry.on_press("k"),vec![0]);
+        assert_eq!(registry.on_release("k"),nothing);
+        assert_eq!(registry.on_release("ctrl-left"),nothing);
+        assert_eq!(registry.on_press("ctrl-right"),nothing);
+        assert_eq!(registry.on_press("t"),nothing);
+        assert_eq!(registry.on_release("t"),vec![2]);
+        assert_eq!(registry.on_release("ctrl-right"),nothing);
+        // Broken sequence.
+        assert_eq!(registry.on_press("ctrl-left"),nothing);
+        assert_eq!(registry.on_press("k"),vec![0]);
+        assert_eq!(registry.on_release("k"),nothing);
+        assert_eq!(registry.on_press("x"),nothing);
+        assert_eq!(registry.on_release("x"),nothing);
+        assert_eq!(registry.on_press("s"),nothing);
+        assert_eq!(registry.on_release("s"),nothing);
+        assert_eq!(registry.on_release("ctrl-left"),nothing);
+        // Timeout.
+        assert_eq!(registry.on_press("ctrl"),nothing);
+        assert_eq!(registry.on_press("k"),vec![0]);
+        assert_eq!(registry.on_release("k"),nothing);
+        assert_eq!(registry.pending_sequences().len(),2);
+        web::simulate_sleep(1000.0);
+        assert_eq!(registry.pending_sequences(),vec![]);
+        assert_eq!(registry.on_press("s"),nothing);
+        assert_eq!(registry.on_release("s"),nothing);
+        assert_eq!(registry.on_release("ctrl"),nothing);
+        registry
+    }
+
+    #[test] fn automata_registry_invalid_strokes() { invalid_strokes::<AutomataRegistry<i32>>(); }
+    #[test] fn hash_set_registry_invalid_strokes() { invalid_strokes::<HashSetRegistry<i32>>(); }
+    fn invalid_strokes<T:Registry<i32>>() -> T {
+        let nothing  = Vec::<i32>::new();
+        let registry = <T>::default();
+        registry.add(DoublePress    , "ctrl k ctrl s" , 0);
+        registry.add(PressAndRepeat , "ctrl k ctrl t" , 1);
+        assert_eq!(registry.on_press("ctrl-left"),nothing);
+        assert_eq!(registry.on_press("k"),nothing);
+        assert_eq!(registry.on_release("k"),nothing);
+        assert_eq!(registry.pending_sequences(),vec![]);
+        registry
+    }
+
+
+    // === Clearing ===
+
+    #[test] fn automata_registry_clear() { clear::<AutomataRegistry<i32>>(); }
+    #[test] fn hash_set_registry_clear() { clear::<HashSetRegistry<i32>>(); }
+    fn clear<T:Registry<i32>>() -> T {
+        let nothing  = Vec::<i32>::new();
+        let registry = <T>::default();
+        registry.add(Press, "ctrl a", 0);
+        registry.add(Press, "ctrl k ctrl s", 1);
+        registry.clear();
+        registry.add(Press, "ctrl b", 2);
+        assert_eq!(registry.on_press("ctrl-left"),nothing);
+        assert_eq!(registry.on_press("a"),nothing);
+        assert_eq!(registry.on_release("a"),nothing);
+        assert_eq!(registry.on_press("k"),nothing);
+        assert_eq!(registry.on_release("k"),nothing);
+        assert_eq!(registry.on_press("s"),nothing);
+        assert_eq!(registry.on_release("s"),nothing);
+        assert_eq!(registry.on_press("b"),vec![2]);
+        assert_eq!(registry.on_release("b"),nothing);
+        registry
+    }
+
+    #[test] fn automata_registry_clear_when_pressed() {
+        clear_when_pressed::<AutomataRegistry<i32>>();
+    }
+    #[test] fn hash_set_registry_clear_when_pressed() {
+        clear_when_pressed::<HashSetRegistry<i32>>();
+    }
+    fn clear_when_pressed<T:Registry<i32>>() -> T {
+        let nothing  = Vec::<i32>::new();
+        let registry = <T>::default();
+        registry.add(Press, "ctrl a", 0);
+        assert_eq!(registry.on_press("ctrl-left"),nothing);
+        registry.clear();
+        registry.add(Press, "ctrl b", 1);
+        assert_eq!(registry.on_press("a"),nothing);
+        assert_eq!(registry.on_release("a"),nothing);
+        assert_eq!(registry.on_press("b"),vec![1]);
+        assert_eq!(registry.on_release("b"),nothing);
+        assert_eq!(registry.on_release("ctrl-left"),nothing);
+        assert_eq!(registry.on_press("b"),nothing);
+        registry
+    }
+
+
+    // === Overlapping Actions ===
+
+    #[test]
+    fn automata_registry_overlapping_actions() {
+        let registry = AutomataRegistry::<i32>::new();
+        registry.add(Press          , "ctrl a"       , 0);
+        registry.add(Press          , "ctrl-left a"  , 1);
+        registry.add(Press          , "ctrl b"       , 2);
+        registry.add(Release        , "ctrl b"       , 3);
+        registry.add(Release        , "ctrl-right b" , 4);
+        registry.add(Release        , "shift b"      , 5);
+        registry.add(PressAndRepeat , "shift c"      , 6);
+        registry.add(PressAndRepeat , "shift-left c" , 7);
+        let expected = vec![(Press,vec![0,1]),(Release,vec![3,4]),(PressAndRepeat,vec![6,7])];
+        assert_eq!(registry.overlapping_actions(),expected);
+        assert_eq!(resolve_aliases("control  a"),"ctrl a");
+    }
+
+
     // === Valid States ===
 
     #[test] fn automata_registry_valid_states() { valid_states::<AutomataRegistry<i32>>(true); }
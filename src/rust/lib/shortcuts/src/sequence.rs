@@ -0,0 +1,234 @@
+//! Multi-stroke shortcuts, like `ctrl k ctrl s`.
+//!
+//! A shortcut expression consists of one or more strokes. A new stroke starts whenever a modifier
+//! key (like `ctrl` or `shift`) follows a regular key, so `ctrl k ctrl s` means pressing `ctrl k`
+//! and then `ctrl s`, while `ctrl shift a` is a single stroke. A stroke is matched when its regular
+//! key is pressed, and the set of all pressed keys is exactly the set of keys of the stroke. Every
+//! stroke has to be matched in a limited time after the previous one, which is configurable with
+//! `SequenceMatcher::set_timeout`. Pressing any other chord in the meantime breaks the sequence.
+//!
+//! Matching a stroke does not prevent single-stroke shortcuts from firing, so `ctrl k` can be
+//! bound to an action on its own, even if it starts a sequence.
+
+use crate::*;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// Default maximum time between subsequent strokes of a sequence.
+pub const DEFAULT_TIMEOUT_MS : f32 = 1500.0;
+
+
+
+// ===============
+// === Parsing ===
+// ===============
+
+/// Split the shortcut expression into strokes. Every returned stroke is an expression of keys
+/// separated with spaces.
+pub fn strokes(expr:&str) -> Vec<String> {
+    let aliases     = key_aliases();
+    let mut strokes = Vec::<Vec<&str>>::new();
+    let mut regular = false;
+    for key in expr.split(' ').map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        let is_modifier = is_modifier(aliases.get(key).map(|t| t.as_ref()).unwrap_or(key));
+        if strokes.is_empty() || (is_modifier && regular) {
+            strokes.push(vec![]);
+            regular = false;
+        }
+        regular = regular || !is_modifier;
+        strokes.last_mut().unwrap().push(key);
+    }
+    strokes.into_iter().map(|stroke| stroke.join(" ")).collect()
+}
+
+/// Check whether the key is a modifier key, like `ctrl` or `shift-left`.
+pub fn is_modifier(key:&str) -> bool {
+    let key = key.strip_suffix("-left").or_else(|| key.strip_suffix("-right")).unwrap_or(key);
+    SIDE_KEYS_SET.contains(key)
+}
+
+
+
+// =======================
+// === PendingSequence ===
+// =======================
+
+/// A sequence shortcut which was partially matched.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct PendingSequence<T> {
+    /// The type of the action of the shortcut.
+    pub action_type : ActionType,
+    /// The full shortcut expression.
+    pub expr        : String,
+    /// Strokes matched so far.
+    pub matched     : Vec<String>,
+    /// Strokes which still need to be matched.
+    pub remaining   : Vec<String>,
+    /// The action which will be triggered when the sequence is completed.
+    pub action      : T,
+}
+
+
+
+// =======================
+// === SequenceMatcher ===
+// =======================
+
+/// Matcher of multi-stroke shortcuts. It is used internally by all registries for shortcuts
+/// consisting of more than one stroke.
+#[derive(Debug)]
+pub struct SequenceMatcher<T> {
+    sequences   : Vec<Sequence<T>>,
+    pending     : Vec<PartialMatch>,
+    /// Actions of sequences completed on the last press, which fire on the next release.
+    releasing   : Vec<T>,
+    timeout     : f32,
+    side_keys   : HashMap<String,Vec<String>>,
+    key_aliases : HashMap<String,String>,
+}
+
+/// A registered sequence shortcut. Every stroke is stored as a set of all its possible
+/// expressions. See `possible_exprs` to learn more.
+#[derive(Debug)]
+struct Sequence<T> {
+    action_type : ActionType,
+    expr        : String,
+    strokes     : Vec<String>,
+    chords      : Vec<HashSet<String>>,
+    action      : T,
+}
+
+#[derive(Clone,Copy,Debug)]
+struct PartialMatch {
+    sequence : usize,
+    matched  : usize,
+    time     : f32,
+}
+
+impl<T> SequenceMatcher<T> {
+    /// Constructor.
+    pub fn new() -> Self {
+        let sequences   = default();
+        let pending     = default();
+        let releasing   = default();
+        let timeout     = DEFAULT_TIMEOUT_MS;
+        let side_keys   = side_keys();
+        let key_aliases = key_aliases();
+        Self {sequences,pending,releasing,timeout,side_keys,key_aliases}
+    }
+
+    /// Set the maximum time in milliseconds between subsequent strokes of a sequence.
+    pub fn set_timeout(&mut self, timeout:f32) {
+        self.timeout = timeout;
+    }
+
+    /// Remove all sequence shortcuts. The timeout is preserved.
+    pub fn clear(&mut self) {
+        self.sequences = default();
+        self.pending   = default();
+        self.releasing = default();
+    }
+
+    /// Add a new sequence shortcut. Only `Press` and `Release` action types are supported for
+    /// sequences. `Release` actions fire on the first key release after the sequence is completed.
+    /// Shortcuts of other action types are ignored with a warning, as they would never fire.
+    pub fn add(&mut self, action_type:ActionType, expr:impl AsRef<str>, action:T) {
+        let expr = expr.as_ref().to_string();
+        if !matches!(action_type,Press|Release) {
+            let logger : Logger = Logger::new("Shortcuts");
+            warning!(logger,"Ignoring the {action_type:?} shortcut `{expr}`. Only Press and \
+                Release shortcuts can consist of multiple strokes.");
+            return
+        }
+        let strokes = strokes(&expr);
+        let chords  = strokes.iter().map(|stroke| {
+            possible_exprs(&self.side_keys,&self.key_aliases,stroke).into_iter().collect()
+        }).collect();
+        self.sequences.push(Sequence {action_type,expr,strokes,chords,action});
+    }
+
+    fn is_expired(&self, partial:&PartialMatch, time:f32) -> bool {
+        time - partial.time > self.timeout
+    }
+}
+
+impl<T:Clone> SequenceMatcher<T> {
+    /// Process the input event. The `pressed` set should contain all keys pressed after the event
+    /// was processed.
+    pub fn on_event(&mut self, input:&str, press:bool, pressed:&HashSet<String>) -> Vec<T> {
+        if !press {
+            return mem::take(&mut self.releasing)
+        }
+        if is_modifier(input) {
+            return default()
+        }
+        let time       = web::time_from_start() as f32;
+        let chord      = pressed.iter().sorted().join(" ");
+        let continued  = self.pending.iter().filter(|partial| !self.is_expired(partial,time));
+        let continued  = continued.map(|partial| (partial.sequence,partial.matched));
+        let started    = (0..self.sequences.len()).map(|sequence| (sequence,0));
+        let candidates = continued.chain(started).collect_vec();
+        let mut out    = vec![];
+        self.pending   = default();
+        for (index,matched) in candidates {
+            let sequence = &self.sequences[index];
+            if sequence.chords[matched].contains(&chord) {
+                let matched = matched + 1;
+                if matched < sequence.chords.len() {
+                    self.pending.push(PartialMatch {sequence:index,matched,time});
+                } else {
+                    match sequence.action_type {
+                        Press   => out.push(sequence.action.clone()),
+                        Release => self.releasing.push(sequence.action.clone()),
+                        _       => {}
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// All sequences which were partially matched and did not time out yet.
+    pub fn pending(&self) -> Vec<PendingSequence<T>> {
+        let time = web::time_from_start() as f32;
+        self.pending.iter().filter(|partial| !self.is_expired(partial,time)).map(|partial| {
+            let sequence    = &self.sequences[partial.sequence];
+            let action_type = sequence.action_type;
+            let expr        = sequence.expr.clone();
+            let matched     = sequence.strokes[..partial.matched].to_vec();
+            let remaining   = sequence.strokes[partial.matched..].to_vec();
+            let action      = sequence.action.clone();
+            PendingSequence {action_type,expr,matched,remaining,action}
+        }).collect()
+    }
+}
+
+impl<T> Default for SequenceMatcher<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_strokes() {
+        assert_eq!(strokes("ctrl shift a"),vec!["ctrl shift a"]);
+        assert_eq!(strokes("ctrl k ctrl s"),vec!["ctrl k","ctrl s"]);
+        assert_eq!(strokes("cmd k  shift-left x a"),vec!["cmd k","shift-left x a"]);
+        assert_eq!(strokes("a b"),vec!["a b"]);
+        assert_eq!(strokes(""),Vec::<String>::new());
+    }
+}
@@ -0,0 +1,433 @@
+//! 2D geometry primitives measured in `Pixels`: points, segments, rectangles and axis-aligned
+//! bounding boxes.
+//!
+//! The `Rect` is described by its origin (the corner with the lowest coordinates) and size, which
+//! is convenient for layouts. The `Aabb` is described by its minimum and maximum corners, which is
+//! convenient for computing bounds of many shapes. Both can be converted into each other, and all
+//! shapes can be transformed by a `Matrix4`, like the ones used by display objects and cameras.
+
+use crate::algebra::*;
+use crate::topology::*;
+
+
+
+// =============
+// === Point ===
+// =============
+
+/// A point in 2D space.
+#[derive(Clone,Copy,Debug,Default,PartialEq)]
+#[allow(missing_docs)]
+pub struct Point {
+    pub x : Pixels,
+    pub y : Pixels,
+}
+
+impl Point {
+    /// Constructor.
+    pub fn new(x:impl Into<Pixels>, y:impl Into<Pixels>) -> Self {
+        let x = x.into();
+        let y = y.into();
+        Self {x,y}
+    }
+
+    /// The distance to the other point.
+    pub fn distance(self, other:Point) -> Pixels {
+        Pixels((self - other).to_vector().norm())
+    }
+
+    /// Transform the point by the matrix. The point is assumed to lie on the `z = 0` plane.
+    pub fn transform(self, matrix:&Matrix4<f32>) -> Self {
+        let transformed = matrix * Vector4(self.x.value,self.y.value,0.0,1.0);
+        Self::new(transformed.x / transformed.w, transformed.y / transformed.w)
+    }
+
+    /// The point as a vector of raw values.
+    pub fn to_vector(self) -> Vector2<f32> {
+        Vector2(self.x.value,self.y.value)
+    }
+}
+
+impl From<Vector2<f32>> for Point {
+    fn from(vector:Vector2<f32>) -> Self {
+        Self::new(vector.x,vector.y)
+    }
+}
+
+impl From<Point> for Vector2<f32> {
+    fn from(point:Point) -> Self {
+        point.to_vector()
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+    fn add(self, rhs:Point) -> Self::Output {
+        Point {x:self.x + rhs.x, y:self.y + rhs.y}
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+    fn sub(self, rhs:Point) -> Self::Output {
+        Point {x:self.x - rhs.x, y:self.y - rhs.y}
+    }
+}
+
+
+
+// ===============
+// === Segment ===
+// ===============
+
+/// A line segment between two points.
+#[derive(Clone,Copy,Debug,Default,PartialEq)]
+#[allow(missing_docs)]
+pub struct Segment {
+    pub start : Point,
+    pub end   : Point,
+}
+
+impl Segment {
+    /// Constructor.
+    pub fn new(start:Point, end:Point) -> Self {
+        Self {start,end}
+    }
+
+    /// The length of the segment.
+    pub fn length(&self) -> Pixels {
+        self.start.distance(self.end)
+    }
+
+    /// The point of the segment closest to the given one.
+    pub fn closest_point(&self, point:Point) -> Point {
+        let direction = (self.end - self.start).to_vector();
+        let length_sq = direction.norm_squared();
+        if length_sq == 0.0 {
+            self.start
+        } else {
+            let offset = (point - self.start).to_vector().dot(&direction) / length_sq;
+            let offset = offset.max(0.0).min(1.0);
+            (self.start.to_vector() + direction * offset).into()
+        }
+    }
+
+    /// The distance from the point to the closest point of the segment.
+    pub fn distance(&self, point:Point) -> Pixels {
+        point.distance(self.closest_point(point))
+    }
+}
+
+
+
+// ============
+// === Aabb ===
+// ============
+
+/// An axis-aligned bounding box. The box is empty if any of its `min` coordinates is greater than
+/// the corresponding `max` coordinate. Boxes with zero width or height are not empty, they contain
+/// the points on their boundary.
+#[derive(Clone,Copy,Debug,PartialEq)]
+#[allow(missing_docs)]
+pub struct Aabb {
+    pub min : Point,
+    pub max : Point,
+}
+
+impl Default for Aabb {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl Aabb {
+    /// The empty box. It is the neutral element of the `union` operation.
+    pub fn empty() -> Self {
+        let min = Point::new(f32::INFINITY,f32::INFINITY);
+        let max = Point::new(f32::NEG_INFINITY,f32::NEG_INFINITY);
+        Self {min,max}
+    }
+
+    /// The box spanning the two points, given in any order.
+    pub fn from_corners(p1:Point, p2:Point) -> Self {
+        let min = Point::new(p1.x.value.min(p2.x.value),p1.y.value.min(p2.y.value));
+        let max = Point::new(p1.x.value.max(p2.x.value),p1.y.value.max(p2.y.value));
+        Self {min,max}
+    }
+
+    /// The smallest box containing all the points. Empty if there are no points.
+    pub fn from_points(points:impl IntoIterator<Item=Point>) -> Self {
+        points.into_iter().fold(Self::empty(),|aabb,point| aabb.extended(point))
+    }
+
+    /// Check whether the box contains no points.
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y
+    }
+
+    /// The width of the box. Zero for empty boxes.
+    pub fn width(&self) -> Pixels {
+        if self.is_empty() { Pixels(0.0) } else { self.max.x - self.min.x }
+    }
+
+    /// The height of the box. Zero for empty boxes.
+    pub fn height(&self) -> Pixels {
+        if self.is_empty() { Pixels(0.0) } else { self.max.y - self.min.y }
+    }
+
+    /// The center of the box.
+    pub fn center(&self) -> Point {
+        Point::new((self.min.x + self.max.x) / 2.0, (self.min.y + self.max.y) / 2.0)
+    }
+
+    /// All four corners of the box.
+    pub fn corners(&self) -> [Point;4] {
+        let Self {min,max} = *self;
+        [min,Point {x:max.x,y:min.y},max,Point {x:min.x,y:max.y}]
+    }
+
+    /// Check whether the point lies within the box or on its boundary.
+    pub fn contains(&self, point:Point) -> bool {
+        let contains_x = point.x >= self.min.x && point.x <= self.max.x;
+        let contains_y = point.y >= self.min.y && point.y <= self.max.y;
+        contains_x && contains_y
+    }
+
+    /// Check whether the other box lies within this box. The empty box lies within any box.
+    pub fn contains_aabb(&self, other:&Aabb) -> bool {
+        other.is_empty() || (self.contains(other.min) && self.contains(other.max))
+    }
+
+    /// Check whether the boxes have any common point.
+    pub fn intersects(&self, other:&Aabb) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// The common part of the boxes. It is empty if the boxes do not intersect.
+    pub fn intersection(&self, other:&Aabb) -> Aabb {
+        let min = Point::new(self.min.x.value.max(other.min.x.value)
+                            ,self.min.y.value.max(other.min.y.value));
+        let max = Point::new(self.max.x.value.min(other.max.x.value)
+                            ,self.max.y.value.min(other.max.y.value));
+        Self {min,max}
+    }
+
+    /// The smallest box containing both boxes.
+    pub fn union(&self, other:&Aabb) -> Aabb {
+        let min = Point::new(self.min.x.value.min(other.min.x.value)
+                            ,self.min.y.value.min(other.min.y.value));
+        let max = Point::new(self.max.x.value.max(other.max.x.value)
+                            ,self.max.y.value.max(other.max.y.value));
+        Self {min,max}
+    }
+
+    /// The smallest box containing this box and the point.
+    pub fn extended(&self, point:Point) -> Aabb {
+        self.union(&Self {min:point,max:point})
+    }
+
+    /// The box grown by the margin in every direction. Negative margins shrink the box.
+    pub fn grown(&self, margin:impl Into<Pixels>) -> Aabb {
+        let margin = margin.into();
+        let offset = Point {x:margin,y:margin};
+        Self {min:self.min - offset, max:self.max + offset}
+    }
+
+    /// The distance from the point to the closest point of the box. Zero for points inside. The
+    /// distance to the empty box is infinite.
+    pub fn distance(&self, point:Point) -> Pixels {
+        if self.is_empty() {
+            Pixels(f32::INFINITY)
+        } else {
+            let dx = (self.min.x.value - point.x.value).max(point.x.value - self.max.x.value);
+            let dy = (self.min.y.value - point.y.value).max(point.y.value - self.max.y.value);
+            Pixels(Vector2(dx.max(0.0),dy.max(0.0)).norm())
+        }
+    }
+
+    /// The bounding box of this box transformed by the matrix.
+    pub fn transform(&self, matrix:&Matrix4<f32>) -> Aabb {
+        if self.is_empty() {
+            *self
+        } else {
+            Self::from_points(self.corners().iter().map(|corner| corner.transform(matrix)))
+        }
+    }
+}
+
+
+
+// ============
+// === Rect ===
+// ============
+
+/// A rectangle described by its origin and size. Negative sizes are allowed and mean that the
+/// rectangle extends from the origin towards the lower coordinates.
+#[derive(Clone,Copy,Debug,Default,PartialEq)]
+#[allow(missing_docs)]
+pub struct Rect {
+    pub origin : Point,
+    pub size   : Point,
+}
+
+impl Rect {
+    /// Constructor.
+    pub fn new(origin:Point, size:Point) -> Self {
+        Self {origin,size}
+    }
+
+    /// The rectangle of the given size, centered at the point.
+    pub fn from_center(center:Point, size:Point) -> Self {
+        let half = Point {x:size.x / 2.0, y:size.y / 2.0};
+        Self::new(center - half,size)
+    }
+
+    /// The width of the rectangle, always non-negative.
+    pub fn width(&self) -> Pixels {
+        Pixels(self.size.x.value.abs())
+    }
+
+    /// The height of the rectangle, always non-negative.
+    pub fn height(&self) -> Pixels {
+        Pixels(self.size.y.value.abs())
+    }
+
+    /// The center of the rectangle.
+    pub fn center(&self) -> Point {
+        self.aabb().center()
+    }
+
+    /// The bounding box covering exactly the rectangle.
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_corners(self.origin,self.origin + self.size)
+    }
+
+    /// Check whether the point lies within the rectangle or on its boundary.
+    pub fn contains(&self, point:Point) -> bool {
+        self.aabb().contains(point)
+    }
+
+    /// Check whether the rectangles have any common point.
+    pub fn intersects(&self, other:&Rect) -> bool {
+        self.aabb().intersects(&other.aabb())
+    }
+
+    /// The bounding box of the rectangle transformed by the matrix.
+    pub fn transform(&self, matrix:&Matrix4<f32>) -> Aabb {
+        self.aabb().transform(matrix)
+    }
+}
+
+impl From<Aabb> for Rect {
+    fn from(aabb:Aabb) -> Self {
+        if aabb.is_empty() {
+            Self::default()
+        } else {
+            Self::new(aabb.min,aabb.max - aabb.min)
+        }
+    }
+}
+
+impl From<Rect> for Aabb {
+    fn from(rect:Rect) -> Self {
+        rect.aabb()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    fn aabb(x1:f32, y1:f32, x2:f32, y2:f32) -> Aabb {
+        Aabb::from_corners(Point::new(x1,y1),Point::new(x2,y2))
+    }
+
+    #[test]
+    fn segment_distance() {
+        let segment = Segment::new(Point::new(0,0),Point::new(10,0));
+        assert_eq!(segment.length(),Pixels(10.0));
+        assert_eq!(segment.distance(Point::new(5,3)),Pixels(3.0));
+        assert_eq!(segment.distance(Point::new(-3,4)),Pixels(5.0));
+        assert_eq!(segment.distance(Point::new(13,-4)),Pixels(5.0));
+        let point = Segment::new(Point::new(1,1),Point::new(1,1));
+        assert_eq!(point.distance(Point::new(4,5)),Pixels(5.0));
+    }
+
+    #[test]
+    fn aabb_operations() {
+        let first  = aabb(0.0,0.0,4.0,4.0);
+        let second = aabb(6.0,6.0,2.0,2.0);
+        let far    = aabb(10.0,10.0,12.0,12.0);
+        assert_eq!(second.min,Point::new(2,2));
+        assert_eq!(first.intersection(&second),aabb(2.0,2.0,4.0,4.0));
+        assert_eq!(first.union(&second),aabb(0.0,0.0,6.0,6.0));
+        assert!(first.intersects(&second));
+        assert!(!first.intersects(&far));
+        assert!(first.intersection(&far).is_empty());
+        assert!(first.intersects(&aabb(4.0,4.0,5.0,5.0)));
+        assert!(first.contains(Point::new(4,0)));
+        assert!(!first.contains(Point::new(5,0)));
+        assert!(first.contains_aabb(&aabb(1.0,1.0,2.0,2.0)));
+        assert!(!first.contains_aabb(&second));
+        assert_eq!(first.grown(1),aabb(-1.0,-1.0,5.0,5.0));
+        assert_eq!(first.center(),Point::new(2,2));
+    }
+
+    #[test]
+    fn empty_aabb() {
+        let empty = Aabb::empty();
+        let first = aabb(0.0,0.0,4.0,4.0);
+        assert!(empty.is_empty());
+        assert_eq!(empty.width(),Pixels(0.0));
+        assert_eq!(empty.union(&first),first);
+        assert!(!empty.intersects(&first));
+        assert!(first.contains_aabb(&empty));
+        let points = vec![Point::new(1,5),Point::new(-2,3),Point::new(0,-1)];
+        assert_eq!(Aabb::from_points(points),aabb(-2.0,-1.0,1.0,5.0));
+        assert_eq!(Aabb::from_points(vec![]),empty);
+    }
+
+    #[test]
+    fn aabb_distance() {
+        let first = aabb(0.0,0.0,4.0,4.0);
+        assert_eq!(first.distance(Point::new(2,2)),Pixels(0.0));
+        assert_eq!(first.distance(Point::new(2,7)),Pixels(3.0));
+        assert_eq!(first.distance(Point::new(7,8)),Pixels(5.0));
+        assert_eq!(Aabb::empty().distance(Point::new(0,0)),Pixels(f32::INFINITY));
+    }
+
+    #[test]
+    fn transformations() {
+        let translation = Matrix4::new_translation(&Vector3(10.0,20.0,0.0));
+        let rotation    = Matrix4::from_euler_angles(0.0,0.0,std::f32::consts::FRAC_PI_2);
+        let first       = aabb(0.0,0.0,4.0,2.0);
+        assert_eq!(first.transform(&translation),aabb(10.0,20.0,14.0,22.0));
+        let rotated = first.transform(&rotation);
+        assert_approx_eq!(rotated.min.x.value,-2.0);
+        assert_approx_eq!(rotated.min.y.value, 0.0);
+        assert_approx_eq!(rotated.max.x.value, 0.0);
+        assert_approx_eq!(rotated.max.y.value, 4.0);
+        assert_eq!(Point::new(1,2).transform(&translation),Point::new(11,22));
+    }
+
+    #[test]
+    fn rect_conversions() {
+        let rect = Rect::new(Point::new(4,4),Point::new(-4,2));
+        assert_eq!(rect.width(),Pixels(4.0));
+        assert_eq!(rect.aabb(),aabb(0.0,4.0,4.0,6.0));
+        assert_eq!(Rect::from(rect.aabb()),Rect::new(Point::new(0,4),Point::new(4,2)));
+        assert_eq!(Rect::from_center(Point::new(2,5),Point::new(4,2)),Rect::from(rect.aabb()));
+        assert!(rect.contains(Point::new(1,5)));
+        assert!(!rect.contains(Point::new(5,5)));
+        assert!(rect.intersects(&Rect::new(Point::new(3,3),Point::new(2,2))));
+        assert_eq!(Rect::from(Aabb::empty()),Rect::default());
+    }
+}
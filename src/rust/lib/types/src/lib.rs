@@ -11,6 +11,7 @@
 #![warn(unused_qualifications)]
 
 pub mod algebra;
+pub mod geometry;
 pub mod num;
 pub mod topology;
 pub mod unit;
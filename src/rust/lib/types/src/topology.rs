@@ -60,6 +60,23 @@ impl From<Degrees> for Radians {
 }
 
 
+
+// =========================
+// === Coordinate Spaces ===
+// =========================
+
+crate::space_vector!(
+    /// Position on the screen in pixels, relative to the screen center.
+    ScreenPosition::screen_position(f32));
+crate::space_vector!(
+    /// Position in the scene coordinates, independent of the camera.
+    ScenePosition::scene_position(f32));
+crate::space_vector!(
+    /// Position in the local coordinate system of a display object.
+    LocalPosition::local_position(f32));
+
+
+
 // ==============
 // === Traits ===
 // ==============
@@ -69,6 +86,9 @@ pub mod traits {
     pub use super::pixels::Into  as TRAIT_IntoPixels;
     pub use super::radians::Into as TRAIT_IntoRadians;
     pub use super::degrees::Into as TRAIT_IntoDegrees;
+    pub use super::screen_position::Into as TRAIT_IntoScreenPosition;
+    pub use super::scene_position::Into  as TRAIT_IntoScenePosition;
+    pub use super::local_position::Into  as TRAIT_IntoLocalPosition;
 }
 
 pub use traits::*;
@@ -104,4 +124,19 @@ mod tests {
         assert_equivalence(0.0.degrees(),    0.0.radians());
         assert_equivalence(-180.0.degrees(), -PI.radians());
     }
+
+    #[test]
+    fn space_vector_operations() {
+        let position = Vector2(1.0,2.0).screen_position();
+        let offset   = (3.0,4.0).screen_position();
+        assert_eq!(position + offset, ScreenPosition(4.0,6.0));
+        assert_eq!(offset - position, ScreenPosition(2.0,2.0));
+        assert_eq!(position * 2.0,    ScreenPosition(2.0,4.0));
+        assert_eq!(offset / 2.0,      ScreenPosition(1.5,2.0));
+        assert_eq!(-position,         ScreenPosition(-1.0,-2.0));
+        assert_eq!(position.to_vector(), Vector2(1.0,2.0));
+        let mut sum = ScenePosition(1.0,1.0);
+        sum += ScenePosition(1.0,0.0);
+        assert_eq!(Vector2::from(sum), Vector2(2.0,1.0));
+    }
 }
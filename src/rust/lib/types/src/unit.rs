@@ -18,6 +18,10 @@
 //! `impl_UNIT_x_FIELD_to_UNIT`. You should interpret it as a macro defining a transformation
 //! similar to `fn<Unit,Field>(unit:Unit, field:Field) -> Unit`, where `Field` is a specific field
 //! of the `Unit` (in most cases units have single field).
+//!
+//! ## Coordinate Spaces
+//! The `space_vector` macro defines 2D vectors tagged with a coordinate space. They prevent mixing
+//! positions expressed in different spaces, like screen pixels and scene coordinates.
 
 
 
@@ -294,6 +298,91 @@ macro_rules! newtype {
     };
 }
 
+/// Define a 2D vector type tagged with a coordinate space, like the screen or the scene. Vectors
+/// from different spaces cannot be mixed together, and need to be converted explicitly. They can be
+/// added, subtracted, negated, and scaled by a number. The raw `Vector2` can be tagged with the
+/// space by the generated conversion method, for example `Vector2(1.0,2.0).screen_position()`.
+#[macro_export]
+macro_rules! space_vector {
+    ($(#$meta:tt)* $name:ident :: $vname:ident ($field_type:ty)) => {
+        #[allow(missing_docs)]
+        pub mod $vname {
+            use super::*;
+            use std::ops::Add;
+            use std::ops::AddAssign;
+            use std::ops::Div;
+            use std::ops::Mul;
+            use std::ops::Neg;
+            use std::ops::Sub;
+
+            $(#$meta)*
+            #[derive(Clone,Copy,Debug,Default,PartialEq)]
+            pub struct $name {
+                pub x : $field_type,
+                pub y : $field_type,
+            }
+
+            /// Smart constructor.
+            #[allow(non_snake_case)]
+            pub fn $name(x:$field_type, y:$field_type) -> $name { $name {x,y} }
+
+            $crate::impl_T_x_T_to_T! {Sub::sub for $name {x,y}}
+            $crate::impl_T_x_T_to_T! {Add::add for $name {x,y}}
+            $crate::impl_T_x_S_to_T! {Mul::mul [$field_type] for $name {x,y}}
+            $crate::impl_T_x_S_to_T! {Div::div [$field_type] for $name {x,y}}
+            $crate::impl_T_to_T!     {Neg::neg for $name {x,y}}
+
+            impl AddAssign<$name> for $name {
+                fn add_assign(&mut self, rhs:Self) {
+                    *self = *self + rhs
+                }
+            }
+
+            impl $name {
+                /// The vector without the space tag.
+                pub fn to_vector(self) -> $crate::algebra::Vector2<$field_type> {
+                    $crate::algebra::Vector2::new(self.x,self.y)
+                }
+            }
+
+            impl From<$crate::algebra::Vector2<$field_type>> for $name {
+                fn from(t:$crate::algebra::Vector2<$field_type>) -> Self { $name(t.x,t.y) }
+            }
+
+            impl From<$name> for $crate::algebra::Vector2<$field_type> {
+                fn from(t:$name) -> Self { t.to_vector() }
+            }
+
+            /// Conversion of raw vectors into this space.
+            pub trait Into {
+                type Output;
+                fn $vname(self) -> Self::Output;
+            }
+
+            impl Into for $crate::algebra::Vector2<$field_type> {
+                type Output = $name;
+                fn $vname(self) -> Self::Output {
+                    $name::from(self)
+                }
+            }
+
+            impl Into for ($field_type,$field_type) {
+                type Output = $name;
+                fn $vname(self) -> Self::Output {
+                    $name(self.0,self.1)
+                }
+            }
+
+            /// Exports. The traits are renamed not to pollute the scope.
+            pub mod export {
+                pub use super::$name;
+                pub use super::Into as TRAIT_Into;
+            }
+        }
+        pub use $vname::export::*;
+    };
+}
+
 /// Unit definition macro. See module docs to learn more.
 #[macro_export]
 macro_rules! newtype_struct {